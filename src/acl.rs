@@ -0,0 +1,1192 @@
+//! ACL 用户和权限
+//!
+//! 每个用户有开关状态、密码（只保存 SHA-256）、允许执行的命令、允许访问的键和频道，
+//! 规则的写法和 Redis 的 ACL SETUSER 一致。连接在执行命令前通过 `check_current`
+//! 检查当前用户的权限，键的位置来自 command table；被拒绝的命令和认证失败记录到
+//! ACL LOG 中，相同的拒绝在一分钟内合并为一条。
+//!
+//! `default` 用户始终存在，默认 `on nopass ~* &* +@all`，requirepass 只是给它设置密码。
+
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
+use tracing::{error, info};
+
+use crate::client::CONTAINER_COMMANDS;
+use crate::process::command_table::{self, CommandSpec, COMMAND_TABLE};
+use crate::{glob, Resp};
+
+pub const DEFAULT_USER: &str = "default";
+pub const DEFAULT_LOG_MAX_LEN: usize = 128;
+// 相同的拒绝在这个时间内合并为一条日志
+const LOG_GROUPING_MILLIS: u64 = 60_000;
+
+// 命令分类
+const CAT_KEYSPACE: u32 = 1 << 0;
+const CAT_READ: u32 = 1 << 1;
+const CAT_WRITE: u32 = 1 << 2;
+const CAT_SET: u32 = 1 << 3;
+const CAT_SORTEDSET: u32 = 1 << 4;
+const CAT_LIST: u32 = 1 << 5;
+const CAT_HASH: u32 = 1 << 6;
+const CAT_STRING: u32 = 1 << 7;
+const CAT_PUBSUB: u32 = 1 << 8;
+const CAT_ADMIN: u32 = 1 << 9;
+const CAT_FAST: u32 = 1 << 10;
+const CAT_SLOW: u32 = 1 << 11;
+const CAT_DANGEROUS: u32 = 1 << 12;
+const CAT_CONNECTION: u32 = 1 << 13;
+const CAT_TRANSACTION: u32 = 1 << 14;
+
+pub const CATEGORIES: &[(&str, u32)] = &[
+    ("keyspace", CAT_KEYSPACE),
+    ("read", CAT_READ),
+    ("write", CAT_WRITE),
+    ("set", CAT_SET),
+    ("sortedset", CAT_SORTEDSET),
+    ("list", CAT_LIST),
+    ("hash", CAT_HASH),
+    ("string", CAT_STRING),
+    ("pubsub", CAT_PUBSUB),
+    ("admin", CAT_ADMIN),
+    ("fast", CAT_FAST),
+    ("slow", CAT_SLOW),
+    ("dangerous", CAT_DANGEROUS),
+    ("connection", CAT_CONNECTION),
+    ("transaction", CAT_TRANSACTION),
+];
+
+// 按命令名归类，read/write/admin/fast/slow 由命令标志决定
+const CATEGORY_COMMANDS: &[(u32, &[&str])] = &[
+    (
+        CAT_KEYSPACE,
+        &[
+            "del",
+            "exists",
+            "type",
+            "keys",
+            "expire",
+            "pexpireat",
+            "ttl",
+            "persist",
+            "scan",
+            "dump",
+            "restore",
+            "move",
+            "rename",
+            "renamenx",
+            "copy",
+            "randomkey",
+            "touch",
+            "unlink",
+            "object",
+            "dbsize",
+            "flushdb",
+            "flushall",
+            "swapdb",
+            "migrate",
+        ],
+    ),
+    (CAT_STRING, &["set", "get", "incr", "decr", "strlen"]),
+    (
+        CAT_HASH,
+        &["hset", "hget", "hdel", "hgetall", "hkeys", "hvals", "hlen"],
+    ),
+    (
+        CAT_LIST,
+        &["lpush", "rpush", "lpop", "rpop", "llen", "lrange", "lrem"],
+    ),
+    (CAT_SET, &["sadd", "scard", "smembers", "srem", "sismember"]),
+    (CAT_SORTEDSET, &["zadd", "zcard", "zscore", "zrem"]),
+    (
+        CAT_PUBSUB,
+        &[
+            "subscribe",
+            "unsubscribe",
+            "psubscribe",
+            "punsubscribe",
+            "ssubscribe",
+            "sunsubscribe",
+            "publish",
+            "spublish",
+            "pubsub",
+        ],
+    ),
+    (
+        CAT_CONNECTION,
+        &["ping", "select", "hello", "auth", "command", "asking"],
+    ),
+    (
+        CAT_TRANSACTION,
+        &["multi", "exec", "discard", "watch", "unwatch"],
+    ),
+    (
+        CAT_DANGEROUS,
+        &[
+            "keys", "flushdb", "flushall", "swapdb", "migrate", "restore", "info", "role",
+        ],
+    ),
+];
+
+const CLIENT_CONNECTION: u32 = CAT_SLOW | CAT_CONNECTION;
+const CLIENT_ADMIN: u32 = CAT_ADMIN | CAT_SLOW | CAT_DANGEROUS | CAT_CONNECTION;
+const ACL_ADMIN: u32 = CAT_ADMIN | CAT_SLOW | CAT_DANGEROUS;
+
+// 按子命令归类的命令，例如 CLIENT SETNAME 属于 @connection，CLIENT KILL 属于 @admin
+const SUBCOMMAND_CATEGORIES: &[(&str, &[(&str, u32)])] = &[
+    (
+        "client",
+        &[
+            ("id", CLIENT_CONNECTION),
+            ("info", CLIENT_CONNECTION),
+            ("getname", CLIENT_CONNECTION),
+            ("setname", CLIENT_CONNECTION),
+            ("setinfo", CLIENT_CONNECTION),
+            ("reply", CLIENT_CONNECTION),
+            ("tracking", CLIENT_CONNECTION),
+            ("caching", CLIENT_CONNECTION),
+            ("getredir", CLIENT_CONNECTION),
+            ("trackinginfo", CLIENT_CONNECTION),
+            ("help", CLIENT_CONNECTION),
+            ("list", CLIENT_ADMIN),
+            ("kill", CLIENT_ADMIN),
+            ("pause", CLIENT_ADMIN),
+            ("unpause", CLIENT_ADMIN),
+            ("no-evict", CLIENT_ADMIN),
+        ],
+    ),
+    (
+        "acl",
+        &[
+            ("cat", CAT_SLOW),
+            ("whoami", CAT_SLOW),
+            ("genpass", CAT_SLOW),
+            ("help", CAT_SLOW),
+            ("setuser", ACL_ADMIN),
+            ("getuser", ACL_ADMIN),
+            ("deluser", ACL_ADMIN),
+            ("list", ACL_ADMIN),
+            ("users", ACL_ADMIN),
+            ("dryrun", ACL_ADMIN),
+            ("log", ACL_ADMIN),
+            ("save", ACL_ADMIN),
+            ("load", ACL_ADMIN),
+        ],
+    ),
+];
+
+// ACL SETUSER 的错误原因
+const SYNTAX_ERROR: &str = "Syntax error";
+const UNKNOWN_COMMAND: &str = "Unknown command or category name in ACL";
+const FIRST_ARG: &str = "Allowing first-arg of a subcommand is not supported";
+const KEY_AFTER_ALL: &str = "Adding a pattern after the * pattern (or the 'allkeys' flag) is not valid and does not have any effect. Try 'resetkeys' to start with an empty list of patterns";
+const CHANNEL_AFTER_ALL: &str = "Adding a pattern after the * pattern (or the 'allchannels' flag) is not valid and does not have any effect. Try 'resetchannels' to start with an empty list of channels";
+const BAD_HASH: &str = "The password hash must be exactly 64 characters and contain only lowercase hexadecimal characters";
+const NO_SUCH_PASSWORD: &str = "The password you are trying to remove from the user does not exist";
+
+/// 分类名对应的位，未知分类返回 None
+pub fn category(name: &str) -> Option<u32> {
+    CATEGORIES
+        .iter()
+        .find(|(category, _)| category.eq_ignore_ascii_case(name))
+        .map(|(_, bit)| *bit)
+}
+
+// 命令所属的分类
+fn categories(spec: &CommandSpec) -> u32 {
+    let mut categories = if spec.is_fast() { CAT_FAST } else { CAT_SLOW };
+    if spec.is_write() {
+        categories |= CAT_WRITE;
+    }
+    if spec.is_readonly() {
+        categories |= CAT_READ;
+    }
+    if spec.is_admin() {
+        categories |= CAT_ADMIN | CAT_DANGEROUS;
+    }
+    for (category, names) in CATEGORY_COMMANDS {
+        if names.contains(&spec.name) {
+            categories |= category;
+        }
+    }
+    categories
+}
+
+fn subcommands(name: &str) -> Option<&'static [(&'static str, u32)]> {
+    SUBCOMMAND_CATEGORIES
+        .iter()
+        .find(|(command, _)| *command == name)
+        .map(|(_, subcommands)| *subcommands)
+}
+
+/// 属于分类 `bit` 的所有命令，按子命令归类的命令显示为 `命令|子命令`
+pub fn category_commands(bit: u32) -> Vec<String> {
+    let mut commands = Vec::new();
+    for spec in COMMAND_TABLE {
+        match subcommands(spec.name) {
+            Some(subcommands) => commands.extend(
+                subcommands
+                    .iter()
+                    .filter(|(_, categories)| categories & bit != 0)
+                    .map(|(sub, _)| format!("{}|{sub}", spec.name)),
+            ),
+            None if categories(spec) & bit != 0 => commands.push(spec.name.to_owned()),
+            None => {}
+        }
+    }
+    commands
+}
+
+/// 密码的 SHA-256，小写十六进制
+pub fn hash_password(password: &str) -> String {
+    format!("{:x}", Sha256::digest(password.as_bytes()))
+}
+
+fn valid_hash(hash: &str) -> bool {
+    hash.len() == 64
+        && hash
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// 用户允许执行的命令
+#[derive(Debug, Clone)]
+struct Commands {
+    // +@all 之后没有再禁止任何命令，command table 之外的命令也允许执行
+    all: bool,
+    allowed: HashSet<&'static str>,
+    // 单独允许或禁止的子命令，格式为 `命令|子命令`
+    allowed_subs: HashSet<String>,
+    denied_subs: HashSet<String>,
+    // ACL LIST 中显示的规则：+@all 或 -@all 加上之后依次应用的规则
+    rules: Vec<String>,
+}
+
+impl Commands {
+    fn all() -> Self {
+        Self {
+            all: true,
+            allowed: COMMAND_TABLE.iter().map(|spec| spec.name).collect(),
+            allowed_subs: HashSet::new(),
+            denied_subs: HashSet::new(),
+            rules: vec!["+@all".to_owned()],
+        }
+    }
+
+    fn none() -> Self {
+        Self {
+            all: false,
+            allowed: HashSet::new(),
+            allowed_subs: HashSet::new(),
+            denied_subs: HashSet::new(),
+            rules: vec!["-@all".to_owned()],
+        }
+    }
+
+    fn allows(&self, name: &str, sub: Option<&str>) -> bool {
+        if self.allowed.contains(name) {
+            sub.is_none_or(|sub| !self.denied_subs.contains(&format!("{name}|{sub}")))
+        } else {
+            sub.is_some_and(|sub| self.allowed_subs.contains(&format!("{name}|{sub}")))
+        }
+    }
+
+    fn set(&mut self, name: &'static str, allow: bool) {
+        if allow {
+            self.allowed.insert(name);
+        } else {
+            self.allowed.remove(name);
+        }
+        let prefix = format!("{name}|");
+        self.allowed_subs.retain(|sub| !sub.starts_with(&prefix));
+        self.denied_subs.retain(|sub| !sub.starts_with(&prefix));
+    }
+
+    fn set_sub(&mut self, name: &str, sub: &str, allow: bool) {
+        let full = format!("{name}|{sub}");
+        match (self.allowed.contains(name), allow) {
+            (true, true) => self.denied_subs.remove(&full),
+            (true, false) => self.denied_subs.insert(full),
+            (false, true) => self.allowed_subs.insert(full),
+            (false, false) => self.allowed_subs.remove(&full),
+        };
+    }
+
+    // +cmd、-cmd、+cmd|sub、+@category 等规则
+    fn apply(&mut self, rule: &str) -> Result<(), &'static str> {
+        let allow = rule.starts_with('+');
+        let name = rule[1..].to_lowercase();
+        if name == "@all" {
+            *self = if allow { Self::all() } else { Self::none() };
+            return Ok(());
+        }
+        if let Some(category) = name.strip_prefix('@') {
+            let bit = self::category(category).ok_or(UNKNOWN_COMMAND)?;
+            for spec in COMMAND_TABLE {
+                match subcommands(spec.name) {
+                    Some(subcommands) => subcommands
+                        .iter()
+                        .filter(|(_, categories)| categories & bit != 0)
+                        .for_each(|(sub, _)| self.set_sub(spec.name, sub, allow)),
+                    None if categories(spec) & bit != 0 => self.set(spec.name, allow),
+                    None => {}
+                }
+            }
+        } else if let Some((command, sub)) = name.split_once('|') {
+            let spec = command_table::lookup(command).ok_or(UNKNOWN_COMMAND)?;
+            if !CONTAINER_COMMANDS.contains(&spec.name) || sub.is_empty() || sub.contains('|') {
+                return Err(FIRST_ARG);
+            }
+            self.set_sub(spec.name, sub, allow);
+        } else {
+            let spec = command_table::lookup(&name).ok_or(UNKNOWN_COMMAND)?;
+            self.set(spec.name, allow);
+        }
+        if !allow {
+            self.all = false;
+        }
+        self.rules
+            .push(format!("{}{name}", if allow { '+' } else { '-' }));
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct KeyPattern {
+    pattern: String,
+    read: bool,
+    write: bool,
+}
+
+impl KeyPattern {
+    fn describe(&self) -> String {
+        match (self.read, self.write) {
+            (true, true) => format!("~{}", self.pattern),
+            (true, false) => format!("%R~{}", self.pattern),
+            _ => format!("%W~{}", self.pattern),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct User {
+    pub name: String,
+    enabled: bool,
+    nopass: bool,
+    // 密码的 SHA-256
+    passwords: BTreeSet<String>,
+    commands: Commands,
+    allkeys: bool,
+    keys: Vec<KeyPattern>,
+    allchannels: bool,
+    channels: Vec<String>,
+}
+
+impl User {
+    /// 新建的用户是关闭的，没有密码，不能执行任何命令
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            enabled: false,
+            nopass: false,
+            passwords: BTreeSet::new(),
+            commands: Commands::none(),
+            allkeys: false,
+            keys: Vec::new(),
+            allchannels: false,
+            channels: Vec::new(),
+        }
+    }
+
+    fn default_user() -> Self {
+        let mut user = Self::new(DEFAULT_USER);
+        for rule in ["on", "nopass", "~*", "&*", "+@all"] {
+            let _ = user.apply(rule);
+        }
+        user
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn nopass(&self) -> bool {
+        self.nopass
+    }
+
+    /// 应用一条 ACL SETUSER 规则，返回错误原因
+    pub fn apply(&mut self, rule: &str) -> Result<(), &'static str> {
+        match rule.to_lowercase().as_str() {
+            "on" => self.enabled = true,
+            "off" => self.enabled = false,
+            "nopass" => {
+                self.passwords.clear();
+                self.nopass = true;
+            }
+            "resetpass" => {
+                self.passwords.clear();
+                self.nopass = false;
+            }
+            "allkeys" => self.add_key("*", true, true)?,
+            "resetkeys" => {
+                self.allkeys = false;
+                self.keys.clear();
+            }
+            "allchannels" => self.add_channel("*")?,
+            "resetchannels" => {
+                self.allchannels = false;
+                self.channels.clear();
+            }
+            "allcommands" => self.commands.apply("+@all")?,
+            "nocommands" => self.commands.apply("-@all")?,
+            "reset" => *self = Self::new(&self.name),
+            _ => match rule.as_bytes().first() {
+                Some(b'>') => {
+                    self.passwords.insert(hash_password(&rule[1..]));
+                    self.nopass = false;
+                }
+                Some(b'<') => {
+                    if !self.passwords.remove(&hash_password(&rule[1..])) {
+                        return Err(NO_SUCH_PASSWORD);
+                    }
+                }
+                Some(b'#') => {
+                    if !valid_hash(&rule[1..]) {
+                        return Err(BAD_HASH);
+                    }
+                    self.passwords.insert(rule[1..].to_owned());
+                    self.nopass = false;
+                }
+                Some(b'!') => {
+                    if !valid_hash(&rule[1..]) {
+                        return Err(BAD_HASH);
+                    }
+                    if !self.passwords.remove(&rule[1..]) {
+                        return Err(NO_SUCH_PASSWORD);
+                    }
+                }
+                Some(b'~') => self.add_key(&rule[1..], true, true)?,
+                // %R~pattern、%W~pattern、%RW~pattern
+                Some(b'%') => {
+                    let (flags, pattern) = rule[1..].split_once('~').ok_or(SYNTAX_ERROR)?;
+                    let mut read = false;
+                    let mut write = false;
+                    for flag in flags.chars() {
+                        match flag.to_ascii_uppercase() {
+                            'R' => read = true,
+                            'W' => write = true,
+                            _ => return Err(SYNTAX_ERROR),
+                        }
+                    }
+                    if !read && !write {
+                        return Err(SYNTAX_ERROR);
+                    }
+                    self.add_key(pattern, read, write)?;
+                }
+                Some(b'&') => self.add_channel(&rule[1..])?,
+                Some(b'+') | Some(b'-') => self.commands.apply(rule)?,
+                _ => return Err(SYNTAX_ERROR),
+            },
+        }
+        Ok(())
+    }
+
+    fn add_key(&mut self, pattern: &str, read: bool, write: bool) -> Result<(), &'static str> {
+        if self.allkeys {
+            return Err(KEY_AFTER_ALL);
+        }
+        if pattern == "*" && read && write {
+            self.allkeys = true;
+            self.keys.clear();
+            return Ok(());
+        }
+        let pattern = KeyPattern {
+            pattern: pattern.to_owned(),
+            read,
+            write,
+        };
+        if !self.keys.contains(&pattern) {
+            self.keys.push(pattern);
+        }
+        Ok(())
+    }
+
+    fn add_channel(&mut self, pattern: &str) -> Result<(), &'static str> {
+        if self.allchannels {
+            return Err(CHANNEL_AFTER_ALL);
+        }
+        if pattern == "*" {
+            self.allchannels = true;
+            self.channels.clear();
+        } else if !self.channels.iter().any(|channel| channel == pattern) {
+            self.channels.push(pattern.to_owned());
+        }
+        Ok(())
+    }
+
+    fn authenticate(&self, password: &str) -> bool {
+        self.enabled && (self.nopass || self.passwords.contains(&hash_password(password)))
+    }
+
+    fn key_allowed(&self, key: &str, write: bool) -> bool {
+        self.allkeys
+            || self.keys.iter().any(|pattern| {
+                (if write { pattern.write } else { pattern.read })
+                    && glob::glob_match(&pattern.pattern, key)
+            })
+    }
+
+    // PSUBSCRIBE 的模式必须和用户的某个模式完全相同
+    fn channel_allowed(&self, channel: &str, is_pattern: bool) -> bool {
+        self.allchannels
+            || self.channels.iter().any(|pattern| {
+                if is_pattern {
+                    pattern == channel
+                } else {
+                    glob::glob_match(pattern, channel)
+                }
+            })
+    }
+
+    /// 检查用户能否执行 `argv`
+    pub fn check(&self, argv: &[&str]) -> Result<(), Denial> {
+        let name = argv.first().copied().unwrap_or_default().to_lowercase();
+        let Some(spec) = command_table::lookup(&name) else {
+            if self.commands.all {
+                return Ok(());
+            }
+            return Err(Denial::new(Reason::Command, name));
+        };
+        if spec.no_auth() {
+            return Ok(());
+        }
+        let sub = argv
+            .get(1)
+            .filter(|_| CONTAINER_COMMANDS.contains(&spec.name))
+            .map(|sub| sub.to_lowercase());
+        if !self.commands.allows(spec.name, sub.as_deref()) {
+            let object = match &sub {
+                Some(sub) => format!("{}|{sub}", spec.name),
+                None => spec.name.to_owned(),
+            };
+            return Err(Denial::new(Reason::Command, object));
+        }
+
+        let channels: Vec<(&str, bool)> = match spec.name {
+            "publish" | "spublish" => argv
+                .get(1)
+                .map(|channel| (*channel, false))
+                .into_iter()
+                .collect(),
+            "subscribe" | "ssubscribe" => {
+                argv[1..].iter().map(|channel| (*channel, false)).collect()
+            }
+            "psubscribe" => argv[1..].iter().map(|pattern| (*pattern, true)).collect(),
+            // 分片频道在 command table 中占用键的位置，不按键检查
+            "sunsubscribe" => return Ok(()),
+            _ => {
+                for key in command_table::argv_keys(argv) {
+                    if !self.key_allowed(key, spec.is_write()) {
+                        return Err(Denial::new(Reason::Key, key.to_owned()));
+                    }
+                }
+                Vec::new()
+            }
+        };
+        for (channel, is_pattern) in channels {
+            if !self.channel_allowed(channel, is_pattern) {
+                return Err(Denial::new(Reason::Channel, channel.to_owned()));
+            }
+        }
+        Ok(())
+    }
+
+    /// ACL GETUSER 中的 flags
+    pub fn flags(&self) -> Vec<&'static str> {
+        let mut flags = vec![if self.enabled { "on" } else { "off" }];
+        if self.nopass {
+            flags.push("nopass");
+        }
+        flags
+    }
+
+    pub fn passwords(&self) -> Vec<String> {
+        self.passwords.iter().cloned().collect()
+    }
+
+    pub fn describe_commands(&self) -> String {
+        self.commands.rules.join(" ")
+    }
+
+    pub fn describe_keys(&self) -> String {
+        if self.allkeys {
+            return "~*".to_owned();
+        }
+        self.keys
+            .iter()
+            .map(KeyPattern::describe)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn describe_channels(&self) -> String {
+        if self.allchannels {
+            return "&*".to_owned();
+        }
+        self.channels
+            .iter()
+            .map(|channel| format!("&{channel}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// ACL LIST 和 ACL 文件中的一行
+    pub fn describe(&self) -> String {
+        let mut parts = vec![
+            "user".to_owned(),
+            self.name.clone(),
+            self.flags()[0].to_owned(),
+        ];
+        if self.nopass {
+            parts.push("nopass".to_owned());
+        }
+        parts.extend(self.passwords.iter().map(|hash| format!("#{hash}")));
+        let keys = self.describe_keys();
+        if !keys.is_empty() {
+            parts.push(keys);
+        }
+        if !self.allchannels {
+            parts.push("resetchannels".to_owned());
+        }
+        let channels = self.describe_channels();
+        if !channels.is_empty() {
+            parts.push(channels);
+        }
+        parts.push(self.describe_commands());
+        parts.join(" ")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    Command,
+    Key,
+    Channel,
+    Auth,
+}
+
+impl Reason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Reason::Command => "command",
+            Reason::Key => "key",
+            Reason::Channel => "channel",
+            Reason::Auth => "auth",
+        }
+    }
+}
+
+/// 权限检查失败的原因和被拒绝的命令、键或频道
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Denial {
+    pub reason: Reason,
+    pub object: String,
+}
+
+impl Denial {
+    fn new(reason: Reason, object: String) -> Self {
+        Self { reason, object }
+    }
+
+    /// 回复给客户端的错误
+    pub fn error(&self, username: &str) -> String {
+        match self.reason {
+            Reason::Command => format!(
+                "NOPERM User {username} has no permissions to run the '{}' command",
+                self.object
+            ),
+            Reason::Key => "NOPERM No permissions to access a key".to_owned(),
+            Reason::Channel => "NOPERM No permissions to access a channel".to_owned(),
+            Reason::Auth => {
+                "WRONGPASS invalid username-password pair or user is disabled.".to_owned()
+            }
+        }
+    }
+
+    /// ACL DRYRUN 的说明，会给出具体的键和频道
+    pub fn describe(&self, username: &str) -> String {
+        match self.reason {
+            Reason::Command | Reason::Auth => format!(
+                "User {username} has no permissions to run the '{}' command",
+                self.object
+            ),
+            Reason::Key => format!(
+                "User {username} has no permissions to access the '{}' key",
+                self.object
+            ),
+            Reason::Channel => format!(
+                "User {username} has no permissions to access the '{}' channel",
+                self.object
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub id: u64,
+    pub count: u64,
+    pub reason: Reason,
+    // toplevel 或 multi
+    pub context: String,
+    pub object: String,
+    pub username: String,
+    // Unix 时间（毫秒）
+    pub created: u64,
+    pub updated: u64,
+    pub client_info: String,
+}
+
+#[derive(Debug)]
+pub struct Acl {
+    users: RwLock<BTreeMap<String, User>>,
+    // requirepass 的明文，CONFIG GET 时返回
+    requirepass: Mutex<String>,
+    // ACL SAVE/LOAD 读写的文件
+    file: Mutex<Option<PathBuf>>,
+    log: Mutex<VecDeque<LogEntry>>,
+    log_max_len: AtomicUsize,
+    next_log_id: AtomicU64,
+    // INFO 中按原因统计的拒绝次数，顺序和 Reason 一致
+    denied: [AtomicU64; 4],
+}
+
+impl Default for Acl {
+    fn default() -> Self {
+        let mut users = BTreeMap::new();
+        users.insert(DEFAULT_USER.to_owned(), User::default_user());
+        Self {
+            users: RwLock::new(users),
+            requirepass: Mutex::new(String::new()),
+            file: Mutex::new(None),
+            log: Mutex::new(VecDeque::new()),
+            log_max_len: AtomicUsize::new(DEFAULT_LOG_MAX_LEN),
+            next_log_id: AtomicU64::new(0),
+            denied: Default::default(),
+        }
+    }
+}
+
+impl Acl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, BTreeMap<String, User>> {
+        self.users.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, BTreeMap<String, User>> {
+        self.users.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// ACL SETUSER：依次应用规则，任一规则出错时都不生效
+    pub fn set_user(&self, name: &str, rules: &[&str]) -> Result<(), String> {
+        if name.contains([' ', '\0']) {
+            return Err("ERR Usernames can't contain spaces or null characters".to_owned());
+        }
+        let mut users = self.write();
+        let mut user = users.get(name).cloned().unwrap_or_else(|| User::new(name));
+        for rule in rules {
+            user.apply(rule).map_err(|reason| {
+                format!("ERR Error in ACL SETUSER modifier '{rule}': {reason}")
+            })?;
+        }
+        users.insert(name.to_owned(), user);
+        Ok(())
+    }
+
+    pub fn user(&self, name: &str) -> Option<User> {
+        self.read().get(name).cloned()
+    }
+
+    /// ACL DELUSER，返回删除的用户数
+    pub fn delete_users(&self, names: &[String]) -> Result<usize, String> {
+        if names.iter().any(|name| name == DEFAULT_USER) {
+            return Err("ERR The 'default' user cannot be removed".to_owned());
+        }
+        let mut users = self.write();
+        Ok(names
+            .iter()
+            .filter(|name| users.remove(name.as_str()).is_some())
+            .count())
+    }
+
+    pub fn usernames(&self) -> Vec<String> {
+        self.read().keys().cloned().collect()
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        self.read().contains_key(name)
+    }
+
+    /// ACL LIST
+    pub fn list(&self) -> Vec<String> {
+        self.read().values().map(User::describe).collect()
+    }
+
+    /// 用户存在、没有被关闭并且密码正确（或 nopass）
+    pub fn authenticate(&self, name: &str, password: &str) -> bool {
+        self.read()
+            .get(name)
+            .is_some_and(|user| user.authenticate(password))
+    }
+
+    /// 新连接是否需要先认证：default 用户被关闭或设置了密码
+    pub fn requires_auth(&self) -> bool {
+        self.read()
+            .get(DEFAULT_USER)
+            .is_none_or(|user| !(user.enabled && user.nopass))
+    }
+
+    /// 检查用户能否执行 `frame`，用户不存在时拒绝
+    pub fn check(&self, username: &str, frame: &Resp) -> Result<(), Denial> {
+        let argv = command_table::command_argv(frame);
+        match self.read().get(username) {
+            Some(user) => user.check(&argv),
+            None => Err(Denial::new(
+                Reason::Command,
+                argv.first().copied().unwrap_or_default().to_lowercase(),
+            )),
+        }
+    }
+
+    /// 检查当前客户端能否执行 `frame`，被拒绝时记录 ACL LOG 并返回错误信息
+    ///
+    /// `context` 为 toplevel 或 multi；不在连接中执行的命令（例如主节点同步过来的）不检查。
+    pub fn check_current(&self, frame: &Resp, context: &str) -> Result<(), String> {
+        let Some(client) = crate::client::current() else {
+            return Ok(());
+        };
+        // 主节点发来的复制流不受副本上的 ACL 限制
+        if client.is_master() {
+            return Ok(());
+        }
+        let username = client.user();
+        self.check(&username, frame).map_err(|denial| {
+            self.log(denial.reason, context, &denial.object, &username);
+            denial.error(&username)
+        })
+    }
+
+    /// 记录一次拒绝，一分钟内相同的拒绝只增加计数
+    pub fn log(&self, reason: Reason, context: &str, object: &str, username: &str) {
+        self.denied[reason as usize].fetch_add(1, Ordering::Relaxed);
+        let client_info = crate::client::current()
+            .map(|client| client.info_line())
+            .unwrap_or_default();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let max_len = self.log_max_len();
+        let mut log = self.lock_log();
+        let similar = log.iter().position(|entry| {
+            entry.reason == reason
+                && entry.context == context
+                && entry.object == object
+                && entry.username == username
+                && now.saturating_sub(entry.updated) < LOG_GROUPING_MILLIS
+        });
+        let entry = match similar.and_then(|pos| log.remove(pos)) {
+            Some(mut entry) => {
+                entry.count += 1;
+                entry.updated = now;
+                entry.client_info = client_info;
+                entry
+            }
+            None => LogEntry {
+                id: self.next_log_id.fetch_add(1, Ordering::Relaxed),
+                count: 1,
+                reason,
+                context: context.to_owned(),
+                object: object.to_owned(),
+                username: username.to_owned(),
+                created: now,
+                updated: now,
+                client_info,
+            },
+        };
+        log.push_front(entry);
+        log.truncate(max_len);
+    }
+
+    fn lock_log(&self) -> std::sync::MutexGuard<'_, VecDeque<LogEntry>> {
+        self.log.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 最新的 `count` 条日志
+    pub fn log_entries(&self, count: usize) -> Vec<LogEntry> {
+        self.lock_log().iter().take(count).cloned().collect()
+    }
+
+    pub fn reset_log(&self) {
+        self.lock_log().clear();
+    }
+
+    pub fn log_max_len(&self) -> usize {
+        self.log_max_len.load(Ordering::Relaxed)
+    }
+
+    pub fn set_log_max_len(&self, max_len: usize) {
+        self.log_max_len.store(max_len, Ordering::Relaxed);
+        self.lock_log().truncate(max_len);
+    }
+
+    /// 按原因统计的拒绝次数
+    pub fn denied(&self, reason: Reason) -> u64 {
+        self.denied[reason as usize].load(Ordering::Relaxed)
+    }
+
+    pub fn requirepass(&self) -> String {
+        self.requirepass
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// requirepass：给 default 用户设置唯一的密码，为空时恢复 nopass
+    pub fn set_requirepass(&self, password: &str) {
+        let mut users = self.write();
+        let user = users
+            .entry(DEFAULT_USER.to_owned())
+            .or_insert_with(User::default_user);
+        let _ = user.apply("resetpass");
+        if password.is_empty() {
+            let _ = user.apply("nopass");
+        } else {
+            let _ = user.apply(&format!(">{password}"));
+        }
+        *self.requirepass.lock().unwrap_or_else(|e| e.into_inner()) = password.to_owned();
+    }
+
+    pub fn file(&self) -> Option<PathBuf> {
+        self.file.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn set_file(&self, path: PathBuf) {
+        *self.file.lock().unwrap_or_else(|e| e.into_inner()) = Some(path);
+    }
+
+    /// 从 ACL 文件加载所有用户，任一行出错时保持原来的用户不变
+    ///
+    /// 文件中没有 default 用户时使用默认的 default 用户。
+    pub fn load(&self) -> Result<(), String> {
+        let Some(path) = self.file() else {
+            return Err(NO_ACL_FILE.to_owned());
+        };
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            format!(
+                "ERR Error loading ACLs, opening file '{}': {e}",
+                path.display()
+            )
+        })?;
+        let mut users = BTreeMap::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |msg: &str| format!("ERR {}:{}: {msg}", path.display(), index + 1);
+            let mut args = line.split_whitespace();
+            if args.next() != Some("user") {
+                return Err(error("line should start with user keyword"));
+            }
+            let Some(name) = args.next() else {
+                return Err(error("missing username"));
+            };
+            if users.contains_key(name) {
+                return Err(error(&format!("Duplicate user '{name}' found")));
+            }
+            let mut user = User::new(name);
+            for rule in args {
+                user.apply(rule).map_err(|reason| {
+                    error(&format!("Error in applying operation '{rule}': {reason}"))
+                })?;
+            }
+            users.insert(name.to_owned(), user);
+        }
+        users
+            .entry(DEFAULT_USER.to_owned())
+            .or_insert_with(User::default_user);
+        let count = users.len();
+        *self.write() = users;
+        info!("🔐 Loaded {} ACL users from {}", count, path.display());
+        Ok(())
+    }
+
+    /// 把所有用户写入 ACL 文件，先写临时文件再替换
+    pub fn save(&self) -> Result<(), String> {
+        let Some(path) = self.file() else {
+            return Err(NO_ACL_FILE.to_owned());
+        };
+        let mut content = self.list().join("\n");
+        content.push('\n');
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, content)
+            .and_then(|_| std::fs::rename(&tmp, &path))
+            .map_err(|e| {
+                error!("❌ Failed to save ACL file {}: {}", path.display(), e);
+                "ERR There was an error trying to save the ACLs. Please check the server logs for more information".to_owned()
+            })
+    }
+}
+
+const NO_ACL_FILE: &str = "ERR This Redis instance is not configured to use an ACL file. You may want to specify users via the ACL SETUSER command and then issue a CONFIG REWRITE (assuming you have a Redis configuration file set) in order to store users in the Redis configuration.";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(rules: &[&str]) -> User {
+        let mut user = User::new("alice");
+        for rule in rules {
+            user.apply(rule).unwrap();
+        }
+        user
+    }
+
+    #[test]
+    fn test_default_user() {
+        let acl = Acl::new();
+        assert!(!acl.requires_auth());
+        assert!(acl.authenticate(DEFAULT_USER, "anything"));
+        assert_eq!(acl.list(), vec!["user default on nopass ~* &* +@all"]);
+
+        acl.set_requirepass("secret");
+        assert!(acl.requires_auth());
+        assert!(!acl.authenticate(DEFAULT_USER, "wrong"));
+        assert!(acl.authenticate(DEFAULT_USER, "secret"));
+        acl.set_requirepass("");
+        assert!(!acl.requires_auth());
+    }
+
+    #[test]
+    fn test_set_user() {
+        let acl = Acl::new();
+        acl.set_user("alice", &[]).unwrap();
+        assert_eq!(acl.list()[0], "user alice off resetchannels -@all");
+        assert!(!acl.authenticate("alice", ""));
+
+        acl.set_user("alice", &["on", ">p1", "~cache:*", "+get"])
+            .unwrap();
+        assert!(acl.authenticate("alice", "p1"));
+        assert_eq!(
+            acl.list()[0],
+            format!(
+                "user alice on #{} ~cache:* resetchannels -@all +get",
+                hash_password("p1")
+            )
+        );
+        // 任一规则出错时都不生效
+        assert_eq!(
+            acl.set_user("alice", &["off", "+nosuch"]).unwrap_err(),
+            "ERR Error in ACL SETUSER modifier '+nosuch': Unknown command or category name in ACL"
+        );
+        assert!(acl.user("alice").unwrap().enabled());
+        assert!(acl.set_user("alice", &["~*", "~foo"]).is_err());
+        assert!(acl.set_user("alice", &["#abc"]).is_err());
+        assert!(acl.set_user("alice", &["<nosuch"]).is_err());
+    }
+
+    #[test]
+    fn test_command_permissions() {
+        let alice = user(&["on", "allkeys", "+@all", "-@admin", "-flushall"]);
+        assert!(alice.check(&["get", "k"]).is_ok());
+        assert!(alice.check(&["client", "setname", "x"]).is_ok());
+        assert_eq!(
+            alice.check(&["CLIENT", "KILL", "id", "1"]).unwrap_err(),
+            Denial::new(Reason::Command, "client|kill".to_owned())
+        );
+        assert!(alice.check(&["flushall"]).is_err());
+        assert!(alice.check(&["config", "get", "*"]).is_err());
+        // AUTH 和 HELLO 不受限制
+        let nobody = user(&["on"]);
+        assert!(nobody.check(&["auth", "x"]).is_ok());
+        assert!(nobody.check(&["ping"]).is_err());
+
+        let reader = user(&["on", "allkeys", "+@read", "+client|id"]);
+        assert!(reader.check(&["hgetall", "h"]).is_ok());
+        assert!(reader.check(&["set", "k", "v"]).is_err());
+        assert!(reader.check(&["client", "id"]).is_ok());
+        assert!(reader.check(&["client", "list"]).is_err());
+    }
+
+    #[test]
+    fn test_key_and_channel_permissions() {
+        let alice = user(&["on", "+@all", "~app:*", "%R~ro:*", "%W~wo:*", "&news.*"]);
+        assert!(alice.check(&["set", "app:1", "v"]).is_ok());
+        assert!(alice.check(&["get", "ro:1"]).is_ok());
+        assert!(alice.check(&["set", "ro:1", "v"]).is_err());
+        assert!(alice.check(&["set", "wo:1", "v"]).is_ok());
+        assert!(alice.check(&["get", "wo:1"]).is_err());
+        assert_eq!(
+            alice.check(&["del", "app:1", "other"]).unwrap_err(),
+            Denial::new(Reason::Key, "other".to_owned())
+        );
+        assert!(alice.check(&["publish", "news.tech", "hi"]).is_ok());
+        assert!(alice.check(&["subscribe", "news.a", "sports"]).is_err());
+        // 模式必须和用户的模式完全相同
+        assert!(alice.check(&["psubscribe", "news.*"]).is_ok());
+        assert!(alice.check(&["psubscribe", "news.t*"]).is_err());
+        assert_eq!(
+            alice.describe(),
+            "user alice on ~app:* %R~ro:* %W~wo:* resetchannels &news.* +@all"
+        );
+    }
+
+    #[test]
+    fn test_log_groups_similar_entries() {
+        let acl = Acl::new();
+        acl.log(Reason::Command, "toplevel", "get", "alice");
+        acl.log(Reason::Key, "toplevel", "k", "alice");
+        acl.log(Reason::Command, "toplevel", "get", "alice");
+        let entries = acl.log_entries(10);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].object, "get");
+        assert_eq!(entries[0].count, 2);
+        assert_eq!(acl.denied(Reason::Command), 2);
+
+        acl.set_log_max_len(1);
+        assert_eq!(acl.log_entries(10).len(), 1);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path =
+            std::env::temp_dir().join(format!("simple-redis-acl-{}.acl", std::process::id()));
+        let acl = Acl::new();
+        acl.set_file(path.clone());
+        acl.set_user("alice", &["on", ">secret", "~*", "+@read", "-client|kill"])
+            .unwrap();
+        acl.save().unwrap();
+
+        let loaded = Acl::new();
+        loaded.set_file(path.clone());
+        loaded.load().unwrap();
+        assert_eq!(loaded.list(), acl.list());
+        assert!(loaded.authenticate("alice", "secret"));
+
+        std::fs::write(&path, "user bob on\nbob off\n").unwrap();
+        assert!(loaded
+            .load()
+            .unwrap_err()
+            .ends_with(":2: line should start with user keyword"));
+        assert!(loaded.exists("alice"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,246 @@
+//! 找出每种类型中最大的键，对应 `redis-cli --bigkeys` 和 `redis-cli --memkeys`
+//!
+//! 用 SCAN 遍历整个键空间，对每个键执行 TYPE，再按类型取元素数量
+//! （STRLEN/HLEN/LLEN/SCARD/ZCARD），`--memkeys` 时改用 MEMORY USAGE 取内存占用。
+//!
+//! 用法：bigkeys [--host <host>] [--port <port>] [--memkeys] [--memkeys-samples <n>]
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+use anyhow::Result;
+use bytes::BytesMut;
+use simple_redis::{Arrays, BulkStrings, Resp, RespDecoder, RespEncoder, RespError};
+
+// 每次 SCAN 的 COUNT
+const SCAN_COUNT: &str = "100";
+const TYPES: [&str; 5] = ["string", "list", "set", "zset", "hash"];
+
+fn main() -> Result<()> {
+    let args = Args::parse(std::env::args().skip(1))?;
+    let mut conn = Connection::connect(&args.host, args.port)?;
+
+    println!("# Scanning the entire keyspace to find biggest keys as well as");
+    println!("# average sizes per key type.\n");
+
+    let mut stats: BTreeMap<&str, TypeStats> = BTreeMap::new();
+    let mut sampled = 0u64;
+    let mut total_key_len = 0u64;
+    let mut cursor = "0".to_owned();
+    loop {
+        let reply = conn.query(&["SCAN", &cursor, "COUNT", SCAN_COUNT])?;
+        let (next, keys) = parse_scan(reply)?;
+        for key in keys {
+            let Some(key_type) = TYPES.iter().find(|t| **t == conn.key_type(&key)) else {
+                // 扫描过程中被删除的键
+                continue;
+            };
+            let size = if args.memkeys {
+                let samples = args.samples.to_string();
+                conn.integer(&["MEMORY", "USAGE", &key, "SAMPLES", &samples])?
+            } else {
+                let command = match *key_type {
+                    "string" => "STRLEN",
+                    "list" => "LLEN",
+                    "set" => "SCARD",
+                    "zset" => "ZCARD",
+                    _ => "HLEN",
+                };
+                conn.integer(&[command, &key])?
+            };
+            sampled += 1;
+            total_key_len += key.len() as u64;
+            let entry = stats.entry(key_type).or_default();
+            entry.count += 1;
+            entry.total += size;
+            if entry.biggest.as_ref().is_none_or(|(_, max)| size > *max) {
+                println!(
+                    "Biggest {key_type:>6} found so far '\"{key}\"' with {size} {}",
+                    unit(key_type, args.memkeys)
+                );
+                entry.biggest = Some((key, size));
+            }
+        }
+        cursor = next;
+        if cursor == "0" {
+            break;
+        }
+    }
+
+    println!("\n-------- summary -------\n");
+    println!("Sampled {sampled} keys in the keyspace!");
+    let avg_key_len = if sampled == 0 {
+        0.0
+    } else {
+        total_key_len as f64 / sampled as f64
+    };
+    println!("Total key length in bytes is {total_key_len} (avg len {avg_key_len:.2})\n");
+    for key_type in TYPES {
+        if let Some((key, size)) = stats.get(key_type).and_then(|s| s.biggest.as_ref()) {
+            println!(
+                "Biggest {key_type:>6} found '\"{key}\"' has {size} {}",
+                unit(key_type, args.memkeys)
+            );
+        }
+    }
+    println!();
+    for key_type in TYPES {
+        let s = stats.get(key_type).cloned().unwrap_or_default();
+        let percentage = if sampled == 0 {
+            0.0
+        } else {
+            s.count as f64 * 100.0 / sampled as f64
+        };
+        let avg = if s.count == 0 {
+            0.0
+        } else {
+            s.total as f64 / s.count as f64
+        };
+        println!(
+            "{} {key_type}s with {} {} ({percentage:.2}% of keys, avg size {avg:.2})",
+            s.count,
+            s.total,
+            unit(key_type, args.memkeys)
+        );
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default, Clone)]
+struct TypeStats {
+    count: u64,
+    total: u64,
+    biggest: Option<(String, u64)>,
+}
+
+fn unit(key_type: &str, memkeys: bool) -> &'static str {
+    if memkeys {
+        return "bytes";
+    }
+    match key_type {
+        "string" => "bytes",
+        "list" => "items",
+        "hash" => "fields",
+        _ => "members",
+    }
+}
+
+fn parse_scan(reply: Resp) -> Result<(String, Vec<String>)> {
+    let Resp::Arrays(reply) = reply else {
+        return Err(anyhow::anyhow!("unexpected SCAN reply: {reply:?}"));
+    };
+    match reply.val.as_slice() {
+        [Resp::BulkStrings(cursor), Resp::Arrays(keys)] => {
+            let keys = keys
+                .val
+                .iter()
+                .filter_map(|key| match key {
+                    Resp::BulkStrings(key) => Some(key.val.clone()),
+                    _ => None,
+                })
+                .collect();
+            Ok((cursor.val.clone(), keys))
+        }
+        _ => Err(anyhow::anyhow!("unexpected SCAN reply")),
+    }
+}
+
+struct Connection {
+    stream: TcpStream,
+    buf: BytesMut,
+}
+
+impl Connection {
+    fn connect(host: &str, port: u16) -> Result<Self> {
+        Ok(Self {
+            stream: TcpStream::connect((host, port))?,
+            buf: BytesMut::new(),
+        })
+    }
+
+    fn query(&mut self, args: &[&str]) -> Result<Resp> {
+        let frame = Resp::Arrays(Arrays::new(
+            args.iter()
+                .map(|arg| Resp::BulkStrings(BulkStrings::new(arg.to_string())))
+                .collect(),
+        ));
+        self.stream.write_all(&frame.encode()?)?;
+        loop {
+            // 数据不完整时不消耗缓冲区
+            match Resp::decode(&mut self.buf) {
+                Ok(Resp::SimpleErrors(e)) => {
+                    return Err(anyhow::anyhow!("{} failed: {}", args[0], e.error_msg));
+                }
+                Ok(reply) => return Ok(reply),
+                Err(RespError::NotComplete) => {}
+                Err(e) => return Err(e.into()),
+            }
+            let mut chunk = [0u8; 4096];
+            let n = self.stream.read(&mut chunk)?;
+            if n == 0 {
+                return Err(anyhow::anyhow!("connection closed by server"));
+            }
+            self.buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    fn integer(&mut self, args: &[&str]) -> Result<u64> {
+        match self.query(args)? {
+            Resp::Integers(i) => Ok(i.val.max(0) as u64),
+            // 键在扫描过程中被删除
+            _ => Ok(0),
+        }
+    }
+
+    fn key_type(&mut self, key: &str) -> String {
+        match self.query(&["TYPE", key]) {
+            Ok(Resp::SimpleStrings(s)) => s.val,
+            _ => "none".to_owned(),
+        }
+    }
+}
+
+struct Args {
+    host: String,
+    port: u16,
+    memkeys: bool,
+    samples: usize,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut parsed = Self {
+            host: "127.0.0.1".to_owned(),
+            port: 6379,
+            memkeys: false,
+            samples: simple_redis::memory::DEFAULT_SAMPLES,
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--host" | "-h" => {
+                    parsed.host = args
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("--host requires a value"))?;
+                }
+                "--port" | "-p" => {
+                    parsed.port = args
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("--port requires a value"))?
+                        .parse()?;
+                }
+                "--bigkeys" => parsed.memkeys = false,
+                "--memkeys" => parsed.memkeys = true,
+                "--memkeys-samples" => {
+                    parsed.memkeys = true;
+                    parsed.samples = args
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("--memkeys-samples requires a value"))?
+                        .parse()?;
+                }
+                other => return Err(anyhow::anyhow!("unknown argument: {other}")),
+            }
+        }
+        Ok(parsed)
+    }
+}
@@ -0,0 +1,783 @@
+//! 客户端连接表
+//!
+//! 每个连接在 accept 之后注册一个 `Client`，保存 id、地址、名字、选择的库、最近执行的
+//! 命令、缓冲区大小等信息，供 CLIENT LIST / INFO / KILL 使用。连接断开时自动注销。
+//!
+//! 正在执行命令的客户端放在线程局部变量中，慢查询日志、MONITOR 和 CLIENT 命令
+//! 通过 `current()` 取得。CLIENT PAUSE 的状态也保存在这里，由连接在执行命令前等待。
+
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+use crate::limits::{Limits, Outbox, OutputClass};
+use crate::pubsub::Message;
+use crate::tracking::TrackingOptions;
+
+// 带子命令的命令，cmd= 中显示为 `命令|子命令`
+pub(crate) const CONTAINER_COMMANDS: &[&str] = &[
+    "acl", "client", "cluster", "command", "config", "latency", "memory", "object", "pubsub",
+    "slowlog",
+];
+
+thread_local! {
+    // 正在执行命令的客户端
+    static CURRENT: RefCell<Option<Arc<Client>>> = const { RefCell::new(None) };
+}
+
+/// 以 `client` 作为当前客户端执行 `f`，结束后（包括 panic 时）恢复原来的客户端
+pub(crate) fn with_client<R>(client: &Arc<Client>, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Arc<Client>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            CURRENT.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+    let previous = CURRENT.with(|current| current.replace(Some(client.clone())));
+    let _restore = Restore(previous);
+    f()
+}
+
+/// 正在执行命令的客户端，不在连接中执行时为 None
+pub(crate) fn current() -> Option<Arc<Client>> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// 正在执行命令的客户端地址和名字，用于慢查询日志和 MONITOR
+pub(crate) fn current_addr_and_name() -> (String, String) {
+    current()
+        .map(|client| (client.addr.clone(), client.name()))
+        .unwrap_or_default()
+}
+
+/// 客户端名字和库信息中不允许出现空格、换行和不可打印字符
+pub fn valid_name(name: &str) -> bool {
+    name.bytes().all(|byte| (b'!'..=b'~').contains(&byte))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientType {
+    Normal,
+    // 连接到本节点的副本
+    Replica,
+    // 本节点作为副本时连接的主节点
+    Master,
+    PubSub,
+}
+
+impl ClientType {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "normal" => Some(Self::Normal),
+            "replica" | "slave" => Some(Self::Replica),
+            "master" => Some(Self::Master),
+            "pubsub" => Some(Self::PubSub),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseMode {
+    // 只暂停写命令
+    Write,
+    All,
+}
+
+/// CLIENT REPLY 的模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyMode {
+    On,
+    Off,
+    // 不回复下一条命令
+    Skip,
+}
+
+/// 连接在每条命令后更新的状态
+#[derive(Debug)]
+struct ClientState {
+    name: String,
+    // 认证的 ACL 用户，未认证时为 default
+    user: String,
+    authenticated: bool,
+    lib_name: String,
+    lib_ver: String,
+    db: usize,
+    resp: u8,
+    last_interaction: Instant,
+    last_cmd: String,
+    replica: bool,
+    // 本节点作为副本时到主节点的连接，复制流中的命令以它的身份执行
+    master: bool,
+    monitor: bool,
+    no_evict: bool,
+    // 通过 unix socket 连接
+    unix_socket: bool,
+    channels: usize,
+    patterns: usize,
+    shard_channels: usize,
+    // 事务中排队的命令数，None 表示不在事务中
+    multi: Option<usize>,
+    watch: usize,
+    // 读缓冲区已用和空闲的字节数，写缓冲区中待发送的字节数
+    qbuf: usize,
+    qbuf_free: usize,
+    obl: usize,
+    reply_off: bool,
+    // CLIENT REPLY OFF|SKIP 自己也不回复
+    suppress_current: bool,
+    skip_next: bool,
+    skipping: bool,
+    tracking: Option<TrackingOptions>,
+    // CLIENT CACHING yes|no，只对下一条命令（或下一个事务）有效
+    caching: Option<bool>,
+    caching_set: bool,
+    // 推送失效消息等异步消息
+    push: Option<Outbox<Message>>,
+    // 输出缓冲区开始超过软限制的时间
+    soft_limit_since: Option<Instant>,
+}
+
+#[derive(Debug)]
+pub struct Client {
+    pub id: u64,
+    pub addr: String,
+    pub laddr: String,
+    created: Instant,
+    state: Mutex<ClientState>,
+    kill: CancellationToken,
+    // 异步推送中还没有写出的字节数
+    omem: AtomicUsize,
+}
+
+/// 连接在每条命令执行后同步给 `Client` 的状态
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Snapshot {
+    pub db: usize,
+    pub resp: u8,
+    pub channels: usize,
+    pub patterns: usize,
+    pub shard_channels: usize,
+    pub multi: Option<usize>,
+    pub watch: usize,
+    pub qbuf: usize,
+    pub qbuf_free: usize,
+    pub obl: usize,
+}
+
+impl Client {
+    pub fn new(id: u64, addr: String, laddr: String) -> Self {
+        let now = Instant::now();
+        Self {
+            id,
+            addr,
+            laddr,
+            created: now,
+            state: Mutex::new(ClientState {
+                name: String::new(),
+                user: crate::acl::DEFAULT_USER.to_owned(),
+                authenticated: false,
+                lib_name: String::new(),
+                lib_ver: String::new(),
+                db: 0,
+                resp: 2,
+                last_interaction: now,
+                last_cmd: "NULL".to_owned(),
+                replica: false,
+                master: false,
+                monitor: false,
+                no_evict: false,
+                unix_socket: false,
+                channels: 0,
+                patterns: 0,
+                shard_channels: 0,
+                multi: None,
+                watch: 0,
+                qbuf: 0,
+                qbuf_free: 0,
+                obl: 0,
+                reply_off: false,
+                suppress_current: false,
+                skip_next: false,
+                skipping: false,
+                tracking: None,
+                caching: None,
+                caching_set: false,
+                push: None,
+                soft_limit_since: None,
+            }),
+            kill: CancellationToken::new(),
+            omem: AtomicUsize::new(0),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ClientState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn name(&self) -> String {
+        self.lock().name.clone()
+    }
+
+    pub fn set_name(&self, name: &str) {
+        self.lock().name = name.to_owned();
+    }
+
+    pub fn user(&self) -> String {
+        self.lock().user.clone()
+    }
+
+    /// AUTH / HELLO AUTH 成功，或者 default 用户不需要密码时以它的身份认证
+    pub fn authenticate(&self, user: &str) {
+        let mut state = self.lock();
+        state.user = user.to_owned();
+        state.authenticated = true;
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.lock().authenticated
+    }
+
+    pub fn set_lib_name(&self, lib_name: &str) {
+        self.lock().lib_name = lib_name.to_owned();
+    }
+
+    pub fn set_lib_ver(&self, lib_ver: &str) {
+        self.lock().lib_ver = lib_ver.to_owned();
+    }
+
+    pub fn set_no_evict(&self, no_evict: bool) {
+        self.lock().no_evict = no_evict;
+    }
+
+    /// 连接转为向副本传输数据
+    pub fn set_replica(&self) {
+        self.lock().replica = true;
+    }
+
+    /// 连接是本节点的主节点，执行复制流时不检查 ACL
+    pub fn set_master(&self) {
+        self.lock().master = true;
+    }
+
+    pub fn is_master(&self) -> bool {
+        self.lock().master
+    }
+
+    pub fn set_monitor(&self) {
+        self.lock().monitor = true;
+    }
+
+    pub fn is_monitor(&self) -> bool {
+        self.lock().monitor
+    }
+
+    /// 收到一条命令，`frame` 为命令的原始参数
+    pub fn begin_command(&self, frame: &crate::Resp) {
+        let name = crate::process::command_table::command_name(frame)
+            .unwrap_or_default()
+            .to_lowercase();
+        let subcommand = match frame {
+            crate::Resp::Arrays(arr) if CONTAINER_COMMANDS.contains(&name.as_str()) => {
+                match arr.val.get(1) {
+                    Some(crate::Resp::BulkStrings(sub)) => Some(sub.val.to_lowercase()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        let mut state = self.lock();
+        state.last_interaction = Instant::now();
+        state.last_cmd = match subcommand {
+            Some(subcommand) => format!("{name}|{subcommand}"),
+            None => name,
+        };
+    }
+
+    /// 命令执行完后同步连接的状态
+    pub fn update(&self, snapshot: Snapshot) {
+        let mut state = self.lock();
+        // CLIENT CACHING 之后的一条命令（事务中则为整个事务）执行完后失效
+        let caching_set = std::mem::take(&mut state.caching_set);
+        if !caching_set && snapshot.multi.is_none() {
+            state.caching = None;
+        }
+        state.db = snapshot.db;
+        state.resp = snapshot.resp;
+        state.channels = snapshot.channels;
+        state.patterns = snapshot.patterns;
+        state.shard_channels = snapshot.shard_channels;
+        state.multi = snapshot.multi;
+        state.watch = snapshot.watch;
+        state.qbuf = snapshot.qbuf;
+        state.qbuf_free = snapshot.qbuf_free;
+        state.obl = snapshot.obl;
+    }
+
+    /// CLIENT REPLY ON|OFF|SKIP，OFF 和 SKIP 本身也不回复
+    pub fn set_reply_mode(&self, mode: ReplyMode) {
+        let mut state = self.lock();
+        match mode {
+            ReplyMode::On => {
+                state.reply_off = false;
+                state.skip_next = false;
+            }
+            ReplyMode::Off => {
+                state.reply_off = true;
+                state.suppress_current = true;
+            }
+            ReplyMode::Skip => {
+                state.skip_next = !state.reply_off;
+                state.suppress_current = true;
+            }
+        }
+    }
+
+    /// 一条命令执行完，返回是否要把回复写回客户端
+    pub fn end_command(&self) -> bool {
+        let mut state = self.lock();
+        let send = !(state.reply_off || state.suppress_current || state.skipping);
+        state.suppress_current = false;
+        state.skipping = std::mem::take(&mut state.skip_next);
+        send
+    }
+
+    pub fn protocol(&self) -> u8 {
+        self.lock().resp
+    }
+
+    pub fn is_pubsub(&self) -> bool {
+        let state = self.lock();
+        state.channels + state.patterns + state.shard_channels > 0
+    }
+
+    /// 设置接收异步消息的一端，连接建立时设置
+    pub fn set_push(&self, tx: Outbox<Message>) {
+        self.lock().push = Some(tx);
+    }
+
+    /// 给连接推送一条异步消息，由连接按自己的协议版本写出
+    pub fn push(&self, message: Message) {
+        // 超过输出缓冲区限制时 send 会断开这个连接，不能持有锁
+        let push = self.lock().push.clone();
+        if let Some(tx) = push {
+            tx.send(message);
+        }
+    }
+
+    /// 异步消息进入输出缓冲区，超过 client-output-buffer-limit 时返回 false
+    pub fn charge_output(&self, size: usize, limits: &Limits) -> bool {
+        let before = self.omem.fetch_add(size, Ordering::Relaxed);
+        let limit = limits.output(OutputClass::of(self.client_type()));
+        let mut state = self.lock();
+        !limit.exceeded(
+            before as u64,
+            (before + size) as u64,
+            &mut state.soft_limit_since,
+        )
+    }
+
+    /// 连接写出了异步消息
+    pub fn release_output(&self, size: usize) {
+        let _ = self
+            .omem
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |omem| {
+                Some(omem.saturating_sub(size))
+            });
+    }
+
+    pub fn output_memory(&self) -> usize {
+        self.omem.load(Ordering::Relaxed)
+    }
+
+    pub fn tracking(&self) -> Option<TrackingOptions> {
+        self.lock().tracking.clone()
+    }
+
+    pub fn set_tracking(&self, tracking: Option<TrackingOptions>) {
+        let mut state = self.lock();
+        state.tracking = tracking;
+        state.caching = None;
+    }
+
+    pub fn caching(&self) -> Option<bool> {
+        self.lock().caching
+    }
+
+    pub fn set_caching(&self, caching: bool) {
+        let mut state = self.lock();
+        state.caching = Some(caching);
+        state.caching_set = true;
+    }
+
+    /// 默认模式下当前命令读取的键是否需要记住
+    pub fn tracks_reads(&self) -> bool {
+        let state = self.lock();
+        match &state.tracking {
+            Some(options) if options.bcast => false,
+            Some(options) if options.optin => state.caching == Some(true),
+            Some(options) if options.optout => state.caching != Some(false),
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    pub fn client_type(&self) -> ClientType {
+        let state = self.lock();
+        // 和 Redis 一样，MONITOR 中的连接算作普通客户端
+        if state.replica {
+            ClientType::Replica
+        } else if state.master {
+            ClientType::Master
+        } else if state.channels + state.patterns + state.shard_channels > 0 {
+            ClientType::PubSub
+        } else {
+            ClientType::Normal
+        }
+    }
+
+    /// 关闭连接：其他连接立即断开，当前连接在回复之后断开
+    pub fn kill(&self) {
+        self.kill.cancel();
+    }
+
+    pub fn is_killed(&self) -> bool {
+        self.kill.is_cancelled()
+    }
+
+    /// 连接被 CLIENT KILL 关闭时就绪
+    pub async fn killed(&self) {
+        self.kill.cancelled().await
+    }
+
+    pub fn age(&self) -> Duration {
+        self.created.elapsed()
+    }
+
+    /// 距离上一条命令的时间
+    pub fn idle(&self) -> Duration {
+        self.lock().last_interaction.elapsed()
+    }
+
+    /// CLIENT LIST / CLIENT INFO 中的一行
+    pub fn info_line(&self) -> String {
+        let state = self.lock();
+        let mut flags = String::new();
+        if state.monitor {
+            flags.push('O');
+        } else if state.replica {
+            flags.push('S');
+        } else if state.master {
+            flags.push('M');
+        }
+        if state.channels + state.patterns + state.shard_channels > 0 {
+            flags.push('P');
+        }
+        if state.multi.is_some() {
+            flags.push('x');
+        }
+        if self.is_killed() {
+            flags.push('A');
+        }
+        if state.no_evict {
+            flags.push('e');
+        }
+        if let Some(tracking) = &state.tracking {
+            flags.push('t');
+            if tracking.bcast {
+                flags.push('B');
+            }
+        }
+        if state.unix_socket {
+            flags.push('U');
+        }
+        if flags.is_empty() {
+            flags.push('N');
+        }
+        let mut line = String::new();
+        let _ = write!(
+            line,
+            "id={} addr={} laddr={} fd=-1 name={} age={} idle={} flags={} db={} \
+             sub={} psub={} ssub={} multi={} watch={} qbuf={} qbuf-free={} \
+             obl={} oll=0 omem={} tot-mem={} events=r cmd={} user={} redir={} \
+             resp={} lib-name={} lib-ver={}",
+            self.id,
+            self.addr,
+            self.laddr,
+            state.name,
+            self.created.elapsed().as_secs(),
+            state.last_interaction.elapsed().as_secs(),
+            flags,
+            state.db,
+            state.channels,
+            state.patterns,
+            state.shard_channels,
+            state.multi.map_or(-1, |count| count as i64),
+            state.watch,
+            state.qbuf,
+            state.qbuf_free,
+            state.obl,
+            self.output_memory(),
+            state.qbuf + state.qbuf_free + state.obl + self.output_memory(),
+            state.last_cmd,
+            state.user,
+            match &state.tracking {
+                Some(tracking) => tracking.redirect.map_or(0, |id| id as i64),
+                None => -1,
+            },
+            state.resp,
+            state.lib_name,
+            state.lib_ver,
+        );
+        line
+    }
+}
+
+/// 所有在线的客户端和 CLIENT PAUSE 状态
+#[derive(Debug, Default)]
+pub struct Clients {
+    next_id: AtomicU64,
+    clients: DashMap<u64, Arc<Client>>,
+    // 已经注册的连接数，maxclients 按它检查
+    connected: AtomicUsize,
+    pause: Mutex<Option<(Instant, PauseMode)>>,
+    unpaused: Notify,
+}
+
+impl Clients {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册一个新连接，返回的 guard 在连接断开时注销
+    pub fn register(self: &Arc<Self>, addr: String, laddr: String) -> Registered {
+        self.connected.fetch_add(1, Ordering::Relaxed);
+        self.insert(addr, laddr)
+    }
+
+    /// 连接数小于 `maxclients` 时注册，检查和占用名额是一个原子操作，并发的连接不会超过上限
+    pub fn try_register(
+        self: &Arc<Self>,
+        addr: String,
+        laddr: String,
+        maxclients: usize,
+    ) -> Option<Registered> {
+        self.connected
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                (n < maxclients).then_some(n + 1)
+            })
+            .ok()?;
+        Some(self.insert(addr, laddr))
+    }
+
+    /// 注册一个 unix socket 连接，和 Redis 一样地址显示为 `path:0`
+    pub fn try_register_unix(
+        self: &Arc<Self>,
+        path: &str,
+        maxclients: usize,
+    ) -> Option<Registered> {
+        let addr = format!("{path}:0");
+        let registered = self.try_register(addr.clone(), addr, maxclients)?;
+        registered.client.lock().unix_socket = true;
+        Some(registered)
+    }
+
+    // 加入连接表，调用方已经占用了名额
+    fn insert(self: &Arc<Self>, addr: String, laddr: String) -> Registered {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let client = Arc::new(Client::new(id, addr, laddr));
+        self.clients.insert(id, client.clone());
+        Registered {
+            clients: self.clone(),
+            client,
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<Arc<Client>> {
+        self.clients.get(&id).map(|client| client.clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// 所有客户端，按 id 排序
+    pub fn list(&self) -> Vec<Arc<Client>> {
+        let mut clients: Vec<_> = self
+            .clients
+            .iter()
+            .map(|client| client.value().clone())
+            .collect();
+        clients.sort_by_key(|client| client.id);
+        clients
+    }
+
+    /// CLIENT PAUSE：已经在暂停中时取更晚的结束时间和更严格的模式
+    pub fn pause(&self, timeout: Duration, mode: PauseMode) {
+        let until = Instant::now() + timeout;
+        let mut pause = self.pause.lock().unwrap_or_else(|e| e.into_inner());
+        *pause = Some(match *pause {
+            Some((end, current)) if end > Instant::now() => {
+                let mode = if current == PauseMode::All {
+                    current
+                } else {
+                    mode
+                };
+                (end.max(until), mode)
+            }
+            _ => (until, mode),
+        });
+    }
+
+    pub fn unpause(&self) {
+        *self.pause.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.unpaused.notify_waiters();
+    }
+
+    /// 写命令（`write` 为 true）或所有命令正在被暂停时返回暂停的结束时间
+    pub fn paused_until(&self, write: bool) -> Option<Instant> {
+        let pause = self.pause.lock().unwrap_or_else(|e| e.into_inner());
+        match *pause {
+            Some((until, mode)) if until > Instant::now() && (write || mode == PauseMode::All) => {
+                Some(until)
+            }
+            _ => None,
+        }
+    }
+
+    /// 等待暂停结束，超时或 CLIENT UNPAUSE 时返回
+    pub async fn wait_unpaused(&self, write: bool) {
+        loop {
+            // 先注册等待，避免错过检查之后的 UNPAUSE
+            let unpaused = self.unpaused.notified();
+            let Some(until) = self.paused_until(write) else {
+                return;
+            };
+            tokio::select! {
+                _ = tokio::time::sleep_until(until.into()) => {}
+                _ = unpaused => {}
+            }
+        }
+    }
+}
+
+/// 注册中的客户端，drop 时从连接表中移除
+#[derive(Debug)]
+pub struct Registered {
+    clients: Arc<Clients>,
+    client: Arc<Client>,
+}
+
+impl Registered {
+    pub fn client(&self) -> &Arc<Client> {
+        &self.client
+    }
+}
+
+impl Drop for Registered {
+    fn drop(&mut self) {
+        self.clients.clients.remove(&self.client.id);
+        self.clients.connected.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Arrays, BulkStrings, Resp};
+
+    fn frame(args: &[&str]) -> Resp {
+        Resp::Arrays(Arrays::new(
+            args.iter()
+                .map(|arg| Resp::BulkStrings(BulkStrings::new(arg.to_string())))
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn test_register_and_info_line() {
+        let clients = Arc::new(Clients::new());
+        let first = clients.register("127.0.0.1:5000".to_owned(), "127.0.0.1:6379".to_owned());
+        let second = clients.register("127.0.0.1:5001".to_owned(), "127.0.0.1:6379".to_owned());
+        assert_eq!(clients.len(), 2);
+        assert_eq!(second.client().id, first.client().id + 1);
+
+        let client = first.client();
+        client.set_name("worker");
+        client.begin_command(&frame(&["CLIENT", "LIST"]));
+        client.update(Snapshot {
+            db: 3,
+            channels: 1,
+            multi: Some(2),
+            ..Default::default()
+        });
+        let line = client.info_line();
+        assert!(line.starts_with(&format!("id={} addr=127.0.0.1:5000 ", client.id)));
+        assert!(line.contains(" name=worker "));
+        assert!(line.contains(" flags=Px db=3 sub=1 psub=0 ssub=0 multi=2 "));
+        assert!(line.contains(" cmd=client|list "));
+        assert_eq!(client.client_type(), ClientType::PubSub);
+
+        drop(first);
+        assert_eq!(clients.len(), 1);
+        assert!(clients.get(second.client().id).is_some());
+
+        // 达到上限时不注册
+        assert!(clients
+            .try_register("127.0.0.1:5002".to_owned(), String::new(), 1)
+            .is_none());
+        assert_eq!(clients.len(), 1);
+
+        let unix = clients.try_register_unix("/tmp/redis.sock", 2).unwrap();
+        let line = unix.client().info_line();
+        assert!(line.contains(" addr=/tmp/redis.sock:0 laddr=/tmp/redis.sock:0 "));
+        assert!(line.contains(" flags=U "));
+    }
+
+    #[test]
+    fn test_reply_mode() {
+        let client = Client::new(1, String::new(), String::new());
+        assert!(client.end_command());
+        // SKIP 本身和下一条命令都不回复
+        client.set_reply_mode(ReplyMode::Skip);
+        assert!(!client.end_command());
+        assert!(!client.end_command());
+        assert!(client.end_command());
+
+        client.set_reply_mode(ReplyMode::Off);
+        assert!(!client.end_command());
+        assert!(!client.end_command());
+        client.set_reply_mode(ReplyMode::On);
+        assert!(client.end_command());
+    }
+
+    #[tokio::test]
+    async fn test_pause() {
+        let clients = Clients::new();
+        clients.pause(Duration::from_secs(10), PauseMode::Write);
+        assert!(clients.paused_until(true).is_some());
+        assert!(clients.paused_until(false).is_none());
+        // 不能降级为更宽松的模式
+        clients.pause(Duration::from_millis(1), PauseMode::All);
+        clients.pause(Duration::from_millis(1), PauseMode::Write);
+        assert!(clients.paused_until(false).is_some());
+
+        clients.unpause();
+        assert!(clients.paused_until(true).is_none());
+        clients.pause(Duration::from_millis(20), PauseMode::All);
+        let start = Instant::now();
+        clients.wait_unpaused(false).await;
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,70 @@
+//! Redis Cluster 使用的 CRC16（CCITT/XMODEM 多项式 0x1021）
+
+pub const CLUSTER_SLOTS: u16 = 16384;
+
+const fn build_table() -> [u16; 256] {
+    let mut table = [0u16; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+static CRC16_TABLE: [u16; 256] = build_table();
+
+pub fn crc16(buf: &[u8]) -> u16 {
+    buf.iter().fold(0u16, |crc, &byte| {
+        (crc << 8) ^ CRC16_TABLE[(((crc >> 8) as u8) ^ byte) as usize]
+    })
+}
+
+/// 计算键所属的 hash slot
+///
+/// 键中包含非空的 `{...}` 时只对第一对花括号中的内容求值（hash tag），
+/// 让相关的键可以落在同一个 slot。
+pub fn key_hash_slot(key: &str) -> u16 {
+    let bytes = key.as_bytes();
+    let hashed = match bytes.iter().position(|&b| b == b'{') {
+        Some(start) => match bytes[start + 1..].iter().position(|&b| b == b'}') {
+            Some(len) if len > 0 => &bytes[start + 1..start + 1 + len],
+            _ => bytes,
+        },
+        None => bytes,
+    };
+    crc16(hashed) & (CLUSTER_SLOTS - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc16() {
+        assert_eq!(crc16(b"123456789"), 0x31C3);
+    }
+
+    #[test]
+    fn test_key_hash_slot() {
+        assert_eq!(key_hash_slot("foo"), 12182);
+        assert_eq!(key_hash_slot("somekey"), 11058);
+        assert_eq!(
+            key_hash_slot("{user1000}.following"),
+            key_hash_slot("{user1000}.followers")
+        );
+        // 空的 hash tag 不生效，整个键参与计算
+        assert_eq!(key_hash_slot("foo{}{bar}"), crc16(b"foo{}{bar}") & 16383);
+        assert_eq!(key_hash_slot("foo{{bar}}zap"), key_hash_slot("{bar"));
+    }
+}
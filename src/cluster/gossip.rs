@@ -0,0 +1,70 @@
+//! 节点间信息交换
+//!
+//! 每秒向所有已知节点发送 `CLUSTER NODES`，把对方的节点和 slot 信息合并到本地；
+//! 如果对方还不认识自己，再发送 `CLUSTER MEET` 让它把自己加进去。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio_util::codec::Framed;
+use tracing::{debug, info};
+
+use crate::network::RespFrameCodec;
+use crate::replication::command;
+use crate::{Data, Resp};
+
+use super::ClusterNode;
+
+const GOSSIP_PERIOD: Duration = Duration::from_secs(1);
+const GOSSIP_TIMEOUT: Duration = Duration::from_secs(1);
+
+pub async fn run(data: Arc<Data>) {
+    let mut interval = tokio::time::interval(GOSSIP_PERIOD);
+    loop {
+        interval.tick().await;
+        for peer in data.cluster.peers() {
+            let result = tokio::time::timeout(GOSSIP_TIMEOUT, exchange(&data, &peer)).await;
+            match result {
+                Ok(Ok(())) => {
+                    if !peer.link_up {
+                        info!("🤝 Cluster link to {} is up", peer.addr());
+                    }
+                }
+                Ok(Err(e)) => {
+                    debug!("Cluster gossip with {} failed: {}", peer.addr(), e);
+                    data.cluster.set_link(&peer.id, false);
+                }
+                Err(_) => {
+                    debug!("Cluster gossip with {} timed out", peer.addr());
+                    data.cluster.set_link(&peer.id, false);
+                }
+            }
+        }
+    }
+}
+
+async fn exchange(data: &Data, peer: &ClusterNode) -> Result<()> {
+    let stream = TcpStream::connect(peer.addr()).await?;
+    let mut framed = Framed::new(stream, RespFrameCodec::new());
+
+    framed.send(command(&["CLUSTER", "NODES"])).await?;
+    let text = match framed.next().await {
+        Some(Ok(Resp::BulkStrings(text))) => text.val,
+        Some(Ok(other)) => return Err(anyhow::anyhow!("unexpected reply: {:?}", other)),
+        Some(Err(e)) => return Err(e),
+        None => return Err(anyhow::anyhow!("connection closed")),
+    };
+    data.cluster.merge_nodes(peer, &text);
+
+    if !data.cluster.known_by(&text) {
+        let (host, port) = data.cluster.myself_addr();
+        framed
+            .send(command(&["CLUSTER", "MEET", &host, &port.to_string()]))
+            .await?;
+        framed.next().await;
+    }
+    Ok(())
+}
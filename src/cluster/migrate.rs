@@ -0,0 +1,114 @@
+//! MIGRATE：把键原子地搬到目标节点
+//!
+//! 命令处理是同步的，这里直接用阻塞的 TcpStream 和目标节点通信。
+//! 每个键先发送 `ASKING`（目标 slot 可能还处于 IMPORTING 状态），
+//! 再用 `RESTORE` 写入，成功后删除本地的键。
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use anyhow::Result;
+use bytes::BytesMut;
+
+use crate::replication::command;
+use crate::snapshot;
+use crate::{Data, Resp, RespDecoder, RespEncoder, RespError};
+
+#[derive(Debug)]
+pub struct MigrateOptions {
+    pub host: String,
+    pub port: u16,
+    pub keys: Vec<String>,
+    pub timeout_millis: u64,
+    pub copy: bool,
+    pub replace: bool,
+}
+
+/// 执行迁移，返回实际迁移的键数量
+pub fn migrate(data: &Data, options: &MigrateOptions) -> Result<usize> {
+    // 先取出所有键的快照，不存在的键直接跳过
+    let mut payloads = Vec::new();
+    for key in &options.keys {
+        if data.is_expired(key) {
+            continue;
+        }
+        if let Some(payload) = snapshot::dump_key(data, key) {
+            let ttl = data.get_ttl_millis(key).unwrap_or(0).max(0);
+            payloads.push((key, ttl, payload));
+        }
+    }
+    if payloads.is_empty() {
+        return Ok(0);
+    }
+
+    let timeout = Duration::from_millis(options.timeout_millis.max(1));
+    let addr = format!("{}:{}", options.host, options.port);
+    let mut conn = Connection::connect(&addr, timeout)?;
+    for (key, ttl, payload) in &payloads {
+        conn.request(&["ASKING"])?;
+        let ttl = ttl.to_string();
+        let mut args = vec!["RESTORE", key.as_str(), ttl.as_str(), payload.as_str()];
+        if options.replace {
+            args.push("REPLACE");
+        }
+        if let Resp::SimpleErrors(e) = conn.request(&args)? {
+            return Err(anyhow::anyhow!(
+                "Target instance replied with error: {}",
+                e.error_msg
+            ));
+        }
+    }
+
+    if !options.copy {
+        for (key, _, _) in &payloads {
+            data.remove_key(key);
+        }
+    }
+    Ok(payloads.len())
+}
+
+struct Connection {
+    stream: TcpStream,
+    buf: BytesMut,
+}
+
+impl Connection {
+    fn connect(addr: &str, timeout: Duration) -> Result<Self> {
+        let socket_addr = std::net::ToSocketAddrs::to_socket_addrs(addr)?
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("invalid target address {addr}"))?;
+        let stream = TcpStream::connect_timeout(&socket_addr, timeout)
+            .map_err(|e| anyhow::anyhow!("IOERR error or timeout connecting to the client: {e}"))?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        Ok(Self {
+            stream,
+            buf: BytesMut::new(),
+        })
+    }
+
+    fn request(&mut self, args: &[&str]) -> Result<Resp> {
+        self.stream.write_all(&command(args).encode()?)?;
+        let mut chunk = [0u8; 4096];
+        loop {
+            if !self.buf.is_empty() {
+                // 数据不完整时不消耗缓冲区
+                match Resp::decode(&mut self.buf) {
+                    Ok(reply) => return Ok(reply),
+                    Err(RespError::NotComplete) => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            let n = self.stream.read(&mut chunk).map_err(|e| {
+                anyhow::anyhow!("IOERR error or timeout reading to target instance: {e}")
+            })?;
+            if n == 0 {
+                return Err(anyhow::anyhow!(
+                    "IOERR target instance closed the connection"
+                ));
+            }
+            self.buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+}
@@ -0,0 +1,773 @@
+//! 集群模式
+//!
+//! 键按 CRC16 映射到 16384 个 hash slot，每个 slot 由一个节点负责。
+//! 节点之间没有独立的集群总线，而是定期通过普通客户端端口互相拉取
+//! `CLUSTER NODES` 来交换节点和 slot 信息（见 `gossip`）。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use rand::Rng;
+
+use crate::process::command_table;
+use crate::{Arrays, BulkStrings, Data, Integers, Resp, SimpleErrors};
+
+use self::crc16::{key_hash_slot, CLUSTER_SLOTS};
+
+pub mod crc16;
+pub mod gossip;
+pub mod migrate;
+
+#[derive(Debug, Clone)]
+pub struct ClusterNode {
+    pub id: String,
+    pub host: String,
+    pub port: u16,
+    pub config_epoch: u64,
+    // 刚通过 CLUSTER MEET 加入、还不知道真实 id 的节点
+    pub handshake: bool,
+    pub link_up: bool,
+}
+
+impl ClusterNode {
+    fn new(id: String, host: String, port: u16) -> Self {
+        Self {
+            id,
+            host,
+            port,
+            config_epoch: 0,
+            handshake: false,
+            link_up: true,
+        }
+    }
+
+    pub fn addr(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+#[derive(Debug)]
+struct ClusterState {
+    myself: String,
+    current_epoch: u64,
+    nodes: HashMap<String, ClusterNode>,
+    // 每个 slot 的负责节点 id
+    slots: Vec<Option<String>>,
+    // 正在迁出的 slot -> 目标节点 id
+    migrating: HashMap<u16, String>,
+    // 正在迁入的 slot -> 源节点 id
+    importing: HashMap<u16, String>,
+}
+
+#[derive(Debug)]
+pub struct Cluster {
+    enabled: AtomicBool,
+    state: RwLock<ClusterState>,
+}
+
+/// 命令的 slot 检查结果
+#[derive(Debug, PartialEq)]
+pub enum Redirect {
+    Moved(u16, String),
+    Ask(u16, String),
+    CrossSlot,
+    TryAgain,
+    Down,
+}
+
+impl Redirect {
+    pub fn into_resp(self) -> Resp {
+        let msg = match self {
+            Redirect::Moved(slot, addr) => format!("MOVED {slot} {addr}"),
+            Redirect::Ask(slot, addr) => format!("ASK {slot} {addr}"),
+            Redirect::CrossSlot => {
+                "CROSSSLOT Keys in request don't hash to the same slot".to_owned()
+            }
+            Redirect::TryAgain => {
+                "TRYAGAIN Multiple keys request during rehashing of slot".to_owned()
+            }
+            Redirect::Down => "CLUSTERDOWN Hash slot not served".to_owned(),
+        };
+        Resp::SimpleErrors(SimpleErrors::new(msg))
+    }
+}
+
+impl Cluster {
+    pub fn new() -> Self {
+        let myself = random_node_id();
+        let mut nodes = HashMap::new();
+        nodes.insert(
+            myself.clone(),
+            ClusterNode::new(myself.clone(), "127.0.0.1".to_owned(), 6379),
+        );
+        Self {
+            enabled: AtomicBool::new(false),
+            state: RwLock::new(ClusterState {
+                myself,
+                current_epoch: 0,
+                nodes,
+                slots: vec![None; CLUSTER_SLOTS as usize],
+                migrating: HashMap::new(),
+                importing: HashMap::new(),
+            }),
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, ClusterState> {
+        self.state.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, ClusterState> {
+        self.state.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// 设置本节点对外公布的地址
+    pub fn set_myself_addr(&self, host: &str, port: u16) {
+        let mut state = self.write();
+        let myself = state.myself.clone();
+        if let Some(node) = state.nodes.get_mut(&myself) {
+            node.host = host.to_owned();
+            node.port = port;
+        }
+    }
+
+    pub fn myself_id(&self) -> String {
+        self.read().myself.clone()
+    }
+
+    pub fn myself_addr(&self) -> (String, u16) {
+        let state = self.read();
+        state
+            .nodes
+            .get(&state.myself)
+            .map(|node| (node.host.clone(), node.port))
+            .unwrap_or_else(|| ("127.0.0.1".to_owned(), 6379))
+    }
+
+    /// 检查命令中的键是否都由本节点负责，需要重定向时返回对应的错误
+    pub fn check_redirect(&self, data: &Data, frame: &Resp, asking: bool) -> Option<Redirect> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let keys = command_table::command_keys(frame);
+        let first = keys.first()?;
+        let slot = key_hash_slot(first);
+        if keys.iter().any(|key| key_hash_slot(key) != slot) {
+            return Some(Redirect::CrossSlot);
+        }
+
+        let state = self.read();
+        let Some(owner) = state.slots[slot as usize].as_ref() else {
+            return Some(Redirect::Down);
+        };
+        if *owner == state.myself {
+            // slot 正在迁出：键已经不在本节点时让客户端去目标节点找
+            let target = state.migrating.get(&slot)?;
+            let missing = keys.iter().filter(|key| !data.contains_key(key)).count();
+            return match missing {
+                0 => None,
+                n if n == keys.len() => state
+                    .nodes
+                    .get(target)
+                    .map(|node| Redirect::Ask(slot, node.addr())),
+                _ => Some(Redirect::TryAgain),
+            };
+        }
+        if asking && state.importing.contains_key(&slot) {
+            return None;
+        }
+        match state.nodes.get(owner) {
+            Some(node) => Some(Redirect::Moved(slot, node.addr())),
+            None => Some(Redirect::Down),
+        }
+    }
+
+    /// CLUSTER ADDSLOTS
+    pub fn add_slots(&self, slots: &[u16]) -> Result<(), String> {
+        let mut state = self.write();
+        if let Some(slot) = slots
+            .iter()
+            .find(|slot| state.slots[**slot as usize].is_some())
+        {
+            return Err(format!("ERR Slot {slot} is already busy"));
+        }
+        let myself = state.myself.clone();
+        for slot in slots {
+            state.slots[*slot as usize] = Some(myself.clone());
+            state.importing.remove(slot);
+        }
+        Ok(())
+    }
+
+    /// CLUSTER DELSLOTS
+    pub fn del_slots(&self, slots: &[u16]) -> Result<(), String> {
+        let mut state = self.write();
+        if let Some(slot) = slots
+            .iter()
+            .find(|slot| state.slots[**slot as usize].is_none())
+        {
+            return Err(format!("ERR Slot {slot} is already unassigned"));
+        }
+        for slot in slots {
+            state.slots[*slot as usize] = None;
+            state.migrating.remove(slot);
+            state.importing.remove(slot);
+        }
+        Ok(())
+    }
+
+    /// CLUSTER SETSLOT <slot> IMPORTING|MIGRATING|STABLE|NODE [node-id]
+    pub fn set_slot(
+        &self,
+        data: &Data,
+        slot: u16,
+        action: &str,
+        node_id: Option<&str>,
+    ) -> Result<(), String> {
+        let mut state = self.write();
+        let owned = state.slots[slot as usize].as_deref() == Some(state.myself.as_str());
+        let node = match node_id {
+            Some(id) if state.nodes.contains_key(id) => Some(id.to_owned()),
+            Some(id) => return Err(format!("ERR I don't know about node {id}")),
+            None => None,
+        };
+        match (action.to_lowercase().as_str(), node) {
+            ("migrating", Some(node)) => {
+                if !owned {
+                    return Err(format!("ERR I'm not the owner of hash slot {slot}"));
+                }
+                state.migrating.insert(slot, node);
+            }
+            ("importing", Some(node)) => {
+                if owned {
+                    return Err(format!("ERR I'm already the owner of hash slot {slot}"));
+                }
+                state.importing.insert(slot, node);
+            }
+            ("stable", None) => {
+                state.migrating.remove(&slot);
+                state.importing.remove(&slot);
+            }
+            ("node", Some(node)) => {
+                if owned && node != state.myself && count_keys_in_slot(data, slot) > 0 {
+                    return Err(format!(
+                        "ERR Can't assign hashslot {slot} to a different node while I still hold keys for this hash slot."
+                    ));
+                }
+                state.migrating.remove(&slot);
+                // 迁入完成，提升自己的 config epoch，让其他节点接受新的归属
+                if node == state.myself && state.importing.remove(&slot).is_some() {
+                    state.current_epoch += 1;
+                    let epoch = state.current_epoch;
+                    let myself = state.myself.clone();
+                    if let Some(me) = state.nodes.get_mut(&myself) {
+                        me.config_epoch = epoch;
+                    }
+                }
+                state.slots[slot as usize] = Some(node);
+            }
+            _ => {
+                return Err(
+                    "ERR Invalid CLUSTER SETSLOT action or number of arguments. Try CLUSTER HELP"
+                        .to_owned(),
+                )
+            }
+        }
+        Ok(())
+    }
+
+    /// CLUSTER MEET：先以握手状态加入，真实 id 由 gossip 获取
+    pub fn meet(&self, host: &str, port: u16) {
+        let mut state = self.write();
+        if state
+            .nodes
+            .values()
+            .any(|node| node.host == host && node.port == port)
+        {
+            return;
+        }
+        let id = random_node_id();
+        let mut node = ClusterNode::new(id.clone(), host.to_owned(), port);
+        node.handshake = true;
+        node.link_up = false;
+        state.nodes.insert(id, node);
+    }
+
+    /// 除自己以外的所有节点
+    pub fn peers(&self) -> Vec<ClusterNode> {
+        let state = self.read();
+        state
+            .nodes
+            .values()
+            .filter(|node| node.id != state.myself)
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn set_link(&self, id: &str, up: bool) {
+        if let Some(node) = self.write().nodes.get_mut(id) {
+            node.link_up = up;
+        }
+    }
+
+    /// 合并从 `peer` 拉取到的 CLUSTER NODES 信息
+    ///
+    /// 远端节点对自身 slot 的声明是权威的：config epoch 更大时覆盖本地记录。
+    /// 对于第三方节点只记录其地址，slot 信息等直接和它通信时再更新。
+    pub(crate) fn merge_nodes(&self, peer: &ClusterNode, text: &str) {
+        let lines: Vec<NodeLine> = text.lines().filter_map(NodeLine::parse).collect();
+        let mut state = self.write();
+        let Some(remote) = lines.iter().find(|line| line.myself) else {
+            return;
+        };
+
+        // 握手节点换成真实 id
+        if peer.id != remote.id {
+            state.nodes.remove(&peer.id);
+        }
+        let node = state
+            .nodes
+            .entry(remote.id.clone())
+            .or_insert_with(|| ClusterNode::new(remote.id.clone(), peer.host.clone(), peer.port));
+        node.handshake = false;
+        node.link_up = true;
+        node.config_epoch = remote.config_epoch;
+        state.current_epoch = state.current_epoch.max(remote.config_epoch);
+
+        for slot in 0..CLUSTER_SLOTS {
+            let claimed = remote.slots.contains(&slot);
+            let owner = state.slots[slot as usize].clone();
+            match (claimed, owner) {
+                (true, Some(owner)) if owner == remote.id => {}
+                (true, Some(owner)) => {
+                    let owner_epoch = state
+                        .nodes
+                        .get(&owner)
+                        .map(|node| node.config_epoch)
+                        .unwrap_or(0);
+                    if owner_epoch < remote.config_epoch {
+                        state.slots[slot as usize] = Some(remote.id.clone());
+                        state.migrating.remove(&slot);
+                    }
+                }
+                (true, None) => state.slots[slot as usize] = Some(remote.id.clone()),
+                // 远端不再声明的 slot（例如 DELSLOTS）
+                (false, Some(owner)) if owner == remote.id => state.slots[slot as usize] = None,
+                _ => {}
+            }
+        }
+
+        for line in lines.iter().filter(|line| !line.myself) {
+            if line.id == state.myself || state.nodes.contains_key(&line.id) {
+                continue;
+            }
+            let exists = state
+                .nodes
+                .values()
+                .any(|node| node.host == line.host && node.port == line.port);
+            if !exists {
+                let mut node = ClusterNode::new(line.id.clone(), line.host.clone(), line.port);
+                node.config_epoch = line.config_epoch;
+                node.link_up = false;
+                state.nodes.insert(line.id.clone(), node);
+            }
+        }
+    }
+
+    /// 远端节点列表中是否已经包含自己
+    pub(crate) fn known_by(&self, text: &str) -> bool {
+        let myself = self.myself_id();
+        text.lines()
+            .filter_map(NodeLine::parse)
+            .any(|line| line.id == myself)
+    }
+
+    /// CLUSTER NODES
+    pub fn nodes_text(&self) -> String {
+        let state = self.read();
+        let mut nodes: Vec<&ClusterNode> = state.nodes.values().collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        let mut text = String::new();
+        for node in nodes {
+            let mut flags = Vec::new();
+            if node.id == state.myself {
+                flags.push("myself");
+            }
+            flags.push(if node.handshake {
+                "handshake"
+            } else {
+                "master"
+            });
+            let link = if node.id == state.myself || node.link_up {
+                "connected"
+            } else {
+                "disconnected"
+            };
+            text.push_str(&format!(
+                "{} {}:{}@{} {} - 0 0 {} {}",
+                node.id,
+                node.host,
+                node.port,
+                node.port as u32 + 10000,
+                flags.join(","),
+                node.config_epoch,
+                link
+            ));
+            for (start, end) in slot_ranges(&state, &node.id) {
+                if start == end {
+                    text.push_str(&format!(" {start}"));
+                } else {
+                    text.push_str(&format!(" {start}-{end}"));
+                }
+            }
+            if node.id == state.myself {
+                let mut migrating: Vec<_> = state.migrating.iter().collect();
+                migrating.sort();
+                for (slot, target) in migrating {
+                    text.push_str(&format!(" [{slot}->-{target}]"));
+                }
+                let mut importing: Vec<_> = state.importing.iter().collect();
+                importing.sort();
+                for (slot, source) in importing {
+                    text.push_str(&format!(" [{slot}-<-{source}]"));
+                }
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// CLUSTER SLOTS
+    pub fn slots_reply(&self) -> Resp {
+        let state = self.read();
+        let mut ranges = Vec::new();
+        for node in state.nodes.values() {
+            for (start, end) in slot_ranges(&state, &node.id) {
+                ranges.push((start, end, node));
+            }
+        }
+        ranges.sort_by_key(|(start, _, _)| *start);
+        Resp::Arrays(Arrays::new(
+            ranges
+                .into_iter()
+                .map(|(start, end, node)| {
+                    Resp::Arrays(Arrays::new(vec![
+                        integer(start as i64),
+                        integer(end as i64),
+                        Resp::Arrays(Arrays::new(vec![
+                            bulk(&node.host),
+                            integer(node.port as i64),
+                            bulk(&node.id),
+                        ])),
+                    ]))
+                })
+                .collect(),
+        ))
+    }
+
+    /// CLUSTER SHARDS
+    pub fn shards_reply(&self) -> Resp {
+        let state = self.read();
+        let mut nodes: Vec<&ClusterNode> = state.nodes.values().collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        Resp::Arrays(Arrays::new(
+            nodes
+                .into_iter()
+                .filter(|node| !node.handshake)
+                .map(|node| {
+                    let slots = slot_ranges(&state, &node.id)
+                        .into_iter()
+                        .flat_map(|(start, end)| [integer(start as i64), integer(end as i64)])
+                        .collect();
+                    let health = if node.id == state.myself || node.link_up {
+                        "online"
+                    } else {
+                        "fail"
+                    };
+                    Resp::Arrays(Arrays::new(vec![
+                        bulk("slots"),
+                        Resp::Arrays(Arrays::new(slots)),
+                        bulk("nodes"),
+                        Resp::Arrays(Arrays::new(vec![Resp::Arrays(Arrays::new(vec![
+                            bulk("id"),
+                            bulk(&node.id),
+                            bulk("port"),
+                            integer(node.port as i64),
+                            bulk("ip"),
+                            bulk(&node.host),
+                            bulk("endpoint"),
+                            bulk(&node.host),
+                            bulk("role"),
+                            bulk("master"),
+                            bulk("replication-offset"),
+                            integer(0),
+                            bulk("health"),
+                            bulk(health),
+                        ]))])),
+                    ]))
+                })
+                .collect(),
+        ))
+    }
+
+    /// CLUSTER INFO
+    pub fn info_text(&self) -> String {
+        let state = self.read();
+        let assigned = state.slots.iter().filter(|slot| slot.is_some()).count();
+        let size = state
+            .nodes
+            .keys()
+            .filter(|id| state.slots.iter().any(|owner| owner.as_ref() == Some(id)))
+            .count();
+        let my_epoch = state
+            .nodes
+            .get(&state.myself)
+            .map(|node| node.config_epoch)
+            .unwrap_or(0);
+        format!(
+            "cluster_enabled:1\r\n\
+            cluster_state:{}\r\n\
+            cluster_slots_assigned:{}\r\n\
+            cluster_slots_ok:{}\r\n\
+            cluster_slots_pfail:0\r\n\
+            cluster_slots_fail:0\r\n\
+            cluster_known_nodes:{}\r\n\
+            cluster_size:{}\r\n\
+            cluster_current_epoch:{}\r\n\
+            cluster_my_epoch:{}\r\n",
+            if assigned == CLUSTER_SLOTS as usize {
+                "ok"
+            } else {
+                "fail"
+            },
+            assigned,
+            assigned,
+            state.nodes.len(),
+            size,
+            state.current_epoch,
+            my_epoch
+        )
+    }
+}
+
+impl Default for Cluster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 统计 slot 中的键数量
+pub fn count_keys_in_slot(data: &Data, slot: u16) -> usize {
+    keys_in_slot(data, slot, usize::MAX).len()
+}
+
+/// 取出 slot 中最多 `count` 个键
+pub fn keys_in_slot(data: &Data, slot: u16, count: usize) -> Vec<String> {
+    let mut keys = Vec::new();
+    let mut collect = |key: &String| {
+        if keys.len() < count && key_hash_slot(key) == slot {
+            keys.push(key.clone());
+        }
+    };
+    data.db().string_data.iter().for_each(|e| collect(e.key()));
+    data.db().hash_data.iter().for_each(|e| collect(e.key()));
+    data.db().list_data.iter().for_each(|e| collect(e.key()));
+    data.db().set_data.iter().for_each(|e| collect(e.key()));
+    data.db()
+        .sorted_set_data
+        .iter()
+        .for_each(|e| collect(e.key()));
+    keys
+}
+
+/// 启动集群 gossip 后台任务
+pub fn start(data: Arc<Data>) {
+    if data.cluster.is_enabled() {
+        tokio::spawn(gossip::run(data));
+    }
+}
+
+// CLUSTER NODES 中的一行
+#[derive(Debug)]
+struct NodeLine {
+    id: String,
+    host: String,
+    port: u16,
+    myself: bool,
+    config_epoch: u64,
+    slots: Vec<u16>,
+}
+
+impl NodeLine {
+    fn parse(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 8 {
+            return None;
+        }
+        let addr = fields[1].split('@').next()?;
+        let (host, port) = addr.rsplit_once(':')?;
+        let mut slots = Vec::new();
+        for field in &fields[8..] {
+            if field.starts_with('[') {
+                continue;
+            }
+            match field.split_once('-') {
+                Some((start, end)) => {
+                    let (start, end): (u16, u16) = (start.parse().ok()?, end.parse().ok()?);
+                    slots.extend(start..=end.min(CLUSTER_SLOTS - 1));
+                }
+                None => slots.push(field.parse().ok()?),
+            }
+        }
+        Some(Self {
+            id: fields[0].to_owned(),
+            host: host.to_owned(),
+            port: port.parse().ok()?,
+            myself: fields[2].split(',').any(|flag| flag == "myself"),
+            config_epoch: fields[6].parse().ok()?,
+            slots,
+        })
+    }
+}
+
+fn slot_ranges(state: &ClusterState, id: &str) -> Vec<(u16, u16)> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for slot in 0..=CLUSTER_SLOTS {
+        let owned = slot < CLUSTER_SLOTS && state.slots[slot as usize].as_deref() == Some(id);
+        match (owned, start) {
+            (true, None) => start = Some(slot),
+            (false, Some(begin)) => {
+                ranges.push((begin, slot - 1));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    ranges
+}
+
+fn random_node_id() -> String {
+    let mut rng = rand::thread_rng();
+    (0..40)
+        .map(|_| char::from_digit(rng.gen_range(0..16), 16).unwrap_or('0'))
+        .collect()
+}
+
+fn bulk(val: &str) -> Resp {
+    Resp::BulkStrings(BulkStrings::new(val.to_owned()))
+}
+
+fn integer(val: i64) -> Resp {
+    Resp::Integers(Integers::new(val))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleStringsData;
+
+    fn frame(args: &[&str]) -> Resp {
+        Resp::Arrays(Arrays::new(args.iter().map(|arg| bulk(arg)).collect()))
+    }
+
+    #[test]
+    fn test_redirects() {
+        let data = Data::new();
+        data.cluster.set_enabled(true);
+        data.cluster.add_slots(&[key_hash_slot("foo")]).unwrap();
+
+        assert_eq!(
+            data.cluster
+                .check_redirect(&data, &frame(&["GET", "foo"]), false),
+            None
+        );
+        assert_eq!(
+            data.cluster
+                .check_redirect(&data, &frame(&["DEL", "foo", "bar"]), false),
+            Some(Redirect::CrossSlot)
+        );
+        assert_eq!(
+            data.cluster
+                .check_redirect(&data, &frame(&["GET", "bar"]), false),
+            Some(Redirect::Down)
+        );
+        assert_eq!(
+            data.cluster.check_redirect(&data, &frame(&["PING"]), false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_merge_nodes_and_migrating_slot() {
+        let a = Data::new();
+        a.cluster.set_enabled(true);
+        a.cluster.set_myself_addr("127.0.0.1", 7001);
+        let b = Data::new();
+        b.cluster.set_enabled(true);
+        b.cluster.set_myself_addr("127.0.0.1", 7002);
+        let slot = key_hash_slot("foo");
+        a.cluster.add_slots(&[slot]).unwrap();
+        b.cluster.add_slots(&[0]).unwrap();
+
+        // a 通过 MEET 认识 b，再从 b 拉取节点信息
+        a.cluster.meet("127.0.0.1", 7002);
+        let peer = a.cluster.peers().pop().unwrap();
+        a.cluster.merge_nodes(&peer, &b.cluster.nodes_text());
+        let b_id = b.cluster.myself_id();
+        assert_eq!(
+            a.cluster
+                .check_redirect(&a, &frame(&["GET", "{x}"]), false)
+                .map(|r| matches!(r, Redirect::Moved(_, _) | Redirect::Down)),
+            Some(true)
+        );
+        assert_eq!(
+            a.cluster
+                .check_redirect(&a, &frame(&["GET", "key:0"]), false),
+            if key_hash_slot("key:0") == 0 {
+                Some(Redirect::Moved(0, "127.0.0.1:7002".to_owned()))
+            } else {
+                Some(Redirect::Down)
+            }
+        );
+
+        // 迁移中的 slot：键不在本节点时回复 ASK
+        a.cluster
+            .set_slot(&a, slot, "MIGRATING", Some(&b_id))
+            .unwrap();
+        assert_eq!(
+            a.cluster.check_redirect(&a, &frame(&["GET", "foo"]), false),
+            Some(Redirect::Ask(slot, "127.0.0.1:7002".to_owned()))
+        );
+        a.db().string_data.insert(
+            "foo".to_owned(),
+            Resp::SimpleStrings(SimpleStringsData::new("bar".to_owned())),
+        );
+        assert_eq!(
+            a.cluster.check_redirect(&a, &frame(&["GET", "foo"]), false),
+            None
+        );
+        assert!(a.cluster.set_slot(&a, slot, "NODE", Some(&b_id)).is_err());
+    }
+
+    #[test]
+    fn test_parse_node_line() {
+        let line = NodeLine::parse(
+            "07c37dfeb235213a872192d90877d0cd55635b91 127.0.0.1:30004@40004 myself,master - 0 0 3 connected 0-2 5 [6->-abc]",
+        )
+        .unwrap();
+        assert_eq!(line.port, 30004);
+        assert!(line.myself);
+        assert_eq!(line.config_epoch, 3);
+        assert_eq!(line.slots, vec![0, 1, 2, 5]);
+    }
+}
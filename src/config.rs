@@ -0,0 +1,669 @@
+//! 服务端配置
+//!
+//! 配置来自 redis.conf 风格的配置文件和命令行参数：`simple-redis [redis.conf] [--port 7000 ...]`，
+//! 命令行中的参数在配置文件之后生效。每条配置先解析成 `Update`：监听地址、数据库数量等只能在
+//! 启动时设置的参数保存在 `ServerConfig` 中，运行期间可以修改的参数在启动时和 CONFIG SET
+//! 走同一条路径生效。CONFIG REWRITE 把当前的值写回配置文件，保留注释和空行。
+
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use anyhow::{anyhow, Result};
+
+use crate::limits::{self, OutputBufferLimit, OutputClass};
+use crate::memory::{self, EvictionPolicy};
+use crate::replication::MasterAddr;
+use crate::snapshot::DEFAULT_DBFILENAME;
+use crate::tls::{AuthClients, TlsConfig};
+use crate::{db, notify, Data};
+
+// 只能在启动时设置，CONFIG SET 返回错误
+const IMMUTABLE: u8 = 1;
+// 值由多个参数组成（bind、replicaof 等），写回配置文件时不加引号
+const MULTI_ARG: u8 = 1 << 1;
+
+// CONFIG REWRITE 追加的参数前面的注释
+const REWRITE_MARKER: &str = "# Generated by CONFIG REWRITE";
+
+/// 一个配置参数
+#[derive(Debug)]
+pub struct Param {
+    pub name: &'static str,
+    flags: u8,
+}
+
+impl Param {
+    const fn new(name: &'static str, flags: u8) -> Self {
+        Self { name, flags }
+    }
+
+    pub fn is_immutable(&self) -> bool {
+        self.flags & IMMUTABLE != 0
+    }
+
+    fn is_multi_arg(&self) -> bool {
+        self.flags & MULTI_ARG != 0
+    }
+}
+
+/// 支持的参数，CONFIG GET 和 CONFIG REWRITE 按这个顺序输出
+pub const PARAMS: &[Param] = &[
+    Param::new("bind", IMMUTABLE | MULTI_ARG),
+    Param::new("port", IMMUTABLE),
+    Param::new("tls-port", IMMUTABLE),
+    Param::new("tls-cert-file", IMMUTABLE),
+    Param::new("tls-key-file", IMMUTABLE),
+    Param::new("tls-ca-cert-file", IMMUTABLE),
+    Param::new("tls-auth-clients", IMMUTABLE),
+    Param::new("tls-replication", IMMUTABLE),
+    Param::new("unixsocket", IMMUTABLE),
+    Param::new("unixsocketperm", IMMUTABLE),
+    Param::new("databases", IMMUTABLE),
+    Param::new("cluster-enabled", IMMUTABLE),
+    Param::new("aclfile", IMMUTABLE),
+    Param::new("pidfile", IMMUTABLE),
+    Param::new("dir", IMMUTABLE),
+    Param::new("dbfilename", IMMUTABLE),
+    Param::new("replicaof", IMMUTABLE | MULTI_ARG),
+    Param::new("replica-read-only", 0),
+    Param::new("notify-keyspace-events", 0),
+    Param::new("maxmemory", 0),
+    Param::new("maxmemory-policy", 0),
+    Param::new("maxmemory-samples", 0),
+    Param::new("slowlog-log-slower-than", 0),
+    Param::new("slowlog-max-len", 0),
+    Param::new("latency-monitor-threshold", 0),
+    Param::new("requirepass", 0),
+    Param::new("acllog-max-len", 0),
+    Param::new("shutdown-timeout", 0),
+    Param::new("maxclients", 0),
+    Param::new("timeout", 0),
+    Param::new("tcp-keepalive", 0),
+    Param::new("client-query-buffer-limit", 0),
+    Param::new("proto-max-bulk-len", 0),
+    Param::new("client-output-buffer-limit", MULTI_ARG),
+];
+
+/// 按名字查找参数，支持 slaveof 等旧的名字
+pub fn param(name: &str) -> Option<&'static Param> {
+    let name = name.to_lowercase();
+    let name = match name.as_str() {
+        "slaveof" => "replicaof",
+        "slave-read-only" => "replica-read-only",
+        name => name,
+    };
+    PARAMS.iter().find(|param| param.name == name)
+}
+
+/// 校验通过、等待生效的参数值
+#[derive(Debug, Clone)]
+pub enum Update {
+    Bind(Vec<String>),
+    Port(u16),
+    TlsPort(u16),
+    TlsCertFile(PathBuf),
+    TlsKeyFile(PathBuf),
+    TlsCaCertFile(PathBuf),
+    TlsAuthClients(AuthClients),
+    TlsReplication(bool),
+    UnixSocket(PathBuf),
+    UnixSocketPerm(u32),
+    Databases(usize),
+    ClusterEnabled(bool),
+    AclFile(PathBuf),
+    PidFile(PathBuf),
+    Dir(PathBuf),
+    DbFilename(PathBuf),
+    ReplicaOf(Option<MasterAddr>),
+    ReplicaReadOnly(bool),
+    NotifyKeyspaceEvents(u32),
+    MaxMemory(u64),
+    MaxMemoryPolicy(EvictionPolicy),
+    MaxMemorySamples(usize),
+    SlowLogLogSlowerThan(i64),
+    SlowLogMaxLen(usize),
+    LatencyMonitorThreshold(u64),
+    RequirePass(String),
+    AclLogMaxLen(usize),
+    ShutdownTimeout(u64),
+    MaxClients(usize),
+    Timeout(u64),
+    TcpKeepAlive(u64),
+    ClientQueryBufferLimit(usize),
+    ProtoMaxBulkLen(usize),
+    ClientOutputBufferLimit(Vec<(OutputClass, OutputBufferLimit)>),
+}
+
+impl Update {
+    /// 解析 `param` 的值，参数个数不对或者值非法时返回 None
+    pub fn parse(param: &Param, args: &[String]) -> Option<Self> {
+        if param.name == "bind" {
+            return (!args.is_empty()).then(|| Self::Bind(args.to_vec()));
+        }
+        if param.name == "replicaof" {
+            return match args {
+                [no, one] if no.eq_ignore_ascii_case("no") && one.eq_ignore_ascii_case("one") => {
+                    Some(Self::ReplicaOf(None))
+                }
+                [host, port] => Some(Self::ReplicaOf(Some(MasterAddr {
+                    host: host.clone(),
+                    port: port.parse().ok()?,
+                }))),
+                _ => None,
+            };
+        }
+        if param.name == "client-output-buffer-limit" {
+            // CONFIG SET 时多个类别在同一个参数中
+            let args: Vec<String> = args
+                .iter()
+                .flat_map(|arg| arg.split_whitespace())
+                .map(str::to_owned)
+                .collect();
+            return limits::parse_output_limits(&args).map(Self::ClientOutputBufferLimit);
+        }
+        let [value] = args else {
+            return None;
+        };
+        let update = match param.name {
+            "port" => Self::Port(value.parse().ok()?),
+            "tls-port" => Self::TlsPort(value.parse().ok()?),
+            "tls-cert-file" => Self::TlsCertFile(PathBuf::from(value)),
+            "tls-key-file" => Self::TlsKeyFile(PathBuf::from(value)),
+            "tls-ca-cert-file" => Self::TlsCaCertFile(PathBuf::from(value)),
+            "tls-auth-clients" => Self::TlsAuthClients(AuthClients::parse(value)?),
+            "tls-replication" => Self::TlsReplication(parse_bool(value)?),
+            "unixsocket" => Self::UnixSocket(PathBuf::from(value)),
+            // 和 redis.conf 一样按八进制解析，例如 700
+            "unixsocketperm" => Self::UnixSocketPerm(
+                u32::from_str_radix(value, 8)
+                    .ok()
+                    .filter(|perm| *perm <= 0o777)?,
+            ),
+            "databases" => Self::Databases(value.parse().ok().filter(|n| *n > 0)?),
+            "cluster-enabled" => Self::ClusterEnabled(parse_bool(value)?),
+            "aclfile" => Self::AclFile(PathBuf::from(value)),
+            "pidfile" => Self::PidFile(PathBuf::from(value)),
+            "dir" => Self::Dir(PathBuf::from(value)),
+            // 和 Redis 一样只能是文件名，不能包含路径
+            "dbfilename" if value.is_empty() || value.contains('/') => return None,
+            "dbfilename" => Self::DbFilename(PathBuf::from(value)),
+            "replica-read-only" => Self::ReplicaReadOnly(parse_bool(value)?),
+            "notify-keyspace-events" => Self::NotifyKeyspaceEvents(notify::parse_flags(value)?),
+            "maxmemory" => Self::MaxMemory(memory::parse_memory(value)?),
+            "maxmemory-policy" => Self::MaxMemoryPolicy(EvictionPolicy::parse(value)?),
+            "maxmemory-samples" => {
+                Self::MaxMemorySamples(value.parse().ok().filter(|samples| *samples > 0)?)
+            }
+            "slowlog-log-slower-than" => Self::SlowLogLogSlowerThan(value.parse().ok()?),
+            "slowlog-max-len" => Self::SlowLogMaxLen(value.parse().ok()?),
+            "latency-monitor-threshold" => Self::LatencyMonitorThreshold(value.parse().ok()?),
+            "requirepass" => Self::RequirePass(value.clone()),
+            "acllog-max-len" => Self::AclLogMaxLen(value.parse().ok()?),
+            "shutdown-timeout" => Self::ShutdownTimeout(value.parse().ok()?),
+            "maxclients" => Self::MaxClients(value.parse().ok().filter(|n| *n > 0)?),
+            "timeout" => Self::Timeout(value.parse().ok()?),
+            "tcp-keepalive" => Self::TcpKeepAlive(value.parse().ok()?),
+            "client-query-buffer-limit" => Self::ClientQueryBufferLimit(
+                memory::parse_memory(value)
+                    .and_then(|bytes| usize::try_from(bytes).ok())
+                    .filter(|bytes| *bytes >= limits::MIN_QUERY_BUFFER_LIMIT)?,
+            ),
+            "proto-max-bulk-len" => Self::ProtoMaxBulkLen(
+                memory::parse_memory(value)
+                    .and_then(|bytes| usize::try_from(bytes).ok())
+                    .filter(|bytes| *bytes >= limits::MIN_QUERY_BUFFER_LIMIT)?,
+            ),
+            _ => return None,
+        };
+        Some(update)
+    }
+
+    /// 运行期间可以修改的参数立即生效，启动时的参数在这里被忽略
+    pub fn apply(self, data: &Data) {
+        match self {
+            Self::ReplicaReadOnly(read_only) => data.replication.set_read_only(read_only),
+            Self::NotifyKeyspaceEvents(flags) => data.notifications.set_flags(flags),
+            Self::MaxMemory(bytes) => data.memory.set_maxmemory(bytes),
+            Self::MaxMemoryPolicy(policy) => data.memory.set_policy(policy),
+            Self::MaxMemorySamples(samples) => data.memory.set_samples(samples),
+            Self::SlowLogLogSlowerThan(micros) => data.slowlog.set_log_slower_than(micros),
+            Self::SlowLogMaxLen(max_len) => data.slowlog.set_max_len(max_len),
+            Self::LatencyMonitorThreshold(millis) => data.latency.set_threshold(millis),
+            Self::RequirePass(password) => data.acl.set_requirepass(&password),
+            Self::AclLogMaxLen(max_len) => data.acl.set_log_max_len(max_len),
+            Self::ShutdownTimeout(secs) => data.shutdown.set_timeout(secs),
+            Self::MaxClients(maxclients) => data.limits.set_maxclients(maxclients),
+            Self::Timeout(secs) => data.limits.set_timeout(secs),
+            Self::TcpKeepAlive(secs) => data.limits.set_tcp_keepalive(secs),
+            Self::ClientQueryBufferLimit(bytes) => data.limits.set_query_buffer(bytes),
+            Self::ProtoMaxBulkLen(bytes) => data.limits.set_proto_max_bulk_len(bytes),
+            Self::ClientOutputBufferLimit(classes) => {
+                for (class, limit) in classes {
+                    data.limits.set_output(class, limit);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 启动时确定、运行期间不变的配置
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    // 启动时使用的配置文件，CONFIG REWRITE 写回这个文件
+    pub file: Option<PathBuf>,
+    pub bind: Vec<String>,
+    pub port: u16,
+    pub tls_port: u16,
+    pub tls: TlsConfig,
+    pub unixsocket: Option<PathBuf>,
+    pub unixsocketperm: Option<u32>,
+    pub databases: usize,
+    pub cluster_enabled: bool,
+    pub aclfile: Option<PathBuf>,
+    // 启动时写入进程号，关闭时删除
+    pub pidfile: Option<PathBuf>,
+    // SHUTDOWN SAVE 时快照保存到 dir/dbfilename
+    pub dir: PathBuf,
+    pub dbfilename: PathBuf,
+    pub replicaof: Option<MasterAddr>,
+    // 运行期间可以修改的参数，创建 `Data` 之后按顺序生效
+    pub updates: Vec<Update>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            bind: vec!["127.0.0.1".to_owned()],
+            port: 6379,
+            tls_port: 0,
+            tls: TlsConfig::default(),
+            unixsocket: None,
+            unixsocketperm: None,
+            databases: db::DEFAULT_DATABASES,
+            cluster_enabled: false,
+            aclfile: None,
+            pidfile: None,
+            dir: PathBuf::from("."),
+            dbfilename: PathBuf::from(DEFAULT_DBFILENAME),
+            replicaof: None,
+            updates: Vec::new(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// 解析命令行：第一个参数不以 `--` 开头时是配置文件，之后的 `--name value ...` 覆盖配置文件
+    pub fn load(args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut args = args.peekable();
+        let mut config = Self::default();
+        if let Some(path) = args.next_if(|arg| !arg.starts_with("--")) {
+            let path = PathBuf::from(path);
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| anyhow!("can't open config file {}: {e}", path.display()))?;
+            for (index, line) in content.lines().enumerate() {
+                let mut args = split_args(line)
+                    .map_err(|e| anyhow!("{}:{}: {e}", path.display(), index + 1))?;
+                if args.is_empty() || args[0].starts_with('#') {
+                    continue;
+                }
+                let name = args.remove(0);
+                config
+                    .set(&name, &args)
+                    .map_err(|e| anyhow!("{}:{}: {e}", path.display(), index + 1))?;
+            }
+            config.file = Some(path);
+        }
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| anyhow!("unknown argument: {arg}"))?;
+            let mut values = Vec::new();
+            while let Some(value) = args.next_if(|arg| !arg.starts_with("--")) {
+                values.push(value);
+            }
+            // 同时支持 --replicaof "127.0.0.1 6379" 和 --replicaof 127.0.0.1 6379
+            if values.len() == 1 && param(name).is_some_and(Param::is_multi_arg) {
+                values = values[0].split_whitespace().map(str::to_owned).collect();
+            }
+            config
+                .set(name, &values)
+                .map_err(|e| anyhow!("--{name}: {e}"))?;
+        }
+        Ok(config)
+    }
+
+    // 设置一条配置，运行期间可以修改的参数暂存到 `updates`
+    fn set(&mut self, name: &str, args: &[String]) -> Result<()> {
+        let param = param(name).ok_or_else(|| anyhow!("Bad directive '{name}'"))?;
+        let update = Update::parse(param, args)
+            .ok_or_else(|| anyhow!("Invalid argument '{}' for '{name}'", args.join(" ")))?;
+        match update {
+            Update::Bind(bind) => self.bind = bind,
+            Update::Port(port) => self.port = port,
+            Update::TlsPort(port) => self.tls_port = port,
+            Update::TlsCertFile(path) => self.tls.cert_file = Some(path),
+            Update::TlsKeyFile(path) => self.tls.key_file = Some(path),
+            Update::TlsCaCertFile(path) => self.tls.ca_cert_file = Some(path),
+            Update::TlsAuthClients(auth_clients) => self.tls.auth_clients = auth_clients,
+            Update::TlsReplication(replication) => self.tls.replication = replication,
+            Update::UnixSocket(path) => self.unixsocket = Some(path),
+            Update::UnixSocketPerm(perm) => self.unixsocketperm = Some(perm),
+            Update::Databases(databases) => self.databases = databases,
+            Update::ClusterEnabled(enabled) => self.cluster_enabled = enabled,
+            Update::AclFile(path) => self.aclfile = Some(path),
+            Update::PidFile(path) => self.pidfile = Some(path),
+            Update::Dir(path) => self.dir = path,
+            Update::DbFilename(path) => self.dbfilename = path,
+            Update::ReplicaOf(master) => self.replicaof = master,
+            update => self.updates.push(update),
+        }
+        Ok(())
+    }
+}
+
+/// 保存在 `Data` 中的启动配置
+#[derive(Debug, Default)]
+pub struct Config {
+    server: RwLock<ServerConfig>,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn server(&self) -> ServerConfig {
+        self.server
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn set_server(&self, mut server: ServerConfig) {
+        server.updates.clear();
+        *self.server.write().unwrap_or_else(|e| e.into_inner()) = server;
+    }
+}
+
+/// 参数当前的值，CONFIG GET 和 CONFIG REWRITE 使用
+pub fn get(data: &Data, name: &str) -> String {
+    let server = data.config.server();
+    let path = |path: Option<&PathBuf>| path.map(|path| path.display().to_string());
+    match name {
+        "bind" => server.bind.join(" "),
+        "port" => server.port.to_string(),
+        "tls-port" => server.tls_port.to_string(),
+        "tls-cert-file" => path(server.tls.cert_file.as_ref()).unwrap_or_default(),
+        "tls-key-file" => path(server.tls.key_file.as_ref()).unwrap_or_default(),
+        "tls-ca-cert-file" => path(server.tls.ca_cert_file.as_ref()).unwrap_or_default(),
+        "tls-auth-clients" => match server.tls.auth_clients {
+            AuthClients::No => "no",
+            AuthClients::Yes => "yes",
+            AuthClients::Optional => "optional",
+        }
+        .to_owned(),
+        "tls-replication" => yes_no(server.tls.replication),
+        "unixsocket" => path(server.unixsocket.as_ref()).unwrap_or_default(),
+        "unixsocketperm" => format!("{:o}", server.unixsocketperm.unwrap_or(0)),
+        "databases" => data.databases().to_string(),
+        "cluster-enabled" => yes_no(data.cluster.is_enabled()),
+        "aclfile" => path(data.acl.file().as_ref()).unwrap_or_default(),
+        "pidfile" => path(server.pidfile.as_ref()).unwrap_or_default(),
+        "dir" => server.dir.display().to_string(),
+        "dbfilename" => server.dbfilename.display().to_string(),
+        "replicaof" => data
+            .replication
+            .master_addr()
+            .map(|master| format!("{} {}", master.host, master.port))
+            .unwrap_or_default(),
+        "replica-read-only" => yes_no(data.replication.is_read_only()),
+        "notify-keyspace-events" => data.notifications.config(),
+        "maxmemory" => data.memory.maxmemory().to_string(),
+        "maxmemory-policy" => data.memory.policy().as_str().to_owned(),
+        "maxmemory-samples" => data.memory.samples().to_string(),
+        "slowlog-log-slower-than" => data.slowlog.log_slower_than().to_string(),
+        "slowlog-max-len" => data.slowlog.max_len().to_string(),
+        "latency-monitor-threshold" => data.latency.threshold().to_string(),
+        "requirepass" => data.acl.requirepass(),
+        "acllog-max-len" => data.acl.log_max_len().to_string(),
+        "shutdown-timeout" => data.shutdown.timeout().as_secs().to_string(),
+        "maxclients" => data.limits.maxclients().to_string(),
+        "timeout" => data.limits.timeout().as_secs().to_string(),
+        "tcp-keepalive" => data.limits.tcp_keepalive().as_secs().to_string(),
+        "client-query-buffer-limit" => data.limits.query_buffer().to_string(),
+        "proto-max-bulk-len" => data.limits.proto_max_bulk_len().to_string(),
+        "client-output-buffer-limit" => data.limits.output_config(),
+        _ => String::new(),
+    }
+}
+
+/// CONFIG REWRITE：原有的参数行原地改写，注释和空行保持不变，
+/// 等于默认值的参数被删除，其余参数追加到文件末尾
+pub fn rewrite(data: &Data) -> Result<(), String> {
+    let path = data
+        .config
+        .server()
+        .file
+        .ok_or_else(|| "ERR The server is running without a config file".to_owned())?;
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("ERR Rewriting config file: {e}")),
+    };
+    let defaults = Data::new();
+    // 非默认值的参数和写回的行
+    let mut pending: Vec<(&Param, String)> = PARAMS
+        .iter()
+        .map(|param| (param, get(data, param.name)))
+        .filter(|(param, value)| *value != get(&defaults, param.name))
+        .map(|(param, value)| (param, format_line(param, &value)))
+        .collect();
+    let mut lines = Vec::new();
+    let mut has_marker = false;
+    for line in content.lines() {
+        has_marker |= line.trim() == REWRITE_MARKER;
+        let name = split_args(line)
+            .ok()
+            .and_then(|args| args.into_iter().next())
+            .filter(|name| !name.starts_with('#'));
+        let Some(param) = name.as_deref().and_then(param) else {
+            lines.push(line.to_owned());
+            continue;
+        };
+        // 同一个参数只保留第一行
+        if let Some(index) = pending.iter().position(|(p, _)| p.name == param.name) {
+            lines.push(pending.remove(index).1);
+        }
+    }
+    if !pending.is_empty() {
+        if !has_marker {
+            lines.push(REWRITE_MARKER.to_owned());
+        }
+        lines.extend(pending.into_iter().map(|(_, line)| line));
+    }
+    let mut content = lines.join("\n");
+    content.push('\n');
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, content)
+        .and_then(|_| std::fs::rename(&tmp, &path))
+        .map_err(|e| format!("ERR Rewriting config file: {e}"))
+}
+
+fn format_line(param: &Param, value: &str) -> String {
+    if param.is_multi_arg() {
+        format!("{} {value}", param.name)
+    } else {
+        format!("{} {}", param.name, quote(value))
+    }
+}
+
+// 值为空或者包含空白、引号时加上双引号
+fn quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && !value
+            .chars()
+            .any(|c| c.is_whitespace() || c == '"' || c == '\'' || c == '\\');
+    if plain {
+        return value.to_owned();
+    }
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// 按 redis.conf 的规则切分一行：空白分隔，支持双引号（带转义）和单引号
+pub fn split_args(line: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut chars = line.trim().chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut arg = String::new();
+        match c {
+            '"' | '\'' => {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        Some('\\') if c == '"' => match chars.next() {
+                            Some('n') => arg.push('\n'),
+                            Some('r') => arg.push('\r'),
+                            Some('t') => arg.push('\t'),
+                            Some(escaped) => arg.push(escaped),
+                            None => return Err("unbalanced quotes in configuration line".into()),
+                        },
+                        Some('\\') if chars.peek() == Some(&'\'') => {
+                            arg.push('\'');
+                            chars.next();
+                        }
+                        Some(ch) => arg.push(ch),
+                        None => return Err("unbalanced quotes in configuration line".into()),
+                    }
+                }
+                // 右引号之后必须是空白
+                if chars.peek().is_some_and(|c| !c.is_whitespace()) {
+                    return Err("unbalanced quotes in configuration line".into());
+                }
+            }
+            _ => {
+                while let Some(ch) = chars.next_if(|ch| !ch.is_whitespace()) {
+                    arg.push(ch);
+                }
+            }
+        }
+        args.push(arg);
+    }
+    Ok(args)
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "yes" => Some(true),
+        "no" => Some(false),
+        _ => None,
+    }
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "yes" } else { "no" }.to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        args.iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(
+            split_args(r#"requirepass "a b\"c" 'd e'"#).unwrap(),
+            vec!["requirepass", "a b\"c", "d e"]
+        );
+        assert_eq!(split_args("   ").unwrap(), Vec::<String>::new());
+        assert!(split_args(r#"requirepass "abc"#).is_err());
+        assert!(split_args(r#"requirepass "abc"d"#).is_err());
+    }
+
+    #[test]
+    fn test_load_file_and_args() {
+        let path = std::env::temp_dir().join(format!("simple-redis-{}.conf", std::process::id()));
+        std::fs::write(
+            &path,
+            "# cache\nport 7000\nbind 0.0.0.0 ::1\nmaxmemory 10mb\nslaveof 10.0.0.1 6380\n",
+        )
+        .unwrap();
+        let config = ServerConfig::load(args(&[
+            path.to_str().unwrap(),
+            "--port",
+            "7001",
+            "--maxmemory-policy",
+            "allkeys-lru",
+        ]))
+        .unwrap();
+        assert_eq!(config.port, 7001);
+        assert_eq!(config.bind, vec!["0.0.0.0", "::1"]);
+        assert_eq!(config.replicaof.as_ref().unwrap().port, 6380);
+        assert_eq!(config.updates.len(), 2);
+        assert_eq!(config.file.as_deref(), Some(path.as_path()));
+
+        std::fs::write(&path, "port 7000\nmaxmemory\n").unwrap();
+        let err = ServerConfig::load(args(&[path.to_str().unwrap()])).unwrap_err();
+        assert!(err
+            .to_string()
+            .ends_with(":2: Invalid argument '' for 'maxmemory'"));
+        assert!(ServerConfig::load(args(&["--no-such-option", "1"])).is_err());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let path =
+            std::env::temp_dir().join(format!("simple-redis-rw-{}.conf", std::process::id()));
+        std::fs::write(
+            &path,
+            "# my cache\nport 7000\n\nmaxmemory 1mb\nmaxmemory 2mb\nslowlog-max-len 128\n",
+        )
+        .unwrap();
+        let data = Data::new();
+        // 注释和空行原样保留
+        data.config.set_server(ServerConfig {
+            file: Some(path.clone()),
+            port: 7000,
+            ..Default::default()
+        });
+        data.memory.set_maxmemory(3 * 1024 * 1024);
+        data.acl.set_requirepass("a b");
+        rewrite(&data).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# my cache\nport 7000\n\nmaxmemory 3145728\n\
+             # Generated by CONFIG REWRITE\nrequirepass \"a b\"\n"
+        );
+        // 再次改写时结果不变
+        rewrite(&data).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.matches(REWRITE_MARKER).count(), 1);
+        assert!(rewrite(&Data::new()).is_err());
+        let _ = std::fs::remove_file(path);
+    }
+}
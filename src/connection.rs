@@ -0,0 +1,560 @@
+//! 客户端连接
+//!
+//! 1. 从连接中读取 frame，解析出命令和参数
+//! 2. 处理连接级别的状态：选择的库、事务、订阅、协议版本、集群 ASKING 等
+//! 3. 其余命令交给对应的 Processor，把结果写回连接
+//!
+//! 订阅了频道的连接还会收到其他客户端发布的消息，和命令回复一起写出。
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::Result;
+use futures::SinkExt;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{mpsc, watch};
+use tokio_stream::StreamExt;
+use tokio_util::codec::Framed;
+use tracing::{debug, error, info};
+
+use crate::client::{self, Client, Registered, Snapshot};
+use crate::limits::{Outbox, OutputSize};
+use crate::network::RespFrameCodec;
+use crate::process::cluster::ClusterCommand;
+use crate::process::command_table::{self, CommandSpec};
+use crate::process::connection::{auth, ConnectionCommand};
+use crate::process::pubsub::PubSubCommand;
+use crate::process::replication::psync::PSyncCommandPara;
+use crate::process::replication::ReplicationCommand;
+use crate::process::server::ServerCommand;
+use crate::process::CommandGroup;
+use crate::pubsub::{self, Subscriber};
+use crate::shutdown::Phase;
+use crate::stats::Stats;
+use crate::transaction::{self, Transaction};
+use crate::{
+    Arrays, BulkStrings, Data, Processor, Resp, RespError, SimpleErrors, SimpleStringsData,
+};
+
+// RESP2 订阅状态下允许执行的命令
+const SUBSCRIBE_CONTEXT_COMMANDS: &[&str] = &[
+    "subscribe",
+    "unsubscribe",
+    "psubscribe",
+    "punsubscribe",
+    "ssubscribe",
+    "sunsubscribe",
+    "ping",
+    "quit",
+    "reset",
+];
+
+// 事务中不排队、直接执行的命令
+const TRANSACTION_CONTROL_COMMANDS: &[&str] = &["exec", "discard", "multi", "watch"];
+
+/// 处理一个客户端连接，直到连接关闭或被 CLIENT KILL
+///
+/// `registered` 为 accept 时在连接表中注册的客户端，连接结束时注销
+pub async fn serve<T>(stream: T, registered: Registered, data: Arc<Data>) -> Result<()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut framed = Framed::new(stream, RespFrameCodec::with_limits(data.limits.clone()));
+    let _connected = Stats::client_connected(&data);
+    let client = registered.client().clone();
+    let addr = client.addr.clone();
+    let (subscriber, mut messages) = Subscriber::new(data.clone(), client.id);
+    client.set_push(subscriber.sender());
+    // default 用户没有密码时连接直接以它的身份认证
+    if !data.acl.requires_auth() {
+        client.authenticate(crate::acl::DEFAULT_USER);
+    }
+    let mut conn = Connection {
+        transaction: Transaction::new(data.clone()),
+        subscriber,
+        data,
+        client: client.clone(),
+        db: 0,
+        protocol: 2,
+        repl_listening_port: None,
+        asking: false,
+    };
+    // MONITOR 之后接收命令推送的一端
+    let mut monitor: Option<mpsc::UnboundedReceiver<Resp>> = None;
+
+    loop {
+        tokio::select! {
+            frame = framed.next() => match frame {
+                Some(Ok(frame)) => {
+                    info!("📥 Received frame from {}: {:?}", addr, frame);
+                    // CLIENT PAUSE 期间等待暂停结束再执行
+                    let write = conn.may_replicate(&frame);
+                    tokio::select! {
+                        _ = conn.data.clients.wait_unpaused(write) => {}
+                        _ = client.killed() => return Ok(()),
+                    }
+                    client.begin_command(&frame);
+                    let outcome = client::with_client(&client, || conn.handle(frame))?;
+                    let replies = match outcome {
+                        Outcome::Reply(replies) => replies,
+                        Outcome::Replica(psync) => {
+                            client.set_replica();
+                            return crate::replication::master::serve_replica(
+                                framed,
+                                conn.data.clone(),
+                                &client,
+                                &psync,
+                                conn.repl_listening_port,
+                                // unix socket 连接的副本和主节点在同一台机器上
+                                addr.parse()
+                                    .unwrap_or(SocketAddr::from(([127, 0, 0, 1], 0))),
+                            )
+                            .await;
+                        }
+                        Outcome::Monitor(rx) => {
+                            monitor = Some(rx);
+                            client.set_monitor();
+                            vec![Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned()))]
+                        }
+                        Outcome::Shutdown(mut phase) => {
+                            // 关闭成功时连接被断开，不回复
+                            tokio::select! {
+                                _ = client.killed() => return Ok(()),
+                                _ = phase.wait_for(|phase| *phase == Phase::Running) => {
+                                    vec![error("ERR Errors trying to SHUTDOWN. Check logs.")]
+                                }
+                            }
+                        }
+                    };
+                    // CLIENT REPLY OFF|SKIP 时丢弃回复
+                    let replies = if client.end_command() { replies } else { Vec::new() };
+                    // 客户端不读取时写操作会一直阻塞，这期间仍然要响应 CLIENT KILL 和输出缓冲区限制；
+                    // 优先写出，这样 CLIENT KILL 关闭自己时仍然能收到回复
+                    tokio::select! {
+                        biased;
+                        written = write_replies(&mut framed, replies, &addr) => {
+                            if let Err(e) = written {
+                                error!("❌ Failed to send response to {}: {}", addr, e);
+                                return Err(e);
+                            }
+                            debug!("✅ Response sent successfully to {}", addr);
+                        }
+                        _ = client.killed() => return killed(&addr),
+                    }
+                    conn.sync_client(&framed);
+                    // CLIENT KILL 关闭自己时在回复之后断开
+                    if client.is_killed() {
+                        return killed(&addr);
+                    }
+                }
+                Some(Err(e)) => {
+                    error!("🔥 Frame decode error from {}: {}", addr, e);
+                    // 无法解析的请求先告诉客户端原因再断开，读写出错和超过查询缓冲区限制时直接断开
+                    if let Some(e) = e.downcast_ref::<RespError>() {
+                        let msg = match e {
+                            RespError::Protocol(msg) => msg.clone(),
+                            e => e.to_string(),
+                        };
+                        let _ = framed.send(error(&format!("ERR Protocol error: {msg}"))).await;
+                    }
+                    return Err(e);
+                }
+                None => {
+                    info!("👋 Client {} disconnected", addr);
+                    return Ok(());
+                }
+            },
+            Some(message) = messages.recv() => {
+                let size = message.output_size();
+                tokio::select! {
+                    sent = framed.send(pubsub::into_frame(message, conn.protocol)) => sent?,
+                    _ = client.killed() => return killed(&addr),
+                }
+                client.release_output(size);
+            }
+            Some(line) = recv_monitor(&mut monitor) => {
+                let size = line.output_size();
+                tokio::select! {
+                    sent = framed.send(line) => sent?,
+                    _ = client.killed() => return killed(&addr),
+                }
+                client.release_output(size);
+            }
+            _ = client.killed() => return killed(&addr),
+        }
+    }
+}
+
+// 写出一条命令的所有回复
+async fn write_replies<T>(
+    framed: &mut Framed<T, RespFrameCodec>,
+    replies: Vec<Resp>,
+    addr: &str,
+) -> Result<()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    for reply in replies {
+        info!("📤 Response to {}: {:?}", addr, reply);
+        framed.feed(reply).await?;
+    }
+    framed.flush().await
+}
+
+fn killed(addr: &str) -> Result<()> {
+    info!("🔪 Client {} killed", addr);
+    Ok(())
+}
+
+// 命令处理结果
+enum Outcome {
+    Reply(Vec<Resp>),
+    // PSYNC 之后连接转为向副本传输数据
+    Replica(PSyncCommandPara),
+    // MONITOR 之后连接接收所有客户端执行的命令
+    Monitor(mpsc::UnboundedReceiver<Resp>),
+    // SHUTDOWN 之后等待关闭完成或者被 SHUTDOWN ABORT 取消
+    Shutdown(watch::Receiver<Phase>),
+}
+
+struct Connection {
+    data: Arc<Data>,
+    client: Arc<Client>,
+    // SELECT 选择的库
+    db: usize,
+    // RESP 协议版本，通过 HELLO 切换
+    protocol: u8,
+    // 副本在握手阶段通过 REPLCONF listening-port 告知的端口
+    repl_listening_port: Option<u16>,
+    // 集群模式下 ASKING 只对紧随其后的一条命令生效
+    asking: bool,
+    transaction: Transaction,
+    subscriber: Subscriber,
+}
+
+impl Connection {
+    fn handle(&mut self, frame: Resp) -> Result<Outcome> {
+        let data = self.data.clone();
+        let name = command_table::command_name(&frame)
+            .unwrap_or_default()
+            .to_lowercase();
+        let spec = command_table::lookup(&name);
+        let is_write = command_table::is_write_command(&frame);
+
+        // 未认证的连接只能执行 AUTH、HELLO 这类命令
+        if !self.client.is_authenticated() && !spec.is_some_and(CommandSpec::no_auth) {
+            self.transaction.flag_error();
+            return Ok(self.reject(spec, error("NOAUTH Authentication required.")));
+        }
+        let context = if self.transaction.in_multi() {
+            "multi"
+        } else {
+            "toplevel"
+        };
+        if let Err(e) = data.acl.check_current(&frame, context) {
+            self.transaction.flag_error();
+            return Ok(self.reject(spec, error(&e)));
+        }
+        let redirect = data
+            .cluster
+            .check_redirect(&data, &frame, std::mem::take(&mut self.asking));
+        if let Some(redirect) = redirect {
+            self.transaction.flag_error();
+            return Ok(self.reject(spec, redirect.into_resp()));
+        }
+        let resp2_subscribed = self.protocol < 3 && self.subscriber.is_subscribed();
+        if resp2_subscribed && !SUBSCRIBE_CONTEXT_COMMANDS.contains(&name.as_str()) {
+            return Ok(self.reject(spec, error(&format!(
+                "ERR Can't execute '{name}': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context"
+            ))));
+        }
+        if is_write && data.replication.is_read_only_replica() {
+            self.transaction.flag_error();
+            return Ok(self.reject(
+                spec,
+                error("READONLY You can't write against a read only replica."),
+            ));
+        }
+        if self.transaction.in_multi() && !TRANSACTION_CONTROL_COMMANDS.contains(&name.as_str()) {
+            // MULTI 之后的命令先排队，EXEC 时统一执行
+            let queued = self.transaction.queue(frame);
+            if matches!(queued, Resp::SimpleErrors(_)) {
+                return Ok(self.reject(spec, queued));
+            }
+            return Ok(reply(queued));
+        }
+        // 未知命令和参数个数不对的命令直接回复错误，不能断开连接
+        if let Err(e) = command_table::check_arity(&frame) {
+            return Ok(self.reject(spec, error(&e)));
+        }
+        if resp2_subscribed && name == "ping" {
+            // 订阅状态下 PING 以消息的形式回复
+            let message = match &frame {
+                Resp::Arrays(arr) => arr.val.get(1).cloned(),
+                _ => None,
+            };
+            return Ok(reply(Resp::Arrays(Arrays::new(vec![
+                Resp::BulkStrings(BulkStrings::new("pong".to_owned())),
+                message.unwrap_or_else(|| Resp::BulkStrings(BulkStrings::new(String::new()))),
+            ]))));
+        }
+
+        // 解析之前把原始命令推送给 MONITOR 中的连接
+        data.monitors.feed(self.db, &frame);
+        if let Some(spec) = spec.filter(|spec| spec.is_connection_level()) {
+            let start = Instant::now();
+            let result = self.transaction_command(spec.name, &frame);
+            data.stats.record_call(spec.name, start.elapsed(), &result);
+            return Ok(reply(result));
+        }
+        let command = match CommandGroup::try_from(frame.clone()) {
+            Ok(command) => command,
+            Err(e) => return Ok(self.reject(spec, error(&format!("ERR {e}")))),
+        };
+        info!("⚡ Processing command: {:?}", command);
+        // 连接级别的命令在这里统计，其余命令由 transaction::call 统计
+        let start = Instant::now();
+        let outcome = match command {
+            CommandGroup::Replication(ReplicationCommand::PSync(psync)) => {
+                return Ok(Outcome::Replica(psync));
+            }
+            CommandGroup::Connection(ConnectionCommand::Monitor(_)) => {
+                let (tx, rx) = Outbox::channel(Some(self.client.clone()), &data.limits);
+                data.monitors.attach(tx);
+                Outcome::Monitor(rx)
+            }
+            CommandGroup::Server(ServerCommand::Shutdown(shutdown)) if shutdown.is_request() => {
+                shutdown.process(&data)?;
+                Outcome::Shutdown(data.shutdown.subscribe())
+            }
+            CommandGroup::PubSub(PubSubCommand::Subscribe(subscribe)) => {
+                let messages = self
+                    .subscriber
+                    .subscribe(subscribe.kind, &subscribe.channels);
+                self.messages(messages)
+            }
+            CommandGroup::PubSub(PubSubCommand::Unsubscribe(unsubscribe)) => {
+                let messages = self
+                    .subscriber
+                    .unsubscribe(unsubscribe.kind, &unsubscribe.channels);
+                self.messages(messages)
+            }
+            CommandGroup::Connection(ConnectionCommand::Select(select)) => {
+                reply(match select.validate(&data) {
+                    Ok(index) => {
+                        self.db = index;
+                        Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned()))
+                    }
+                    Err(e) => e,
+                })
+            }
+            CommandGroup::Connection(ConnectionCommand::Hello(hello)) => {
+                reply(match hello.protover {
+                    Some(protover) if !(2..=3).contains(&protover) => {
+                        error("NOPROTO unsupported protocol version")
+                    }
+                    _ if hello
+                        .setname
+                        .as_deref()
+                        .is_some_and(|name| !client::valid_name(name)) =>
+                    {
+                        error("ERR Client names cannot contain spaces, newlines or special characters.")
+                    }
+                    protover => 'hello: {
+                        // HELLO AUTH 先认证，再切换协议
+                        if let Some((username, password)) = &hello.auth {
+                            let auth = auth::authenticate(&data, &self.client, username, password);
+                            if matches!(auth, Resp::SimpleErrors(_)) {
+                                break 'hello auth;
+                            }
+                        }
+                        if !self.client.is_authenticated() {
+                            break 'hello error("NOAUTH HELLO must be called with the client already authenticated, otherwise the HELLO <proto> AUTH <user> <pass> option can be used to authenticate the client and select the RESP protocol version at the same time");
+                        }
+                        if let Some(name) = &hello.setname {
+                            self.client.set_name(name);
+                        }
+                        self.protocol = protover.unwrap_or(self.protocol);
+                        hello.reply(&data, self.protocol, self.client.id)
+                    }
+                })
+            }
+            command => {
+                match &command {
+                    CommandGroup::Replication(ReplicationCommand::ReplConf(replconf)) => {
+                        if let Some(port) = replconf.listening_port() {
+                            self.repl_listening_port = Some(port);
+                        }
+                    }
+                    CommandGroup::Cluster(ClusterCommand::Asking(_)) => {
+                        self.asking = data.cluster.is_enabled();
+                    }
+                    _ => {}
+                }
+                let _guard = data.transactions.shared();
+                return Ok(reply(
+                    data.with_db(self.db, || transaction::call(&data, frame, &command))?,
+                ));
+            }
+        };
+        if let (Some(spec), Outcome::Reply(replies)) = (spec, &outcome) {
+            if let Some(first) = replies.first() {
+                data.stats.record_call(spec.name, start.elapsed(), first);
+            }
+        }
+        Ok(outcome)
+    }
+
+    // CLIENT PAUSE WRITE 时需要等待的命令：写命令、PUBLISH 和包含写命令的 EXEC
+    fn may_replicate(&self, frame: &Resp) -> bool {
+        if command_table::is_write_command(frame) {
+            return true;
+        }
+        let name = command_table::command_name(frame)
+            .unwrap_or_default()
+            .to_lowercase();
+        match name.as_str() {
+            "publish" | "spublish" => true,
+            "exec" => self.transaction.queued_writes(),
+            _ => false,
+        }
+    }
+
+    // 把连接的状态同步给连接表，供 CLIENT LIST 使用
+    fn sync_client<T>(&self, framed: &Framed<T, RespFrameCodec>) {
+        let (channels, patterns, shard_channels) = self.subscriber.counts();
+        let read_buffer = framed.read_buffer();
+        self.client.update(Snapshot {
+            db: self.db,
+            resp: self.protocol,
+            channels,
+            patterns,
+            shard_channels,
+            multi: self.transaction.queued_len(),
+            watch: self.transaction.watched_len(),
+            qbuf: read_buffer.len(),
+            qbuf_free: read_buffer.capacity() - read_buffer.len(),
+            obl: framed.write_buffer().len(),
+        });
+    }
+
+    // 事务控制命令，事务状态属于连接
+    fn transaction_command(&mut self, name: &str, frame: &Resp) -> Resp {
+        match name {
+            "multi" => self.transaction.multi(),
+            "exec" => self.transaction.exec(&mut self.db),
+            "discard" => self.transaction.discard(),
+            "watch" => {
+                let keys: Vec<String> = command_table::command_keys(frame)
+                    .into_iter()
+                    .map(str::to_owned)
+                    .collect();
+                self.transaction.watch(self.db, &keys)
+            }
+            _ => self.transaction.unwatch(),
+        }
+    }
+
+    // 命令在执行前被拒绝
+    fn reject(&self, spec: Option<&'static CommandSpec>, frame: Resp) -> Outcome {
+        let msg = match &frame {
+            Resp::SimpleErrors(e) => e.error_msg.as_str(),
+            _ => "",
+        };
+        self.data
+            .stats
+            .record_rejected(spec.map(|spec| spec.name), msg);
+        reply(frame)
+    }
+
+    fn messages(&self, messages: Vec<pubsub::Message>) -> Outcome {
+        Outcome::Reply(
+            messages
+                .into_iter()
+                .map(|message| pubsub::into_frame(message, self.protocol))
+                .collect(),
+        )
+    }
+}
+
+// 没有进入监视模式时永远不会就绪
+async fn recv_monitor(monitor: &mut Option<mpsc::UnboundedReceiver<Resp>>) -> Option<Resp> {
+    match monitor {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+fn reply(frame: Resp) -> Outcome {
+    Outcome::Reply(vec![frame])
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // 发送一段原始请求，返回连接关闭之前收到的所有数据
+    async fn exchange(request: &[u8]) -> String {
+        let data = Arc::new(Data::new());
+        let registered = data
+            .clients
+            .register("127.0.0.1:5000".to_owned(), "127.0.0.1:6379".to_owned());
+        let (mut client, server) = tokio::io::duplex(16 * 1024);
+        let server = tokio::spawn(serve(server, registered, data));
+        client.write_all(request).await.unwrap();
+        let mut reply = Vec::new();
+        client.read_to_end(&mut reply).await.unwrap();
+        assert!(server.await.unwrap().is_err());
+        String::from_utf8(reply).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_protocol_error_reply_then_close() {
+        // 之前完整的命令照常回复
+        assert_eq!(
+            exchange(b"*1\r\n$4\r\nPING\r\n*-5\r\n").await,
+            "+PONG\r\n-ERR Protocol error: invalid multibulk length\r\n"
+        );
+        assert_eq!(
+            exchange(b"*1\r\n$4\r\nPINGXX").await,
+            "-ERR Protocol error: bulk string not terminated by CRLF\r\n"
+        );
+        assert_eq!(
+            exchange(b"?foo\r\n").await,
+            "-ERR Protocol error: Invalid frame type: unknown frame type: '?'\r\n"
+        );
+        assert_eq!(
+            exchange(b"\r\n").await,
+            "-ERR Protocol error: expected a type byte\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transaction_commands() {
+        // 事务控制命令由连接处理，UNWATCH 在 MULTI 中照常入队
+        assert_eq!(
+            exchange(b"*2\r\n$5\r\nWATCH\r\n$1\r\nk\r\n*1\r\n$5\r\nMULTI\r\n*1\r\n$7\r\nUNWATCH\r\n*1\r\n$4\r\nEXEC\r\n*1\r\n$7\r\nDISCARD\r\n?\r\n").await,
+            "+OK\r\n+OK\r\n+QUEUED\r\n*1\r\n+OK\r\n-ERR DISCARD without MULTI\r\n-ERR Protocol error: Invalid frame type: unknown frame type: '?'\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_command_errors_keep_connection() {
+        // 参数错误和未知命令只回复错误，后面的命令照常执行
+        assert_eq!(
+            exchange(b"*1\r\n$3\r\nGET\r\n*1\r\n$3\r\nFOO\r\n*1\r\n$4\r\nPING\r\n?\r\n").await,
+            "-ERR wrong number of arguments for 'get' command\r\n-ERR unknown command 'FOO'\r\n+PONG\r\n-ERR Protocol error: Invalid frame type: unknown frame type: '?'\r\n"
+        );
+        // MULTI 中的未知命令同样报错
+        assert_eq!(
+            exchange(b"*1\r\n$5\r\nMULTI\r\n*1\r\n$3\r\nFOO\r\n?\r\n").await,
+            "+OK\r\n-ERR unknown command 'FOO'\r\n-ERR Protocol error: Invalid frame type: unknown frame type: '?'\r\n"
+        );
+    }
+}
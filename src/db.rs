@@ -0,0 +1,418 @@
+//! 逻辑数据库
+//!
+//! `Data` 持有多个相互独立的 `Db`（默认 16 个，对应 Redis 的 `databases` 配置），
+//! 每个连接通过 SELECT 选择自己使用的库。
+//!
+//! 命令是在连接的任务中同步执行的，执行期间不会切换线程，所以当前选择的库
+//! 用线程局部变量传递：连接在执行命令前通过 `Data::with_db` 设置，
+//! Processor 中通过 `data.db()` 访问当前库，不需要在每个命令里传递库编号。
+
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use dashmap::DashMap;
+use rand::Rng;
+
+use crate::keymap::KeyMap;
+use crate::Resp;
+
+// 默认的数据库数量
+pub const DEFAULT_DATABASES: usize = 16;
+
+// LFU 计数器的初始值和对数增长因子，与 Redis 的 lfu-log-factor 默认值一致
+const LFU_INIT_VAL: u8 = 5;
+const LFU_LOG_FACTOR: f64 = 10.0;
+// 每隔多少毫秒没有访问，LFU 计数器减一（lfu-decay-time 为 1 分钟）
+const LFU_DECAY_MILLIS: u64 = 60 * 1000;
+// 超过这个元素数量的值交给后台线程释放
+const LAZYFREE_THRESHOLD: usize = 64;
+
+thread_local! {
+    static SELECTED_DB: Cell<usize> = const { Cell::new(0) };
+}
+
+#[derive(Debug, Default)]
+pub struct Db {
+    pub(crate) string_data: KeyMap<Resp>,
+    pub(crate) hash_data: KeyMap<HashMap<String, String>>,
+    pub(crate) list_data: KeyMap<VecDeque<String>>,
+    pub(crate) set_data: KeyMap<HashSet<String>>,
+    pub(crate) sorted_set_data: KeyMap<BTreeMap<String, f64>>,
+    // 过期时间存储，键 -> 过期时间戳（毫秒）
+    pub(crate) expiry_data: KeyMap<u64>,
+    // 键的访问信息，用于 OBJECT IDLETIME/FREQ
+    pub(crate) access_data: DashMap<String, KeyAccess>,
+}
+
+/// 任意类型的值，用于在库之间或键之间整体搬运
+#[derive(Debug, Clone)]
+pub enum Value {
+    String(Resp),
+    Hash(HashMap<String, String>),
+    List(VecDeque<String>),
+    Set(HashSet<String>),
+    ZSet(BTreeMap<String, f64>),
+}
+
+impl Value {
+    /// 元素数量，字符串算一个
+    pub fn len(&self) -> usize {
+        match self {
+            Value::String(_) => 1,
+            Value::Hash(hash) => hash.len(),
+            Value::List(list) => list.len(),
+            Value::Set(set) => set.len(),
+            Value::ZSet(zset) => zset.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 释放值，元素较多时在后台线程释放，不阻塞当前命令
+    pub fn free(self) {
+        if self.len() > LAZYFREE_THRESHOLD {
+            std::thread::spawn(move || drop(self));
+        }
+    }
+}
+
+/// 键最近一次访问的时间和 LFU 访问频率计数器
+#[derive(Debug, Clone, Copy)]
+pub struct KeyAccess {
+    pub last_access: u64,
+    counter: u8,
+}
+
+impl KeyAccess {
+    fn new(now: u64) -> Self {
+        Self {
+            last_access: now,
+            counter: LFU_INIT_VAL,
+        }
+    }
+
+    /// 按距离上次访问的时间衰减后的访问频率
+    pub fn frequency(&self, now: u64) -> u8 {
+        let periods = now.saturating_sub(self.last_access) / LFU_DECAY_MILLIS;
+        self.counter
+            .saturating_sub(periods.min(u8::MAX as u64) as u8)
+    }
+
+    // 记录一次访问：计数器按对数增长，越大越难增加
+    fn hit(&mut self, now: u64) {
+        let mut counter = self.frequency(now);
+        if counter < u8::MAX {
+            let base = counter.saturating_sub(LFU_INIT_VAL) as f64;
+            let p = 1.0 / (base * LFU_LOG_FACTOR + 1.0);
+            if rand::thread_rng().gen::<f64>() < p {
+                counter += 1;
+            }
+        }
+        self.counter = counter;
+        self.last_access = now;
+    }
+}
+
+impl Db {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 键的数量（DBSIZE）
+    pub fn len(&self) -> usize {
+        self.string_data.len()
+            + self.hash_data.len()
+            + self.list_data.len()
+            + self.set_data.len()
+            + self.sorted_set_data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 设置了过期时间的键的数量
+    pub fn expires(&self) -> usize {
+        self.expiry_data.len()
+    }
+
+    /// 复制出键的值，键不存在时返回 None
+    pub fn get_value(&self, key: &str) -> Option<Value> {
+        if let Some(value) = self.string_data.get(key) {
+            return Some(Value::String(value.clone()));
+        }
+        if let Some(value) = self.hash_data.get(key) {
+            return Some(Value::Hash(value.clone()));
+        }
+        if let Some(value) = self.list_data.get(key) {
+            return Some(Value::List(value.clone()));
+        }
+        if let Some(value) = self.set_data.get(key) {
+            return Some(Value::Set(value.clone()));
+        }
+        self.sorted_set_data
+            .get(key)
+            .map(|value| Value::ZSet(value.clone()))
+    }
+
+    /// 取出键的值（不包括过期时间），键不存在时返回 None
+    pub fn take_value(&self, key: &str) -> Option<Value> {
+        if let Some((_, value)) = self.string_data.remove(key) {
+            return Some(Value::String(value));
+        }
+        if let Some((_, value)) = self.hash_data.remove(key) {
+            return Some(Value::Hash(value));
+        }
+        if let Some((_, value)) = self.list_data.remove(key) {
+            return Some(Value::List(value));
+        }
+        if let Some((_, value)) = self.set_data.remove(key) {
+            return Some(Value::Set(value));
+        }
+        self.sorted_set_data
+            .remove(key)
+            .map(|(_, value)| Value::ZSet(value))
+    }
+
+    /// 写入键的值，调用方需要先删除同名的旧键
+    pub fn put_value(&self, key: String, value: Value) {
+        match value {
+            Value::String(value) => {
+                self.string_data.insert(key, value);
+            }
+            Value::Hash(value) => {
+                self.hash_data.insert(key, value);
+            }
+            Value::List(value) => {
+                self.list_data.insert(key, value);
+            }
+            Value::Set(value) => {
+                self.set_data.insert(key, value);
+            }
+            Value::ZSet(value) => {
+                self.sorted_set_data.insert(key, value);
+            }
+        }
+    }
+
+    /// 把键（包括过期时间和访问信息）移动到另一个库的 `to_key`，键不存在时返回 false
+    ///
+    /// 调用方需要先删除目标库中同名的键。
+    pub fn move_key(&self, key: &str, to: &Db, to_key: &str) -> bool {
+        let Some(value) = self.take_value(key) else {
+            return false;
+        };
+        to.put_value(to_key.to_owned(), value);
+        if let Some((_, expiry)) = self.expiry_data.remove(key) {
+            to.expiry_data.insert(to_key.to_owned(), expiry);
+        }
+        if let Some((_, access)) = self.access_data.remove(key) {
+            to.access_data.insert(to_key.to_owned(), access);
+        }
+        true
+    }
+
+    /// 记录键被访问，键已经不存在时清理它的访问信息
+    pub fn record_access(&self, key: &str, now: u64) {
+        let exists = self.string_data.contains_key(key)
+            || self.hash_data.contains_key(key)
+            || self.list_data.contains_key(key)
+            || self.set_data.contains_key(key)
+            || self.sorted_set_data.contains_key(key);
+        if exists {
+            self.access_data
+                .entry(key.to_owned())
+                .or_insert_with(|| KeyAccess::new(now))
+                .hit(now);
+        } else {
+            self.access_data.remove(key);
+        }
+    }
+
+    /// 键的访问信息，从未被记录过时视为刚刚访问
+    pub fn access(&self, key: &str, now: u64) -> KeyAccess {
+        self.access_data
+            .get(key)
+            .map(|access| *access)
+            .unwrap_or_else(|| KeyAccess::new(now))
+    }
+
+    /// 键的类型名（TYPE），键不存在时返回 None
+    pub fn key_type(&self, key: &str) -> Option<&'static str> {
+        if self.string_data.contains_key(key) {
+            Some("string")
+        } else if self.hash_data.contains_key(key) {
+            Some("hash")
+        } else if self.list_data.contains_key(key) {
+            Some("list")
+        } else if self.set_data.contains_key(key) {
+            Some("set")
+        } else if self.sorted_set_data.contains_key(key) {
+            Some("zset")
+        } else {
+            None
+        }
+    }
+
+    /// 从游标开始按哈希顺序取出大约 `count` 个键，返回下一个游标和这批键
+    ///
+    /// `key_type` 只遍历指定类型的键。下一个游标为 0 表示遍历结束。
+    pub fn scan(&self, cursor: u64, count: usize, key_type: Option<&str>) -> (u64, Vec<String>) {
+        let indexes = [
+            ("string", self.string_data.index()),
+            ("hash", self.hash_data.index()),
+            ("list", self.list_data.index()),
+            ("set", self.set_data.index()),
+            ("zset", self.sorted_set_data.index()),
+        ];
+        let indexes: Vec<_> = indexes
+            .into_iter()
+            .filter(|(name, _)| key_type.is_none_or(|key_type| key_type == *name))
+            .collect();
+
+        // 先找出这一批的边界：所有类型合起来第 count 小的哈希值
+        let count = count.max(1);
+        let mut hashes = Vec::new();
+        let mut truncated = false;
+        for (_, index) in &indexes {
+            let batch = index.hashes_from(cursor, count);
+            truncated |= batch.len() == count;
+            hashes.extend(batch);
+        }
+        if hashes.is_empty() {
+            return (0, Vec::new());
+        }
+        hashes.sort_unstable();
+        let end = hashes[count.min(hashes.len()) - 1];
+        let done = !truncated && end == hashes[hashes.len() - 1];
+
+        // 返回哈希值不超过边界的所有键，哈希相同的键会在同一批里返回
+        let mut keys = Vec::new();
+        for (name, index) in &indexes {
+            keys.extend(
+                index
+                    .keys_between(cursor, end)
+                    .into_iter()
+                    .filter(|key| self.key_type(key) == Some(*name)),
+            );
+        }
+        let next = if done { 0 } else { end.wrapping_add(1) };
+        (next, keys)
+    }
+
+    /// 所有键估算的内存占用
+    pub fn used_memory(&self) -> usize {
+        self.string_data.index().used_memory()
+            + self.hash_data.index().used_memory()
+            + self.list_data.index().used_memory()
+            + self.set_data.index().used_memory()
+            + self.sorted_set_data.index().used_memory()
+            + self.expiry_data.index().used_memory()
+    }
+
+    /// 键估算的内存占用（MEMORY USAGE），键不存在时返回 None
+    pub fn memory_usage(&self, key: &str, samples: usize) -> Option<usize> {
+        self.string_data
+            .memory_usage(key, samples)
+            .or_else(|| self.hash_data.memory_usage(key, samples))
+            .or_else(|| self.list_data.memory_usage(key, samples))
+            .or_else(|| self.set_data.memory_usage(key, samples))
+            .or_else(|| self.sorted_set_data.memory_usage(key, samples))
+    }
+
+    /// 键的值被原地修改后重新估算它的内存占用
+    pub fn refresh_memory(&self, key: &str) {
+        self.string_data.refresh(key);
+        self.hash_data.refresh(key);
+        self.list_data.refresh(key);
+        self.set_data.refresh(key);
+        self.sorted_set_data.refresh(key);
+    }
+
+    /// 随机抽样 `count` 个键（可能重复），用于淘汰
+    pub fn random_keys(&self, count: usize) -> Vec<String> {
+        let indexes = [
+            self.string_data.index(),
+            self.hash_data.index(),
+            self.list_data.index(),
+            self.set_data.index(),
+            self.sorted_set_data.index(),
+        ];
+        // 取所有类型中哈希值不小于随机位置的第一个键，没有时从头开始
+        let first_from = |cursor: u64| {
+            indexes
+                .iter()
+                .filter_map(|index| index.first_from(cursor))
+                .min_by_key(|(hash, _)| *hash)
+                .map(|(_, key)| key)
+        };
+        let mut rng = rand::thread_rng();
+        (0..count)
+            .filter_map(|_| first_from(rng.gen()).or_else(|| first_from(0)))
+            .collect()
+    }
+
+    /// 随机返回一个键，和淘汰一样按哈希索引抽样，不需要遍历所有键
+    pub fn random_key(&self) -> Option<String> {
+        self.random_keys(1).pop()
+    }
+
+    /// 清空库中的所有键
+    ///
+    /// `lazy` 为 true 时集合类型的值交给后台线程释放，大库也不会阻塞当前命令。
+    pub fn clear(&self, lazy: bool) {
+        if !lazy {
+            self.string_data.clear();
+            self.hash_data.clear();
+            self.list_data.clear();
+            self.set_data.clear();
+            self.sorted_set_data.clear();
+            self.expiry_data.clear();
+            self.access_data.clear();
+            return;
+        }
+        let mut hashes = Vec::new();
+        let mut lists = Vec::new();
+        let mut sets = Vec::new();
+        let mut sorted_sets = Vec::new();
+        self.hash_data.retain(|_, value| {
+            hashes.push(std::mem::take(value));
+            false
+        });
+        self.list_data.retain(|_, value| {
+            lists.push(std::mem::take(value));
+            false
+        });
+        self.set_data.retain(|_, value| {
+            sets.push(std::mem::take(value));
+            false
+        });
+        self.sorted_set_data.retain(|_, value| {
+            sorted_sets.push(std::mem::take(value));
+            false
+        });
+        self.string_data.clear();
+        self.expiry_data.clear();
+        self.access_data.clear();
+        std::thread::spawn(move || drop((hashes, lists, sets, sorted_sets)));
+    }
+}
+
+/// 当前线程正在执行的命令所选择的库
+pub fn selected_db() -> usize {
+    SELECTED_DB.with(Cell::get)
+}
+
+/// 在指定的库上执行 `f`，结束后（包括 panic 时）恢复原来的选择
+pub(crate) fn with_selected<R>(index: usize, f: impl FnOnce() -> R) -> R {
+    struct Restore(usize);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SELECTED_DB.with(|selected| selected.set(self.0));
+        }
+    }
+    let _restore = Restore(SELECTED_DB.with(|selected| selected.replace(index)));
+    f()
+}
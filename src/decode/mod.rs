@@ -1,175 +1,289 @@
-use bytes::{Buf, BytesMut};
+//! RESP 解码
+//!
+//! 解码分两步：`FrameScanner` 先在不消费输入的情况下确认缓冲区中已经有一个完整的 frame，
+//! 扫描的位置保存在扫描器中，数据分多次到达时从上次停下的地方继续，不会重复扫描；
+//! 声明的长度和嵌套层数也在这一步检查。确认完整之后再一次性解析出 `Resp`。
+
+use bytes::BytesMut;
 use tracing::debug;
 
 use crate::{
-    resp::Resp, Arrays, BigNumbers, Booleans, BulkStrings, Doubles, Integers, Nulls, RespDecoder,
-    RespEncoder, RespError, SimpleErrors, SimpleStringsData, ARRAYS_BYTE, BIG_NUMBERS_BYTE,
-    BOOLEANS_BYTE, BULK_STRINGS_BYTE, CRLF, DOUBLES_BYTE, ERRORS_BYTE, INTEGERS_BYTE, NULLS_BYTE,
-    SIMPLE_STRINGS_BYTE,
+    resp::Resp, Arrays, BigNumbers, Booleans, BulkStrings, Doubles, Integers, Maps, Nulls, Pushes,
+    RespDecoder, RespError, SimpleErrors, SimpleStringsData, ARRAYS_BYTE, BIG_NUMBERS_BYTE,
+    BOOLEANS_BYTE, BULK_STRINGS_BYTE, CRLF, DOUBLES_BYTE, ERRORS_BYTE, INTEGERS_BYTE, MAPS_BYTE,
+    NULLS_BYTE, PUSHES_BYTE, SIMPLE_STRINGS_BYTE,
 };
 
-impl RespDecoder for Resp {
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        debug!("RespDecoder.decode buf length: {}", buf.len());
-        //1. 取出第一个byte 判断RESP
-        //2. 根据每个RESP的类型的反序列化规则 转化为 对应的RESP类型
-        //3. 如果是Array类型 则继续递归解析
-        //Clients send commands to a Redis server as an array of bulk strings.
-        //The first (and sometimes also the second) bulk string in the array is the command's name.
-        //Subsequent elements of the array are the arguments for the command.
-        match buf.first() {
-            Some(&SIMPLE_STRINGS_BYTE) => Ok(Resp::SimpleStrings(SimpleStringsData::new(
-                exact_advance(buf, true)?,
-            ))),
-            Some(&ERRORS_BYTE) => Ok(Resp::SimpleErrors(SimpleErrors::new(exact_advance(
-                buf, true,
-            )?))),
-            Some(&INTEGERS_BYTE) => {
-                let str = exact_advance(buf, true)?;
-                str.parse::<i64>()
-                    .map(|val| Resp::Integers(Integers::new(val)))
-                    .map_err(RespError::ParseIntError)
-            }
-            Some(&BULK_STRINGS_BYTE) => {
-                let str_len = exact_advance(buf, true)?.parse::<isize>()?;
-                if str_len < 0 {
-                    // 处理 NULL bulk string
-                    return Ok(Resp::Nulls(Nulls::new()));
-                }
-                let str_len = str_len as usize;
-                if buf.len() < str_len + CRLF.len() {
-                    return Err(RespError::NotComplete);
-                }
-                let val = buf[..str_len].to_vec();
-                buf.advance(str_len + CRLF.len());
-                Ok(Resp::BulkStrings(BulkStrings::new(String::from_utf8(val)?)))
-            }
-            Some(&ARRAYS_BYTE) => {
-                let array_len = exact_advance(buf, true)?;
-                let array_len: usize = array_len.parse()?;
-                let mut arr: Vec<Resp> = Vec::new();
-                //array的长度就是需要解析元素的次数
-                for i in 0..array_len {
-                    debug!("Parsing array element {}/{}", i + 1, array_len);
-
-                    // 检查是否有足够的数据继续解析
-                    if buf.is_empty() {
-                        return Err(RespError::NotComplete);
-                    }
+// proto-max-bulk-len 的默认值，和 Redis 一样为 512mb
+pub const DEFAULT_PROTO_MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+// 数组最多的元素个数。和 Redis 一样是固定值，没有对应的配置项：
+// 一个元素至少占几个字节，元素个数已经间接受 client-query-buffer-limit 和 proto-max-bulk-len 约束，
+// 这个上限只是让明显不合理的长度在数据到达之前就被拒绝
+pub const MAX_MULTIBULK_LEN: usize = 1024 * 1024;
+// 数组最多嵌套的层数
+pub const MAX_NESTING_DEPTH: usize = 32;
+
+/// 解码时的协议限制：声明的长度在分配之前检查，防止一个恶意的 frame 耗尽内存或栈
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtoLimits {
+    pub max_bulk_len: usize,
+    pub max_multibulk_len: usize,
+    pub max_depth: usize,
+}
 
-                    arr.push(Resp::decode(buf)?);
+impl Default for ProtoLimits {
+    fn default() -> Self {
+        Self {
+            max_bulk_len: DEFAULT_PROTO_MAX_BULK_LEN,
+            max_multibulk_len: MAX_MULTIBULK_LEN,
+            max_depth: MAX_NESTING_DEPTH,
+        }
+    }
+}
+
+/// 可以断点续扫的 frame 扫描器，只检查缓冲区中是否有完整的 frame，不消费输入
+///
+/// 两次 `scan` 之间缓冲区只能在末尾追加数据，取走一个完整的 frame 之后扫描器自动重置。
+#[derive(Debug, Default)]
+pub struct FrameScanner {
+    // 已经确认完整的字节数
+    pos: usize,
+    // 当前行已经找过 CRLF 的位置，很长的行分多次到达时不重复查找
+    searched: usize,
+    // 还没扫描完的数组、Map、Push 中剩余的元素个数，由外到内
+    pending: Vec<usize>,
+}
 
-                    // 不需要手动advance，decode函数会处理
+impl FrameScanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `buf[..len]` 是一个完整的 frame 时返回 Some(len)，数据还不完整时返回 None
+    pub fn scan(&mut self, buf: &[u8], limits: &ProtoLimits) -> Result<Option<usize>, RespError> {
+        loop {
+            let start = self.pos;
+            let Some(line_end) = self.find_crlf(buf) else {
+                return Ok(None);
+            };
+            let line = line_body(buf, start, line_end)?;
+            let next = line_end + CRLF.len();
+            match buf[start] {
+                BULK_STRINGS_BYTE => {
+                    if let Some(len) = declared_len(line, limits.max_bulk_len, "bulk")? {
+                        // 数据还没到齐时停在这一行，下次只需要再比较一次长度
+                        if buf.len() < next + len + CRLF.len() {
+                            return Ok(None);
+                        }
+                        check_bulk_end(buf, next + len)?;
+                        self.pos = next + len + CRLF.len();
+                    } else {
+                        self.pos = next;
+                    }
+                    self.element_done();
                 }
-                Ok(Resp::Arrays(Arrays::new(arr)))
-            }
-            Some(&NULLS_BYTE) => {
-                let null = Resp::Nulls(Nulls::new());
-                let null_encode: Result<Vec<u8>, anyhow::Error> = null.clone().encode();
-                buf.advance(
-                    null_encode
-                        .map_err(|_| {
-                            RespError::InternalError("calc null encode len error".to_owned())
-                        })?
-                        .len(),
-                );
-                Ok(null)
-            }
-            Some(&BOOLEANS_BYTE) => {
-                let val = exact_advance(buf, true)?;
-                if val == "t" {
-                    Ok(Resp::Booleans(Booleans::new(true)))
-                } else if val == "f" {
-                    Ok(Resp::Booleans(Booleans::new(false)))
-                } else {
-                    Err(RespError::InvalidFrameType(format!(
-                        "invalid RESP boolean: {val:?}"
+                ARRAYS_BYTE | PUSHES_BYTE | MAPS_BYTE => {
+                    self.pos = next;
+                    match declared_len(line, limits.max_multibulk_len, "multibulk")? {
+                        None | Some(0) => self.element_done(),
+                        Some(_) if self.pending.len() >= limits.max_depth => {
+                            return Err(RespError::Protocol("too many nested arrays".to_owned()));
+                        }
+                        // Map 的每一项是一个键和一个值
+                        Some(len) if buf[start] == MAPS_BYTE => self.pending.push(len * 2),
+                        Some(len) => self.pending.push(len),
+                    }
+                }
+                SIMPLE_STRINGS_BYTE | ERRORS_BYTE | INTEGERS_BYTE | NULLS_BYTE | BOOLEANS_BYTE
+                | DOUBLES_BYTE | BIG_NUMBERS_BYTE => {
+                    self.pos = next;
+                    self.element_done();
+                }
+                other => {
+                    return Err(RespError::InvalidFrameType(format!(
+                        "unknown frame type: {:?}",
+                        other as char
                     )))
                 }
             }
-            Some(&DOUBLES_BYTE) => {
-                let val = exact_advance(buf, true)?;
-                val.parse::<f64>()
-                    .map(|val| Resp::Doubles(Doubles::new(val)))
-                    .map_err(RespError::ParseFloatError)
+            self.searched = self.pos;
+            if self.pending.is_empty() {
+                let len = self.pos;
+                *self = Self::default();
+                return Ok(Some(len));
             }
-            Some(&BIG_NUMBERS_BYTE) => {
-                let val = exact_advance(buf, true)?;
-                val.parse::<i128>()
-                    .map(|val| Resp::BigNumbers(BigNumbers::new(val)))
-                    .map_err(RespError::ParseIntError)
+        }
+    }
+
+    // 当前行 CRLF 中 \r 的位置，只查找上次没找过的部分
+    fn find_crlf(&mut self, buf: &[u8]) -> Option<usize> {
+        let from = self.searched.max(self.pos);
+        match buf
+            .get(from..)?
+            .windows(CRLF.len())
+            .position(|window| window == CRLF)
+        {
+            Some(index) => Some(from + index),
+            None => {
+                // 最后一个字节可能是 \r，下次从它开始找
+                self.searched = buf.len().saturating_sub(1).max(self.pos);
+                None
             }
-            None => Err(RespError::NotComplete),
-            _ => Err(RespError::InvalidFrameType(format!(
-                "expect_length: unknown frame type: {buf:?}"
-            ))),
         }
     }
-}
 
-#[allow(dead_code)]
-fn exact(buf: &mut BytesMut) -> Result<String, RespError> {
-    exact_advance(buf, false)
+    // 一个元素扫描完，已经扫描完的聚合类型本身算作上一层的一个元素
+    fn element_done(&mut self) {
+        while let Some(remaining) = self.pending.last_mut() {
+            *remaining -= 1;
+            if *remaining > 0 {
+                break;
+            }
+            self.pending.pop();
+        }
+    }
 }
 
-fn exact_advance(buf: &mut BytesMut, advance_flag: bool) -> Result<String, RespError> {
-    debug!("exact_advance start, buf length: {}", buf.len());
-    let pos_opt = buf
-        .windows(CRLF.len())
-        .position(|window: &[u8]| window == CRLF);
-    if let Some(pos) = pos_opt {
-        let res: String = String::from_utf8(buf[1..pos].to_vec())?;
-        if advance_flag {
-            buf.advance(pos + CRLF.len());
-        }
-        debug!("exact_advance end, remaining buf length: {}", buf.len());
-        Ok(res)
-    } else {
-        Err(RespError::NotComplete)
+impl RespDecoder for Resp {
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        Resp::decode_with_limits(buf, &ProtoLimits::default())
     }
 }
 
-#[allow(dead_code)]
-fn advance_to_next(buf: &mut BytesMut) -> Result<usize, RespError> {
-    debug!("advance_to_next start, buf length: {}", buf.len());
+impl Resp {
+    /// 按 `limits` 检查声明的长度和嵌套层数后解码一个 frame，数据不完整时不消费 `buf`
+    pub fn decode_with_limits(buf: &mut BytesMut, limits: &ProtoLimits) -> Result<Self, RespError> {
+        let len = FrameScanner::new()
+            .scan(buf, limits)?
+            .ok_or(RespError::NotComplete)?;
+        let frame = buf.split_to(len);
+        Resp::parse_frame(&frame)
+    }
 
-    // 检查缓冲区是否为空或太小
-    if buf.len() < CRLF.len() {
-        return Err(RespError::NotComplete);
+    /// 解析 `FrameScanner` 确认过的一个完整的 frame
+    pub fn parse_frame(frame: &[u8]) -> Result<Self, RespError> {
+        debug!("RespDecoder.parse_frame length: {}", frame.len());
+        let mut pos = 0;
+        parse(frame, &mut pos)
     }
+}
 
-    let pos_opt = buf
-        .windows(CRLF.len())
-        .position(|window: &[u8]| window == CRLF);
-    if let Some(pos) = pos_opt {
-        let res: usize = pos + CRLF.len();
-        debug!("advance_to_next found CRLF at position: {}", res);
-        match buf.first() {
-            Some(&BULK_STRINGS_BYTE) => {
-                let len: String = exact(buf)?;
-                let parsed_len = len.parse::<isize>().map_err(RespError::ParseIntError)?;
-
-                if parsed_len < 0 {
-                    // NULL bulk string
-                    return Ok(res);
-                }
+// 解析声明的长度：-1 表示 NULL，返回 None；其他负数和超过 `max` 的长度都是协议错误
+fn declared_len(line: &[u8], max: usize, what: &str) -> Result<Option<usize>, RespError> {
+    let invalid = || RespError::Protocol(format!("invalid {what} length"));
+    let len = std::str::from_utf8(line)
+        .ok()
+        .and_then(|line| line.parse::<i64>().ok())
+        .ok_or_else(invalid)?;
+    match len {
+        -1 => Ok(None),
+        len if len < 0 || len as u64 > max as u64 => Err(invalid()),
+        len => Ok(Some(len as usize)),
+    }
+}
 
-                let end = res + parsed_len as usize + 2 * CRLF.len();
-                debug!("advance_to_next end, calculated end: {}", end);
-                Ok(end)
+// 从 `pos` 开始解析一个元素，数组等聚合类型递归解析，`pos` 移动到元素之后
+//Clients send commands to a Redis server as an array of bulk strings.
+//The first (and sometimes also the second) bulk string in the array is the command's name.
+//Subsequent elements of the array are the arguments for the command.
+fn parse(frame: &[u8], pos: &mut usize) -> Result<Resp, RespError> {
+    let kind = *frame.get(*pos).ok_or(RespError::NotComplete)?;
+    let line = read_line(frame, pos)?;
+    let text = || String::from_utf8(line.to_vec());
+    let resp = match kind {
+        SIMPLE_STRINGS_BYTE => Resp::SimpleStrings(SimpleStringsData::new(text()?)),
+        ERRORS_BYTE => Resp::SimpleErrors(SimpleErrors::new(text()?)),
+        INTEGERS_BYTE => Resp::Integers(Integers::new(text()?.parse()?)),
+        BULK_STRINGS_BYTE => match declared_len(line, usize::MAX, "bulk")? {
+            // NULL bulk string
+            None => Resp::Nulls(Nulls::new()),
+            Some(len) => {
+                let val = frame
+                    .get(*pos..*pos + len)
+                    .ok_or(RespError::NotComplete)?
+                    .to_vec();
+                check_bulk_end(frame, *pos + len)?;
+                *pos += len + CRLF.len();
+                Resp::BulkStrings(BulkStrings::new(String::from_utf8(val)?))
             }
+        },
+        ARRAYS_BYTE | PUSHES_BYTE | MAPS_BYTE => {
+            let Some(len) = declared_len(line, usize::MAX, "multibulk")? else {
+                // NULL array
+                return Ok(Resp::Nulls(Nulls::new()));
+            };
+            match kind {
+                MAPS_BYTE => Resp::Maps(Maps::new(
+                    (0..len)
+                        .map(|_| Ok((parse(frame, pos)?, parse(frame, pos)?)))
+                        .collect::<Result<_, RespError>>()?,
+                )),
+                _ => {
+                    //array的长度就是需要解析元素的次数
+                    let items = (0..len)
+                        .map(|_| parse(frame, pos))
+                        .collect::<Result<_, _>>()?;
+                    if kind == PUSHES_BYTE {
+                        Resp::Pushes(Pushes::new(items))
+                    } else {
+                        Resp::Arrays(Arrays::new(items))
+                    }
+                }
+            }
+        }
+        NULLS_BYTE => Resp::Nulls(Nulls::new()),
+        BOOLEANS_BYTE => match line {
+            b"t" => Resp::Booleans(Booleans::new(true)),
+            b"f" => Resp::Booleans(Booleans::new(false)),
             _ => {
-                let end = res;
-                debug!("advance_to_next end, simple end: {}", end);
-                Ok(end)
+                return Err(RespError::InvalidFrameType(format!(
+                    "invalid RESP boolean: {:?}",
+                    String::from_utf8_lossy(line)
+                )))
             }
+        },
+        DOUBLES_BYTE => Resp::Doubles(Doubles::new(text()?.parse()?)),
+        BIG_NUMBERS_BYTE => Resp::BigNumbers(BigNumbers::new(text()?.parse()?)),
+        other => {
+            return Err(RespError::InvalidFrameType(format!(
+                "unknown frame type: {:?}",
+                other as char
+            )))
         }
-    } else {
-        // 不是错误，只是数据不完整
-        Err(RespError::NotComplete)
+    };
+    Ok(resp)
+}
+
+// bulk string 的数据之后必须紧跟 CRLF，否则后面的数据都会错位
+fn check_bulk_end(buf: &[u8], end: usize) -> Result<(), RespError> {
+    match buf.get(end..end + CRLF.len()) {
+        Some(tail) if tail == CRLF => Ok(()),
+        _ => Err(RespError::Protocol(
+            "bulk string not terminated by CRLF".to_owned(),
+        )),
     }
 }
 
+// 读取 `pos` 处的一行，返回去掉类型字节和 CRLF 之后的内容
+fn read_line<'a>(frame: &'a [u8], pos: &mut usize) -> Result<&'a [u8], RespError> {
+    let start = *pos;
+    let end = frame[start..]
+        .windows(CRLF.len())
+        .position(|window| window == CRLF)
+        .map(|index| start + index)
+        .ok_or(RespError::NotComplete)?;
+    *pos = end + CRLF.len();
+    line_body(frame, start, end)
+}
+
+// `start..end` 这一行去掉类型字节之后的内容，空行连类型字节都没有，是协议错误
+fn line_body(buf: &[u8], start: usize, end: usize) -> Result<&[u8], RespError> {
+    if end <= start {
+        return Err(RespError::Protocol("expected a type byte".to_owned()));
+    }
+    Ok(&buf[start + 1..end])
+}
+
 // 单元测试
 #[cfg(test)]
 mod tests {
@@ -265,4 +379,135 @@ mod tests {
         ]));
         assert_eq!(resp, decoded);
     }
+
+    #[test]
+    fn test_declared_length_limits() {
+        let limits = ProtoLimits {
+            max_bulk_len: 16,
+            max_multibulk_len: 4,
+            max_depth: 2,
+        };
+        let decode = |input: &[u8]| Resp::decode_with_limits(&mut BytesMut::from(input), &limits);
+        let protocol = |msg: &str| Err(RespError::Protocol(msg.to_owned()));
+
+        assert_eq!(decode(b"$-1\r\n"), Ok(Resp::Nulls(Nulls::new())));
+        assert_eq!(decode(b"*-1\r\n"), Ok(Resp::Nulls(Nulls::new())));
+        assert_eq!(decode(b"$-2\r\n"), protocol("invalid bulk length"));
+        assert_eq!(decode(b"*-5\r\n"), protocol("invalid multibulk length"));
+        assert_eq!(decode(b"$abc\r\n"), protocol("invalid bulk length"));
+        // 声明的长度超过限制时不等数据到齐就拒绝
+        assert_eq!(decode(b"$17\r\n"), protocol("invalid bulk length"));
+        assert_eq!(
+            decode(b"*99999999999\r\n"),
+            protocol("invalid multibulk length")
+        );
+        assert_eq!(decode(b"*1\r\n*1\r\n:1\r\n").map(|_| ()), Ok(()));
+        assert_eq!(
+            decode(b"*1\r\n*1\r\n*1\r\n:1\r\n"),
+            protocol("too many nested arrays")
+        );
+    }
+
+    #[test]
+    fn test_bulk_string_trailer() {
+        let protocol = || RespError::Protocol("bulk string not terminated by CRLF".to_owned());
+        let limits = ProtoLimits::default();
+        assert_eq!(
+            FrameScanner::new().scan(b"$4\r\nPINGXX", &limits),
+            Err(protocol())
+        );
+        assert_eq!(
+            Resp::decode(&mut BytesMut::from(b"*1\r\n$4\r\nPINGXX".as_slice())),
+            Err(protocol())
+        );
+        assert_eq!(Resp::parse_frame(b"$4\r\nPINGXX"), Err(protocol()));
+        assert_eq!(
+            Resp::parse_frame(b"$4\r\nPING\r\n"),
+            Ok(Resp::BulkStrings(BulkStrings::new("PING".to_owned())))
+        );
+    }
+
+    #[test]
+    fn test_maps_and_pushes() {
+        let resp = Resp::Maps(Maps::new(vec![(
+            Resp::BulkStrings(BulkStrings::new("server".to_string())),
+            Resp::Pushes(Pushes::new(vec![Resp::Integers(Integers::new(1))])),
+        )]));
+        let encoded = resp.clone().encode().unwrap();
+        let decoded: Resp = Resp::decode(&mut BytesMut::from(encoded.as_slice())).unwrap();
+        assert_eq!(resp, decoded);
+    }
+
+    #[test]
+    fn test_incomplete_frame_is_not_consumed() {
+        let mut buf = BytesMut::from(b"*2\r\n$3\r\nGET\r\n$1\r\n".as_slice());
+        let len = buf.len();
+        assert_eq!(Resp::decode(&mut buf), Err(RespError::NotComplete));
+        assert_eq!(buf.len(), len);
+        buf.extend_from_slice(b"k\r\n");
+        assert!(Resp::decode(&mut buf).is_ok());
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_split_reads() {
+        let frames = vec![
+            Resp::Arrays(Arrays::new(vec![
+                Resp::BulkStrings(BulkStrings::new("SET".to_string())),
+                Resp::BulkStrings(BulkStrings::new("k".to_string())),
+                Resp::BulkStrings(BulkStrings::new("v".repeat(100))),
+            ])),
+            Resp::Nulls(Nulls::new()),
+            Resp::Arrays(Arrays::new(vec![Resp::Arrays(Arrays::new(vec![]))])),
+            Resp::Maps(Maps::new(vec![(
+                Resp::SimpleStrings(SimpleStringsData::new("proto".to_string())),
+                Resp::Integers(Integers::new(3)),
+            )])),
+        ];
+        let input: Vec<u8> = frames
+            .iter()
+            .flat_map(|frame| frame.clone().encode().unwrap())
+            .collect();
+        // 每次只到达一个字节，扫描器从上次停下的位置继续
+        let limits = ProtoLimits::default();
+        let mut scanner = FrameScanner::new();
+        let mut buf = BytesMut::new();
+        let mut decoded = Vec::new();
+        for byte in input {
+            buf.extend_from_slice(&[byte]);
+            while let Some(len) = scanner.scan(&buf, &limits).unwrap() {
+                decoded.push(Resp::parse_frame(&buf.split_to(len)).unwrap());
+            }
+        }
+        assert_eq!(decoded, frames);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_empty_line() {
+        let limits = ProtoLimits::default();
+        let empty = || RespError::Protocol("expected a type byte".to_owned());
+        assert_eq!(FrameScanner::new().scan(b"\r\n", &limits), Err(empty()));
+        assert_eq!(
+            Resp::decode(&mut BytesMut::from(b"*1\r\n\r\n".as_slice())),
+            Err(empty())
+        );
+        assert_eq!(Resp::parse_frame(b"\r\n"), Err(empty()));
+
+        // 只有类型字节的行
+        let decode = |input: &[u8]| Resp::decode(&mut BytesMut::from(input));
+        assert_eq!(
+            decode(b"$\r\n"),
+            Err(RespError::Protocol("invalid bulk length".to_owned()))
+        );
+        assert_eq!(
+            decode(b"*\r\n"),
+            Err(RespError::Protocol("invalid multibulk length".to_owned()))
+        );
+        assert!(decode(b":\r\n").is_err());
+        assert_eq!(
+            decode(b"+\r\n"),
+            Ok(Resp::SimpleStrings(SimpleStringsData::new(String::new())))
+        );
+    }
 }
@@ -0,0 +1,31 @@
+use crate::{resp::Maps, RespEncoder};
+
+impl RespEncoder for Maps {
+    fn encode(self) -> Result<Vec<u8>, anyhow::Error> {
+        let mut encoded = format!("%{}\r\n", self.val.len()).as_bytes().to_vec();
+        for (key, val) in self.val {
+            encoded.extend(key.encode()?);
+            encoded.extend(val.encode()?);
+        }
+        Ok(encoded)
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkStrings, Integers, Resp};
+
+    #[test]
+    fn test_encode_maps() {
+        let maps = Maps::new(vec![(
+            Resp::BulkStrings(BulkStrings::new("proto".to_string())),
+            Resp::Integers(Integers::new(3)),
+        )]);
+        assert_eq!(
+            maps.encode().unwrap(),
+            b"%1\r\n$5\r\nproto\r\n:3\r\n".to_vec()
+        );
+    }
+}
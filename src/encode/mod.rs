@@ -4,6 +4,8 @@ mod booleans;
 mod bulk_strings;
 mod doubles;
 mod integers;
+mod maps;
 mod nulls;
+mod pushes;
 mod simple_errs;
 mod simple_strs;
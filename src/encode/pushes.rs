@@ -0,0 +1,30 @@
+use crate::{resp::Pushes, RespEncoder};
+
+impl RespEncoder for Pushes {
+    fn encode(self) -> Result<Vec<u8>, anyhow::Error> {
+        let mut encoded = format!(">{}\r\n", self.val.len()).as_bytes().to_vec();
+        for val in self.val {
+            encoded.extend(val.encode()?);
+        }
+        Ok(encoded)
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkStrings, Resp};
+
+    #[test]
+    fn test_encode_pushes() {
+        let pushes = Pushes::new(vec![
+            Resp::BulkStrings(BulkStrings::new("message".to_string())),
+            Resp::BulkStrings(BulkStrings::new("ch".to_string())),
+        ]);
+        assert_eq!(
+            pushes.encode().unwrap(),
+            b">2\r\n$7\r\nmessage\r\n$2\r\nch\r\n".to_vec()
+        );
+    }
+}
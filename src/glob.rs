@@ -0,0 +1,201 @@
+//! glob 风格的模式匹配
+//!
+//! 移植自 Redis 的 `stringmatchlen`，行为与 Redis 保持一致：
+//! 支持 `*`、`?`、`[abc]`、`[^abc]`、`[a-z]` 和 `\` 转义，不分配内存。
+//! KEYS、SCAN、PSUBSCRIBE、CONFIG GET 等所有需要模式匹配的地方都使用这里的实现。
+
+// 递归深度上限，避免恶意构造的模式（例如大量 `*`）导致栈溢出
+const MAX_NESTING: usize = 1000;
+
+/// 区分大小写的匹配
+pub fn glob_match(pattern: &str, string: &str) -> bool {
+    string_match(pattern.as_bytes(), string.as_bytes(), false)
+}
+
+/// 按字节匹配，`nocase` 为 true 时忽略 ASCII 大小写
+pub fn string_match(pattern: &[u8], string: &[u8], nocase: bool) -> bool {
+    let mut skip_longer_matches = false;
+    match_impl(pattern, string, nocase, &mut skip_longer_matches, 0)
+}
+
+fn match_impl(
+    pattern: &[u8],
+    string: &[u8],
+    nocase: bool,
+    skip_longer_matches: &mut bool,
+    nesting: usize,
+) -> bool {
+    if nesting > MAX_NESTING {
+        return false;
+    }
+    let eq = |a: u8, b: u8| {
+        if nocase {
+            a.eq_ignore_ascii_case(&b)
+        } else {
+            a == b
+        }
+    };
+    let (mut p, mut s) = (0, 0);
+
+    while p < pattern.len() && s < string.len() {
+        match pattern[p] {
+            b'*' => {
+                while pattern.get(p + 1) == Some(&b'*') {
+                    p += 1;
+                }
+                if p + 1 == pattern.len() {
+                    return true;
+                }
+                while s < string.len() {
+                    if match_impl(
+                        &pattern[p + 1..],
+                        &string[s..],
+                        nocase,
+                        skip_longer_matches,
+                        nesting + 1,
+                    ) {
+                        return true;
+                    }
+                    // 后面的 `*` 已经试过所有位置都不匹配，更长的前缀也不可能匹配
+                    if *skip_longer_matches {
+                        return false;
+                    }
+                    s += 1;
+                }
+                *skip_longer_matches = true;
+                return false;
+            }
+            b'?' => s += 1,
+            b'[' => {
+                p += 1;
+                let not = pattern.get(p) == Some(&b'^');
+                if not {
+                    p += 1;
+                }
+                let c = string[s];
+                let mut matched = false;
+                loop {
+                    match pattern.get(p) {
+                        Some(b'\\') if pattern.len() - p >= 2 => {
+                            p += 1;
+                            if pattern[p] == c {
+                                matched = true;
+                            }
+                        }
+                        Some(b']') => break,
+                        // 没有闭合的 `]`，停在最后一个字符上
+                        None => {
+                            p -= 1;
+                            break;
+                        }
+                        Some(&start) if pattern.len() - p >= 3 && pattern[p + 1] == b'-' => {
+                            let mut start = start;
+                            let mut end = pattern[p + 2];
+                            let mut c = c;
+                            if start > end {
+                                std::mem::swap(&mut start, &mut end);
+                            }
+                            if nocase {
+                                start = start.to_ascii_lowercase();
+                                end = end.to_ascii_lowercase();
+                                c = c.to_ascii_lowercase();
+                            }
+                            p += 2;
+                            if (start..=end).contains(&c) {
+                                matched = true;
+                            }
+                        }
+                        Some(&ch) => {
+                            if eq(ch, c) {
+                                matched = true;
+                            }
+                        }
+                    }
+                    p += 1;
+                }
+                if not {
+                    matched = !matched;
+                }
+                if !matched {
+                    return false;
+                }
+                s += 1;
+            }
+            ch => {
+                let ch = if ch == b'\\' && pattern.len() - p >= 2 {
+                    p += 1;
+                    pattern[p]
+                } else {
+                    ch
+                };
+                if !eq(ch, string[s]) {
+                    return false;
+                }
+                s += 1;
+            }
+        }
+        p += 1;
+        if s == string.len() {
+            while pattern.get(p) == Some(&b'*') {
+                p += 1;
+            }
+            break;
+        }
+    }
+    p == pattern.len() && s == string.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcards() {
+        // 与 Redis 一致：空字符串不会进入匹配循环，`*` 也不匹配
+        assert!(!glob_match("*", ""));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("user:*:name", "user:1:name"));
+        assert!(!glob_match("user:*:name", "username"));
+        assert!(glob_match("h?llo", "hello"));
+        assert!(!glob_match("h?llo", "hllo"));
+        assert!(glob_match("a**b", "axxb"));
+        assert!(glob_match("ab*", "ab"));
+        assert!(!glob_match("", "a"));
+        assert!(glob_match("", ""));
+    }
+
+    #[test]
+    fn test_classes() {
+        assert!(glob_match("h[ae]llo", "hallo"));
+        assert!(!glob_match("h[ae]llo", "hillo"));
+        assert!(!glob_match("h[^e]llo", "hello"));
+        assert!(glob_match("h[^e]llo", "hallo"));
+        assert!(glob_match("h[a-b]llo", "hbllo"));
+        assert!(glob_match("h[b-a]llo", "hallo"));
+        assert!(glob_match("[\\]]", "]"));
+        // 没有闭合的 `[` 按 Redis 的行为处理
+        assert!(glob_match("a[b", "ab"));
+    }
+
+    #[test]
+    fn test_escape_and_literals() {
+        assert!(glob_match("a\\*b", "a*b"));
+        assert!(!glob_match("a\\*b", "axb"));
+        assert!(glob_match("a.b+", "a.b+"));
+        assert!(!glob_match("a.b", "axb"));
+    }
+
+    #[test]
+    fn test_nocase() {
+        assert!(string_match(b"HELLO*", b"hello world", true));
+        assert!(string_match(b"[A-C]x", b"bX", true));
+        assert!(!string_match(b"HELLO*", b"hello world", false));
+    }
+
+    #[test]
+    fn test_pathological_pattern() {
+        let pattern = "a*".repeat(50) + "b";
+        let string = "a".repeat(100);
+        assert!(!glob_match(&pattern, &string));
+    }
+}
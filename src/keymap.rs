@@ -0,0 +1,420 @@
+//! 带 SCAN 索引的键空间
+//!
+//! `KeyMap` 包装了 `DashMap`，额外维护一份按键名哈希值排序、按哈希区间分片加锁的索引。
+//! SCAN 的游标就是哈希值：每次从游标开始按哈希顺序取出一批键，
+//! 返回的下一个游标是这一批中最大哈希值加一。键的哈希值不会变化，
+//! 所以在整个遍历过程中一直存在的键一定会被返回，并发的插入和删除
+//! 不会让其它键被跳过或重复，每次调用的开销也只和 COUNT 有关。
+//!
+//! 读操作直接转发给 `DashMap`，写操作（insert/entry/get_mut/remove/remove_if/retain/clear）
+//! 由 `KeyMap` 同步更新索引和键的内存占用。索引总是在持有键所在分片的写锁时修改，
+//! 同一个键的写操作因此是串行的，索引和数据不会不一致。
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{RwLock, RwLockWriteGuard};
+
+use dashmap::iter::Iter;
+use dashmap::mapref::entry::Entry;
+use dashmap::mapref::one::{Ref, RefMut};
+use dashmap::DashMap;
+use rand::Rng;
+
+use crate::memory::{MemoryUsage, DEFAULT_SAMPLES, KEY_OVERHEAD};
+
+/// 键名的哈希值，即键在 SCAN 中的位置
+pub fn key_hash(key: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 用和 SCAN 相同的游标遍历集合类型中的元素（SSCAN/HSCAN/ZSCAN）：
+/// 游标是元素名的哈希值，返回哈希值不小于 `cursor` 的大约 `count` 个元素和下一个游标，
+/// 哈希相同的元素在同一批里返回，遍历完时下一个游标为 0
+pub fn scan_elements<'a, T>(
+    items: impl Iterator<Item = (&'a str, T)>,
+    cursor: u64,
+    count: usize,
+) -> (u64, Vec<(&'a str, T)>) {
+    let mut batch: Vec<_> = items
+        .map(|(name, item)| (key_hash(name), name, item))
+        .filter(|(hash, _, _)| *hash >= cursor)
+        .collect();
+    let count = count.max(1);
+    let next = if batch.len() > count {
+        // 只需要找出第 count 小的哈希值作为这一批的边界，不用整体排序
+        batch.select_nth_unstable_by_key(count - 1, |(hash, _, _)| *hash);
+        let end = batch[count - 1].0;
+        batch.retain(|(hash, _, _)| *hash <= end);
+        end.wrapping_add(1)
+    } else {
+        0
+    };
+    let items = batch
+        .into_iter()
+        .map(|(_, name, item)| (name, item))
+        .collect();
+    (next, items)
+}
+
+// 索引的分片数。分片按哈希值的高位划分，每个分片负责一段连续的哈希区间，
+// 按分片顺序遍历就是按哈希值顺序遍历
+const INDEX_SHARDS: usize = 64;
+const SHARD_SHIFT: u32 = u64::BITS - INDEX_SHARDS.trailing_zeros();
+
+// 哈希值 -> [(键名, 内存占用)]，哈希相同的键放在同一个 Vec 里
+type IndexShard = BTreeMap<u64, Vec<(String, usize)>>;
+
+/// 按哈希值排序的键索引，同时记录每个键估算的内存占用。
+/// 和 `DashMap` 一样分片加锁，写不同分片的键不会互相阻塞；
+/// 更新和删除按哈希值查找，只有新加入的键需要复制键名
+#[derive(Debug)]
+pub struct KeyIndex {
+    shards: Box<[RwLock<IndexShard>]>,
+    used_memory: AtomicUsize,
+}
+
+impl Default for KeyIndex {
+    fn default() -> Self {
+        Self {
+            shards: (0..INDEX_SHARDS).map(|_| RwLock::default()).collect(),
+            used_memory: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl KeyIndex {
+    fn write(&self, hash: u64) -> RwLockWriteGuard<'_, IndexShard> {
+        self.shards[(hash >> SHARD_SHIFT) as usize]
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    // 从 `cursor` 开始按哈希顺序依次把 (哈希值, 键名) 交给 `f`，`f` 返回 false 时停止
+    fn walk(&self, cursor: u64, mut f: impl FnMut(u64, &str) -> bool) {
+        for shard in &self.shards[(cursor >> SHARD_SHIFT) as usize..] {
+            let shard = shard.read().unwrap_or_else(|e| e.into_inner());
+            for (hash, keys) in shard.range(cursor..) {
+                for (key, _) in keys {
+                    if !f(*hash, key) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    // 加入键或更新键的内存占用
+    fn insert(&self, key: &str, size: usize) {
+        let hash = key_hash(key);
+        let mut shard = self.write(hash);
+        let keys = shard.entry(hash).or_default();
+        let old = match keys.iter_mut().find(|(name, _)| name == key) {
+            Some((_, old)) => Some(std::mem::replace(old, size)),
+            None => {
+                keys.push((key.to_owned(), size));
+                None
+            }
+        };
+        self.used_memory.fetch_add(size, Ordering::Relaxed);
+        if let Some(old) = old {
+            self.used_memory.fetch_sub(old, Ordering::Relaxed);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        let hash = key_hash(key);
+        let mut shard = self.write(hash);
+        let Some(keys) = shard.get_mut(&hash) else {
+            return;
+        };
+        if let Some(pos) = keys.iter().position(|(name, _)| name == key) {
+            let (_, old) = keys.swap_remove(pos);
+            if keys.is_empty() {
+                shard.remove(&hash);
+            }
+            self.used_memory.fetch_sub(old, Ordering::Relaxed);
+        }
+    }
+
+    fn clear(&self) {
+        for shard in self.shards.iter() {
+            shard.write().unwrap_or_else(|e| e.into_inner()).clear();
+        }
+        self.used_memory.store(0, Ordering::Relaxed);
+    }
+
+    /// 所有键估算的内存占用之和
+    pub fn used_memory(&self) -> usize {
+        self.used_memory.load(Ordering::Relaxed)
+    }
+
+    /// 哈希值不小于 `cursor` 的前 `count` 个键的哈希值
+    pub fn hashes_from(&self, cursor: u64, count: usize) -> Vec<u64> {
+        let mut hashes = Vec::new();
+        self.walk(cursor, |hash, _| {
+            hashes.push(hash);
+            hashes.len() < count
+        });
+        hashes.truncate(count);
+        hashes
+    }
+
+    /// 哈希值在 `[from, to]` 之间的键
+    pub fn keys_between(&self, from: u64, to: u64) -> Vec<String> {
+        let mut keys = Vec::new();
+        self.walk(from, |hash, key| {
+            if hash > to {
+                return false;
+            }
+            keys.push(key.to_owned());
+            true
+        });
+        keys
+    }
+
+    /// 哈希值不小于 `cursor` 的第一个键
+    pub fn first_from(&self, cursor: u64) -> Option<(u64, String)> {
+        let mut first = None;
+        self.walk(cursor, |hash, key| {
+            first = Some((hash, key.to_owned()));
+            false
+        });
+        first
+    }
+
+    /// 随机抽样 `count` 个键，结果可能重复
+    pub fn random_keys(&self, count: usize) -> Vec<String> {
+        let mut rng = rand::thread_rng();
+        (0..count)
+            .filter_map(|_| self.first_from(rng.gen()).or_else(|| self.first_from(0)))
+            .map(|(_, key)| key)
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct KeyMap<V> {
+    map: DashMap<String, V>,
+    index: KeyIndex,
+}
+
+impl<V> Default for KeyMap<V> {
+    fn default() -> Self {
+        Self {
+            map: DashMap::new(),
+            index: KeyIndex::default(),
+        }
+    }
+}
+
+impl<V> KeyMap<V> {
+    pub fn get(&self, key: &str) -> Option<Ref<'_, String, V>> {
+        self.map.get(key)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn iter(&self) -> Iter<'_, String, V> {
+        self.map.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+/// `KeyMap::entry` 返回的条目
+pub struct KeyEntry<'a, V: MemoryUsage> {
+    entry: Entry<'a, String, V>,
+    index: &'a KeyIndex,
+}
+
+impl<'a, V: MemoryUsage + Default> KeyEntry<'a, V> {
+    /// 键不存在时插入默认值，引用释放时把键加入索引
+    pub fn or_default(self) -> KeyRefMut<'a, V> {
+        KeyRefMut {
+            inner: self.entry.or_default(),
+            index: self.index,
+        }
+    }
+}
+
+/// 键的可变引用，释放时更新索引中键的内存占用
+pub struct KeyRefMut<'a, V: MemoryUsage> {
+    inner: RefMut<'a, String, V>,
+    index: &'a KeyIndex,
+}
+
+impl<V: MemoryUsage> KeyRefMut<'_, V> {
+    pub fn value(&self) -> &V {
+        self.inner.value()
+    }
+
+    pub fn value_mut(&mut self) -> &mut V {
+        self.inner.value_mut()
+    }
+}
+
+impl<V: MemoryUsage> Deref for KeyRefMut<'_, V> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        self.inner.value()
+    }
+}
+
+impl<V: MemoryUsage> DerefMut for KeyRefMut<'_, V> {
+    fn deref_mut(&mut self) -> &mut V {
+        self.inner.value_mut()
+    }
+}
+
+impl<V: MemoryUsage> Drop for KeyRefMut<'_, V> {
+    fn drop(&mut self) {
+        // 此时仍然持有分片的写锁
+        let key = self.inner.key();
+        self.index
+            .insert(key, entry_size(key, self.inner.value(), DEFAULT_SAMPLES));
+    }
+}
+
+// 键估算的内存占用
+fn entry_size<V: MemoryUsage>(key: &str, value: &V, samples: usize) -> usize {
+    KEY_OVERHEAD + key.len() + value.memory_usage(samples)
+}
+
+impl<V: MemoryUsage> KeyMap<V> {
+    pub fn index(&self) -> &KeyIndex {
+        &self.index
+    }
+
+    pub fn insert(&self, key: String, value: V) -> Option<V> {
+        let size = entry_size(&key, &value, DEFAULT_SAMPLES);
+        match self.map.entry(key) {
+            Entry::Occupied(mut entry) => {
+                self.index.insert(entry.key(), size);
+                Some(entry.insert(value))
+            }
+            Entry::Vacant(entry) => {
+                self.index.insert(entry.key(), size);
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    /// 与 `DashMap::entry` 相同，值真正插入时才加入索引
+    pub fn entry(&self, key: String) -> KeyEntry<'_, V> {
+        KeyEntry {
+            entry: self.map.entry(key),
+            index: &self.index,
+        }
+    }
+
+    /// 可变引用，引用释放时重新估算键的内存占用
+    pub fn get_mut(&self, key: &str) -> Option<KeyRefMut<'_, V>> {
+        self.map.get_mut(key).map(|inner| KeyRefMut {
+            inner,
+            index: &self.index,
+        })
+    }
+
+    /// 值被原地修改（get_mut/entry）之后重新估算它的内存占用
+    pub fn refresh(&self, key: &str) {
+        if let Some(value) = self.map.get(key) {
+            self.index
+                .insert(key, entry_size(key, value.value(), DEFAULT_SAMPLES));
+        }
+    }
+
+    /// 按 `samples` 个元素抽样估算键的内存占用（MEMORY USAGE），0 表示遍历所有元素
+    pub fn memory_usage(&self, key: &str, samples: usize) -> Option<usize> {
+        self.map
+            .get(key)
+            .map(|value| entry_size(key, value.value(), samples))
+    }
+
+    pub fn remove(&self, key: &str) -> Option<(String, V)> {
+        self.remove_if(key, |_, _| true)
+    }
+
+    /// 键存在并且 `f` 返回 true 时删除它
+    pub fn remove_if(&self, key: &str, f: impl FnOnce(&String, &V) -> bool) -> Option<(String, V)> {
+        // 判断在分片的写锁内进行，返回 true 之后键一定会被删除，这时就可以更新索引
+        self.map.remove_if(key, |key, value| {
+            let remove = f(key, value);
+            if remove {
+                self.index.remove(key);
+            }
+            remove
+        })
+    }
+
+    pub fn retain(&self, mut f: impl FnMut(&String, &mut V) -> bool) {
+        self.map.retain(|key, value| {
+            let keep = f(key, value);
+            if !keep {
+                self.index.remove(key);
+            }
+            keep
+        });
+    }
+
+    pub fn clear(&self) {
+        self.index.clear();
+        self.map.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_follows_writes() {
+        let map: KeyMap<u64> = KeyMap::default();
+        map.insert("a".to_owned(), 1);
+        map.insert("a".to_owned(), 2);
+        *map.entry("b".to_owned()).or_default() += 1;
+        map.insert("c".to_owned(), 3);
+        // 没有插入值的 entry 不会留在索引里
+        drop(map.entry("d".to_owned()));
+        assert_eq!(map.index().hashes_from(0, 10).len(), 3);
+
+        map.remove("a");
+        map.retain(|key, _| key != "b");
+        assert_eq!(map.index().keys_between(0, u64::MAX), vec!["c".to_owned()]);
+        assert_eq!(map.get("c").map(|v| *v), Some(3));
+
+        assert_eq!(map.index().used_memory(), KEY_OVERHEAD + 1 + 8);
+
+        map.clear();
+        assert!(map.index().hashes_from(0, 10).is_empty());
+        assert_eq!(map.index().used_memory(), 0);
+    }
+
+    #[test]
+    fn test_hash_order() {
+        let map: KeyMap<u64> = KeyMap::default();
+        for i in 0..100 {
+            map.insert(format!("key:{i}"), i);
+        }
+        let hashes = map.index().hashes_from(0, 100);
+        assert!(hashes.windows(2).all(|w| w[0] <= w[1]));
+        let from = hashes[50];
+        let keys = map.index().keys_between(from, u64::MAX);
+        assert_eq!(keys.len(), 50);
+        assert!(keys.iter().all(|key| key_hash(key) >= from));
+    }
+}
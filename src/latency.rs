@@ -0,0 +1,173 @@
+//! 延迟监控（LATENCY）
+//!
+//! 耗时达到 `latency-monitor-threshold` 毫秒的事件会被记录下来，每个事件保存最近
+//! 160 个样本（同一秒内的多个样本只保留最大值）以及历史最大值，和 Redis 的
+//! latency monitor 一致。阈值为 0 时关闭。
+//!
+//! 目前记录的事件：
+//! - `command` / `fast-command`：命令执行，带 CMD_FAST 标志的命令记为 fast-command
+//! - `expire-cycle`：主动过期
+//! - `eviction-cycle`：maxmemory 淘汰
+//! - `fork`：全量同步时生成数据快照（这里没有 fork，快照在当前进程中生成）
+//!
+//! 没有 AOF，所以不会产生 fsync 相关的事件。
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use dashmap::DashMap;
+
+// 每个事件保存的样本数
+const HISTORY_LEN: usize = 160;
+
+pub const EVENT_COMMAND: &str = "command";
+pub const EVENT_FAST_COMMAND: &str = "fast-command";
+pub const EVENT_EXPIRE_CYCLE: &str = "expire-cycle";
+pub const EVENT_EVICTION_CYCLE: &str = "eviction-cycle";
+pub const EVENT_FORK: &str = "fork";
+
+/// 一个样本：Unix 时间（秒）和耗时（毫秒）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencySample {
+    pub time: u64,
+    pub latency: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EventHistory {
+    pub samples: VecDeque<LatencySample>,
+    // 历史最大值，RESET 之前一直保留
+    pub max: u64,
+}
+
+impl EventHistory {
+    pub fn latest(&self) -> Option<LatencySample> {
+        self.samples.back().copied()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LatencyMonitor {
+    // 毫秒，0 表示关闭
+    threshold: AtomicU64,
+    events: DashMap<&'static str, EventHistory>,
+}
+
+impl LatencyMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn threshold(&self) -> u64 {
+        self.threshold.load(Ordering::Relaxed)
+    }
+
+    pub fn set_threshold(&self, millis: u64) {
+        self.threshold.store(millis, Ordering::Relaxed);
+    }
+
+    /// 计时执行 `f`，耗时超过阈值时记为 `event` 的一个样本
+    pub fn monitor<R>(&self, event: &'static str, f: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let result = f();
+        self.record(event, start.elapsed());
+        result
+    }
+
+    /// 耗时达到阈值时记录一个样本
+    pub fn record(&self, event: &'static str, duration: Duration) {
+        let threshold = self.threshold();
+        let latency = duration.as_millis() as u64;
+        if threshold == 0 || latency < threshold {
+            return;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.add_sample(event, now, latency);
+    }
+
+    fn add_sample(&self, event: &'static str, time: u64, latency: u64) {
+        let mut history = self.events.entry(event).or_default();
+        history.max = history.max.max(latency);
+        if let Some(last) = history.samples.back_mut() {
+            if last.time == time {
+                last.latency = last.latency.max(latency);
+                return;
+            }
+        }
+        if history.samples.len() == HISTORY_LEN {
+            history.samples.pop_front();
+        }
+        history.samples.push_back(LatencySample { time, latency });
+    }
+
+    /// 所有有样本的事件，按事件名排序
+    pub fn events(&self) -> Vec<(&'static str, EventHistory)> {
+        let mut events: Vec<_> = self
+            .events
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect();
+        events.sort_by_key(|(name, _)| *name);
+        events
+    }
+
+    pub fn history(&self, event: &str) -> Option<EventHistory> {
+        self.events.get(event).map(|history| history.clone())
+    }
+
+    /// 清空指定的事件，`events` 为空时清空所有事件，返回清空的事件数
+    pub fn reset(&self, events: &[String]) -> usize {
+        if events.is_empty() {
+            let count = self.events.len();
+            self.events.clear();
+            return count;
+        }
+        events
+            .iter()
+            .filter(|event| self.events.remove(event.as_str()).is_some())
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold_and_samples() {
+        let monitor = LatencyMonitor::new();
+        monitor.record(EVENT_COMMAND, Duration::from_millis(500));
+        assert!(monitor.events().is_empty());
+
+        monitor.set_threshold(10);
+        monitor.record(EVENT_COMMAND, Duration::from_millis(9));
+        assert!(monitor.events().is_empty());
+
+        // 同一秒的样本合并为最大值
+        monitor.add_sample(EVENT_COMMAND, 100, 20);
+        monitor.add_sample(EVENT_COMMAND, 100, 30);
+        monitor.add_sample(EVENT_COMMAND, 100, 15);
+        for time in 101..=300 {
+            monitor.add_sample(EVENT_COMMAND, time, 12);
+        }
+        let history = monitor.history(EVENT_COMMAND).unwrap();
+        assert_eq!(history.samples.len(), HISTORY_LEN);
+        assert_eq!(history.max, 30);
+        assert_eq!(
+            history.latest(),
+            Some(LatencySample {
+                time: 300,
+                latency: 12
+            })
+        );
+
+        monitor.add_sample(EVENT_FORK, 100, 50);
+        assert_eq!(monitor.reset(&["fork".to_owned(), "nosuch".to_owned()]), 1);
+        assert_eq!(monitor.reset(&[]), 1);
+        assert!(monitor.events().is_empty());
+    }
+}
@@ -1,15 +1,55 @@
 use bytes::BytesMut;
-use dashmap::DashMap;
-use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
 
+pub mod acl;
+pub mod client;
+pub mod cluster;
+pub mod config;
+pub mod connection;
+pub mod db;
 mod decode;
 mod encode;
+pub mod glob;
+pub mod keymap;
+pub mod latency;
+pub mod limits;
+pub mod memory;
+pub mod monitor;
 pub mod network;
+pub mod notify;
 pub mod process;
+pub mod pubsub;
+pub mod replication;
 pub mod resp;
+pub mod shutdown;
+pub mod slowlog;
+pub mod snapshot;
+pub mod stats;
+pub mod tls;
+pub mod tracking;
+pub mod transaction;
 
+use crate::acl::Acl;
+use crate::client::Clients;
+use crate::cluster::Cluster;
+use crate::config::Config;
+use crate::db::Db;
+use crate::latency::LatencyMonitor;
+use crate::limits::Limits;
+use crate::memory::Memory;
+use crate::monitor::Monitors;
+use crate::notify::Notifications;
 use crate::process::string::get::GetCommandPara;
+use crate::pubsub::PubSub;
+use crate::replication::Replication;
+use crate::shutdown::Shutdown;
+use crate::slowlog::SlowLog;
+use crate::stats::Stats;
+use crate::tracking::Tracking;
+use crate::transaction::Transactions;
+pub use decode::{FrameScanner, ProtoLimits, DEFAULT_PROTO_MAX_BULK_LEN};
 pub use resp::*;
 
 pub trait RespDecoder: Sized {
@@ -26,27 +66,116 @@ pub trait Processor {
 
 #[derive(Debug)]
 pub struct Data {
-    pub(crate) string_data: DashMap<String, Resp>,
-    pub(crate) hash_data: DashMap<String, HashMap<String, String>>,
-    pub(crate) list_data: DashMap<String, VecDeque<String>>,
-    pub(crate) set_data: DashMap<String, HashSet<String>>,
-    pub(crate) sorted_set_data: DashMap<String, BTreeMap<String, f64>>,
-    // 过期时间存储，键 -> 过期时间戳（毫秒）
-    pub(crate) expiry_data: DashMap<String, u64>,
+    dbs: Vec<Db>,
+    // 逻辑库编号 -> dbs 中的下标，SWAPDB 只需要交换映射
+    db_mapping: RwLock<Vec<usize>>,
+    // 主从复制状态
+    pub replication: Replication,
+    // 集群状态
+    pub cluster: Cluster,
+    // 事务锁和 WATCH 状态
+    pub transactions: Transactions,
+    // 发布/订阅
+    pub pubsub: PubSub,
+    // 键空间通知配置
+    pub notifications: Notifications,
+    // maxmemory 配置和淘汰统计
+    pub memory: Memory,
+    // 运行统计
+    pub stats: Stats,
+    // 慢查询日志
+    pub slowlog: SlowLog,
+    // 延迟监控
+    pub latency: LatencyMonitor,
+    // MONITOR 中的连接
+    pub monitors: Monitors,
+    // 在线的客户端和 CLIENT PAUSE 状态
+    pub clients: Arc<Clients>,
+    // maxclients、空闲超时和输入输出缓冲区限制
+    pub limits: Arc<Limits>,
+    // 客户端缓存的失效表
+    pub tracking: Tracking,
+    // ACL 用户和 ACL LOG
+    pub acl: Acl,
+    // 启动配置
+    pub config: Config,
+    // SHUTDOWN 和信号发起的关闭
+    pub shutdown: Shutdown,
 }
 
 impl Data {
     pub fn new() -> Self {
+        Self::with_databases(db::DEFAULT_DATABASES)
+    }
+
+    pub fn with_databases(databases: usize) -> Self {
+        let databases = databases.max(1);
         Self {
-            string_data: DashMap::new(),
-            hash_data: DashMap::new(),
-            list_data: DashMap::new(),
-            set_data: DashMap::new(),
-            sorted_set_data: DashMap::new(),
-            expiry_data: DashMap::new(),
+            dbs: (0..databases).map(|_| Db::new()).collect(),
+            db_mapping: RwLock::new((0..databases).collect()),
+            replication: Replication::new(),
+            cluster: Cluster::new(),
+            transactions: Transactions::new(),
+            pubsub: PubSub::new(),
+            notifications: Notifications::new(),
+            memory: Memory::new(),
+            stats: Stats::new(),
+            slowlog: SlowLog::new(),
+            latency: LatencyMonitor::new(),
+            monitors: Monitors::new(),
+            clients: Arc::new(Clients::new()),
+            limits: Arc::new(Limits::new()),
+            tracking: Tracking::new(),
+            acl: Acl::new(),
+            config: Config::new(),
+            shutdown: Shutdown::new(),
         }
     }
 
+    /// 数据库数量
+    pub fn databases(&self) -> usize {
+        self.dbs.len()
+    }
+
+    /// 当前命令选择的库
+    pub fn db(&self) -> &Db {
+        self.db_at(db::selected_db())
+    }
+
+    /// 编号为 `index` 的库
+    pub fn db_at(&self, index: usize) -> &Db {
+        let mapping = self.db_mapping.read().unwrap_or_else(|e| e.into_inner());
+        &self.dbs[mapping[index]]
+    }
+
+    /// 在编号为 `index` 的库上执行 `f`，`f` 中的 `db()` 都指向这个库
+    pub fn with_db<R>(&self, index: usize, f: impl FnOnce() -> R) -> R {
+        assert!(index < self.databases(), "DB index is out of range");
+        db::with_selected(index, f)
+    }
+
+    /// 所有库中的键估算的内存占用
+    pub fn used_memory(&self) -> usize {
+        self.dbs.iter().map(Db::used_memory).sum()
+    }
+
+    /// SWAPDB：交换两个库的内容，连接上的选择保持不变
+    pub fn swap_db(&self, a: usize, b: usize) {
+        let mut mapping = self.db_mapping.write().unwrap_or_else(|e| e.into_inner());
+        mapping.swap(a, b);
+        drop(mapping);
+        self.transactions.touch_db(a);
+        self.transactions.touch_db(b);
+        // 失效表不区分库，交换后客户端缓存的内容都可能过期
+        self.tracking.invalidate_all(&self.clients);
+    }
+
+    /// 键被修改：让 WATCH 了它的事务失效，并通知缓存了它的客户端
+    pub fn signal_modified_key(&self, db: usize, key: &str) {
+        self.transactions.touch(db, key);
+        self.tracking.invalidate_key(&self.clients, key);
+    }
+
     // 获取当前时间戳（毫秒）
     pub fn current_timestamp_millis(&self) -> u64 {
         SystemTime::now()
@@ -57,7 +186,7 @@ impl Data {
 
     // 检查键是否过期
     pub fn is_expired(&self, key: &str) -> bool {
-        if let Some(expiry) = self.expiry_data.get(key) {
+        if let Some(expiry) = self.db().expiry_data.get(key) {
             let now = self.current_timestamp_millis();
             now >= *expiry
         } else {
@@ -65,44 +194,110 @@ impl Data {
         }
     }
 
-    // 清理过期的键
+    // 清理所有库中过期的键
     pub fn cleanup_expired(&self) {
         let now = self.current_timestamp_millis();
-        let expired_keys: Vec<String> = self
-            .expiry_data
-            .iter()
-            .filter(|entry| now >= *entry.value())
-            .map(|entry| entry.key().clone())
-            .collect();
-
-        for key in expired_keys {
-            self.remove_key(&key);
+        for index in 0..self.databases() {
+            self.with_db(index, || {
+                let expired_keys: Vec<String> = self
+                    .db()
+                    .expiry_data
+                    .iter()
+                    .filter(|entry| now >= *entry.value())
+                    .map(|entry| entry.key().clone())
+                    .collect();
+
+                for key in expired_keys {
+                    self.expire_if_needed(&key);
+                }
+            });
+        }
+        if let Err(e) = self.replication.propagate_expired() {
+            warn!("⚠️ Failed to propagate expired keys: {}", e);
         }
     }
 
+    // 键已过期时删除并发布 expired 事件，返回是否删除
+    //
+    // 主节点上删除的键会以 DEL 传播给副本
+    pub fn expire_if_needed(&self, key: &str) -> bool {
+        if !self.is_expired(key) {
+            return false;
+        }
+        self.remove_key(key);
+        self.replication.expired(key);
+        self.stats.record_expired();
+        self.notify_keyspace_event(notify::NOTIFY_EXPIRED, "expired", key);
+        true
+    }
+
+    // 发布键空间通知
+    pub fn notify_keyspace_event(&self, class: u32, event: &str, key: &str) {
+        if !self.notifications.enabled(class) {
+            return;
+        }
+        let flags = self.notifications.flags();
+        let index = db::selected_db();
+        if flags & notify::NOTIFY_KEYSPACE != 0 {
+            self.pubsub
+                .publish(&format!("__keyspace@{index}__:{key}"), event);
+        }
+        if flags & notify::NOTIFY_KEYEVENT != 0 {
+            self.pubsub
+                .publish(&format!("__keyevent@{index}__:{event}"), key);
+        }
+    }
+
+    // 检查键是否存在（任意类型）
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.db().string_data.contains_key(key)
+            || self.db().hash_data.contains_key(key)
+            || self.db().list_data.contains_key(key)
+            || self.db().set_data.contains_key(key)
+            || self.db().sorted_set_data.contains_key(key)
+    }
+
     // 删除键（从所有数据存储中）
     pub fn remove_key(&self, key: &str) {
-        self.string_data.remove(key);
-        self.hash_data.remove(key);
-        self.list_data.remove(key);
-        self.set_data.remove(key);
-        self.sorted_set_data.remove(key);
-        self.expiry_data.remove(key);
+        self.db().string_data.remove(key);
+        self.db().hash_data.remove(key);
+        self.db().list_data.remove(key);
+        self.db().set_data.remove(key);
+        self.db().sorted_set_data.remove(key);
+        self.db().expiry_data.remove(key);
+        self.db().access_data.remove(key);
+        self.signal_modified_key(db::selected_db(), key);
+    }
+
+    // 清空当前库（FLUSHDB）
+    pub fn flush_db(&self, lazy: bool) {
+        self.db().clear(lazy);
+        self.transactions.touch_db(db::selected_db());
+        self.tracking.invalidate_all(&self.clients);
+    }
+
+    // 清空所有库（FLUSHALL）
+    pub fn flush(&self, lazy: bool) {
+        for db in &self.dbs {
+            db.clear(lazy);
+        }
+        self.transactions.touch_all();
+        self.tracking.invalidate_all(&self.clients);
     }
 
     // 设置键的过期时间
     pub fn set_expiry(&self, key: &str, expiry_millis: u64) {
-        self.expiry_data.insert(key.to_string(), expiry_millis);
+        self.db().expiry_data.insert(key.to_string(), expiry_millis);
     }
 
     // 移除键的过期时间
     pub fn remove_expiry(&self, key: &str) -> bool {
-        self.expiry_data.remove(key).is_some()
+        self.db().expiry_data.remove(key).is_some()
     }
 
     // 获取键的剩余过期时间（毫秒），如果没有过期时间返回None
     pub fn get_ttl_millis(&self, key: &str) -> Option<i64> {
-        if let Some(expiry) = self.expiry_data.get(key) {
+        if let Some(expiry) = self.db().expiry_data.get(key) {
             let now = self.current_timestamp_millis();
             let remaining = (*expiry as i64) - (now as i64);
             Some(remaining.max(-1)) // -1表示已过期
@@ -112,6 +307,28 @@ impl Data {
     }
 }
 
+// 主动过期的执行周期
+const ACTIVE_EXPIRE_PERIOD: Duration = Duration::from_millis(100);
+
+/// 定期清理过期的键，这样即使没有被访问，过期的键也会被删除并发出 expired 事件
+///
+/// 副本不主动过期，等待主节点同步过来的 DEL。
+pub fn start_active_expire(data: Arc<Data>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(ACTIVE_EXPIRE_PERIOD);
+        loop {
+            interval.tick().await;
+            // CLIENT PAUSE WRITE 期间也不主动删除过期的键
+            if data.replication.is_replica() || data.clients.paused_until(true).is_some() {
+                continue;
+            }
+            let _guard = data.transactions.shared();
+            data.latency
+                .monitor(latency::EVENT_EXPIRE_CYCLE, || data.cleanup_expired());
+        }
+    });
+}
+
 impl Default for Data {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,477 @@
+//! 连接限制
+//!
+//! - maxclients：连接数达到上限时新连接收到 `-ERR max number of clients reached` 后被关闭
+//! - timeout：普通客户端空闲超过指定秒数后断开，由 `start` 中的定时任务检查
+//! - tcp-keepalive：accept 之后给 TCP 连接打开 SO_KEEPALIVE
+//! - client-query-buffer-limit：读缓冲区中还没解析完的命令超过上限时断开，由 `RespFrameCodec` 检查
+//! - proto-max-bulk-len：客户端发来的 bulk string 声明的最大长度，超过时回复协议错误并断开
+//! - client-output-buffer-limit：发布/订阅消息、MONITOR、失效消息和复制流先通过 `Outbox` 进入
+//!   channel，连接来不及写出时累计在客户端上，超过硬限制或者持续超过软限制时断开
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::client::{Client, ClientType};
+use crate::{memory, Data, ProtoLimits, Resp, DEFAULT_PROTO_MAX_BULK_LEN};
+
+pub const DEFAULT_MAXCLIENTS: usize = 10000;
+pub const DEFAULT_TCP_KEEPALIVE: u64 = 300;
+pub const DEFAULT_QUERY_BUFFER_LIMIT: usize = 1024 * 1024 * 1024;
+// client-query-buffer-limit 和 proto-max-bulk-len 的最小值，和 Redis 一样为 1mb
+pub const MIN_QUERY_BUFFER_LIMIT: usize = 1024 * 1024;
+
+// 检查空闲连接的间隔
+const CRON_PERIOD: Duration = Duration::from_secs(1);
+
+/// client-output-buffer-limit 区分的客户端类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputClass {
+    Normal,
+    Replica,
+    PubSub,
+}
+
+impl OutputClass {
+    const ALL: [Self; 3] = [Self::Normal, Self::Replica, Self::PubSub];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "normal" => Some(Self::Normal),
+            "replica" | "slave" => Some(Self::Replica),
+            "pubsub" => Some(Self::PubSub),
+            _ => None,
+        }
+    }
+
+    // CONFIG GET 和 Redis 一样使用 slave
+    fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Replica => "slave",
+            Self::PubSub => "pubsub",
+        }
+    }
+
+    /// 客户端所属的类别，MONITOR 和连接的主节点按普通客户端处理
+    pub fn of(client_type: ClientType) -> Self {
+        match client_type {
+            ClientType::Replica => Self::Replica,
+            ClientType::PubSub => Self::PubSub,
+            ClientType::Normal | ClientType::Master => Self::Normal,
+        }
+    }
+}
+
+/// 一个类别的输出缓冲区限制，0 表示不限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OutputBufferLimit {
+    pub hard: u64,
+    pub soft: u64,
+    pub soft_seconds: u64,
+}
+
+impl OutputBufferLimit {
+    const fn new(hard: u64, soft: u64, soft_seconds: u64) -> Self {
+        Self {
+            hard,
+            soft,
+            soft_seconds,
+        }
+    }
+
+    /// 缓冲区从 `before` 增长到 `used` 之后是否需要断开，`since` 记录开始超过软限制的时间
+    pub fn exceeded(&self, before: u64, used: u64, since: &mut Option<Instant>) -> bool {
+        if self.hard > 0 && used > self.hard {
+            return true;
+        }
+        if self.soft == 0 || used <= self.soft {
+            *since = None;
+            return false;
+        }
+        // 从软限制以下增长上来时重新计时
+        if before <= self.soft || since.is_none() {
+            *since = Some(Instant::now());
+        }
+        since.is_some_and(|since| since.elapsed().as_secs() > self.soft_seconds)
+    }
+}
+
+const DEFAULT_OUTPUT_LIMITS: [OutputBufferLimit; 3] = [
+    OutputBufferLimit::new(0, 0, 0),
+    OutputBufferLimit::new(256 * 1024 * 1024, 64 * 1024 * 1024, 60),
+    OutputBufferLimit::new(32 * 1024 * 1024, 8 * 1024 * 1024, 60),
+];
+
+/// 连接相关的限制和因此断开的连接数
+#[derive(Debug)]
+pub struct Limits {
+    maxclients: AtomicUsize,
+    // 空闲超时秒数，0 表示不断开
+    timeout: AtomicU64,
+    tcp_keepalive: AtomicU64,
+    query_buffer: AtomicUsize,
+    proto_max_bulk_len: AtomicUsize,
+    output: RwLock<[OutputBufferLimit; 3]>,
+    rejected_connections: AtomicU64,
+    query_buffer_disconnections: AtomicU64,
+    output_buffer_disconnections: AtomicU64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            maxclients: AtomicUsize::new(DEFAULT_MAXCLIENTS),
+            timeout: AtomicU64::new(0),
+            tcp_keepalive: AtomicU64::new(DEFAULT_TCP_KEEPALIVE),
+            query_buffer: AtomicUsize::new(DEFAULT_QUERY_BUFFER_LIMIT),
+            proto_max_bulk_len: AtomicUsize::new(DEFAULT_PROTO_MAX_BULK_LEN),
+            output: RwLock::new(DEFAULT_OUTPUT_LIMITS),
+            rejected_connections: AtomicU64::new(0),
+            query_buffer_disconnections: AtomicU64::new(0),
+            output_buffer_disconnections: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Limits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn maxclients(&self) -> usize {
+        self.maxclients.load(Ordering::Relaxed)
+    }
+
+    pub fn set_maxclients(&self, maxclients: usize) {
+        self.maxclients.store(maxclients, Ordering::Relaxed);
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.load(Ordering::Relaxed))
+    }
+
+    pub fn set_timeout(&self, secs: u64) {
+        self.timeout.store(secs, Ordering::Relaxed);
+    }
+
+    /// TCP keepalive 的间隔，0 表示不打开
+    pub fn tcp_keepalive(&self) -> Duration {
+        Duration::from_secs(self.tcp_keepalive.load(Ordering::Relaxed))
+    }
+
+    pub fn set_tcp_keepalive(&self, secs: u64) {
+        self.tcp_keepalive.store(secs, Ordering::Relaxed);
+    }
+
+    pub fn query_buffer(&self) -> usize {
+        self.query_buffer.load(Ordering::Relaxed)
+    }
+
+    pub fn set_query_buffer(&self, bytes: usize) {
+        self.query_buffer.store(bytes, Ordering::Relaxed);
+    }
+
+    pub fn proto_max_bulk_len(&self) -> usize {
+        self.proto_max_bulk_len.load(Ordering::Relaxed)
+    }
+
+    pub fn set_proto_max_bulk_len(&self, bytes: usize) {
+        self.proto_max_bulk_len.store(bytes, Ordering::Relaxed);
+    }
+
+    /// 客户端连接上解码时使用的协议限制
+    pub fn proto(&self) -> ProtoLimits {
+        ProtoLimits {
+            max_bulk_len: self.proto_max_bulk_len(),
+            ..Default::default()
+        }
+    }
+
+    pub fn output(&self, class: OutputClass) -> OutputBufferLimit {
+        self.output.read().unwrap_or_else(|e| e.into_inner())[class as usize]
+    }
+
+    pub fn set_output(&self, class: OutputClass, limit: OutputBufferLimit) {
+        self.output.write().unwrap_or_else(|e| e.into_inner())[class as usize] = limit;
+    }
+
+    /// CONFIG GET client-output-buffer-limit
+    pub fn output_config(&self) -> String {
+        OutputClass::ALL
+            .iter()
+            .map(|class| {
+                let limit = self.output(*class);
+                format!(
+                    "{} {} {} {}",
+                    class.name(),
+                    limit.hard,
+                    limit.soft,
+                    limit.soft_seconds
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// 新连接因为 maxclients 被拒绝
+    pub fn record_rejected(&self) {
+        self.rejected_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_query_buffer_disconnection(&self) {
+        self.query_buffer_disconnections
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_output_buffer_disconnection(&self) {
+        self.output_buffer_disconnections
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn rejected_connections(&self) -> u64 {
+        self.rejected_connections.load(Ordering::Relaxed)
+    }
+
+    pub fn query_buffer_disconnections(&self) -> u64 {
+        self.query_buffer_disconnections.load(Ordering::Relaxed)
+    }
+
+    pub fn output_buffer_disconnections(&self) -> u64 {
+        self.output_buffer_disconnections.load(Ordering::Relaxed)
+    }
+
+    /// CONFIG RESETSTAT
+    pub fn reset_stats(&self) {
+        for counter in [
+            &self.rejected_connections,
+            &self.query_buffer_disconnections,
+            &self.output_buffer_disconnections,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 解析 `<class> <hard> <soft> <soft seconds>` 的组合，可以一次设置多个类别
+pub fn parse_output_limits(args: &[String]) -> Option<Vec<(OutputClass, OutputBufferLimit)>> {
+    if args.is_empty() || !args.len().is_multiple_of(4) {
+        return None;
+    }
+    args.chunks(4)
+        .map(|group| {
+            let class = OutputClass::parse(&group[0])?;
+            let limit = OutputBufferLimit {
+                hard: memory::parse_memory(&group[1])?,
+                soft: memory::parse_memory(&group[2])?,
+                soft_seconds: group[3].parse().ok()?,
+            };
+            Some((class, limit))
+        })
+        .collect()
+}
+
+/// 写出时占用输出缓冲区的大小，按编码后的长度估算
+pub trait OutputSize {
+    fn output_size(&self) -> usize;
+}
+
+impl OutputSize for Resp {
+    fn output_size(&self) -> usize {
+        // 类型前缀、长度和 \r\n 按 16 字节估算
+        16 + match self {
+            Resp::SimpleStrings(data) => data.val.len(),
+            Resp::SimpleErrors(data) => data.error_msg.len(),
+            Resp::BulkStrings(data) => data.val.len(),
+            Resp::Arrays(data) => data.val.output_size(),
+            Resp::Pushes(data) => data.val.output_size(),
+            Resp::Maps(data) => data
+                .val
+                .iter()
+                .map(|(key, value)| key.output_size() + value.output_size())
+                .sum(),
+            _ => 0,
+        }
+    }
+}
+
+impl OutputSize for Vec<Resp> {
+    fn output_size(&self) -> usize {
+        self.iter().map(Resp::output_size).sum()
+    }
+}
+
+impl OutputSize for Bytes {
+    fn output_size(&self) -> usize {
+        self.len()
+    }
+}
+
+/// 推送给连接的异步消息，连接写出之后调用 `Client::release_output`
+#[derive(Debug)]
+pub struct Outbox<T> {
+    tx: mpsc::UnboundedSender<T>,
+    // 没有注册的连接（测试中）不统计
+    client: Option<Arc<Client>>,
+    limits: Arc<Limits>,
+}
+
+impl<T> Clone for Outbox<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            client: self.client.clone(),
+            limits: self.limits.clone(),
+        }
+    }
+}
+
+impl<T: OutputSize> Outbox<T> {
+    /// 创建推送给 `client` 的 channel，返回发送和接收的两端
+    pub fn channel(
+        client: Option<Arc<Client>>,
+        limits: &Arc<Limits>,
+    ) -> (Self, mpsc::UnboundedReceiver<T>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let outbox = Self {
+            tx,
+            client,
+            limits: limits.clone(),
+        };
+        (outbox, rx)
+    }
+
+    /// 发送一条消息，连接已经断开或者超过输出缓冲区限制时返回 false
+    pub fn send(&self, item: T) -> bool {
+        if let Some(client) = &self.client {
+            if client.is_killed() {
+                return false;
+            }
+            if !client.charge_output(item.output_size(), &self.limits) {
+                warn!(
+                    "⚠️ Client {} closed for overcoming of output buffer limits",
+                    client.addr
+                );
+                self.limits.record_output_buffer_disconnection();
+                client.kill();
+                return false;
+            }
+        }
+        self.tx.send(item).is_ok()
+    }
+}
+
+/// 定时断开空闲超过 timeout 的普通客户端
+pub fn start(data: Arc<Data>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CRON_PERIOD);
+        loop {
+            interval.tick().await;
+            close_idle_clients(&data);
+        }
+    });
+}
+
+// 副本、订阅中和 MONITOR 中的连接不会因为空闲被断开，返回断开的连接数
+fn close_idle_clients(data: &Data) -> usize {
+    let timeout = data.limits.timeout();
+    if timeout.is_zero() {
+        return 0;
+    }
+    let mut closed = 0;
+    for client in data.clients.list() {
+        let idle = client.client_type() == ClientType::Normal
+            && !client.is_monitor()
+            && !client.is_killed()
+            && client.idle() >= timeout;
+        if idle {
+            info!("⏰ Closing idle client {}", client.addr);
+            client.kill();
+            closed += 1;
+        }
+    }
+    closed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output_limits() {
+        let args: Vec<String> = "normal 0 0 0 slave 1mb 512kb 10"
+            .split_whitespace()
+            .map(str::to_owned)
+            .collect();
+        let limits = parse_output_limits(&args).unwrap();
+        assert_eq!(limits[1].0, OutputClass::Replica);
+        assert_eq!(
+            limits[1].1,
+            OutputBufferLimit::new(1024 * 1024, 512 * 1024, 10)
+        );
+        assert!(parse_output_limits(&args[..3]).is_none());
+        assert!(
+            parse_output_limits(&["master".to_owned(), "0".into(), "0".into(), "0".into()])
+                .is_none()
+        );
+
+        let config = Limits::new();
+        assert_eq!(
+            config.output_config(),
+            "normal 0 0 0 slave 268435456 67108864 60 pubsub 33554432 8388608 60"
+        );
+    }
+
+    #[test]
+    fn test_outbox_limits() {
+        let data = Data::new();
+        let registered = data
+            .clients
+            .register("127.0.0.1:5000".to_owned(), "127.0.0.1:6379".to_owned());
+        let client = registered.client().clone();
+        data.limits
+            .set_output(OutputClass::Normal, OutputBufferLimit::new(100, 0, 0));
+        let (outbox, mut rx) = Outbox::channel(Some(client.clone()), &data.limits);
+        let line = Resp::BulkStrings(crate::BulkStrings::new("x".repeat(40)));
+        assert!(outbox.send(line.clone()));
+        assert_eq!(client.output_memory(), line.output_size());
+        // 连接写出之后释放
+        let sent = rx.try_recv().unwrap();
+        client.release_output(sent.output_size());
+        assert_eq!(client.output_memory(), 0);
+
+        assert!(outbox.send(line.clone()));
+        assert!(!outbox.send(line));
+        assert!(client.is_killed());
+        assert_eq!(data.limits.output_buffer_disconnections(), 1);
+    }
+
+    #[test]
+    fn test_soft_limit() {
+        let limit = OutputBufferLimit::new(0, 10, 0);
+        let mut since = None;
+        assert!(!limit.exceeded(0, 5, &mut since));
+        assert!(!limit.exceeded(5, 20, &mut since));
+        assert!(since.is_some());
+        assert!(!limit.exceeded(20, 5, &mut since));
+        assert!(since.is_none());
+    }
+
+    #[test]
+    fn test_close_idle_clients() {
+        let data = Data::new();
+        let registered = data
+            .clients
+            .register("127.0.0.1:5000".to_owned(), "127.0.0.1:6379".to_owned());
+        assert_eq!(close_idle_clients(&data), 0);
+        data.limits.set_timeout(1);
+        assert_eq!(close_idle_clients(&data), 0);
+        std::thread::sleep(Duration::from_millis(1100));
+        assert_eq!(close_idle_clients(&data), 1);
+        assert!(registered.client().is_killed());
+    }
+}
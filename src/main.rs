@@ -1,60 +1,466 @@
+use std::net::IpAddr;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
-use futures::SinkExt;
-use simple_redis::Processor;
-use simple_redis::{network::RespFrameCodec, process::CommandGroup};
-use tokio::net::TcpListener;
-use tokio_stream::StreamExt;
-use tokio_util::codec::Framed;
-use tracing::{debug, error, info};
+use simple_redis::config::ServerConfig;
+use simple_redis::Data;
+use socket2::{SockRef, TcpKeepalive};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UnixListener};
+use tokio::task::JoinSet;
+use tokio::time::timeout;
+use tokio_rustls::TlsAcceptor;
+use tracing::{error, info, warn};
+
+// accept 出错之后重试前等待的时间
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+// TLS 握手的超时时间
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// 1. 从TcpStream从读取frame，要为Resp实现 frame decode 和 encode
 /// 2. 从frame中解析出命令和参数
 /// 3. 根据命令和参数调用对应的Processor
 /// 4. Processor返回一个Resp，将Resp encode 结果写入TcpStream
+///
+/// 命令行：simple-redis [redis.conf] [--port 7000 --bind 0.0.0.0 ...]，
+/// 支持的参数见 `simple_redis::config::PARAMS`
+///
+/// 关闭成功时退出状态为 0，启动或监听出错、关闭时出错（例如 SHUTDOWN SAVE FORCE 保存失败）时非零
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     tracing_subscriber::fmt().init();
-    let addr = "127.0.0.1:6379";
-    let listener: TcpListener = TcpListener::bind(addr).await?;
-    info!("🚀 Simple Redis Server listening on: {}", addr);
-    let data_arc = Arc::new(simple_redis::Data::new());
+    let config = ServerConfig::load(std::env::args().skip(1))?;
+    if let Some(file) = &config.file {
+        info!("📄 Configuration loaded from {}", file.display());
+    }
+    // port 0 时不监听明文端口，只接受 TLS 或 unix socket 连接
+    if config.port == 0 && config.tls_port == 0 && config.unixsocket.is_none() {
+        return Err(anyhow::anyhow!(
+            "at least one of port, tls-port or unixsocket must be enabled"
+        ));
+    }
+    let data_arc = Arc::new(simple_redis::Data::with_databases(config.databases));
+    // 监听端口之前先加载 SHUTDOWN SAVE 保存的快照，客户端不会看到加载到一半的数据
+    let path = config.dir.join(&config.dbfilename);
+    if let Some(loaded) = simple_redis::snapshot::load_file(&data_arc, &path)? {
+        info!("💾 DB loaded from {}: {} commands", path.display(), loaded);
+    }
+    let mut listeners = Vec::new();
+    for host in bind_addrs(&config.bind) {
+        if config.port != 0 {
+            let listener = TcpListener::bind((host.as_str(), config.port)).await?;
+            info!(
+                "🚀 Simple Redis Server listening on: {}",
+                listener.local_addr()?
+            );
+            listeners.push((listener, None));
+        }
+        if config.tls_port != 0 {
+            let acceptor = config.tls.acceptor()?;
+            let listener = TcpListener::bind((host.as_str(), config.tls_port)).await?;
+            info!(
+                "🔒 Simple Redis Server listening on: {} (TLS)",
+                listener.local_addr()?
+            );
+            listeners.push((listener, Some(acceptor)));
+        }
+    }
+    let unix_listener = match &config.unixsocket {
+        Some(path) => Some(bind_unix(path, config.unixsocketperm)?),
+        None => None,
+    };
+    if config.tls.replication {
+        data_arc.replication.set_tls(config.tls.client_config()?);
+    }
+    data_arc
+        .replication
+        .set_listening_port(replication_port(&config));
+    if let Some(master) = config.replicaof.clone() {
+        data_arc.replication.replicaof(master);
+    }
+    if config.cluster_enabled {
+        data_arc.cluster.set_enabled(true);
+        data_arc
+            .cluster
+            .set_myself_addr(&announce_host(&config.bind), config.port);
+        info!(
+            "🧩 Cluster mode enabled, node id {}",
+            data_arc.cluster.myself_id()
+        );
+    }
+    if let Some(path) = config.aclfile.clone() {
+        data_arc.acl.set_file(path);
+        data_arc.acl.load().map_err(|e| anyhow::anyhow!(e))?;
+    }
+    // 运行期间可以修改的参数和 CONFIG SET 走同一条路径
+    for update in config.updates.clone() {
+        update.apply(&data_arc);
+    }
+    if let Some(path) = &config.pidfile {
+        if let Err(e) = std::fs::write(path, format!("{}\n", std::process::id())) {
+            warn!("⚠️ Failed to write pidfile {}: {}", path.display(), e);
+        }
+    }
+    data_arc.config.set_server(config);
+    simple_redis::shutdown::listen_signals(data_arc.clone())?;
+    simple_redis::start_active_expire(data_arc.clone());
+    simple_redis::stats::start_sampler(data_arc.clone());
+    simple_redis::tracking::start(data_arc.clone());
+    simple_redis::replication::start(data_arc.clone());
+    simple_redis::cluster::start(data_arc.clone());
+    simple_redis::limits::start(data_arc.clone());
+    let mut loops = JoinSet::new();
+    for (listener, acceptor) in listeners {
+        loops.spawn(accept_loop(listener, acceptor, data_arc.clone()));
+    }
+    if let (Some(listener), Some(path)) = (unix_listener, data_arc.config.server().unixsocket) {
+        loops.spawn(unix_accept_loop(listener, path, data_arc.clone()));
+    }
+    // 任一监听出错时退出，否则等待 SHUTDOWN 或信号
+    tokio::select! {
+        result = async {
+            while let Some(result) = loops.join_next().await {
+                result??;
+            }
+            anyhow::Ok(())
+        } => {
+            result?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        _ = simple_redis::shutdown::wait(&data_arc) => {}
+    };
+    // 停止接受新连接，已经建立的连接由 close 断开
+    loops.abort_all();
+    match simple_redis::shutdown::close(&data_arc).await {
+        Ok(()) => Ok(ExitCode::SUCCESS),
+        Err(e) => {
+            error!("❌ Shutdown failed: {}", e);
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
+
+// 向主节点报告的端口：tls-replication 时，或者 port 0 不监听明文端口时报告 tls-port
+fn replication_port(config: &ServerConfig) -> u16 {
+    if (config.tls.replication && config.tls_port != 0) || config.port == 0 {
+        config.tls_port
+    } else {
+        config.port
+    }
+}
+
+// bind 中的地址，和 redis.conf 一样 `*` 表示所有 IPv4 地址，忽略表示地址可选的 `-` 前缀
+fn bind_addrs(bind: &[String]) -> Vec<String> {
+    bind.iter()
+        .map(|host| match host.trim_start_matches('-') {
+            "*" => "0.0.0.0".to_owned(),
+            "::*" => "::".to_owned(),
+            host => host.to_owned(),
+        })
+        .collect()
+}
+
+// 集群中其他节点连接本节点使用的地址，监听所有地址时使用 127.0.0.1
+fn announce_host(bind: &[String]) -> String {
+    bind_addrs(bind)
+        .into_iter()
+        .find(|host| host.parse::<IpAddr>().is_ok_and(|ip| !ip.is_unspecified()))
+        .unwrap_or_else(|| "127.0.0.1".to_owned())
+}
+
+// 绑定 unix socket，上次没有正常退出时留下的 socket 文件先删除，其他类型的文件不会被删除
+fn bind_unix(path: &Path, perm: Option<u32>) -> Result<UnixListener> {
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(anyhow::anyhow!(
+                "unixsocket {} exists and is not a socket",
+                path.display()
+            ))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+    let listener = UnixListener::bind(path)?;
+    if let Some(perm) = perm {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(perm))?;
+    }
+    info!("🚀 Simple Redis Server listening on: {}", path.display());
+    Ok(listener)
+}
+
+// unix socket 上的连接和 TCP 连接共用同一个 `Data`
+async fn unix_accept_loop(listener: UnixListener, path: PathBuf, data: Arc<Data>) -> Result<()> {
+    let path = path.display().to_string();
     loop {
-        let (socket, addr) = listener.accept().await?;
-        info!("📡 New client connected: {}", addr);
-        let data_clone = data_arc.clone();
-        tokio::spawn(async move {
-            let mut framed = Framed::new(socket, RespFrameCodec);
-            // In a loop, read data from the socket and write the data back.
-            loop {
-                match framed.next().await {
-                    Some(Ok(frame)) => {
-                        info!("📥 Received frame from {}: {:?}", addr, frame);
-                        let command: CommandGroup = CommandGroup::try_from(frame)?;
-                        info!("⚡ Processing command from {}: {:?}", addr, command);
-                        let res_frame = command.process(&data_clone)?;
-                        info!("📤 Response to {}: {:?}", addr, res_frame);
-                        match framed.send(res_frame).await {
-                            Ok(_) => {
-                                debug!("✅ Response sent successfully to {}", addr);
-                            }
-                            Err(e) => {
-                                error!("❌ Failed to send response to {}: {}", addr, e);
-                                return Err(e);
-                            }
-                        };
-                    }
-                    Some(Err(e)) => {
-                        error!("🔥 Frame decode error from {}: {}", addr, e);
-                        return Err(e);
-                    }
-                    None => {
-                        info!("👋 Client {} disconnected", addr);
-                        return Ok(());
-                    }
-                };
+        let socket = match listener.accept().await {
+            Ok((socket, _)) => socket,
+            Err(e) => {
+                accept_failed(e).await;
+                continue;
             }
+        };
+        info!("📡 New client connected: {}", path);
+        let data = data.clone();
+        let path = path.clone();
+        tokio::spawn(async move {
+            let maxclients = data.limits.maxclients();
+            let Some(client) = data.clients.try_register_unix(&path, maxclients) else {
+                return reject(socket, &data).await;
+            };
+            simple_redis::connection::serve(socket, client, data).await
         });
     }
 }
+
+// 接受连接，`acceptor` 不为空时先完成 TLS 握手再交给 `connection::serve`
+async fn accept_loop(
+    listener: TcpListener,
+    acceptor: Option<TlsAcceptor>,
+    data: Arc<Data>,
+) -> Result<()> {
+    loop {
+        let (socket, addr, laddr) = match listener.accept().await.and_then(|(socket, addr)| {
+            let laddr = socket.local_addr()?;
+            Ok((socket, addr, laddr))
+        }) {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                accept_failed(e).await;
+                continue;
+            }
+        };
+        info!("📡 New client connected: {}", addr);
+        set_keepalive(&socket, data.limits.tcp_keepalive());
+        let data_clone = data.clone();
+        match acceptor.clone() {
+            Some(acceptor) => {
+                tokio::spawn(async move {
+                    // 握手没有完成之前不占用 maxclients 名额，但也不能无限期等待
+                    let stream = match timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(socket)).await
+                    {
+                        Ok(Ok(stream)) => stream,
+                        Ok(Err(e)) => {
+                            warn!("🔒 TLS handshake with {} failed: {}", addr, e);
+                            return Ok(());
+                        }
+                        Err(_) => {
+                            warn!("🔒 TLS handshake with {} timed out", addr);
+                            return Ok(());
+                        }
+                    };
+                    let maxclients = data_clone.limits.maxclients();
+                    let Some(client) = data_clone.clients.try_register(
+                        addr.to_string(),
+                        laddr.to_string(),
+                        maxclients,
+                    ) else {
+                        return reject(stream, &data_clone).await;
+                    };
+                    simple_redis::connection::serve(stream, client, data_clone).await
+                });
+            }
+            None => {
+                tokio::spawn(async move {
+                    let maxclients = data_clone.limits.maxclients();
+                    let Some(client) = data_clone.clients.try_register(
+                        addr.to_string(),
+                        laddr.to_string(),
+                        maxclients,
+                    ) else {
+                        return reject(socket, &data_clone).await;
+                    };
+                    simple_redis::connection::serve(socket, client, data_clone).await
+                });
+            }
+        }
+    }
+}
+
+// accept 出错时（例如文件描述符用完）记录日志，稍等之后继续接受连接
+async fn accept_failed(e: std::io::Error) {
+    warn!("⚠️ Failed to accept connection: {}", e);
+    tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+}
+
+// 连接数达到 maxclients 时回复错误并关闭连接
+async fn reject<S>(mut stream: S, data: &Data) -> Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    warn!("⚠️ Rejecting connection, max number of clients reached");
+    data.limits.record_rejected();
+    let _ = stream
+        .write_all(b"-ERR max number of clients reached\r\n")
+        .await;
+    let _ = stream.shutdown().await;
+    Ok(())
+}
+
+// tcp-keepalive：空闲 `interval` 之后开始探测，为 0 时不打开
+fn set_keepalive(socket: &TcpStream, interval: Duration) {
+    if interval.is_zero() {
+        return;
+    }
+    let keepalive = TcpKeepalive::new()
+        .with_time(interval)
+        .with_interval((interval / 3).max(Duration::from_secs(1)));
+    if let Err(e) = SockRef::from(socket).set_tcp_keepalive(&keepalive) {
+        warn!("⚠️ Failed to set TCP keepalive: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+    use simple_redis::network::RespFrameCodec;
+    use simple_redis::{Arrays, BulkStrings, Resp};
+    use tokio::io::AsyncReadExt;
+    use tokio::net::UnixStream;
+    use tokio_util::codec::Framed;
+
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("simple-redis-{}-{}.sock", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_replication_port() {
+        let config = |port, tls_port, replication| {
+            let mut config = ServerConfig {
+                port,
+                tls_port,
+                ..Default::default()
+            };
+            config.tls.replication = replication;
+            config
+        };
+        assert_eq!(replication_port(&config(6379, 0, false)), 6379);
+        assert_eq!(replication_port(&config(6379, 6380, false)), 6379);
+        assert_eq!(replication_port(&config(6379, 6380, true)), 6380);
+        // port 0 时只监听 TLS 端口，不管是否开启 tls-replication
+        assert_eq!(replication_port(&config(0, 6380, false)), 6380);
+        assert_eq!(replication_port(&config(0, 6380, true)), 6380);
+    }
+
+    #[tokio::test]
+    async fn test_bind_unix() {
+        let path = socket_path("bind");
+        // 上次没有正常退出时留下的 socket 文件
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let listener = bind_unix(&path, Some(0o700)).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        drop(listener);
+        std::fs::remove_file(&path).unwrap();
+
+        // 不是 socket 的文件不会被删除
+        std::fs::write(&path, "data").unwrap();
+        assert!(bind_unix(&path, None).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "data");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_serve_over_unix_socket() {
+        let path = socket_path("serve");
+        let listener = bind_unix(&path, None).unwrap();
+        let data = Arc::new(Data::new());
+        tokio::spawn(unix_accept_loop(listener, path.clone(), data.clone()));
+
+        let mut stream = UnixStream::connect(&path).await.unwrap();
+        stream.write_all(b"*1\r\n$4\r\nPING\r\n").await.unwrap();
+        let mut reply = [0; 7];
+        stream.read_exact(&mut reply).await.unwrap();
+        assert_eq!(&reply, b"+PONG\r\n");
+        let client = &data.clients.list()[0];
+        assert!(client.info_line().contains(" flags=U "));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    // 在本地随机端口上启动一个服务器
+    async fn start_server() -> (Arc<Data>, u16) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let data = Arc::new(Data::new());
+        data.replication.set_listening_port(port);
+        simple_redis::replication::start(data.clone());
+        tokio::spawn(accept_loop(listener, None, data.clone()));
+        (data, port)
+    }
+
+    async fn connect(port: u16) -> Framed<TcpStream, RespFrameCodec> {
+        let socket = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        Framed::new(socket, RespFrameCodec::new())
+    }
+
+    async fn request(framed: &mut Framed<TcpStream, RespFrameCodec>, args: &[&str]) -> Resp {
+        let frame = Resp::Arrays(Arrays::new(
+            args.iter()
+                .map(|arg| Resp::BulkStrings(BulkStrings::new(arg.to_string())))
+                .collect(),
+        ));
+        framed.send(frame).await.unwrap();
+        framed.next().await.unwrap().unwrap()
+    }
+
+    fn bulk(val: &str) -> Resp {
+        Resp::BulkStrings(BulkStrings::new(val.to_owned()))
+    }
+
+    async fn get(framed: &mut Framed<TcpStream, RespFrameCodec>, key: &str) -> Option<String> {
+        match request(framed, &["GET", key]).await {
+            Resp::SimpleStrings(value) => Some(value.val),
+            Resp::BulkStrings(value) => Some(value.val),
+            _ => None,
+        }
+    }
+
+    // 等待副本上的键变成 `expected`
+    async fn wait_for(framed: &mut Framed<TcpStream, RespFrameCodec>, key: &str, expected: &str) {
+        for _ in 0..100 {
+            if get(framed, key).await.as_deref() == Some(expected) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("replica never received {key}={expected}");
+    }
+
+    #[tokio::test]
+    async fn test_replication_between_two_servers() {
+        let (_, master_port) = start_server().await;
+        let (_, replica_port) = start_server().await;
+        let mut master = connect(master_port).await;
+        let mut replica = connect(replica_port).await;
+
+        // REPLICAOF 之前已有的数据通过全量同步到达副本
+        request(&mut master, &["SET", "before", "1"]).await;
+        let port = master_port.to_string();
+        request(&mut replica, &["REPLICAOF", "127.0.0.1", &port]).await;
+        wait_for(&mut replica, "before", "1").await;
+
+        // 之后的写命令和事务通过复制流到达副本
+        request(&mut master, &["MULTI"]).await;
+        request(&mut master, &["SET", "a", "1"]).await;
+        request(&mut master, &["INCR", "a"]).await;
+        request(&mut master, &["EXEC"]).await;
+        request(&mut master, &["SET", "after", "2"]).await;
+        wait_for(&mut replica, "after", "2").await;
+        assert_eq!(get(&mut replica, "a").await.as_deref(), Some("2"));
+
+        let Resp::Arrays(role) = request(&mut replica, &["ROLE"]).await else {
+            panic!("ROLE should return an array");
+        };
+        assert_eq!(role.val[0], bulk("slave"));
+        assert_eq!(role.val[3], bulk("connected"));
+        assert!(matches!(
+            request(&mut replica, &["SET", "k", "v"]).await,
+            Resp::SimpleErrors(e) if e.error_msg.starts_with("READONLY")
+        ));
+    }
+}
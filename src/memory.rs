@@ -0,0 +1,602 @@
+//! 内存统计和 maxmemory 淘汰
+//!
+//! 每个键的内存占用在写入时估算（集合类型按抽样的平均元素大小乘以元素数量，
+//! 与 Redis 的 MEMORY USAGE 相同），由 `KeyMap` 累加到库的总量上。
+//! 使用量超过 `maxmemory` 时，在执行命令前按 `maxmemory-policy` 淘汰键：
+//! 和 Redis 一样每次抽样 `maxmemory-samples` 个键，放进候选池后淘汰最合适的一个，
+//! 是近似的 LRU/LFU 而不是精确的。
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering};
+
+use rand::Rng;
+use tracing::info;
+
+use crate::db::Db;
+use crate::{notify, Data, Integers, Resp};
+
+// 每个键除了键名和值之外的固定开销（哈希表节点、索引项等）
+pub const KEY_OVERHEAD: usize = 64;
+// 估算集合类型大小时默认抽样的元素个数
+pub const DEFAULT_SAMPLES: usize = 5;
+// 默认的 maxmemory-samples
+const DEFAULT_MAXMEMORY_SAMPLES: usize = 5;
+// 淘汰候选池的大小，与 Redis 的 EVPOOL_SIZE 一致
+const EVICTION_POOL_SIZE: usize = 16;
+
+/// 值的内存占用估算
+pub trait MemoryUsage {
+    /// `samples` 为抽样的元素个数，0 表示遍历所有元素
+    fn memory_usage(&self, samples: usize) -> usize;
+}
+
+// 按抽样的平均大小估算所有元素的总大小
+fn sampled(len: usize, sizes: impl Iterator<Item = usize>, samples: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    let n = if samples == 0 { len } else { samples.min(len) };
+    let total: usize = sizes.take(n).sum();
+    total / n * len
+}
+
+fn string_size(s: &str) -> usize {
+    size_of::<String>() + s.len()
+}
+
+impl MemoryUsage for String {
+    fn memory_usage(&self, _samples: usize) -> usize {
+        string_size(self)
+    }
+}
+
+impl MemoryUsage for u64 {
+    fn memory_usage(&self, _samples: usize) -> usize {
+        size_of::<u64>()
+    }
+}
+
+impl MemoryUsage for Resp {
+    fn memory_usage(&self, _samples: usize) -> usize {
+        size_of::<Resp>()
+            + match self {
+                Resp::SimpleStrings(s) => s.val.len(),
+                Resp::BulkStrings(s) => s.val.len(),
+                _ => 0,
+            }
+    }
+}
+
+impl MemoryUsage for HashMap<String, String> {
+    fn memory_usage(&self, samples: usize) -> usize {
+        let fields = self
+            .iter()
+            .map(|(field, value)| string_size(field) + string_size(value));
+        size_of::<Self>() + sampled(self.len(), fields, samples)
+    }
+}
+
+impl MemoryUsage for VecDeque<String> {
+    fn memory_usage(&self, samples: usize) -> usize {
+        let items = self.iter().map(|item| string_size(item));
+        size_of::<Self>() + sampled(self.len(), items, samples)
+    }
+}
+
+impl MemoryUsage for HashSet<String> {
+    fn memory_usage(&self, samples: usize) -> usize {
+        let members = self.iter().map(|member| string_size(member));
+        size_of::<Self>() + sampled(self.len(), members, samples)
+    }
+}
+
+impl MemoryUsage for BTreeMap<String, f64> {
+    fn memory_usage(&self, samples: usize) -> usize {
+        let members = self
+            .keys()
+            .map(|member| string_size(member) + size_of::<f64>());
+        size_of::<Self>() + sampled(self.len(), members, samples)
+    }
+}
+
+/// 淘汰策略（maxmemory-policy）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    NoEviction,
+    AllKeysLru,
+    AllKeysLfu,
+    AllKeysRandom,
+    VolatileLru,
+    VolatileLfu,
+    VolatileRandom,
+    VolatileTtl,
+}
+
+const POLICIES: [EvictionPolicy; 8] = [
+    EvictionPolicy::NoEviction,
+    EvictionPolicy::AllKeysLru,
+    EvictionPolicy::AllKeysLfu,
+    EvictionPolicy::AllKeysRandom,
+    EvictionPolicy::VolatileLru,
+    EvictionPolicy::VolatileLfu,
+    EvictionPolicy::VolatileRandom,
+    EvictionPolicy::VolatileTtl,
+];
+
+impl EvictionPolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        POLICIES
+            .into_iter()
+            .find(|policy| policy.as_str().eq_ignore_ascii_case(value))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvictionPolicy::NoEviction => "noeviction",
+            EvictionPolicy::AllKeysLru => "allkeys-lru",
+            EvictionPolicy::AllKeysLfu => "allkeys-lfu",
+            EvictionPolicy::AllKeysRandom => "allkeys-random",
+            EvictionPolicy::VolatileLru => "volatile-lru",
+            EvictionPolicy::VolatileLfu => "volatile-lfu",
+            EvictionPolicy::VolatileRandom => "volatile-random",
+            EvictionPolicy::VolatileTtl => "volatile-ttl",
+        }
+    }
+
+    /// 按访问频率淘汰，OBJECT FREQ 只在这时可用
+    pub fn is_lfu(&self) -> bool {
+        matches!(
+            self,
+            EvictionPolicy::AllKeysLfu | EvictionPolicy::VolatileLfu
+        )
+    }
+
+    // 只在设置了过期时间的键中淘汰
+    fn volatile(&self) -> bool {
+        matches!(
+            self,
+            EvictionPolicy::VolatileLru
+                | EvictionPolicy::VolatileLfu
+                | EvictionPolicy::VolatileRandom
+                | EvictionPolicy::VolatileTtl
+        )
+    }
+}
+
+/// 解析 Redis 风格的内存大小，例如 `100mb`、`1gb`、`512k`
+pub fn parse_memory(value: &str) -> Option<u64> {
+    let value = value.to_lowercase();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value.as_str(), ""),
+    };
+    let multiplier = match unit {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+/// 以 K/M/G 为单位显示字节数，用于 INFO 中的 *_human 字段
+pub fn bytes_to_human(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.2}{}", UNITS[unit])
+}
+
+/// maxmemory 配置和淘汰统计
+#[derive(Debug)]
+pub struct Memory {
+    // 0 表示不限制
+    maxmemory: AtomicU64,
+    policy: AtomicU8,
+    samples: AtomicUsize,
+    evicted_keys: AtomicU64,
+    // 观察到的最大内存占用
+    peak: AtomicUsize,
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self {
+            maxmemory: AtomicU64::new(0),
+            policy: AtomicU8::new(0),
+            samples: AtomicUsize::new(DEFAULT_MAXMEMORY_SAMPLES),
+            evicted_keys: AtomicU64::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn maxmemory(&self) -> u64 {
+        self.maxmemory.load(Ordering::Relaxed)
+    }
+
+    pub fn set_maxmemory(&self, bytes: u64) {
+        self.maxmemory.store(bytes, Ordering::Relaxed);
+    }
+
+    pub fn policy(&self) -> EvictionPolicy {
+        POLICIES[self.policy.load(Ordering::Relaxed) as usize]
+    }
+
+    pub fn set_policy(&self, policy: EvictionPolicy) {
+        let index = POLICIES.iter().position(|p| *p == policy).unwrap_or(0);
+        self.policy.store(index as u8, Ordering::Relaxed);
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples.load(Ordering::Relaxed)
+    }
+
+    pub fn set_samples(&self, samples: usize) {
+        self.samples.store(samples.max(1), Ordering::Relaxed);
+    }
+
+    pub fn evicted_keys(&self) -> u64 {
+        self.evicted_keys.load(Ordering::Relaxed)
+    }
+
+    /// 用当前的内存占用更新峰值，返回更新后的峰值
+    pub fn update_peak(&self, used: usize) -> usize {
+        self.peak.fetch_max(used, Ordering::Relaxed).max(used)
+    }
+
+    /// CONFIG RESETSTAT：清零淘汰计数，峰值从当前用量重新开始
+    pub fn reset_stats(&self, used: usize) {
+        self.evicted_keys.store(0, Ordering::Relaxed);
+        self.peak.store(used, Ordering::Relaxed);
+    }
+}
+
+/// 某一时刻的内存占用明细，用于 INFO memory、MEMORY STATS 和 MEMORY DOCTOR
+#[derive(Debug)]
+pub struct MemoryReport {
+    pub used: usize,
+    pub peak: usize,
+    // 键和值本身占用的内存
+    pub dataset: usize,
+    // 哈希表节点、过期时间表和复制积压缓冲区等额外开销
+    pub overhead: usize,
+    pub backlog: usize,
+    pub keys: usize,
+    // 非空的库：(编号, 主表开销, 过期表开销)
+    pub dbs: Vec<(usize, usize, usize)>,
+}
+
+impl MemoryReport {
+    pub fn new(data: &Data) -> Self {
+        let mut dbs = Vec::new();
+        let mut keys = 0;
+        let mut keyspace = 0;
+        let mut overhead = 0;
+        for index in 0..data.databases() {
+            let db = data.db_at(index);
+            if db.is_empty() {
+                continue;
+            }
+            let main = db.len() * KEY_OVERHEAD;
+            let expires = db.expiry_data.index().used_memory();
+            dbs.push((index, main, expires));
+            keys += db.len();
+            keyspace += db.used_memory();
+            overhead += main + expires;
+        }
+        // 复制积压缓冲区不计入 maxmemory，与 Redis 一致
+        let backlog = data.replication.backlog_memory();
+        let used = keyspace + backlog;
+        Self {
+            used,
+            peak: data.memory.update_peak(used),
+            dataset: keyspace - overhead,
+            overhead: overhead + backlog,
+            backlog,
+            keys,
+            dbs,
+        }
+    }
+
+    /// 数据集占总内存的百分比
+    pub fn dataset_percentage(&self) -> f64 {
+        percentage(self.dataset, self.used)
+    }
+
+    /// 当前占用相对峰值的百分比
+    pub fn peak_percentage(&self) -> f64 {
+        percentage(self.used, self.peak)
+    }
+}
+
+fn percentage(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+/// INFO memory 中的字段
+pub fn info(data: &Data) -> String {
+    let report = MemoryReport::new(data);
+    let maxmemory = data.memory.maxmemory();
+    format!(
+        "used_memory:{}\r\n\
+        used_memory_human:{}\r\n\
+        used_memory_peak:{}\r\n\
+        used_memory_peak_human:{}\r\n\
+        used_memory_peak_perc:{:.2}%\r\n\
+        used_memory_overhead:{}\r\n\
+        used_memory_dataset:{}\r\n\
+        used_memory_dataset_perc:{:.2}%\r\n\
+        mem_replication_backlog:{}\r\n\
+        maxmemory:{maxmemory}\r\n\
+        maxmemory_human:{}\r\n\
+        maxmemory_policy:{}\r\n",
+        report.used,
+        bytes_to_human(report.used as u64),
+        report.peak,
+        bytes_to_human(report.peak as u64),
+        report.peak_percentage(),
+        report.overhead,
+        report.dataset,
+        report.dataset_percentage(),
+        report.backlog,
+        bytes_to_human(maxmemory),
+        data.memory.policy().as_str()
+    )
+}
+
+// 候选池中的键：分数越大越应该被淘汰
+struct Candidate {
+    score: u64,
+    db: usize,
+    key: String,
+}
+
+/// 使用量超过 maxmemory 时淘汰键，返回淘汰后是否已经不超过限制
+///
+/// 副本不主动淘汰，由主节点传播过来的 DEL 删除键。
+pub fn perform_evictions(data: &Data) -> bool {
+    let maxmemory = data.memory.maxmemory();
+    if maxmemory == 0 || data.replication.is_replica() {
+        return true;
+    }
+    if data.used_memory() as u64 <= maxmemory {
+        return true;
+    }
+    data.latency
+        .monitor(crate::latency::EVENT_EVICTION_CYCLE, || {
+            evict_until(data, maxmemory)
+        })
+}
+
+// 淘汰键直到内存占用不超过 maxmemory，无法继续淘汰时返回 false
+fn evict_until(data: &Data, maxmemory: u64) -> bool {
+    let policy = data.memory.policy();
+    let samples = data.memory.samples();
+    let mut pool: Vec<Candidate> = Vec::new();
+    while data.used_memory() as u64 > maxmemory {
+        if policy == EvictionPolicy::NoEviction {
+            return false;
+        }
+        let victim = match policy {
+            EvictionPolicy::AllKeysRandom | EvictionPolicy::VolatileRandom => {
+                random_victim(data, policy.volatile())
+            }
+            _ => pooled_victim(data, policy, samples, &mut pool),
+        };
+        let Some((db, key)) = victim else {
+            return false;
+        };
+        data.with_db(db, || evict_key(data, &key));
+    }
+    true
+}
+
+// 随机选一个非空的库中的随机键
+fn random_victim(data: &Data, volatile: bool) -> Option<(usize, String)> {
+    let databases = data.databases();
+    let start = rand::thread_rng().gen_range(0..databases);
+    (0..databases)
+        .map(|offset| (start + offset) % databases)
+        .find_map(|index| {
+            let db = data.db_at(index);
+            sample_keys(db, volatile, 1).pop().map(|key| (index, key))
+        })
+}
+
+// 从每个库抽样放进候选池，取出分数最高且仍然存在的键
+fn pooled_victim(
+    data: &Data,
+    policy: EvictionPolicy,
+    samples: usize,
+    pool: &mut Vec<Candidate>,
+) -> Option<(usize, String)> {
+    let now = data.current_timestamp_millis();
+    for index in 0..data.databases() {
+        let db = data.db_at(index);
+        for key in sample_keys(db, policy.volatile(), samples) {
+            if pool.iter().any(|c| c.db == index && c.key == key) {
+                continue;
+            }
+            let score = match policy {
+                EvictionPolicy::AllKeysLru | EvictionPolicy::VolatileLru => {
+                    now.saturating_sub(db.access(&key, now).last_access)
+                }
+                EvictionPolicy::AllKeysLfu | EvictionPolicy::VolatileLfu => {
+                    u8::MAX as u64 - db.access(&key, now).frequency(now) as u64
+                }
+                _ => match db.expiry_data.get(&key) {
+                    Some(expire_at) => u64::MAX - *expire_at,
+                    None => continue,
+                },
+            };
+            pool.push(Candidate {
+                score,
+                db: index,
+                key,
+            });
+        }
+    }
+    pool.sort_by_key(|c| std::cmp::Reverse(c.score));
+    pool.truncate(EVICTION_POOL_SIZE);
+
+    while !pool.is_empty() {
+        let candidate = pool.remove(0);
+        let db = data.db_at(candidate.db);
+        let exists = if policy.volatile() {
+            db.expiry_data.contains_key(&candidate.key)
+        } else {
+            db.key_type(&candidate.key).is_some()
+        };
+        if exists {
+            return Some((candidate.db, candidate.key));
+        }
+    }
+    None
+}
+
+fn sample_keys(db: &Db, volatile: bool, count: usize) -> Vec<String> {
+    if volatile {
+        db.expiry_data.index().random_keys(count)
+    } else {
+        db.random_keys(count)
+    }
+}
+
+// 删除当前库中的键，传播 DEL 给副本并发布 evicted 事件
+fn evict_key(data: &Data, key: &str) {
+    let del = crate::replication::command(&["DEL", key]);
+    let result = data.replication.propagate(data, &del, || {
+        data.remove_key(key);
+        Ok(Resp::Integers(Integers::new(1)))
+    });
+    if let Err(e) = result {
+        info!("⚠️ Failed to propagate eviction of '{}': {}", key, e);
+    }
+    data.memory.evicted_keys.fetch_add(1, Ordering::Relaxed);
+    data.notify_keyspace_event(notify::NOTIFY_EVICTED, "evicted", key);
+    info!("🧹 Evicted key '{}'", key);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleStringsData;
+
+    fn set(data: &Data, key: &str, value: &str) {
+        data.db().string_data.insert(
+            key.to_owned(),
+            Resp::SimpleStrings(SimpleStringsData::new(value.to_owned())),
+        );
+    }
+
+    #[test]
+    fn test_parse_memory() {
+        assert_eq!(parse_memory("100"), Some(100));
+        assert_eq!(parse_memory("1kb"), Some(1024));
+        assert_eq!(parse_memory("2MB"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_memory("1g"), Some(1_000_000_000));
+        assert_eq!(parse_memory("1x"), None);
+        assert_eq!(parse_memory("mb"), None);
+        assert_eq!(bytes_to_human(1536), "1.50K");
+    }
+
+    #[test]
+    fn test_used_memory_accounting() {
+        let data = Data::new();
+        assert_eq!(data.used_memory(), 0);
+        set(&data, "k", "value");
+        let used = data.used_memory();
+        assert!(used > 0);
+        set(&data, "k", &"x".repeat(1000));
+        assert!(data.used_memory() > used + 900);
+        data.remove_key("k");
+        assert_eq!(data.used_memory(), 0);
+    }
+
+    #[test]
+    fn test_memory_report() {
+        let data = Data::new();
+        set(&data, "k", "value");
+        data.with_db(3, || set(&data, "other", "value"));
+        let report = MemoryReport::new(&data);
+        assert_eq!(report.keys, 2);
+        assert_eq!(report.used, report.dataset + report.overhead);
+        assert_eq!(report.dbs.len(), 2);
+        assert_eq!(report.dbs[1].0, 3);
+
+        data.remove_key("k");
+        let after = MemoryReport::new(&data);
+        assert!(after.used < report.used);
+        assert_eq!(after.peak, report.used);
+    }
+
+    #[test]
+    fn test_noeviction() {
+        let data = Data::new();
+        set(&data, "k", "value");
+        data.memory.set_maxmemory(1);
+        assert!(!perform_evictions(&data));
+        assert!(data.contains_key("k"));
+    }
+
+    #[test]
+    fn test_allkeys_lru_evicts_idle_keys() {
+        let data = Data::new();
+        for i in 0..20 {
+            set(&data, &format!("k{i:02}"), &"x".repeat(100));
+        }
+        let now = data.current_timestamp_millis();
+        for i in 0..20 {
+            let last_access = if i < 10 { now - 60_000 } else { now };
+            data.db().record_access(&format!("k{i:02}"), last_access);
+        }
+        data.memory.set_policy(EvictionPolicy::AllKeysLru);
+        data.memory.set_maxmemory(data.used_memory() as u64 / 2 + 1);
+        assert!(perform_evictions(&data));
+        assert!(data.used_memory() as u64 <= data.memory.maxmemory());
+        assert_eq!(data.memory.evicted_keys(), 10);
+    }
+
+    #[test]
+    fn test_volatile_ttl_only_evicts_volatile_keys() {
+        let data = Data::new();
+        set(&data, "persistent", "v");
+        set(&data, "soon", "v");
+        set(&data, "later", "v");
+        let now = data.current_timestamp_millis();
+        data.set_expiry("soon", now + 1000);
+        data.set_expiry("later", now + 100_000);
+        data.memory.set_policy(EvictionPolicy::VolatileTtl);
+        data.memory.set_maxmemory(data.used_memory() as u64 - 1);
+        assert!(perform_evictions(&data));
+        assert!(!data.contains_key("soon"));
+        assert!(data.contains_key("later"));
+
+        // 没有可以淘汰的键时返回 false
+        data.memory.set_maxmemory(1);
+        assert!(!perform_evictions(&data));
+        assert!(data.contains_key("persistent"));
+    }
+}
@@ -0,0 +1,233 @@
+//! MONITOR：把所有客户端执行的命令实时推送给监视中的连接
+//!
+//! 每条命令在执行前格式化为 `+<时间戳> [<库> <客户端地址>] "CMD" "arg" ...` 发给所有
+//! 监视中的连接。没有连接在监视时只读一个原子计数，不做任何格式化。
+//! 和 Redis 一样，管理类命令不会被推送，AUTH 等命令中的密码会被隐去（慢查询日志也用同样的规则）。
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::limits::Outbox;
+use crate::process::command_table;
+use crate::{Resp, SimpleStringsData};
+
+const REDACTED: &str = "(redacted)";
+
+#[derive(Debug, Default)]
+pub struct Monitors {
+    // 监视中的连接数，快速判断是否需要推送
+    count: AtomicUsize,
+    senders: Mutex<Vec<Outbox<Resp>>>,
+}
+
+impl Monitors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 连接进入监视模式，`tx` 为推送命令给这个连接的一端
+    pub fn attach(&self, tx: Outbox<Resp>) {
+        let mut senders = self.senders.lock().unwrap_or_else(|e| e.into_inner());
+        senders.push(tx);
+        self.count.store(senders.len(), Ordering::Relaxed);
+    }
+
+    /// 推送一条即将在 `db` 上执行的命令，客户端地址取自当前执行命令的客户端
+    pub fn feed(&self, db: usize, frame: &Resp) {
+        if self.count.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let Resp::Arrays(arr) = frame else {
+            return;
+        };
+        let argv: Vec<&str> = arr
+            .val
+            .iter()
+            .map(|arg| match arg {
+                Resp::BulkStrings(arg) => arg.val.as_str(),
+                Resp::SimpleStrings(arg) => arg.val.as_str(),
+                _ => "",
+            })
+            .collect();
+        let is_admin = argv
+            .first()
+            .and_then(|name| command_table::lookup(name))
+            .is_some_and(|spec| spec.is_admin());
+        if argv.is_empty() || is_admin {
+            return;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let (addr, _) = crate::client::current_addr_and_name();
+        let line = format_line(now.as_secs(), now.subsec_micros(), db, &addr, &argv);
+        let frame = Resp::SimpleStrings(SimpleStringsData::new(line));
+
+        let mut senders = self.senders.lock().unwrap_or_else(|e| e.into_inner());
+        // 发送失败说明监视的连接已经断开或者超过了输出缓冲区限制
+        senders.retain(|tx| tx.send(frame.clone()));
+        self.count.store(senders.len(), Ordering::Relaxed);
+    }
+}
+
+fn format_line(secs: u64, micros: u32, db: usize, addr: &str, argv: &[&str]) -> String {
+    let args: Vec<String> = redact(argv).iter().map(|arg| repr(arg)).collect();
+    format!("{secs}.{micros:06} [{db} {addr}] {}", args.join(" "))
+}
+
+/// 隐去命令中的用户名和密码，MONITOR 和慢查询日志都不能看到它们
+pub fn redact<'a>(argv: &[&'a str]) -> Vec<&'a str> {
+    let mut argv = argv.to_vec();
+    let Some(name) = argv.first().map(|name| name.to_lowercase()) else {
+        return argv;
+    };
+    // AUTH [username] password
+    if name == "auth" {
+        argv[1..].fill(REDACTED);
+        return argv;
+    }
+    let sub = argv
+        .get(1)
+        .map(|sub| sub.to_lowercase())
+        .unwrap_or_default();
+    match (name.as_str(), sub.as_str()) {
+        // ACL SETUSER username [rule ...]，>password、<password、#hash、!hash 都是凭据
+        ("acl", "setuser") => {
+            argv.iter_mut()
+                .skip(3)
+                .filter(|rule| rule.starts_with(['>', '<', '#', '!']))
+                .for_each(|rule| *rule = REDACTED);
+            return argv;
+        }
+        // CONFIG SET parameter value [parameter value ...]
+        ("config", "set") => {
+            for pair in argv[2..].chunks_mut(2) {
+                if let [param, value] = pair {
+                    if param.eq_ignore_ascii_case("requirepass")
+                        || param.eq_ignore_ascii_case("masterauth")
+                    {
+                        *value = REDACTED;
+                    }
+                }
+            }
+            return argv;
+        }
+        _ => {}
+    }
+    let mut i = 1;
+    while i < argv.len() {
+        let secrets = match name.as_str() {
+            // HELLO [protover [AUTH username password] ...]
+            "hello" if argv[i].eq_ignore_ascii_case("auth") => 2,
+            // MIGRATE ... [AUTH password | AUTH2 username password]
+            "migrate" if argv[i].eq_ignore_ascii_case("auth") => 1,
+            "migrate" if argv[i].eq_ignore_ascii_case("auth2") => 2,
+            _ => 0,
+        };
+        let end = (i + 1 + secrets).min(argv.len());
+        argv[i + 1..end].fill(REDACTED);
+        i = end;
+    }
+    argv
+}
+
+// 和 Redis 的 sdscatrepr 一样给参数加上引号，转义特殊字符和不可打印的字节
+fn repr(arg: &str) -> String {
+    let mut out = String::with_capacity(arg.len() + 2);
+    out.push('"');
+    for byte in arg.bytes() {
+        match byte {
+            b'\\' => out.push_str("\\\\"),
+            b'"' => out.push_str("\\\""),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            0x07 => out.push_str("\\a"),
+            0x08 => out.push_str("\\b"),
+            0x20..=0x7e => out.push(byte as char),
+            _ => out.push_str(&format!("\\x{byte:02x}")),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Arrays, BulkStrings};
+
+    fn frame(args: &[&str]) -> Resp {
+        Resp::Arrays(Arrays::new(
+            args.iter()
+                .map(|arg| Resp::BulkStrings(BulkStrings::new(arg.to_string())))
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn test_format_line() {
+        assert_eq!(
+            format_line(
+                1339518083,
+                107412,
+                0,
+                "127.0.0.1:60866",
+                &["SET", "k", "a \"b\"\n"]
+            ),
+            "1339518083.107412 [0 127.0.0.1:60866] \"SET\" \"k\" \"a \\\"b\\\"\\n\""
+        );
+        assert_eq!(repr("é"), "\"\\xc3\\xa9\"");
+    }
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(
+            redact(&["AUTH", "user", "secret"]),
+            vec!["AUTH", REDACTED, REDACTED]
+        );
+        assert_eq!(
+            redact(&["HELLO", "3", "AUTH", "user", "secret", "SETNAME", "app"]),
+            vec!["HELLO", "3", "AUTH", REDACTED, REDACTED, "SETNAME", "app"]
+        );
+        assert_eq!(
+            redact(&["MIGRATE", "h", "1", "", "0", "5", "AUTH", "pw", "KEYS", "a"]),
+            vec!["MIGRATE", "h", "1", "", "0", "5", "AUTH", REDACTED, "KEYS", "a"]
+        );
+        assert_eq!(
+            redact(&["ACL", "SETUSER", "carol", "on", ">s3cret", "~*", "#abc"]),
+            vec!["ACL", "SETUSER", "carol", "on", REDACTED, "~*", REDACTED]
+        );
+        assert_eq!(
+            redact(&["CONFIG", "SET", "maxmemory", "1mb", "requirepass", "pw"]),
+            vec!["CONFIG", "SET", "maxmemory", "1mb", "requirepass", REDACTED]
+        );
+        assert_eq!(redact(&["GET", "auth"]), vec!["GET", "auth"]);
+    }
+
+    #[test]
+    fn test_feed() {
+        let monitors = Monitors::new();
+        // 没有监视的连接时直接返回
+        monitors.feed(0, &frame(&["GET", "k"]));
+
+        let limits = std::sync::Arc::new(crate::limits::Limits::new());
+        let (tx, mut rx) = Outbox::channel(None, &limits);
+        monitors.attach(tx);
+        let client = crate::client::Client::new(1, "127.0.0.1:5000".to_owned(), String::new());
+        crate::client::with_client(&std::sync::Arc::new(client), || {
+            monitors.feed(2, &frame(&["GET", "k"]));
+            monitors.feed(0, &frame(&["CONFIG", "GET", "maxmemory"]));
+        });
+        let Ok(Resp::SimpleStrings(line)) = rx.try_recv() else {
+            panic!("expected a monitor line");
+        };
+        assert!(line.val.ends_with(" [2 127.0.0.1:5000] \"GET\" \"k\""));
+        assert!(rx.try_recv().is_err());
+
+        drop(rx);
+        monitors.feed(0, &frame(&["GET", "k"]));
+        assert_eq!(monitors.count.load(Ordering::Relaxed), 0);
+    }
+}
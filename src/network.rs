@@ -1,15 +1,33 @@
-use crate::{Resp, RespDecoder};
-use crate::{RespEncoder, RespError};
+use std::sync::Arc;
+
+use crate::limits::Limits;
+use crate::{FrameScanner, ProtoLimits, Resp, RespEncoder};
 use anyhow::Result;
 use tokio_util::codec::{Decoder, Encoder};
-use tracing::debug;
+use tracing::{debug, warn};
 
 #[derive(Debug)]
-pub struct RespFrameCodec;
+pub struct RespFrameCodec {
+    // 客户端连接上检查 client-query-buffer-limit 和 proto-max-bulk-len，
+    // 主从和集群之间的连接使用默认的协议限制
+    limits: Option<Arc<Limits>>,
+    // 读缓冲区中还不完整的 frame 扫描到的位置，下次读到数据时继续
+    scanner: FrameScanner,
+}
 
 impl RespFrameCodec {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            limits: None,
+            scanner: FrameScanner::new(),
+        }
+    }
+
+    pub fn with_limits(limits: Arc<Limits>) -> Self {
+        Self {
+            limits: Some(limits),
+            scanner: FrameScanner::new(),
+        }
     }
 }
 
@@ -36,10 +54,47 @@ impl Decoder for RespFrameCodec {
     type Error = anyhow::Error;
 
     fn decode(&mut self, src: &mut bytes::BytesMut) -> Result<Option<Resp>> {
-        match Resp::decode(src) {
-            Ok(frame) => Ok(Some(frame)),
-            Err(RespError::NotComplete) => Ok(None),
-            Err(e) => Err(e.into()),
+        // 和 Redis 一样检查整个读缓冲区，超过 client-query-buffer-limit 时断开连接
+        if let Some(limits) = &self.limits {
+            if src.len() > limits.query_buffer() {
+                warn!(
+                    "⚠️ Closing client that reached max query buffer length ({} bytes)",
+                    src.len()
+                );
+                limits.record_query_buffer_disconnection();
+                return Err(anyhow::anyhow!(
+                    "client reached max query buffer length ({} bytes)",
+                    src.len()
+                ));
+            }
         }
+        let proto = match &self.limits {
+            Some(limits) => limits.proto(),
+            None => ProtoLimits::default(),
+        };
+        // 确认 frame 完整之后才从缓冲区中取走
+        match self.scanner.scan(src, &proto)? {
+            Some(len) => Ok(Some(Resp::parse_frame(&src.split_to(len))?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_query_buffer_limit() {
+        let limits = Arc::new(Limits::new());
+        limits.set_query_buffer(crate::limits::MIN_QUERY_BUFFER_LIMIT);
+        let mut codec = RespFrameCodec::with_limits(limits.clone());
+        let mut src = BytesMut::from(&b"*1\r\n$4\r\nPING\r\n"[..]);
+        assert!(codec.decode(&mut src).unwrap().is_some());
+        let mut src = BytesMut::from(&b"*1\r\n$2000000\r\n"[..]);
+        src.extend_from_slice(&vec![b'a'; 1024 * 1024]);
+        assert!(codec.decode(&mut src).is_err());
+        assert_eq!(limits.query_buffer_disconnections(), 1);
     }
 }
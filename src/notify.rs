@@ -0,0 +1,152 @@
+//! 键空间通知
+//!
+//! 键被修改时，按 `notify-keyspace-events` 配置的类别发布到
+//! `__keyspace@<db>__:<key>`（消息为事件名）和 `__keyevent@<db>__:<event>`
+//! （消息为键名）两个频道。
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// 通知类别，对应配置中的字符
+pub const NOTIFY_KEYSPACE: u32 = 1 << 0; // K
+pub const NOTIFY_KEYEVENT: u32 = 1 << 1; // E
+pub const NOTIFY_GENERIC: u32 = 1 << 2; // g
+pub const NOTIFY_STRING: u32 = 1 << 3; // $
+pub const NOTIFY_LIST: u32 = 1 << 4; // l
+pub const NOTIFY_SET: u32 = 1 << 5; // s
+pub const NOTIFY_HASH: u32 = 1 << 6; // h
+pub const NOTIFY_ZSET: u32 = 1 << 7; // z
+pub const NOTIFY_EXPIRED: u32 = 1 << 8; // x
+pub const NOTIFY_EVICTED: u32 = 1 << 9; // e
+pub const NOTIFY_ALL: u32 = NOTIFY_GENERIC
+    | NOTIFY_STRING
+    | NOTIFY_LIST
+    | NOTIFY_SET
+    | NOTIFY_HASH
+    | NOTIFY_ZSET
+    | NOTIFY_EXPIRED
+    | NOTIFY_EVICTED; // A
+
+#[derive(Debug, Default)]
+pub struct Notifications {
+    flags: AtomicU32,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.flags.load(Ordering::Relaxed)
+    }
+
+    pub fn set_flags(&self, flags: u32) {
+        self.flags.store(flags, Ordering::Relaxed);
+    }
+
+    /// 当前配置的字符串形式，用于 CONFIG GET
+    pub fn config(&self) -> String {
+        flags_to_string(self.flags())
+    }
+
+    /// 是否需要为该类别的事件发布通知
+    pub fn enabled(&self, class: u32) -> bool {
+        let flags = self.flags();
+        flags & class != 0 && flags & (NOTIFY_KEYSPACE | NOTIFY_KEYEVENT) != 0
+    }
+}
+
+/// 解析 `notify-keyspace-events` 配置，出现未知字符时返回 None
+pub fn parse_flags(value: &str) -> Option<u32> {
+    let mut flags = 0;
+    for c in value.chars() {
+        flags |= match c {
+            'A' => NOTIFY_ALL,
+            'g' => NOTIFY_GENERIC,
+            '$' => NOTIFY_STRING,
+            'l' => NOTIFY_LIST,
+            's' => NOTIFY_SET,
+            'h' => NOTIFY_HASH,
+            'z' => NOTIFY_ZSET,
+            'x' => NOTIFY_EXPIRED,
+            'e' => NOTIFY_EVICTED,
+            'K' => NOTIFY_KEYSPACE,
+            'E' => NOTIFY_KEYEVENT,
+            _ => return None,
+        };
+    }
+    Some(flags)
+}
+
+pub fn flags_to_string(flags: u32) -> String {
+    let mut value = String::new();
+    if flags & NOTIFY_ALL == NOTIFY_ALL {
+        value.push('A');
+    } else {
+        for (flag, c) in [
+            (NOTIFY_GENERIC, 'g'),
+            (NOTIFY_STRING, '$'),
+            (NOTIFY_LIST, 'l'),
+            (NOTIFY_SET, 's'),
+            (NOTIFY_HASH, 'h'),
+            (NOTIFY_ZSET, 'z'),
+            (NOTIFY_EXPIRED, 'x'),
+            (NOTIFY_EVICTED, 'e'),
+        ] {
+            if flags & flag != 0 {
+                value.push(c);
+            }
+        }
+    }
+    if flags & NOTIFY_KEYSPACE != 0 {
+        value.push('K');
+    }
+    if flags & NOTIFY_KEYEVENT != 0 {
+        value.push('E');
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+
+    #[test]
+    fn test_parse_flags() {
+        assert_eq!(
+            parse_flags("KEA"),
+            Some(NOTIFY_ALL | NOTIFY_KEYSPACE | NOTIFY_KEYEVENT)
+        );
+        assert_eq!(parse_flags("Ex"), Some(NOTIFY_KEYEVENT | NOTIFY_EXPIRED));
+        assert_eq!(parse_flags(""), Some(0));
+        assert_eq!(parse_flags("Q"), None);
+        assert_eq!(flags_to_string(parse_flags("KEA").unwrap()), "AKE");
+        assert_eq!(flags_to_string(parse_flags("lgK").unwrap()), "glK");
+    }
+
+    #[test]
+    fn test_notify_keyspace_event() {
+        let data = std::sync::Arc::new(Data::new());
+        let (mut sub, mut rx) = crate::pubsub::Subscriber::new(data.clone(), 1);
+        sub.subscribe(
+            crate::pubsub::Kind::Channel,
+            &[
+                "__keyspace@0__:k".to_owned(),
+                "__keyevent@0__:del".to_owned(),
+            ],
+        );
+
+        // 未开启时不发布
+        data.notify_keyspace_event(NOTIFY_GENERIC, "del", "k");
+        assert!(rx.try_recv().is_err());
+
+        data.notifications.set_flags(parse_flags("KEg").unwrap());
+        data.notify_keyspace_event(NOTIFY_GENERIC, "del", "k");
+        assert_eq!(rx.try_recv().unwrap().len(), 3);
+        assert_eq!(rx.try_recv().unwrap().len(), 3);
+        // 类别未开启
+        data.notify_keyspace_event(NOTIFY_LIST, "lpush", "k");
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,28 @@
+use tracing::info;
+
+use crate::{process::Parameter, Data, Processor, Resp, SimpleErrors, SimpleStringsData};
+
+#[derive(Debug)]
+pub struct AskingCommandPara {
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl AskingCommandPara {
+    pub fn new(para: Parameter) -> Self {
+        Self { para }
+    }
+}
+
+impl Processor for AskingCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        // ASKING 标志由连接自己维护，这里只负责回复
+        if !data.cluster.is_enabled() {
+            return Ok(Resp::SimpleErrors(SimpleErrors::new(
+                "ERR This instance has cluster support disabled".to_owned(),
+            )));
+        }
+        info!("🔀 ASKING");
+        Ok(Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned())))
+    }
+}
@@ -0,0 +1,156 @@
+use tracing::info;
+
+use crate::cluster::crc16::{key_hash_slot, CLUSTER_SLOTS};
+use crate::cluster::{count_keys_in_slot, keys_in_slot};
+use crate::{
+    process::Parameter, Arrays, BulkStrings, Data, Integers, Processor, Resp, SimpleErrors,
+    SimpleStringsData,
+};
+
+#[derive(Debug)]
+pub struct ClusterCommandPara {
+    pub subcommand: String,
+    pub args: Vec<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl ClusterCommandPara {
+    pub fn new(subcommand: String, args: Vec<String>, para: Parameter) -> Self {
+        Self {
+            subcommand,
+            args,
+            para,
+        }
+    }
+
+    fn parse_slot(&self, arg: &str) -> Result<u16, Resp> {
+        match arg.parse::<u16>() {
+            Ok(slot) if slot < CLUSTER_SLOTS => Ok(slot),
+            _ => Err(error("ERR Invalid or out of range slot")),
+        }
+    }
+
+    // ADDSLOTS/DELSLOTS 的参数是 slot 列表，*RANGE 版本是成对的区间
+    fn parse_slots(&self, range: bool) -> Result<Vec<u16>, Resp> {
+        if self.args.is_empty() || (range && !self.args.len().is_multiple_of(2)) {
+            return Err(wrong_args(&self.subcommand));
+        }
+        let mut slots = Vec::new();
+        if range {
+            for pair in self.args.chunks(2) {
+                let (start, end) = (self.parse_slot(&pair[0])?, self.parse_slot(&pair[1])?);
+                if start > end {
+                    return Err(error(&format!(
+                        "ERR start slot number {start} is greater than end slot number {end}"
+                    )));
+                }
+                slots.extend(start..=end);
+            }
+        } else {
+            for arg in &self.args {
+                slots.push(self.parse_slot(arg)?);
+            }
+        }
+        Ok(slots)
+    }
+
+    fn dispatch(&self, data: &Data) -> Result<Resp, Resp> {
+        let cluster = &data.cluster;
+        match self.subcommand.to_lowercase().as_str() {
+            "info" => Ok(bulk(cluster.info_text())),
+            "nodes" => Ok(bulk(cluster.nodes_text())),
+            "slots" => Ok(cluster.slots_reply()),
+            "shards" => Ok(cluster.shards_reply()),
+            "myid" => Ok(bulk(cluster.myself_id())),
+            "keyslot" => match self.args.as_slice() {
+                [key] => Ok(Resp::Integers(Integers::new(key_hash_slot(key) as i64))),
+                _ => Err(wrong_args(&self.subcommand)),
+            },
+            "countkeysinslot" => match self.args.as_slice() {
+                [slot] => {
+                    let slot = self.parse_slot(slot)?;
+                    Ok(Resp::Integers(Integers::new(
+                        count_keys_in_slot(data, slot) as i64,
+                    )))
+                }
+                _ => Err(wrong_args(&self.subcommand)),
+            },
+            "getkeysinslot" => match self.args.as_slice() {
+                [slot, count] => {
+                    let slot = self.parse_slot(slot)?;
+                    let count = count
+                        .parse::<usize>()
+                        .map_err(|_| error("ERR Invalid number of keys"))?;
+                    let keys = keys_in_slot(data, slot, count);
+                    Ok(Resp::Arrays(Arrays::new(
+                        keys.into_iter().map(bulk).collect(),
+                    )))
+                }
+                _ => Err(wrong_args(&self.subcommand)),
+            },
+            "addslots" | "addslotsrange" => {
+                let slots = self.parse_slots(self.subcommand.len() > "addslots".len())?;
+                cluster.add_slots(&slots).map_err(|e| error(&e))?;
+                Ok(ok())
+            }
+            "delslots" | "delslotsrange" => {
+                let slots = self.parse_slots(self.subcommand.len() > "delslots".len())?;
+                cluster.del_slots(&slots).map_err(|e| error(&e))?;
+                Ok(ok())
+            }
+            "setslot" => match self.args.as_slice() {
+                [slot, action, rest @ ..] if rest.len() <= 1 => {
+                    let slot = self.parse_slot(slot)?;
+                    cluster
+                        .set_slot(data, slot, action, rest.first().map(String::as_str))
+                        .map_err(|e| error(&e))?;
+                    Ok(ok())
+                }
+                _ => Err(wrong_args(&self.subcommand)),
+            },
+            "meet" => match self.args.as_slice() {
+                [host, port, ..] => {
+                    let port = port
+                        .parse::<u16>()
+                        .map_err(|_| error(&format!("ERR Invalid base port specified: {port}")))?;
+                    cluster.meet(host, port);
+                    Ok(ok())
+                }
+                _ => Err(wrong_args(&self.subcommand)),
+            },
+            other => Err(error(&format!(
+                "ERR unknown subcommand '{other}'. Try CLUSTER HELP."
+            ))),
+        }
+    }
+}
+
+impl Processor for ClusterCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("ClusterCommandPara process start: {:?}", &self);
+        if !data.cluster.is_enabled() {
+            return Ok(error("ERR This instance has cluster support disabled"));
+        }
+        Ok(self.dispatch(data).unwrap_or_else(|e| e))
+    }
+}
+
+fn ok() -> Resp {
+    Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned()))
+}
+
+fn bulk(val: String) -> Resp {
+    Resp::BulkStrings(BulkStrings::new(val))
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
+
+fn wrong_args(subcommand: &str) -> Resp {
+    error(&format!(
+        "ERR wrong number of arguments for 'cluster|{}' command",
+        subcommand.to_lowercase()
+    ))
+}
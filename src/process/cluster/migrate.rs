@@ -0,0 +1,45 @@
+use tracing::{info, warn};
+
+use crate::cluster::migrate::{migrate, MigrateOptions};
+use crate::{process::Parameter, Data, Processor, Resp, SimpleErrors, SimpleStringsData};
+
+#[derive(Debug)]
+pub struct MigrateCommandPara {
+    pub options: MigrateOptions,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl MigrateCommandPara {
+    pub fn new(options: MigrateOptions, para: Parameter) -> Self {
+        Self { options, para }
+    }
+}
+
+impl Processor for MigrateCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("MigrateCommandPara process start: {:?}", &self);
+        match migrate(data, &self.options) {
+            Ok(0) => Ok(Resp::SimpleStrings(SimpleStringsData::new(
+                "NOKEY".to_owned(),
+            ))),
+            Ok(count) => {
+                info!(
+                    "🚚 Migrated {} keys to {}:{}",
+                    count, self.options.host, self.options.port
+                );
+                Ok(Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned())))
+            }
+            Err(e) => {
+                warn!("MIGRATE failed: {}", e);
+                let msg = e.to_string();
+                let msg = if msg.starts_with("IOERR") || msg.starts_with("ERR") {
+                    msg
+                } else {
+                    format!("ERR {msg}")
+                };
+                Ok(Resp::SimpleErrors(SimpleErrors::new(msg)))
+            }
+        }
+    }
+}
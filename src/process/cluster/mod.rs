@@ -0,0 +1,10 @@
+pub mod asking;
+pub mod cluster_cmd;
+pub mod migrate;
+
+#[derive(Debug)]
+pub enum ClusterCommand {
+    Cluster(cluster_cmd::ClusterCommandPara),
+    Asking(asking::AskingCommandPara),
+    Migrate(migrate::MigrateCommandPara),
+}
@@ -0,0 +1,345 @@
+use crate::Resp;
+
+// 命令标志位
+pub const CMD_WRITE: u32 = 1 << 0;
+pub const CMD_READONLY: u32 = 1 << 1;
+pub const CMD_ADMIN: u32 = 1 << 2;
+pub const CMD_FAST: u32 = 1 << 3;
+// 执行时不更新键的访问时间
+pub const CMD_NO_TOUCH: u32 = 1 << 4;
+// 可能增加内存占用，超过 maxmemory 时拒绝执行
+pub const CMD_DENYOOM: u32 = 1 << 5;
+// 未认证的连接也可以执行，并且不受 ACL 限制
+pub const CMD_NO_AUTH: u32 = 1 << 6;
+// 由连接自己处理（例如事务控制命令），不会解析成 CommandGroup
+pub const CMD_CONNECTION: u32 = 1 << 7;
+
+/// 命令元信息，参考 Redis 的 command table
+#[derive(Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    // 参数个数（包含命令名），负数表示至少 -arity 个
+    pub arity: i32,
+    pub flags: u32,
+    // 第一个键、最后一个键（负数表示从末尾倒数）的位置以及步长，没有键时都为 0
+    pub first_key: i32,
+    pub last_key: i32,
+    pub key_step: i32,
+}
+
+impl CommandSpec {
+    const fn new(
+        name: &'static str,
+        arity: i32,
+        flags: u32,
+        first_key: i32,
+        last_key: i32,
+        key_step: i32,
+    ) -> Self {
+        Self {
+            name,
+            arity,
+            flags,
+            first_key,
+            last_key,
+            key_step,
+        }
+    }
+
+    pub fn is_write(&self) -> bool {
+        self.flags & CMD_WRITE != 0
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.flags & CMD_ADMIN != 0
+    }
+
+    pub fn is_fast(&self) -> bool {
+        self.flags & CMD_FAST != 0
+    }
+
+    pub fn touches_keys(&self) -> bool {
+        self.flags & CMD_NO_TOUCH == 0
+    }
+
+    pub fn is_denyoom(&self) -> bool {
+        self.flags & CMD_DENYOOM != 0
+    }
+
+    pub fn is_readonly(&self) -> bool {
+        self.flags & CMD_READONLY != 0
+    }
+
+    pub fn no_auth(&self) -> bool {
+        self.flags & CMD_NO_AUTH != 0
+    }
+
+    pub fn is_connection_level(&self) -> bool {
+        self.flags & CMD_CONNECTION != 0
+    }
+}
+
+pub static COMMAND_TABLE: &[CommandSpec] = &[
+    // String commands
+    CommandSpec::new("set", -3, CMD_WRITE | CMD_DENYOOM, 1, 1, 1),
+    CommandSpec::new("get", 2, CMD_READONLY | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("del", -2, CMD_WRITE, 1, -1, 1),
+    CommandSpec::new("exists", -2, CMD_READONLY | CMD_FAST, 1, -1, 1),
+    CommandSpec::new("incr", 2, CMD_WRITE | CMD_DENYOOM | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("decr", 2, CMD_WRITE | CMD_DENYOOM | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("type", 2, CMD_READONLY | CMD_FAST | CMD_NO_TOUCH, 1, 1, 1),
+    CommandSpec::new("keys", 2, CMD_READONLY, 0, 0, 0),
+    CommandSpec::new("expire", 3, CMD_WRITE | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("pexpireat", 3, CMD_WRITE | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("ttl", 2, CMD_READONLY | CMD_FAST | CMD_NO_TOUCH, 1, 1, 1),
+    CommandSpec::new("persist", 2, CMD_WRITE | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("scan", -2, CMD_READONLY, 0, 0, 0),
+    CommandSpec::new("dump", 2, CMD_READONLY, 1, 1, 1),
+    CommandSpec::new("restore", -4, CMD_WRITE | CMD_DENYOOM, 1, 1, 1),
+    CommandSpec::new("move", 3, CMD_WRITE | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("rename", 3, CMD_WRITE, 1, 2, 1),
+    CommandSpec::new("renamenx", 3, CMD_WRITE | CMD_FAST, 1, 2, 1),
+    CommandSpec::new("copy", -3, CMD_WRITE | CMD_DENYOOM, 1, 2, 1),
+    CommandSpec::new("randomkey", 1, CMD_READONLY, 0, 0, 0),
+    CommandSpec::new("touch", -2, CMD_READONLY | CMD_FAST, 1, -1, 1),
+    CommandSpec::new("unlink", -2, CMD_WRITE | CMD_FAST, 1, -1, 1),
+    CommandSpec::new("object", -2, CMD_READONLY | CMD_NO_TOUCH, 2, 2, 1),
+    CommandSpec::new("strlen", 2, CMD_READONLY | CMD_FAST, 1, 1, 1),
+    // Hash commands
+    CommandSpec::new("hset", -4, CMD_WRITE | CMD_DENYOOM | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("hget", 3, CMD_READONLY | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("hdel", -3, CMD_WRITE | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("hgetall", 2, CMD_READONLY, 1, 1, 1),
+    CommandSpec::new("hkeys", 2, CMD_READONLY, 1, 1, 1),
+    CommandSpec::new("hvals", 2, CMD_READONLY, 1, 1, 1),
+    CommandSpec::new("hlen", 2, CMD_READONLY | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("hscan", -3, CMD_READONLY, 1, 1, 1),
+    // List commands
+    CommandSpec::new("lpush", -3, CMD_WRITE | CMD_DENYOOM | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("rpush", -3, CMD_WRITE | CMD_DENYOOM | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("lpop", -2, CMD_WRITE | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("rpop", -2, CMD_WRITE | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("llen", 2, CMD_READONLY | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("lrange", 4, CMD_READONLY, 1, 1, 1),
+    CommandSpec::new("lrem", 4, CMD_WRITE, 1, 1, 1),
+    // Set commands
+    CommandSpec::new("sadd", -3, CMD_WRITE | CMD_DENYOOM | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("scard", 2, CMD_READONLY | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("smembers", 2, CMD_READONLY, 1, 1, 1),
+    CommandSpec::new("srem", -3, CMD_WRITE | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("sismember", 3, CMD_READONLY | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("sscan", -3, CMD_READONLY, 1, 1, 1),
+    // Sorted Set commands
+    CommandSpec::new("zadd", -4, CMD_WRITE | CMD_DENYOOM | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("zcard", 2, CMD_READONLY | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("zscore", 3, CMD_READONLY | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("zrem", -3, CMD_WRITE | CMD_FAST, 1, 1, 1),
+    CommandSpec::new("zscan", -3, CMD_READONLY, 1, 1, 1),
+    // Management commands
+    CommandSpec::new("ping", -1, CMD_FAST, 0, 0, 0),
+    CommandSpec::new("client", -2, CMD_ADMIN, 0, 0, 0),
+    CommandSpec::new("info", -1, 0, 0, 0, 0),
+    CommandSpec::new("select", 2, CMD_FAST, 0, 0, 0),
+    CommandSpec::new("command", -1, 0, 0, 0, 0),
+    CommandSpec::new("config", -2, CMD_ADMIN, 0, 0, 0),
+    CommandSpec::new("dbsize", 1, CMD_READONLY | CMD_FAST, 0, 0, 0),
+    CommandSpec::new("flushdb", -1, CMD_WRITE, 0, 0, 0),
+    CommandSpec::new("flushall", -1, CMD_WRITE, 0, 0, 0),
+    CommandSpec::new("swapdb", 3, CMD_WRITE | CMD_FAST, 0, 0, 0),
+    CommandSpec::new("memory", -2, CMD_READONLY | CMD_NO_TOUCH, 2, 2, 1),
+    CommandSpec::new("slowlog", -2, CMD_ADMIN, 0, 0, 0),
+    CommandSpec::new("latency", -2, CMD_ADMIN, 0, 0, 0),
+    CommandSpec::new("monitor", 1, CMD_ADMIN, 0, 0, 0),
+    CommandSpec::new("shutdown", -1, CMD_ADMIN, 0, 0, 0),
+    // Replication commands
+    CommandSpec::new("replicaof", 3, CMD_ADMIN, 0, 0, 0),
+    CommandSpec::new("slaveof", 3, CMD_ADMIN, 0, 0, 0),
+    CommandSpec::new("replconf", -1, CMD_ADMIN, 0, 0, 0),
+    CommandSpec::new("psync", -3, CMD_ADMIN, 0, 0, 0),
+    CommandSpec::new("role", 1, CMD_FAST, 0, 0, 0),
+    // Cluster commands
+    CommandSpec::new("cluster", -2, 0, 0, 0, 0),
+    CommandSpec::new("asking", 1, CMD_FAST, 0, 0, 0),
+    // MIGRATE 的键位置不固定，由 command_keys 特殊处理
+    CommandSpec::new("migrate", -6, CMD_WRITE, 3, 3, 1),
+    // Transaction commands
+    CommandSpec::new("multi", 1, CMD_FAST | CMD_CONNECTION, 0, 0, 0),
+    CommandSpec::new("exec", 1, CMD_CONNECTION, 0, 0, 0),
+    CommandSpec::new("discard", 1, CMD_FAST | CMD_CONNECTION, 0, 0, 0),
+    CommandSpec::new("watch", -2, CMD_FAST | CMD_CONNECTION, 1, -1, 1),
+    CommandSpec::new("unwatch", 1, CMD_FAST | CMD_CONNECTION, 0, 0, 0),
+    // Pub/Sub commands
+    CommandSpec::new("subscribe", -2, 0, 0, 0, 0),
+    CommandSpec::new("unsubscribe", -1, 0, 0, 0, 0),
+    CommandSpec::new("psubscribe", -2, 0, 0, 0, 0),
+    CommandSpec::new("punsubscribe", -1, 0, 0, 0, 0),
+    CommandSpec::new("ssubscribe", -2, 0, 1, -1, 1),
+    CommandSpec::new("sunsubscribe", -1, 0, 1, -1, 1),
+    CommandSpec::new("publish", 3, CMD_FAST, 0, 0, 0),
+    CommandSpec::new("spublish", 3, CMD_FAST, 1, 1, 1),
+    CommandSpec::new("pubsub", -2, 0, 0, 0, 0),
+    // Connection commands
+    CommandSpec::new("hello", -1, CMD_FAST | CMD_NO_AUTH, 0, 0, 0),
+    CommandSpec::new("auth", -2, CMD_FAST | CMD_NO_AUTH, 0, 0, 0),
+    CommandSpec::new("acl", -2, CMD_ADMIN, 0, 0, 0),
+];
+
+/// 按命令名查找（大小写不敏感）
+pub fn lookup(name: &str) -> Option<&'static CommandSpec> {
+    COMMAND_TABLE
+        .iter()
+        .find(|spec| spec.name.eq_ignore_ascii_case(name))
+}
+
+/// 从客户端发来的 RESP 数组中取出命令名
+pub fn command_name(frame: &Resp) -> Option<&str> {
+    match frame {
+        Resp::Arrays(arr) => match arr.val.first() {
+            Some(Resp::BulkStrings(name)) => Some(name.val.as_str()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// 检查命令是否存在以及参数个数是否正确，出错时返回错误信息
+pub fn check_arity(frame: &Resp) -> Result<&'static CommandSpec, String> {
+    let name = command_name(frame).unwrap_or_default();
+    let spec = lookup(name).ok_or_else(|| format!("ERR unknown command '{name}'"))?;
+    let argc = match frame {
+        Resp::Arrays(arr) => arr.val.len() as i32,
+        _ => 0,
+    };
+    if (spec.arity > 0 && argc != spec.arity) || argc < -spec.arity {
+        return Err(format!(
+            "ERR wrong number of arguments for '{}' command",
+            spec.name
+        ));
+    }
+    Ok(spec)
+}
+
+/// 取出命令的所有参数（包含命令名）
+pub fn command_argv(frame: &Resp) -> Vec<&str> {
+    let Resp::Arrays(arr) = frame else {
+        return Vec::new();
+    };
+    arr.val
+        .iter()
+        .map(|arg| match arg {
+            Resp::BulkStrings(arg) => arg.val.as_str(),
+            _ => "",
+        })
+        .collect()
+}
+
+/// 取出命令中的所有键
+pub fn command_keys(frame: &Resp) -> Vec<&str> {
+    argv_keys(&command_argv(frame))
+}
+
+/// 按 command table 中的键位置从命令参数中取出所有键
+pub fn argv_keys<'a>(argv: &[&'a str]) -> Vec<&'a str> {
+    let Some(spec) = argv.first().copied().and_then(lookup) else {
+        return Vec::new();
+    };
+    if spec.first_key == 0 || argv.len() <= spec.first_key as usize {
+        return Vec::new();
+    }
+
+    // MIGRATE host port key|"" db timeout [...] [KEYS key ...]
+    if spec.name == "migrate" {
+        if !argv[3].is_empty() {
+            return vec![argv[3]];
+        }
+        return match argv.iter().position(|arg| arg.eq_ignore_ascii_case("keys")) {
+            Some(pos) if pos > 5 => argv[pos + 1..].to_vec(),
+            _ => Vec::new(),
+        };
+    }
+
+    let last = if spec.last_key < 0 {
+        argv.len() as i32 + spec.last_key
+    } else {
+        spec.last_key.min(argv.len() as i32 - 1)
+    };
+    (spec.first_key..=last)
+        .step_by(spec.key_step.max(1) as usize)
+        .map(|pos| argv[pos as usize])
+        .collect()
+}
+
+/// 判断客户端发来的命令是否为写命令
+pub fn is_write_command(frame: &Resp) -> bool {
+    command_name(frame)
+        .and_then(lookup)
+        .is_some_and(CommandSpec::is_write)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Arrays, BulkStrings};
+
+    fn frame(args: &[&str]) -> Resp {
+        Resp::Arrays(Arrays::new(
+            args.iter()
+                .map(|arg| Resp::BulkStrings(BulkStrings::new(arg.to_string())))
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn test_lookup_ignores_case() {
+        assert_eq!(lookup("HSET").map(|spec| spec.name), Some("hset"));
+        assert!(lookup("nosuchcommand").is_none());
+    }
+
+    #[test]
+    fn test_is_write_command() {
+        assert!(is_write_command(&frame(&["SET", "k", "v"])));
+        assert!(is_write_command(&frame(&["lpush", "l", "a"])));
+        assert!(!is_write_command(&frame(&["GET", "k"])));
+        assert!(!is_write_command(&frame(&["ping"])));
+        assert!(!is_write_command(&frame(&["unknown"])));
+    }
+
+    #[test]
+    fn test_check_arity() {
+        assert!(check_arity(&frame(&["SET", "k", "v"])).is_ok());
+        assert!(check_arity(&frame(&["ping"])).is_ok());
+        assert_eq!(
+            check_arity(&frame(&["GET"])).unwrap_err(),
+            "ERR wrong number of arguments for 'get' command"
+        );
+        assert_eq!(
+            check_arity(&frame(&["nosuch", "k"])).unwrap_err(),
+            "ERR unknown command 'nosuch'"
+        );
+    }
+
+    #[test]
+    fn test_command_keys() {
+        assert_eq!(command_keys(&frame(&["GET", "k"])), vec!["k"]);
+        assert_eq!(
+            command_keys(&frame(&["DEL", "a", "b", "c"])),
+            vec!["a", "b", "c"]
+        );
+        assert_eq!(command_keys(&frame(&["HSET", "h", "f", "v"])), vec!["h"]);
+        assert!(command_keys(&frame(&["PING"])).is_empty());
+        assert_eq!(
+            command_keys(&frame(&[
+                "MIGRATE",
+                "127.0.0.1",
+                "7001",
+                "",
+                "0",
+                "1000",
+                "KEYS",
+                "a",
+                "b"
+            ])),
+            vec!["a", "b"]
+        );
+    }
+}
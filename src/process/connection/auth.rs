@@ -0,0 +1,57 @@
+use tracing::info;
+
+use crate::acl::{self, Reason};
+use crate::client::{self, Client};
+use crate::{process::Parameter, Data, Processor, Resp, SimpleErrors, SimpleStringsData};
+
+#[derive(Debug)]
+pub struct AuthCommandPara {
+    // AUTH password 时为 None，使用 default 用户
+    pub username: Option<String>,
+    pub password: String,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl AuthCommandPara {
+    pub fn new(username: Option<String>, password: String, para: Parameter) -> Self {
+        Self {
+            username,
+            password,
+            para,
+        }
+    }
+}
+
+/// 以 `username` 的身份认证连接，AUTH 和 HELLO AUTH 共用，失败时记录 ACL LOG
+pub fn authenticate(data: &Data, client: &Client, username: &str, password: &str) -> Resp {
+    if data.acl.authenticate(username, password) {
+        client.authenticate(username);
+        info!("🔐 Client {} authenticated as {}", client.addr, username);
+        return Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned()));
+    }
+    data.acl.log(Reason::Auth, "toplevel", "AUTH", username);
+    error("WRONGPASS invalid username-password pair or user is disabled.")
+}
+
+impl Processor for AuthCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        let Some(client) = client::current() else {
+            return Ok(error("ERR AUTH must be called from a connection"));
+        };
+        // 和 Redis 一样，没有给 default 用户设置密码时 AUTH <password> 是配置错误
+        let default_nopass = data
+            .acl
+            .user(acl::DEFAULT_USER)
+            .is_some_and(|user| user.nopass());
+        if self.username.is_none() && default_nopass {
+            return Ok(error("ERR AUTH <password> called without any password configured for the default user. Are you sure your configuration is correct?"));
+        }
+        let username = self.username.as_deref().unwrap_or(acl::DEFAULT_USER);
+        Ok(authenticate(data, &client, username, &self.password))
+    }
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
@@ -0,0 +1,563 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::info;
+
+use crate::client::{self, Client, ClientType, PauseMode, ReplyMode};
+use crate::tracking::TrackingOptions;
+use crate::{
+    process::Parameter, Arrays, BulkStrings, Data, Integers, Nulls, Processor, Resp, SimpleErrors,
+    SimpleStringsData,
+};
+
+const HELP: &[&str] = &[
+    "CLIENT <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+    "GETNAME",
+    "    Return the name of the current connection.",
+    "ID",
+    "    Return the ID of the current connection.",
+    "INFO",
+    "    Return information about the current client connection.",
+    "KILL <ip:port>",
+    "    Kill connection made from <ip:port>.",
+    "KILL <option> <value> [<option> <value> [...]]",
+    "    Kill connections. Options are:",
+    "    * ADDR (<ip:port>|<unixsocket>:0)",
+    "      Kill connections made from the specified address",
+    "    * LADDR (<ip:port>|<unixsocket>:0)",
+    "      Kill connections made to specified local address",
+    "    * TYPE (NORMAL|MASTER|REPLICA|PUBSUB)",
+    "      Kill connections by type.",
+    "    * USER <username>",
+    "      Kill connections authenticated by <username>.",
+    "    * SKIPME (YES|NO)",
+    "      Skip killing current connection (default: yes).",
+    "    * ID <client-id>",
+    "      Kill connections by client id.",
+    "    * MAXAGE <maxage>",
+    "      Kill connections older than the specified age.",
+    "LIST [options ...]",
+    "    Return information about client connections. Options:",
+    "    * TYPE (NORMAL|MASTER|REPLICA|PUBSUB)",
+    "      Return clients of specified type.",
+    "    * ID <client-id> [<client-id> ...]",
+    "      Return clients of specified IDs only.",
+    "PAUSE <timeout> [WRITE|ALL]",
+    "    Suspend all, or just write, clients for <timeout> milliseconds.",
+    "UNPAUSE",
+    "    Stop the current client pause, resuming traffic.",
+    "SETNAME <name>",
+    "    Assign the name <name> to the current connection.",
+    "SETINFO <option> <value>",
+    "    Set client meta attr. Options are:",
+    "    * LIB-NAME: the client lib name.",
+    "    * LIB-VER: the client lib version.",
+    "NO-EVICT (ON|OFF)",
+    "    Protect current client connection from eviction.",
+    "REPLY (ON|OFF|SKIP)",
+    "    Control the replies sent to the current connection.",
+    "TRACKING (ON|OFF) [REDIRECT <id>] [BCAST] [PREFIX <prefix> [...]]",
+    "         [OPTIN] [OPTOUT] [NOLOOP]",
+    "    Control server assisted client side caching.",
+    "CACHING (YES|NO)",
+    "    Enable/disable tracking of the keys for next command in OPTIN/OPTOUT modes.",
+    "GETREDIR",
+    "    Return the client ID we are redirecting to when tracking is enabled.",
+    "TRACKINGINFO",
+    "    Report tracking status for the current connection.",
+    "HELP",
+    "    Print this help.",
+];
+
+#[derive(Debug)]
+pub struct ClientCommandPara {
+    pub subcommand: String,
+    pub args: Vec<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl ClientCommandPara {
+    pub fn new(subcommand: String, args: Vec<String>, para: Parameter) -> Self {
+        Self {
+            subcommand,
+            args,
+            para,
+        }
+    }
+
+    // CLIENT LIST [TYPE type] [ID id [id ...]]
+    fn list(&self, data: &Data) -> Resp {
+        let mut client_type = None;
+        let mut ids = None;
+        let mut i = 0;
+        while i < self.args.len() {
+            match self.args[i].to_lowercase().as_str() {
+                "type" if i + 1 < self.args.len() => {
+                    let Some(parsed) = ClientType::parse(&self.args[i + 1]) else {
+                        return error(&format!("ERR Unknown client type '{}'", self.args[i + 1]));
+                    };
+                    client_type = Some(parsed);
+                    i += 2;
+                }
+                "id" if i + 1 < self.args.len() => {
+                    let mut parsed = Vec::new();
+                    for id in &self.args[i + 1..] {
+                        match id.parse::<u64>() {
+                            Ok(id) if id > 0 => parsed.push(id),
+                            _ => return error("ERR Invalid client ID"),
+                        }
+                    }
+                    ids = Some(parsed);
+                    i = self.args.len();
+                }
+                _ => return error("ERR syntax error"),
+            }
+        }
+        let lines: String = data
+            .clients
+            .list()
+            .iter()
+            .filter(|client| client_type.is_none_or(|t| client.client_type() == t))
+            .filter(|client| ids.as_ref().is_none_or(|ids| ids.contains(&client.id)))
+            .map(|client| client.info_line() + "\n")
+            .collect();
+        bulk(&lines)
+    }
+
+    // CLIENT KILL ip:port 或 CLIENT KILL <filter> <value> ...
+    fn kill(&self, data: &Data, me: &Arc<Client>) -> Resp {
+        if let [addr] = self.args.as_slice() {
+            return match data
+                .clients
+                .list()
+                .iter()
+                .find(|client| client.addr == *addr)
+            {
+                Some(client) => {
+                    client.kill();
+                    ok()
+                }
+                None => error("ERR No such client"),
+            };
+        }
+        if self.args.is_empty() || !self.args.len().is_multiple_of(2) {
+            return error("ERR syntax error");
+        }
+        let mut filter = KillFilter {
+            skipme: true,
+            ..Default::default()
+        };
+        for pair in self.args.chunks(2) {
+            let value = &pair[1];
+            match pair[0].to_lowercase().as_str() {
+                "id" => match value.parse::<u64>() {
+                    Ok(id) if id > 0 => filter.id = Some(id),
+                    _ => return error("ERR client-id should be greater than 0"),
+                },
+                "type" => match ClientType::parse(value) {
+                    Some(client_type) => filter.client_type = Some(client_type),
+                    None => return error(&format!("ERR Unknown client type '{value}'")),
+                },
+                "addr" => filter.addr = Some(value.clone()),
+                "laddr" => filter.laddr = Some(value.clone()),
+                "user" if !data.acl.exists(value) => {
+                    return error(&format!("ERR No such user '{value}'"))
+                }
+                "user" => filter.user = Some(value.clone()),
+                "skipme" => match value.to_lowercase().as_str() {
+                    "yes" => filter.skipme = true,
+                    "no" => filter.skipme = false,
+                    _ => return error("ERR syntax error"),
+                },
+                "maxage" => match value.parse::<u64>() {
+                    Ok(maxage) => filter.maxage = Some(maxage),
+                    Err(_) => return error("ERR value is not an integer or out of range"),
+                },
+                _ => return error("ERR syntax error"),
+            }
+        }
+        let killed = data
+            .clients
+            .list()
+            .iter()
+            .filter(|client| filter.matches(client, me))
+            .map(|client| client.kill())
+            .count();
+        integer(killed as i64)
+    }
+
+    // CLIENT PAUSE timeout [WRITE|ALL]
+    fn pause(&self, data: &Data) -> Resp {
+        let (timeout, mode) = match self.args.as_slice() {
+            [timeout] => (timeout, PauseMode::All),
+            [timeout, mode] => match mode.to_lowercase().as_str() {
+                "write" => (timeout, PauseMode::Write),
+                "all" => (timeout, PauseMode::All),
+                _ => return error("ERR CLIENT PAUSE mode must be WRITE or ALL"),
+            },
+            _ => return error("ERR wrong number of arguments for 'client|pause' command"),
+        };
+        let Ok(timeout) = timeout.parse::<u64>() else {
+            return error("ERR timeout is not an integer or out of range");
+        };
+        data.clients.pause(Duration::from_millis(timeout), mode);
+        ok()
+    }
+
+    // CLIENT SETINFO LIB-NAME|LIB-VER value
+    fn setinfo(&self, me: &Client) -> Resp {
+        let [attr, value] = self.args.as_slice() else {
+            return error("ERR wrong number of arguments for 'client|setinfo' command");
+        };
+        let attr = attr.to_lowercase();
+        if !client::valid_name(value) && !value.is_empty() {
+            return error(&format!(
+                "ERR {attr} cannot contain spaces, newlines or special characters."
+            ));
+        }
+        match attr.as_str() {
+            "lib-name" => me.set_lib_name(value),
+            "lib-ver" => me.set_lib_ver(value),
+            _ => return error(&format!("ERR Unrecognized option '{}'", self.args[0])),
+        }
+        ok()
+    }
+
+    // CLIENT TRACKING ON|OFF [REDIRECT id] [PREFIX prefix ...] [BCAST] [OPTIN] [OPTOUT] [NOLOOP]
+    fn tracking(&self, data: &Data, me: &Client) -> Resp {
+        let Some((switch, args)) = self.args.split_first() else {
+            return error("ERR wrong number of arguments for 'client|tracking' command");
+        };
+        let mut options = TrackingOptions::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.to_lowercase().as_str() {
+                "redirect" => {
+                    let Some(id) = args.next() else {
+                        return error("ERR syntax error");
+                    };
+                    let Ok(id) = id.parse::<u64>() else {
+                        return error("ERR value is not an integer or out of range");
+                    };
+                    if data.clients.get(id).is_none() {
+                        return error("ERR The client ID you want redirect to does not exist");
+                    }
+                    options.redirect = Some(id);
+                }
+                "prefix" => {
+                    let Some(prefix) = args.next() else {
+                        return error("ERR syntax error");
+                    };
+                    options.prefixes.push(prefix.clone());
+                }
+                "bcast" => options.bcast = true,
+                "optin" => options.optin = true,
+                "optout" => options.optout = true,
+                "noloop" => options.noloop = true,
+                _ => return error("ERR syntax error"),
+            }
+        }
+        match switch.to_lowercase().as_str() {
+            "on" => {}
+            "off" => {
+                data.tracking.disable(me);
+                return ok();
+            }
+            _ => return error("ERR syntax error"),
+        }
+        let current = me.tracking();
+        if !options.bcast && !options.prefixes.is_empty() {
+            return error("ERR PREFIX option requires BCAST mode to be enabled");
+        }
+        if current
+            .as_ref()
+            .is_some_and(|current| current.bcast != options.bcast)
+        {
+            return error("ERR You can't switch BCAST mode on/off before disabling tracking for this client, and then re-enabling it with a different mode.");
+        }
+        if options.optin && options.optout {
+            return error("ERR You can't use both OPTIN and OPTOUT");
+        }
+        if (options.optin || options.optout) && options.bcast {
+            return error("ERR OPTIN and OPTOUT are not compatible with BCAST");
+        }
+        if current.as_ref().is_some_and(|current| {
+            current.optin != options.optin || current.optout != options.optout
+        }) {
+            return error("ERR You can't switch OPTIN/OPTOUT mode before disabling tracking for this client, and then re-enabling it with a different mode.");
+        }
+        // 同一个客户端的前缀不能互相包含，否则一个键会收到多次通知
+        let existing = current.map(|current| current.prefixes).unwrap_or_default();
+        for (i, prefix) in options.prefixes.iter().enumerate() {
+            let overlap = existing.iter().chain(&options.prefixes[..i]).find(|other| {
+                other.starts_with(prefix.as_str()) || prefix.starts_with(other.as_str())
+            });
+            if let Some(other) = overlap {
+                return error(&format!("ERR Prefix '{prefix}' overlaps with an existing prefix '{other}'. Prefixes for a single client must not overlap."));
+            }
+        }
+        data.tracking.enable(me, options);
+        ok()
+    }
+
+    // CLIENT CACHING YES|NO
+    fn caching(&self, me: &Client) -> Resp {
+        let [flag] = self.args.as_slice() else {
+            return error("ERR wrong number of arguments for 'client|caching' command");
+        };
+        let Some(options) = me
+            .tracking()
+            .filter(|options| options.optin || options.optout)
+        else {
+            return error("ERR CLIENT CACHING can be called only when the client is in tracking mode with OPTIN or OPTOUT mode enabled");
+        };
+        match flag.to_lowercase().as_str() {
+            "yes" if options.optin => me.set_caching(true),
+            "yes" => {
+                return error(
+                    "ERR CLIENT CACHING YES is only valid when tracking is enabled in OPTIN mode.",
+                )
+            }
+            "no" if options.optout => me.set_caching(false),
+            "no" => {
+                return error(
+                    "ERR CLIENT CACHING NO is only valid when tracking is enabled in OPTOUT mode.",
+                )
+            }
+            _ => return error("ERR syntax error"),
+        }
+        ok()
+    }
+
+    // CLIENT TRACKINGINFO：flags、redirect、prefixes 三项
+    fn tracking_info(&self, data: &Data, me: &Client) -> Resp {
+        let (flags, redirect, prefixes) = match me.tracking() {
+            None => (vec!["off"], -1, Vec::new()),
+            Some(options) => {
+                let mut flags = vec!["on"];
+                if options.bcast {
+                    flags.push("bcast");
+                }
+                if options.optin {
+                    flags.push("optin");
+                    if me.caching() == Some(true) {
+                        flags.push("caching-yes");
+                    }
+                }
+                if options.optout {
+                    flags.push("optout");
+                    if me.caching() == Some(false) {
+                        flags.push("caching-no");
+                    }
+                }
+                if options.noloop {
+                    flags.push("noloop");
+                }
+                let redirect = match options.redirect {
+                    Some(id) => {
+                        if data.clients.get(id).is_none() {
+                            flags.push("broken_redirect");
+                        }
+                        id as i64
+                    }
+                    None => -1,
+                };
+                (flags, redirect, options.prefixes)
+            }
+        };
+        Resp::Arrays(Arrays::new(vec![
+            bulk("flags"),
+            Resp::Arrays(Arrays::new(flags.into_iter().map(bulk).collect())),
+            bulk("redirect"),
+            integer(redirect),
+            bulk("prefixes"),
+            Resp::Arrays(Arrays::new(prefixes.iter().map(|p| bulk(p)).collect())),
+        ]))
+    }
+}
+
+impl Processor for ClientCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("ClientCommandPara process start: {:?}", &self);
+        let subcommand = self.subcommand.to_lowercase();
+        // 其余子命令都作用于当前连接
+        match subcommand.as_str() {
+            "list" => return Ok(self.list(data)),
+            "pause" => return Ok(self.pause(data)),
+            "unpause" if self.args.is_empty() => {
+                data.clients.unpause();
+                return Ok(ok());
+            }
+            "help" if self.args.is_empty() => {
+                return Ok(Resp::Arrays(Arrays::new(
+                    HELP.iter().map(|line| bulk(line)).collect(),
+                )))
+            }
+            _ => {}
+        }
+        let Some(me) = client::current() else {
+            return Ok(error("ERR CLIENT must be called from a client connection"));
+        };
+        let reply = match (subcommand.as_str(), self.args.as_slice()) {
+            ("id", []) => integer(me.id as i64),
+            ("info", []) => bulk(&(me.info_line() + "\n")),
+            ("getname", []) => match me.name() {
+                name if name.is_empty() => Resp::Nulls(Nulls::new()),
+                name => bulk(&name),
+            },
+            ("setname", [name]) => {
+                if client::valid_name(name) || name.is_empty() {
+                    me.set_name(name);
+                    ok()
+                } else {
+                    error("ERR Client names cannot contain spaces, newlines or special characters.")
+                }
+            }
+            ("setinfo", _) => self.setinfo(&me),
+            ("tracking", _) => self.tracking(data, &me),
+            ("caching", _) => self.caching(&me),
+            ("getredir", []) => integer(match me.tracking() {
+                Some(options) => options.redirect.map_or(0, |id| id as i64),
+                None => -1,
+            }),
+            ("trackinginfo", []) => self.tracking_info(data, &me),
+            ("kill", _) => self.kill(data, &me),
+            ("no-evict", [flag]) => match flag.to_lowercase().as_str() {
+                "on" => {
+                    me.set_no_evict(true);
+                    ok()
+                }
+                "off" => {
+                    me.set_no_evict(false);
+                    ok()
+                }
+                _ => error("ERR syntax error"),
+            },
+            ("reply", [mode]) => {
+                let mode = match mode.to_lowercase().as_str() {
+                    "on" => ReplyMode::On,
+                    "off" => ReplyMode::Off,
+                    "skip" => ReplyMode::Skip,
+                    _ => return Ok(error("ERR syntax error")),
+                };
+                me.set_reply_mode(mode);
+                ok()
+            }
+            (
+                "id" | "info" | "getname" | "setname" | "no-evict" | "reply" | "unpause" | "help"
+                | "getredir" | "trackinginfo",
+                _,
+            ) => error(&format!(
+                "ERR wrong number of arguments for 'client|{subcommand}' command"
+            )),
+            _ => error(&format!(
+                "ERR unknown subcommand '{}'. Try CLIENT HELP.",
+                self.subcommand
+            )),
+        };
+        Ok(reply)
+    }
+}
+
+// CLIENT KILL 的过滤条件，所有条件都满足的连接才会被关闭
+#[derive(Debug, Default)]
+struct KillFilter {
+    id: Option<u64>,
+    client_type: Option<ClientType>,
+    addr: Option<String>,
+    laddr: Option<String>,
+    user: Option<String>,
+    skipme: bool,
+    maxage: Option<u64>,
+}
+
+impl KillFilter {
+    fn matches(&self, client: &Client, me: &Client) -> bool {
+        self.id.is_none_or(|id| client.id == id)
+            && self.client_type.is_none_or(|t| client.client_type() == t)
+            && self.addr.as_ref().is_none_or(|addr| client.addr == *addr)
+            && self
+                .laddr
+                .as_ref()
+                .is_none_or(|laddr| client.laddr == *laddr)
+            && self.user.as_ref().is_none_or(|user| client.user() == *user)
+            && self
+                .maxage
+                .is_none_or(|maxage| client.age().as_secs() >= maxage)
+            && !(self.skipme && client.id == me.id)
+    }
+}
+
+fn ok() -> Resp {
+    Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned()))
+}
+
+fn bulk(val: &str) -> Resp {
+    Resp::BulkStrings(BulkStrings::new(val.to_owned()))
+}
+
+fn integer(val: i64) -> Resp {
+    Resp::Integers(Integers::new(val))
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_cmd(data: &Data, args: &[&str]) -> Resp {
+        ClientCommandPara::new(
+            args[0].to_owned(),
+            args[1..].iter().map(|arg| arg.to_string()).collect(),
+            Parameter::new(),
+        )
+        .process(data)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_client_setname_list_kill() {
+        let data = Data::new();
+        let me = data
+            .clients
+            .register("127.0.0.1:5000".to_owned(), "127.0.0.1:6379".to_owned());
+        let other = data
+            .clients
+            .register("127.0.0.1:5001".to_owned(), "127.0.0.1:6379".to_owned());
+        client::with_client(me.client(), || {
+            assert_eq!(client_cmd(&data, &["GETNAME"]), Resp::Nulls(Nulls::new()));
+            assert!(matches!(
+                client_cmd(&data, &["SETNAME", "bad name"]),
+                Resp::SimpleErrors(_)
+            ));
+            assert_eq!(client_cmd(&data, &["setname", "worker"]), ok());
+            assert_eq!(client_cmd(&data, &["GETNAME"]), bulk("worker"));
+            assert_eq!(client_cmd(&data, &["ID"]), integer(me.client().id as i64));
+
+            let id = other.client().id.to_string();
+            let Resp::BulkStrings(list) = client_cmd(&data, &["LIST", "ID", &id]) else {
+                panic!("expected bulk string");
+            };
+            assert_eq!(list.val.lines().count(), 1);
+            assert!(list.val.contains("addr=127.0.0.1:5001"));
+
+            // 默认跳过自己
+            assert_eq!(
+                client_cmd(&data, &["KILL", "LADDR", "127.0.0.1:6379"]),
+                integer(1)
+            );
+            assert!(other.client().is_killed());
+            assert!(!me.client().is_killed());
+            assert!(matches!(
+                client_cmd(&data, &["KILL", "127.0.0.1:9999"]),
+                Resp::SimpleErrors(_)
+            ));
+            assert_eq!(client_cmd(&data, &["KILL", "127.0.0.1:5000"]), ok());
+            assert!(me.client().is_killed());
+        });
+    }
+}
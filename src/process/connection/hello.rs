@@ -0,0 +1,80 @@
+use crate::{
+    process::Parameter, Arrays, BulkStrings, Data, Integers, Maps, Processor, Resp, SimpleErrors,
+};
+
+#[derive(Debug)]
+pub struct HelloCommandPara {
+    // 切换到的协议版本，未指定时保持不变
+    pub protover: Option<u8>,
+    pub auth: Option<(String, String)>,
+    pub setname: Option<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl HelloCommandPara {
+    pub fn new(
+        protover: Option<u8>,
+        auth: Option<(String, String)>,
+        setname: Option<String>,
+        para: Parameter,
+    ) -> Self {
+        Self {
+            protover,
+            auth,
+            setname,
+            para,
+        }
+    }
+
+    /// 连接切换协议后的回复：RESP3 为 Map，RESP2 为扁平数组
+    pub fn reply(&self, data: &Data, protocol: u8, client_id: u64) -> Resp {
+        let mode = if data.cluster.is_enabled() {
+            "cluster"
+        } else {
+            "standalone"
+        };
+        let role = if data.replication.is_replica() {
+            "replica"
+        } else {
+            "master"
+        };
+        let fields = vec![
+            ("server", bulk("redis")),
+            ("version", bulk("7.0.0-simple")),
+            ("proto", Resp::Integers(Integers::new(protocol as i64))),
+            ("id", Resp::Integers(Integers::new(client_id as i64))),
+            ("mode", bulk(mode)),
+            ("role", bulk(role)),
+            ("modules", Resp::Arrays(Arrays::new(Vec::new()))),
+        ];
+        if protocol >= 3 {
+            Resp::Maps(Maps::new(
+                fields
+                    .into_iter()
+                    .map(|(key, val)| (bulk(key), val))
+                    .collect(),
+            ))
+        } else {
+            Resp::Arrays(Arrays::new(
+                fields
+                    .into_iter()
+                    .flat_map(|(key, val)| [bulk(key), val])
+                    .collect(),
+            ))
+        }
+    }
+}
+
+impl Processor for HelloCommandPara {
+    fn process(&self, _data: &Data) -> Result<Resp, anyhow::Error> {
+        // 协议版本属于连接，由连接自己处理
+        Ok(Resp::SimpleErrors(SimpleErrors::new(
+            "ERR HELLO must be handled by the connection".to_owned(),
+        )))
+    }
+}
+
+fn bulk(val: &str) -> Resp {
+    Resp::BulkStrings(BulkStrings::new(val.to_owned()))
+}
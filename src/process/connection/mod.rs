@@ -0,0 +1,14 @@
+pub mod auth;
+pub mod client;
+pub mod hello;
+pub mod monitor;
+pub mod select;
+
+#[derive(Debug)]
+pub enum ConnectionCommand {
+    Hello(hello::HelloCommandPara),
+    Select(select::SelectCommandPara),
+    Monitor(monitor::MonitorCommandPara),
+    Client(client::ClientCommandPara),
+    Auth(auth::AuthCommandPara),
+}
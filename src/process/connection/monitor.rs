@@ -0,0 +1,22 @@
+use crate::{process::Parameter, Data, Processor, Resp, SimpleErrors};
+
+#[derive(Debug)]
+pub struct MonitorCommandPara {
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl MonitorCommandPara {
+    pub fn new(para: Parameter) -> Self {
+        Self { para }
+    }
+}
+
+impl Processor for MonitorCommandPara {
+    fn process(&self, _data: &Data) -> Result<Resp, anyhow::Error> {
+        // 监视模式属于连接，由连接自己处理；走到这里说明在事务中执行
+        Ok(Resp::SimpleErrors(SimpleErrors::new(
+            "ERR MONITOR isn't allowed for DENY BLOCKING client".to_owned(),
+        )))
+    }
+}
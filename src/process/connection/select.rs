@@ -0,0 +1,40 @@
+use crate::{process::Parameter, Data, Processor, Resp, SimpleErrors};
+
+#[derive(Debug)]
+pub struct SelectCommandPara {
+    pub index: String,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl SelectCommandPara {
+    pub fn new(index: String, para: Parameter) -> Self {
+        Self { index, para }
+    }
+
+    /// 检查库编号，返回可以切换到的库
+    pub fn validate(&self, data: &Data) -> Result<usize, Resp> {
+        let index = self
+            .index
+            .parse::<i64>()
+            .map_err(|_| error("ERR value is not an integer or out of range"))?;
+        if data.cluster.is_enabled() && index != 0 {
+            return Err(error("ERR SELECT is not allowed in cluster mode"));
+        }
+        if index < 0 || index as usize >= data.databases() {
+            return Err(error("ERR DB index is out of range"));
+        }
+        Ok(index as usize)
+    }
+}
+
+impl Processor for SelectCommandPara {
+    fn process(&self, _data: &Data) -> Result<Resp, anyhow::Error> {
+        // 选择的库属于连接，由连接自己处理
+        Ok(error("ERR SELECT must be handled by the connection"))
+    }
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
@@ -1,6 +1,6 @@
 use tracing::info;
 
-use crate::{process::Parameter, Data, Integers, Processor, Resp};
+use crate::{notify, process::Parameter, Data, Integers, Processor, Resp};
 
 #[derive(Debug)]
 pub struct HDelCommandPara {
@@ -22,17 +22,24 @@ impl Processor for HDelCommandPara {
 
         let mut deleted_count = 0i64;
 
-        if let Some(mut hash_entry) = data.hash_data.get_mut(&self.key) {
+        if let Some(mut hash_entry) = data.db().hash_data.get_mut(&self.key) {
             for field in &self.fields {
                 if hash_entry.remove(field).is_some() {
                     deleted_count += 1;
                 }
             }
-
-            // 如果hash为空，删除整个键
-            if hash_entry.is_empty() {
-                data.hash_data.remove(&self.key);
-            }
+        }
+        if deleted_count > 0 {
+            data.notify_keyspace_event(notify::NOTIFY_HASH, "hdel", &self.key);
+        }
+        // 如果hash为空，删除整个键（需要先释放 get_mut 的引用，否则会死锁）
+        if data
+            .db()
+            .hash_data
+            .remove_if(&self.key, |_, hash| hash.is_empty())
+            .is_some()
+        {
+            data.notify_keyspace_event(notify::NOTIFY_GENERIC, "del", &self.key);
         }
 
         Ok(Resp::Integers(Integers::new(deleted_count)))
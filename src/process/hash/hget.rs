@@ -18,7 +18,7 @@ impl HGetCommandPara {
 
 impl Processor for HGetCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
-        match data.hash_data.get(&self.key) {
+        match data.db().hash_data.get(&self.key) {
             Some(hash_entry) => {
                 let hash = hash_entry.value();
                 match hash.get(&self.field) {
@@ -19,7 +19,7 @@ impl Processor for HGetAllCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         info!("HGetAllCommandPara process start: {:?}", &self);
 
-        match data.hash_data.get(&self.key) {
+        match data.db().hash_data.get(&self.key) {
             Some(hash) => {
                 let mut result = Vec::new();
                 for (field, value) in hash.iter() {
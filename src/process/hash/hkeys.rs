@@ -19,7 +19,7 @@ impl Processor for HKeysCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         info!("HKeysCommandPara process start: {:?}", &self);
 
-        match data.hash_data.get(&self.key) {
+        match data.db().hash_data.get(&self.key) {
             Some(hash) => {
                 let keys: Vec<Resp> = hash
                     .keys()
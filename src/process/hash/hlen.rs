@@ -0,0 +1,28 @@
+use tracing::info;
+
+use crate::{process::Parameter, Data, Integers, Processor, Resp};
+
+#[derive(Debug)]
+pub struct HLenCommandPara {
+    pub key: String,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl HLenCommandPara {
+    pub fn new(key: String, para: Parameter) -> Self {
+        Self { key, para }
+    }
+}
+
+impl Processor for HLenCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("HLenCommandPara process start: {:?}", &self);
+        data.expire_if_needed(&self.key);
+
+        match data.db().hash_data.get(&self.key) {
+            Some(hash) => Ok(Resp::Integers(Integers::new(hash.len() as i64))),
+            None => Ok(Resp::Integers(Integers::new(0))),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use crate::{process::string::scan::ElementScan, Data, Processor, Resp};
+
+#[derive(Debug)]
+pub struct HScanCommandPara {
+    key: String,
+    scan: ElementScan,
+}
+
+impl HScanCommandPara {
+    pub fn new(key: String, scan: ElementScan) -> Self {
+        Self { key, scan }
+    }
+}
+
+impl Processor for HScanCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        // 检查键是否过期
+        if data.expire_if_needed(&self.key) {
+            return Ok(ElementScan::empty());
+        }
+        match data.db().hash_data.get(&self.key) {
+            Some(hash) => Ok(self.scan.reply(
+                hash.iter().map(|(field, value)| (field.as_str(), value)),
+                |value| Some(value.clone()),
+            )),
+            None => Ok(ElementScan::empty()),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use tracing::info;
 
-use crate::{process::Parameter, Data, Integers, Processor, Resp};
+use crate::{notify, process::Parameter, Data, Integers, Processor, Resp};
 
 #[derive(Debug)]
 pub struct HSetCommandPara {
@@ -23,6 +23,7 @@ impl HSetCommandPara {
 impl Processor for HSetCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         let mut hash_map = data
+            .db()
             .hash_data
             .get(&self.key)
             .map(|entry| entry.value().clone())
@@ -36,7 +37,8 @@ impl Processor for HSetCommandPara {
             hash_map.insert(field.clone(), value.clone());
         }
 
-        data.hash_data.insert(self.key.clone(), hash_map);
+        data.db().hash_data.insert(self.key.clone(), hash_map);
+        data.notify_keyspace_event(notify::NOTIFY_HASH, "hset", &self.key);
 
         info!("✅ HSET '{}' -> {} new fields added", self.key, new_fields);
         Ok(Resp::Integers(Integers::new(new_fields as i64)))
@@ -19,7 +19,7 @@ impl Processor for HValsCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         info!("HValsCommandPara process start: {:?}", &self);
 
-        match data.hash_data.get(&self.key) {
+        match data.db().hash_data.get(&self.key) {
             Some(hash) => {
                 let values: Vec<Resp> = hash
                     .values()
@@ -2,6 +2,8 @@ pub mod hdel;
 pub mod hget;
 pub mod hgetall;
 pub mod hkeys;
+pub mod hlen;
+pub mod hscan;
 pub mod hset;
 pub mod hvals;
 
@@ -13,4 +15,6 @@ pub enum HashCommand {
     HGetAll(hgetall::HGetAllCommandPara),
     HKeys(hkeys::HKeysCommandPara),
     HVals(hvals::HValsCommandPara),
+    HLen(hlen::HLenCommandPara),
+    HScan(hscan::HScanCommandPara),
 }
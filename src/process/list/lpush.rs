@@ -1,6 +1,6 @@
 use tracing::info;
 
-use crate::{process::Parameter, Data, Integers, Processor, Resp};
+use crate::{notify, process::Parameter, Data, Integers, Processor, Resp};
 
 #[derive(Debug)]
 pub struct LPushCommandPara {
@@ -21,6 +21,7 @@ impl Processor for LPushCommandPara {
         info!("LPushCommandPara process start: {:?}", &self);
 
         let mut list = data
+            .db()
             .list_data
             .get(&self.key)
             .map(|entry| entry.value().clone())
@@ -31,7 +32,8 @@ impl Processor for LPushCommandPara {
         }
 
         let new_length = list.len() as i64;
-        data.list_data.insert(self.key.clone(), list);
+        data.db().list_data.insert(self.key.clone(), list);
+        data.notify_keyspace_event(notify::NOTIFY_LIST, "lpush", &self.key);
 
         Ok(Resp::Integers(Integers::new(new_length)))
     }
@@ -26,7 +26,7 @@ impl Processor for LRangeCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         info!("LRangeCommandPara process start: {:?}", &self);
 
-        match data.list_data.get(&self.key) {
+        match data.db().list_data.get(&self.key) {
             Some(list) => {
                 let len = list.len() as i64;
                 if len == 0 {
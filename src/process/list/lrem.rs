@@ -1,6 +1,6 @@
 use tracing::info;
 
-use crate::{process::Parameter, Data, Integers, Processor, Resp};
+use crate::{notify, process::Parameter, Data, Integers, Processor, Resp};
 
 #[derive(Debug)]
 pub struct LRemCommandPara {
@@ -26,7 +26,7 @@ impl Processor for LRemCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         info!("LRemCommandPara process start: {:?}", &self);
 
-        match data.list_data.get_mut(&self.key) {
+        let removed_count = match data.db().list_data.get_mut(&self.key) {
             Some(mut list) => {
                 let mut removed_count = 0;
 
@@ -68,12 +68,24 @@ impl Processor for LRemCommandPara {
                 }
 
                 info!("🗑️ LREM '{}' removed {} elements", self.key, removed_count);
-                Ok(Resp::Integers(Integers::new(removed_count)))
+                removed_count
             }
             None => {
                 info!("🗑️ LREM '{}' key not found", self.key);
-                Ok(Resp::Integers(Integers::new(0)))
+                0
             }
+        };
+        if removed_count > 0 {
+            data.notify_keyspace_event(notify::NOTIFY_LIST, "lrem", &self.key);
         }
+        if data
+            .db()
+            .list_data
+            .remove_if(&self.key, |_, list| list.is_empty())
+            .is_some()
+        {
+            data.notify_keyspace_event(notify::NOTIFY_GENERIC, "del", &self.key);
+        }
+        Ok(Resp::Integers(Integers::new(removed_count)))
     }
 }
@@ -1,6 +1,6 @@
 use tracing::info;
 
-use crate::{process::Parameter, Arrays, BulkStrings, Data, Nulls, Processor, Resp};
+use crate::{notify, process::Parameter, Arrays, BulkStrings, Data, Nulls, Processor, Resp};
 
 #[derive(Debug)]
 pub struct RPopCommandPara {
@@ -20,7 +20,7 @@ impl Processor for RPopCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         info!("RPopCommandPara process start: {:?}", &self);
 
-        match data.list_data.get_mut(&self.key) {
+        let reply = match data.db().list_data.get_mut(&self.key) {
             Some(mut list) => {
                 if list.is_empty() {
                     return Ok(Resp::Nulls(Nulls::new()));
@@ -37,24 +37,28 @@ impl Processor for RPopCommandPara {
                             }
                         }
 
-                        if list.is_empty() {
-                            data.list_data.remove(&self.key);
-                        }
-
-                        Ok(Resp::Arrays(Arrays::new(results)))
+                        Resp::Arrays(Arrays::new(results))
                     }
                     _ => match list.pop_back() {
-                        Some(value) => {
-                            if list.is_empty() {
-                                data.list_data.remove(&self.key);
-                            }
-                            Ok(Resp::BulkStrings(BulkStrings::new(value)))
-                        }
-                        None => Ok(Resp::Nulls(Nulls::new())),
+                        Some(value) => Resp::BulkStrings(BulkStrings::new(value)),
+                        None => Resp::Nulls(Nulls::new()),
                     },
                 }
             }
-            None => Ok(Resp::Nulls(Nulls::new())),
+            None => Resp::Nulls(Nulls::new()),
+        };
+        if !matches!(reply, Resp::Nulls(_)) {
+            data.notify_keyspace_event(notify::NOTIFY_LIST, "rpop", &self.key);
+        }
+        // 弹出后列表为空则删除整个键（需要先释放 get_mut 的引用，否则会死锁）
+        if data
+            .db()
+            .list_data
+            .remove_if(&self.key, |_, list| list.is_empty())
+            .is_some()
+        {
+            data.notify_keyspace_event(notify::NOTIFY_GENERIC, "del", &self.key);
         }
+        Ok(reply)
     }
 }
@@ -1,17 +1,30 @@
 use tracing::{debug, info};
 
+use crate::cluster::migrate::MigrateOptions;
+use crate::process::cluster::ClusterCommand;
+use crate::process::connection::ConnectionCommand;
 use crate::process::hash::HashCommand;
 use crate::process::list::ListCommand;
+use crate::process::pubsub::PubSubCommand;
+use crate::process::replication::ReplicationCommand;
+use crate::process::server::ServerCommand;
 use crate::process::set::SetCommand;
 use crate::process::sorted_set::SortedSetCommand;
 use crate::process::string::StringCommand;
+use crate::pubsub::Kind;
 use crate::{GetCommandPara, Resp};
 
 use self::string::set::SetCommandPara;
 use std::convert::TryFrom;
 
+pub mod cluster;
+pub mod command_table;
+pub mod connection;
 pub mod hash;
 pub mod list;
+pub mod pubsub;
+pub mod replication;
+pub mod server;
 pub mod set;
 pub mod sorted_set;
 pub mod string;
@@ -66,6 +79,11 @@ pub enum CommandGroup {
     List(ListCommand),
     Set(SetCommand),
     SortedSet(SortedSetCommand),
+    Replication(ReplicationCommand),
+    Cluster(ClusterCommand),
+    PubSub(PubSubCommand),
+    Connection(ConnectionCommand),
+    Server(ServerCommand),
 }
 
 impl TryFrom<Resp> for CommandGroup {
@@ -78,7 +96,8 @@ impl TryFrom<Resp> for CommandGroup {
                 let mut iter = arr.val.iter();
                 let command = try_exact_bulk_string(iter.next())?;
                 info!("🎯 Executing command: {}", &command.to_uppercase());
-                match command.to_lowercase().as_str() {
+                let cmd = command.to_lowercase();
+                match cmd.as_str() {
                     // String commands
                     "set" => {
                         let key = try_exact_bulk_string(iter.next())?;
@@ -205,6 +224,148 @@ impl TryFrom<Resp> for CommandGroup {
                             ),
                         )))
                     }
+                    "pexpireat" => {
+                        let key = try_exact_bulk_string(iter.next())?;
+                        let timestamp = try_exact_bulk_string(iter.next())?;
+                        let timestamp = timestamp
+                            .parse::<u64>()
+                            .map_err(|_| anyhow::anyhow!("invalid expire time"))?;
+                        info!(
+                            "⏰ PEXPIREAT operation: key='{}', timestamp={}",
+                            key, timestamp
+                        );
+                        Ok(CommandGroup::String(StringCommand::PExpireAt(
+                            crate::process::string::pexpireat::PExpireAtCommandPara::new(
+                                key.to_string(),
+                                timestamp,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+
+                    "dump" => {
+                        let key = try_exact_bulk_string(iter.next())?;
+                        info!("📦 DUMP operation: key='{}'", key);
+                        Ok(CommandGroup::String(StringCommand::Dump(
+                            crate::process::string::dump::DumpCommandPara::new(
+                                key.to_string(),
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "move" => {
+                        let key = try_exact_bulk_string(iter.next())?;
+                        let db = try_exact_bulk_string(iter.next())?;
+                        Ok(CommandGroup::String(StringCommand::Move(
+                            crate::process::string::move_cmd::MoveCommandPara::new(
+                                key.to_string(),
+                                db.to_string(),
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "rename" | "renamenx" => {
+                        let key = try_exact_bulk_string(iter.next())?;
+                        let new_key = try_exact_bulk_string(iter.next())?;
+                        Ok(CommandGroup::String(StringCommand::Rename(
+                            crate::process::string::rename::RenameCommandPara::new(
+                                key.to_string(),
+                                new_key.to_string(),
+                                cmd == "renamenx",
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "copy" => {
+                        // COPY source destination [DB destination-db] [REPLACE]
+                        let source = try_exact_bulk_string(iter.next())?;
+                        let destination = try_exact_bulk_string(iter.next())?;
+                        let mut db = None;
+                        let mut replace = false;
+                        while let Some(item) = iter.next() {
+                            match try_exact_bulk_string(Some(item))?.to_lowercase().as_str() {
+                                "db" => db = Some(try_exact_bulk_string(iter.next())?.to_string()),
+                                "replace" => replace = true,
+                                other => {
+                                    return Err(anyhow::anyhow!("syntax error near '{}'", other))
+                                }
+                            }
+                        }
+                        Ok(CommandGroup::String(StringCommand::Copy(
+                            crate::process::string::copy::CopyCommandPara::new(
+                                source.to_string(),
+                                destination.to_string(),
+                                db,
+                                replace,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "randomkey" => Ok(CommandGroup::String(StringCommand::RandomKey(
+                        crate::process::string::randomkey::RandomKeyCommandPara::new(
+                            Parameter::new(),
+                        ),
+                    ))),
+                    "touch" | "unlink" => {
+                        let keys = iter
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        if cmd == "touch" {
+                            Ok(CommandGroup::String(StringCommand::Touch(
+                                crate::process::string::touch::TouchCommandPara::new(
+                                    keys,
+                                    Parameter::new(),
+                                ),
+                            )))
+                        } else {
+                            Ok(CommandGroup::String(StringCommand::Unlink(
+                                crate::process::string::unlink::UnlinkCommandPara::new(
+                                    keys,
+                                    Parameter::new(),
+                                ),
+                            )))
+                        }
+                    }
+                    "object" => {
+                        let subcommand = try_exact_bulk_string(iter.next())?;
+                        let args = iter
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok(CommandGroup::String(StringCommand::Object(
+                            crate::process::string::object::ObjectCommandPara::new(
+                                subcommand.to_string(),
+                                args,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "restore" => {
+                        let key = try_exact_bulk_string(iter.next())?;
+                        let ttl = try_exact_bulk_string(iter.next())?
+                            .parse::<u64>()
+                            .map_err(|_| anyhow::anyhow!("Invalid TTL value, must be >= 0"))?;
+                        let payload = try_exact_bulk_string(iter.next())?;
+                        let mut replace = false;
+                        let mut absttl = false;
+                        for item in iter {
+                            match try_exact_bulk_string(Some(item))?.to_uppercase().as_str() {
+                                "REPLACE" => replace = true,
+                                "ABSTTL" => absttl = true,
+                                _ => return Err(anyhow::anyhow!("syntax error")),
+                            }
+                        }
+                        info!("📦 RESTORE operation: key='{}', ttl={}", key, ttl);
+                        Ok(CommandGroup::String(StringCommand::Restore(
+                            crate::process::string::restore::RestoreCommandPara::new(
+                                key.to_string(),
+                                ttl,
+                                payload.to_string(),
+                                replace,
+                                absttl,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
 
                     // Hash commands
                     "hset" => {
@@ -215,7 +376,7 @@ impl TryFrom<Resp> for CommandGroup {
                             .map(|item| try_exact_bulk_string(Some(item)))
                             .collect::<Result<Vec<_>, _>>()?;
 
-                        if fields_and_values.len() % 2 != 0 {
+                        if !fields_and_values.len().is_multiple_of(2) {
                             return Err(anyhow::anyhow!("wrong number of arguments for HSET"));
                         }
 
@@ -237,6 +398,35 @@ impl TryFrom<Resp> for CommandGroup {
                             ),
                         )))
                     }
+                    "hlen" => {
+                        let key = try_exact_bulk_string(iter.next())?;
+                        Ok(CommandGroup::Hash(HashCommand::HLen(
+                            crate::process::hash::hlen::HLenCommandPara::new(
+                                key.to_string(),
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "hscan" => {
+                        let key = try_exact_bulk_string(iter.next())?;
+                        let scan = parse_element_scan(iter)?;
+                        info!("🔍 HSCAN operation: key={}, scan={:?}", key, scan);
+                        Ok(CommandGroup::Hash(HashCommand::HScan(
+                            crate::process::hash::hscan::HScanCommandPara::new(
+                                key.to_string(),
+                                scan,
+                            ),
+                        )))
+                    }
+                    "strlen" => {
+                        let key = try_exact_bulk_string(iter.next())?;
+                        Ok(CommandGroup::String(StringCommand::StrLen(
+                            crate::process::string::strlen::StrLenCommandPara::new(
+                                key.to_string(),
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
                     "hget" => {
                         let key = try_exact_bulk_string(iter.next())?;
                         let field = try_exact_bulk_string(iter.next())?;
@@ -433,17 +623,13 @@ impl TryFrom<Resp> for CommandGroup {
                             crate::process::set::sadd::SAddCommandPara::new(
                                 key.to_string(),
                                 members,
-                                Parameter::new(),
                             ),
                         )))
                     }
                     "scard" => {
                         let key = try_exact_bulk_string(iter.next())?;
                         Ok(CommandGroup::Set(SetCommand::SCard(
-                            crate::process::set::scard::SCardCommandPara::new(
-                                key.to_string(),
-                                Parameter::new(),
-                            ),
+                            crate::process::set::scard::SCardCommandPara::new(key.to_string()),
                         )))
                     }
                     "smembers" => {
@@ -451,7 +637,6 @@ impl TryFrom<Resp> for CommandGroup {
                         Ok(CommandGroup::Set(SetCommand::SMembers(
                             crate::process::set::smembers::SMembersCommandPara::new(
                                 key.to_string(),
-                                Parameter::new(),
                             ),
                         )))
                     }
@@ -466,7 +651,6 @@ impl TryFrom<Resp> for CommandGroup {
                             crate::process::set::srem::SRemCommandPara::new(
                                 key.to_string(),
                                 members,
-                                Parameter::new(),
                             ),
                         )))
                     }
@@ -477,11 +661,21 @@ impl TryFrom<Resp> for CommandGroup {
                             crate::process::set::sismember::SIsMemberCommandPara::new(
                                 key.to_string(),
                                 member.to_string(),
-                                Parameter::new(),
                             ),
                         )))
                     }
 
+                    "sscan" => {
+                        let key = try_exact_bulk_string(iter.next())?;
+                        let scan = parse_element_scan(iter)?;
+                        info!("🔍 SSCAN operation: key={}, scan={:?}", key, scan);
+                        Ok(CommandGroup::Set(SetCommand::SScan(
+                            crate::process::set::sscan::SScanCommandPara::new(
+                                key.to_string(),
+                                scan,
+                            ),
+                        )))
+                    }
                     // Sorted Set commands
                     "zadd" => {
                         let key = try_exact_bulk_string(iter.next())?;
@@ -491,7 +685,7 @@ impl TryFrom<Resp> for CommandGroup {
                             .map(|item| try_exact_bulk_string(Some(item)))
                             .collect::<Result<Vec<_>, _>>()?;
 
-                        if args.len() % 2 != 0 {
+                        if !args.len().is_multiple_of(2) {
                             return Err(anyhow::anyhow!("wrong number of arguments for ZADD"));
                         }
 
@@ -512,7 +706,6 @@ impl TryFrom<Resp> for CommandGroup {
                             crate::process::sorted_set::zadd::ZAddCommandPara::new(
                                 key.to_string(),
                                 score_members,
-                                Parameter::new(),
                             ),
                         )))
                     }
@@ -521,7 +714,17 @@ impl TryFrom<Resp> for CommandGroup {
                         Ok(CommandGroup::SortedSet(SortedSetCommand::ZCard(
                             crate::process::sorted_set::zcard::ZCardCommandPara::new(
                                 key.to_string(),
-                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "zscan" => {
+                        let key = try_exact_bulk_string(iter.next())?;
+                        let scan = parse_element_scan(iter)?;
+                        info!("🔍 ZSCAN operation: key={}, scan={:?}", key, scan);
+                        Ok(CommandGroup::SortedSet(SortedSetCommand::ZScan(
+                            crate::process::sorted_set::zscan::ZScanCommandPara::new(
+                                key.to_string(),
+                                scan,
                             ),
                         )))
                     }
@@ -532,7 +735,6 @@ impl TryFrom<Resp> for CommandGroup {
                             crate::process::sorted_set::zscore::ZScoreCommandPara::new(
                                 key.to_string(),
                                 member.to_string(),
-                                Parameter::new(),
                             ),
                         )))
                     }
@@ -547,7 +749,6 @@ impl TryFrom<Resp> for CommandGroup {
                             crate::process::sorted_set::zrem::ZRemCommandPara::new(
                                 key.to_string(),
                                 members,
-                                Parameter::new(),
                             ),
                         )))
                     }
@@ -566,25 +767,27 @@ impl TryFrom<Resp> for CommandGroup {
                     }
 
                     "client" => {
-                        // CLIENT命令 (如CLIENT SETNAME)，简单返回OK
-                        debug!("CLIENT command received, returning OK");
-                        Ok(CommandGroup::String(StringCommand::Set(
-                            SetCommandPara::new(
-                                Some("__client__".to_string()),
-                                Some("OK".to_string()),
+                        let subcommand = try_exact_bulk_string(iter.next())?;
+                        let args = iter
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok(CommandGroup::Connection(ConnectionCommand::Client(
+                            crate::process::connection::client::ClientCommandPara::new(
+                                subcommand.to_string(),
+                                args,
                                 Parameter::new(),
                             ),
                         )))
                     }
                     "info" => {
                         // INFO命令，返回真实的服务器信息
-                        let section = iter
-                            .next()
-                            .map(|s| try_exact_bulk_string(Some(s)).unwrap_or_default());
-                        info!("ℹ️ INFO operation: section={:?}", section);
+                        let sections = iter
+                            .map(|s| try_exact_bulk_string(Some(s)).map(str::to_owned))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        info!("ℹ️ INFO operation: sections={:?}", sections);
                         Ok(CommandGroup::String(StringCommand::Info(
                             crate::process::string::info::InfoCommandPara::new(
-                                section.map(|s| s.to_string()),
+                                sections,
                                 Parameter::new(),
                             ),
                         )))
@@ -598,6 +801,7 @@ impl TryFrom<Resp> for CommandGroup {
 
                         let mut pattern = None;
                         let mut count = None;
+                        let mut key_type = None;
 
                         // 解析可选参数
                         while let Some(arg) = iter.next() {
@@ -620,6 +824,13 @@ impl TryFrom<Resp> for CommandGroup {
                                         );
                                     }
                                 }
+                                "TYPE" => {
+                                    if let Some(type_arg) = iter.next() {
+                                        key_type = Some(
+                                            try_exact_bulk_string(Some(type_arg))?.to_string(),
+                                        );
+                                    }
+                                }
                                 _ => {
                                     // 忽略未知参数
                                 }
@@ -627,25 +838,29 @@ impl TryFrom<Resp> for CommandGroup {
                         }
 
                         info!(
-                            "🔍 SCAN operation: cursor={}, pattern={:?}, count={:?}",
-                            cursor, pattern, count
+                            "🔍 SCAN operation: cursor={}, pattern={:?}, count={:?}, type={:?}",
+                            cursor, pattern, count, key_type
                         );
                         Ok(CommandGroup::String(StringCommand::Scan(
                             crate::process::string::scan::ScanCommandPara::new(
                                 cursor,
                                 pattern,
                                 count,
+                                key_type,
                                 Parameter::new(),
                             ),
                         )))
                     }
+                    "monitor" => Ok(CommandGroup::Connection(ConnectionCommand::Monitor(
+                        crate::process::connection::monitor::MonitorCommandPara::new(
+                            Parameter::new(),
+                        ),
+                    ))),
                     "select" => {
-                        // SELECT命令，选择数据库，简单返回OK
-                        debug!("SELECT command received, returning OK");
-                        Ok(CommandGroup::String(StringCommand::Set(
-                            SetCommandPara::new(
-                                Some("__select__".to_string()),
-                                Some("OK".to_string()),
+                        let index = try_exact_bulk_string(iter.next())?;
+                        Ok(CommandGroup::Connection(ConnectionCommand::Select(
+                            crate::process::connection::select::SelectCommandPara::new(
+                                index.to_string(),
                                 Parameter::new(),
                             ),
                         )))
@@ -662,17 +877,372 @@ impl TryFrom<Resp> for CommandGroup {
                         )))
                     }
 
-                    comm => {
-                        debug!("Unsupported command: {}, returning generic OK", comm);
-                        // 对于不支持的命令，返回一个友好的错误而不是panic
-                        Ok(CommandGroup::String(StringCommand::Set(
-                            SetCommandPara::new(
-                                Some("__unsupported__".to_string()),
-                                Some("OK".to_string()),
+                    // Replication commands
+                    "replicaof" | "slaveof" => {
+                        let host = try_exact_bulk_string(iter.next())?;
+                        let port = try_exact_bulk_string(iter.next())?;
+                        info!("🔗 REPLICAOF operation: host='{}', port='{}'", host, port);
+                        Ok(CommandGroup::Replication(ReplicationCommand::ReplicaOf(
+                            crate::process::replication::replicaof::ReplicaOfCommandPara::new(
+                                host.to_string(),
+                                port.to_string(),
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "replconf" => {
+                        let args: Vec<&str> = iter
+                            .map(|item| try_exact_bulk_string(Some(item)))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        let options = args
+                            .chunks(2)
+                            .map(|chunk| {
+                                (
+                                    chunk[0].to_string(),
+                                    chunk.get(1).unwrap_or(&"").to_string(),
+                                )
+                            })
+                            .collect();
+                        Ok(CommandGroup::Replication(ReplicationCommand::ReplConf(
+                            crate::process::replication::replconf::ReplConfCommandPara::new(
+                                options,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "psync" => {
+                        let replid = try_exact_bulk_string(iter.next())?;
+                        let offset = try_exact_bulk_string(iter.next())?;
+                        let offset = offset
+                            .parse::<i64>()
+                            .map_err(|_| anyhow::anyhow!("invalid replication offset"))?;
+                        info!("🔗 PSYNC operation: replid='{}', offset={}", replid, offset);
+                        Ok(CommandGroup::Replication(ReplicationCommand::PSync(
+                            crate::process::replication::psync::PSyncCommandPara::new(
+                                replid.to_string(),
+                                offset,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "role" => Ok(CommandGroup::Replication(ReplicationCommand::Role(
+                        crate::process::replication::role::RoleCommandPara::new(Parameter::new()),
+                    ))),
+
+                    // Cluster commands
+                    "cluster" => {
+                        let subcommand = try_exact_bulk_string(iter.next())?;
+                        let args = iter
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        info!("🧩 CLUSTER operation: {} {:?}", subcommand, args);
+                        Ok(CommandGroup::Cluster(ClusterCommand::Cluster(
+                            crate::process::cluster::cluster_cmd::ClusterCommandPara::new(
+                                subcommand.to_string(),
+                                args,
                                 Parameter::new(),
                             ),
                         )))
                     }
+                    "asking" => Ok(CommandGroup::Cluster(ClusterCommand::Asking(
+                        crate::process::cluster::asking::AskingCommandPara::new(Parameter::new()),
+                    ))),
+                    "migrate" => {
+                        // MIGRATE host port key|"" destination-db timeout [COPY] [REPLACE] [KEYS key ...]
+                        let args = iter
+                            .map(|item| try_exact_bulk_string(Some(item)))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        if args.len() < 5 {
+                            return Err(anyhow::anyhow!(
+                                "wrong number of arguments for 'migrate' command"
+                            ));
+                        }
+                        let port = args[1]
+                            .parse::<u16>()
+                            .map_err(|_| anyhow::anyhow!("invalid port"))?;
+                        let timeout_millis = args[4].parse::<u64>().map_err(|_| {
+                            anyhow::anyhow!("value is not an integer or out of range")
+                        })?;
+                        let mut keys = Vec::new();
+                        if !args[2].is_empty() {
+                            keys.push(args[2].to_string());
+                        }
+                        let (mut copy, mut replace) = (false, false);
+                        let mut rest = args[5..].iter();
+                        while let Some(arg) = rest.next() {
+                            match arg.to_uppercase().as_str() {
+                                "COPY" => copy = true,
+                                "REPLACE" => replace = true,
+                                "AUTH" => {
+                                    rest.next();
+                                }
+                                "AUTH2" => {
+                                    rest.next();
+                                    rest.next();
+                                }
+                                "KEYS" => {
+                                    if !keys.is_empty() {
+                                        return Err(anyhow::anyhow!(
+                                            "When using MIGRATE KEYS option, the key argument must be set to the empty string"
+                                        ));
+                                    }
+                                    keys.extend(rest.by_ref().map(|key| key.to_string()));
+                                }
+                                _ => return Err(anyhow::anyhow!("syntax error")),
+                            }
+                        }
+                        info!("🚚 MIGRATE operation: {}:{} keys={:?}", args[0], port, keys);
+                        Ok(CommandGroup::Cluster(ClusterCommand::Migrate(
+                            crate::process::cluster::migrate::MigrateCommandPara::new(
+                                MigrateOptions {
+                                    host: args[0].to_string(),
+                                    port,
+                                    keys,
+                                    timeout_millis,
+                                    copy,
+                                    replace,
+                                },
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+
+                    // Pub/Sub commands
+                    "subscribe" | "psubscribe" | "ssubscribe" => {
+                        let kind = match cmd.as_str() {
+                            "psubscribe" => Kind::Pattern,
+                            "ssubscribe" => Kind::Shard,
+                            _ => Kind::Channel,
+                        };
+                        let channels = iter
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        if channels.is_empty() {
+                            return Err(anyhow::anyhow!(
+                                "wrong number of arguments for '{}' command",
+                                cmd
+                            ));
+                        }
+                        info!("📡 {} operation: {:?}", cmd.to_uppercase(), channels);
+                        Ok(CommandGroup::PubSub(PubSubCommand::Subscribe(
+                            crate::process::pubsub::subscribe::SubscribeCommandPara::new(
+                                kind,
+                                channels,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "unsubscribe" | "punsubscribe" | "sunsubscribe" => {
+                        let kind = match cmd.as_str() {
+                            "punsubscribe" => Kind::Pattern,
+                            "sunsubscribe" => Kind::Shard,
+                            _ => Kind::Channel,
+                        };
+                        let channels = iter
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok(CommandGroup::PubSub(PubSubCommand::Unsubscribe(
+                            crate::process::pubsub::unsubscribe::UnsubscribeCommandPara::new(
+                                kind,
+                                channels,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "publish" | "spublish" => {
+                        let channel = try_exact_bulk_string(iter.next())?;
+                        let message = try_exact_bulk_string(iter.next())?;
+                        Ok(CommandGroup::PubSub(PubSubCommand::Publish(
+                            crate::process::pubsub::publish::PublishCommandPara::new(
+                                channel.to_string(),
+                                message.to_string(),
+                                cmd == "spublish",
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "pubsub" => {
+                        let subcommand = try_exact_bulk_string(iter.next())?;
+                        let args = iter
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok(CommandGroup::PubSub(PubSubCommand::PubSub(
+                            crate::process::pubsub::pubsub_cmd::PubSubCommandPara::new(
+                                subcommand.to_string(),
+                                args,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+
+                    // Connection commands
+                    "hello" => {
+                        // HELLO [protover [AUTH username password] [SETNAME clientname]]
+                        // 无法识别的协议版本记为 0，由连接回复 NOPROTO
+                        let protover = iter
+                            .next()
+                            .map(|item| try_exact_bulk_string(Some(item)))
+                            .transpose()?
+                            .map(|ver| ver.parse::<u8>().unwrap_or(0));
+                        let mut auth = None;
+                        let mut setname = None;
+                        while let Some(item) = iter.next() {
+                            match try_exact_bulk_string(Some(item))?.to_uppercase().as_str() {
+                                "AUTH" => {
+                                    let username = try_exact_bulk_string(iter.next())?;
+                                    let password = try_exact_bulk_string(iter.next())?;
+                                    auth = Some((username.to_string(), password.to_string()));
+                                }
+                                "SETNAME" => {
+                                    setname = Some(try_exact_bulk_string(iter.next())?.to_string());
+                                }
+                                other => {
+                                    return Err(anyhow::anyhow!(
+                                        "Syntax error in HELLO option '{}'",
+                                        other
+                                    ))
+                                }
+                            }
+                        }
+                        Ok(CommandGroup::Connection(ConnectionCommand::Hello(
+                            crate::process::connection::hello::HelloCommandPara::new(
+                                protover,
+                                auth,
+                                setname,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+
+                    "auth" => {
+                        // AUTH [username] password
+                        let args = iter
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        let (username, password) = match args.as_slice() {
+                            [password] => (None, password.clone()),
+                            [username, password] => (Some(username.clone()), password.clone()),
+                            _ => {
+                                return Err(anyhow::anyhow!(
+                                    "wrong number of arguments for 'auth' command"
+                                ))
+                            }
+                        };
+                        Ok(CommandGroup::Connection(ConnectionCommand::Auth(
+                            crate::process::connection::auth::AuthCommandPara::new(
+                                username,
+                                password,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+
+                    // Server commands
+                    "dbsize" => Ok(CommandGroup::Server(ServerCommand::DbSize(
+                        crate::process::server::dbsize::DbSizeCommandPara::new(Parameter::new()),
+                    ))),
+                    "flushdb" | "flushall" => {
+                        let mode = iter
+                            .next()
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .transpose()?;
+                        Ok(CommandGroup::Server(ServerCommand::FlushDb(
+                            crate::process::server::flushdb::FlushDbCommandPara::new(
+                                cmd == "flushall",
+                                mode,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "swapdb" => {
+                        let first = try_exact_bulk_string(iter.next())?;
+                        let second = try_exact_bulk_string(iter.next())?;
+                        Ok(CommandGroup::Server(ServerCommand::SwapDb(
+                            crate::process::server::swapdb::SwapDbCommandPara::new(
+                                first.to_string(),
+                                second.to_string(),
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "memory" => {
+                        let subcommand = try_exact_bulk_string(iter.next())?;
+                        let args = iter
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok(CommandGroup::Server(ServerCommand::Memory(
+                            crate::process::server::memory::MemoryCommandPara::new(
+                                subcommand.to_string(),
+                                args,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "slowlog" => {
+                        let subcommand = try_exact_bulk_string(iter.next())?;
+                        let args = iter
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok(CommandGroup::Server(ServerCommand::SlowLog(
+                            crate::process::server::slowlog::SlowLogCommandPara::new(
+                                subcommand.to_string(),
+                                args,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "latency" => {
+                        let subcommand = try_exact_bulk_string(iter.next())?;
+                        let args = iter
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok(CommandGroup::Server(ServerCommand::Latency(
+                            crate::process::server::latency::LatencyCommandPara::new(
+                                subcommand.to_string(),
+                                args,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "shutdown" => {
+                        let args = iter
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok(CommandGroup::Server(ServerCommand::Shutdown(
+                            crate::process::server::shutdown::ShutdownCommandPara::new(
+                                args,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "acl" => {
+                        let subcommand = try_exact_bulk_string(iter.next())?;
+                        let args = iter
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok(CommandGroup::Server(ServerCommand::Acl(
+                            crate::process::server::acl::AclCommandPara::new(
+                                subcommand.to_string(),
+                                args,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+                    "config" => {
+                        let subcommand = try_exact_bulk_string(iter.next())?;
+                        let args = iter
+                            .map(|item| try_exact_bulk_string(Some(item)).map(str::to_string))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok(CommandGroup::Server(ServerCommand::Config(
+                            crate::process::server::config::ConfigCommandPara::new(
+                                subcommand.to_string(),
+                                args,
+                                Parameter::new(),
+                            ),
+                        )))
+                    }
+
+                    comm => Err(anyhow::anyhow!("unknown command '{comm}'")),
                 }
             }
             _ => Err(anyhow::anyhow!("unsupported command")),
@@ -680,6 +1250,35 @@ impl TryFrom<Resp> for CommandGroup {
     }
 }
 
+// 解析 SSCAN/HSCAN/ZSCAN 的游标和 MATCH、COUNT 选项
+fn parse_element_scan<'a>(
+    mut iter: impl Iterator<Item = &'a Resp>,
+) -> Result<crate::process::string::scan::ElementScan, anyhow::Error> {
+    let cursor = try_exact_bulk_string(iter.next())?
+        .parse::<u64>()
+        .map_err(|_| anyhow::anyhow!("invalid cursor"))?;
+    let mut pattern = None;
+    let mut count = None;
+    while let Some(arg) = iter.next() {
+        let value =
+            try_exact_bulk_string(iter.next()).map_err(|_| anyhow::anyhow!("syntax error"))?;
+        match try_exact_bulk_string(Some(arg))?.to_uppercase().as_str() {
+            "MATCH" => pattern = Some(value.to_string()),
+            "COUNT" => {
+                count = Some(
+                    value
+                        .parse::<u64>()
+                        .map_err(|_| anyhow::anyhow!("value is not an integer or out of range"))?,
+                )
+            }
+            _ => return Err(anyhow::anyhow!("syntax error")),
+        }
+    }
+    Ok(crate::process::string::scan::ElementScan::new(
+        cursor, pattern, count,
+    ))
+}
+
 //断言resp类型为bulk string，返回值，其他的类型视为异常
 pub fn try_exact_bulk_string(resp_opt: Option<&Resp>) -> Result<&str, anyhow::Error> {
     match resp_opt {
@@ -700,6 +1299,11 @@ impl crate::Processor for CommandGroup {
             CommandGroup::List(cmd) => cmd.process(data),
             CommandGroup::Set(cmd) => cmd.process(data),
             CommandGroup::SortedSet(cmd) => cmd.process(data),
+            CommandGroup::Replication(cmd) => cmd.process(data),
+            CommandGroup::Cluster(cmd) => cmd.process(data),
+            CommandGroup::PubSub(cmd) => cmd.process(data),
+            CommandGroup::Connection(cmd) => cmd.process(data),
+            CommandGroup::Server(cmd) => cmd.process(data),
         }
     }
 }
@@ -721,6 +1325,17 @@ impl crate::Processor for StringCommand {
             StringCommand::Expire(cmd) => cmd.process(data),
             StringCommand::Ttl(cmd) => cmd.process(data),
             StringCommand::Persist(cmd) => cmd.process(data),
+            StringCommand::PExpireAt(cmd) => cmd.process(data),
+            StringCommand::Dump(cmd) => cmd.process(data),
+            StringCommand::Restore(cmd) => cmd.process(data),
+            StringCommand::Move(cmd) => cmd.process(data),
+            StringCommand::Rename(cmd) => cmd.process(data),
+            StringCommand::Copy(cmd) => cmd.process(data),
+            StringCommand::RandomKey(cmd) => cmd.process(data),
+            StringCommand::Touch(cmd) => cmd.process(data),
+            StringCommand::Unlink(cmd) => cmd.process(data),
+            StringCommand::Object(cmd) => cmd.process(data),
+            StringCommand::StrLen(cmd) => cmd.process(data),
         }
     }
 }
@@ -735,6 +1350,8 @@ impl crate::Processor for HashCommand {
             HashCommand::HGetAll(cmd) => cmd.process(data),
             HashCommand::HKeys(cmd) => cmd.process(data),
             HashCommand::HVals(cmd) => cmd.process(data),
+            HashCommand::HLen(cmd) => cmd.process(data),
+            HashCommand::HScan(cmd) => cmd.process(data),
         }
     }
 }
@@ -769,6 +1386,7 @@ impl crate::Processor for SetCommand {
             SetCommand::SRandMember(cmd) => cmd.process(data),
             SetCommand::SRem(cmd) => cmd.process(data),
             SetCommand::SUnion(cmd) => cmd.process(data),
+            SetCommand::SScan(cmd) => cmd.process(data),
         }
     }
 }
@@ -779,16 +1397,74 @@ impl crate::Processor for SortedSetCommand {
         match self {
             SortedSetCommand::ZAdd(cmd) => cmd.process(data),
             SortedSetCommand::ZCard(cmd) => cmd.process(data),
-            SortedSetCommand::ZCount(cmd) => cmd.process(data),
-            SortedSetCommand::ZIncrBy(cmd) => cmd.process(data),
-            SortedSetCommand::ZRange(cmd) => cmd.process(data),
-            SortedSetCommand::ZRank(cmd) => cmd.process(data),
             SortedSetCommand::ZRem(cmd) => cmd.process(data),
-            SortedSetCommand::ZRemRangeByRank(cmd) => cmd.process(data),
-            SortedSetCommand::ZRemRangeByScore(cmd) => cmd.process(data),
-            SortedSetCommand::ZRevRange(cmd) => cmd.process(data),
-            SortedSetCommand::ZRevRank(cmd) => cmd.process(data),
             SortedSetCommand::ZScore(cmd) => cmd.process(data),
+            SortedSetCommand::ZScan(cmd) => cmd.process(data),
+        }
+    }
+}
+
+// 手动实现Processor trait for ReplicationCommand
+impl crate::Processor for ReplicationCommand {
+    fn process(&self, data: &crate::Data) -> Result<Resp, anyhow::Error> {
+        match self {
+            ReplicationCommand::ReplicaOf(cmd) => cmd.process(data),
+            ReplicationCommand::ReplConf(cmd) => cmd.process(data),
+            ReplicationCommand::PSync(cmd) => cmd.process(data),
+            ReplicationCommand::Role(cmd) => cmd.process(data),
+        }
+    }
+}
+
+// 手动实现Processor trait for ClusterCommand
+impl crate::Processor for ClusterCommand {
+    fn process(&self, data: &crate::Data) -> Result<Resp, anyhow::Error> {
+        match self {
+            ClusterCommand::Cluster(cmd) => cmd.process(data),
+            ClusterCommand::Asking(cmd) => cmd.process(data),
+            ClusterCommand::Migrate(cmd) => cmd.process(data),
+        }
+    }
+}
+
+// 手动实现Processor trait for PubSubCommand
+impl crate::Processor for PubSubCommand {
+    fn process(&self, data: &crate::Data) -> Result<Resp, anyhow::Error> {
+        match self {
+            PubSubCommand::Subscribe(cmd) => cmd.process(data),
+            PubSubCommand::Unsubscribe(cmd) => cmd.process(data),
+            PubSubCommand::Publish(cmd) => cmd.process(data),
+            PubSubCommand::PubSub(cmd) => cmd.process(data),
+        }
+    }
+}
+
+// 手动实现Processor trait for ConnectionCommand
+impl crate::Processor for ConnectionCommand {
+    fn process(&self, data: &crate::Data) -> Result<Resp, anyhow::Error> {
+        match self {
+            ConnectionCommand::Hello(cmd) => cmd.process(data),
+            ConnectionCommand::Select(cmd) => cmd.process(data),
+            ConnectionCommand::Monitor(cmd) => cmd.process(data),
+            ConnectionCommand::Client(cmd) => cmd.process(data),
+            ConnectionCommand::Auth(cmd) => cmd.process(data),
+        }
+    }
+}
+
+// 手动实现Processor trait for ServerCommand
+impl crate::Processor for ServerCommand {
+    fn process(&self, data: &crate::Data) -> Result<Resp, anyhow::Error> {
+        match self {
+            ServerCommand::Config(cmd) => cmd.process(data),
+            ServerCommand::DbSize(cmd) => cmd.process(data),
+            ServerCommand::FlushDb(cmd) => cmd.process(data),
+            ServerCommand::SwapDb(cmd) => cmd.process(data),
+            ServerCommand::Memory(cmd) => cmd.process(data),
+            ServerCommand::SlowLog(cmd) => cmd.process(data),
+            ServerCommand::Latency(cmd) => cmd.process(data),
+            ServerCommand::Acl(cmd) => cmd.process(data),
+            ServerCommand::Shutdown(cmd) => cmd.process(data),
         }
     }
 }
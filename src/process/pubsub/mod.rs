@@ -0,0 +1,12 @@
+pub mod publish;
+pub mod pubsub_cmd;
+pub mod subscribe;
+pub mod unsubscribe;
+
+#[derive(Debug)]
+pub enum PubSubCommand {
+    Subscribe(subscribe::SubscribeCommandPara),
+    Unsubscribe(unsubscribe::UnsubscribeCommandPara),
+    Publish(publish::PublishCommandPara),
+    PubSub(pubsub_cmd::PubSubCommandPara),
+}
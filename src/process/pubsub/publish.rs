@@ -0,0 +1,36 @@
+use tracing::info;
+
+use crate::{process::Parameter, Data, Integers, Processor, Resp};
+
+#[derive(Debug)]
+pub struct PublishCommandPara {
+    pub channel: String,
+    pub message: String,
+    // SPUBLISH 发布到分片频道
+    pub shard: bool,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl PublishCommandPara {
+    pub fn new(channel: String, message: String, shard: bool, para: Parameter) -> Self {
+        Self {
+            channel,
+            message,
+            shard,
+            para,
+        }
+    }
+}
+
+impl Processor for PublishCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        let receivers = if self.shard {
+            data.pubsub.spublish(&self.channel, &self.message)
+        } else {
+            data.pubsub.publish(&self.channel, &self.message)
+        };
+        info!("📢 PUBLISH '{}' -> {} receivers", self.channel, receivers);
+        Ok(Resp::Integers(Integers::new(receivers as i64)))
+    }
+}
@@ -0,0 +1,68 @@
+use tracing::info;
+
+use crate::{
+    process::Parameter, Arrays, BulkStrings, Data, Integers, Processor, Resp, SimpleErrors,
+};
+
+#[derive(Debug)]
+pub struct PubSubCommandPara {
+    pub subcommand: String,
+    pub args: Vec<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl PubSubCommandPara {
+    pub fn new(subcommand: String, args: Vec<String>, para: Parameter) -> Self {
+        Self {
+            subcommand,
+            args,
+            para,
+        }
+    }
+}
+
+impl Processor for PubSubCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("PubSubCommandPara process start: {:?}", &self);
+        let subcommand = self.subcommand.to_lowercase();
+        let reply = match (subcommand.as_str(), self.args.as_slice()) {
+            ("channels" | "shardchannels", [] | [_]) => {
+                let pattern = self.args.first().map(String::as_str);
+                let channels = data
+                    .pubsub
+                    .active_channels(pattern, subcommand == "shardchannels");
+                Resp::Arrays(Arrays::new(
+                    channels
+                        .into_iter()
+                        .map(|channel| Resp::BulkStrings(BulkStrings::new(channel)))
+                        .collect(),
+                ))
+            }
+            ("numsub" | "shardnumsub", channels) => {
+                let shard = subcommand == "shardnumsub";
+                Resp::Arrays(Arrays::new(
+                    channels
+                        .iter()
+                        .flat_map(|channel| {
+                            let count = data.pubsub.num_subscribers(channel, shard);
+                            [
+                                Resp::BulkStrings(BulkStrings::new(channel.clone())),
+                                Resp::Integers(Integers::new(count as i64)),
+                            ]
+                        })
+                        .collect(),
+                ))
+            }
+            ("numpat", []) => Resp::Integers(Integers::new(data.pubsub.num_patterns() as i64)),
+            ("channels" | "shardchannels" | "numpat", _) => Resp::SimpleErrors(SimpleErrors::new(
+                format!("ERR wrong number of arguments for 'pubsub|{subcommand}' command"),
+            )),
+            _ => Resp::SimpleErrors(SimpleErrors::new(format!(
+                "ERR unknown subcommand '{}'. Try PUBSUB HELP.",
+                self.subcommand
+            ))),
+        };
+        Ok(reply)
+    }
+}
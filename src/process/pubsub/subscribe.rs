@@ -0,0 +1,29 @@
+use crate::pubsub::Kind;
+use crate::{process::Parameter, Data, Processor, Resp, SimpleErrors};
+
+#[derive(Debug)]
+pub struct SubscribeCommandPara {
+    pub kind: Kind,
+    pub channels: Vec<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl SubscribeCommandPara {
+    pub fn new(kind: Kind, channels: Vec<String>, para: Parameter) -> Self {
+        Self {
+            kind,
+            channels,
+            para,
+        }
+    }
+}
+
+impl Processor for SubscribeCommandPara {
+    fn process(&self, _data: &Data) -> Result<Resp, anyhow::Error> {
+        // 订阅状态属于连接，由连接自己处理
+        Ok(Resp::SimpleErrors(SimpleErrors::new(
+            "ERR SUBSCRIBE must be handled by the connection".to_owned(),
+        )))
+    }
+}
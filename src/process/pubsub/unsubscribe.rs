@@ -0,0 +1,29 @@
+use crate::pubsub::Kind;
+use crate::{process::Parameter, Data, Processor, Resp, SimpleErrors};
+
+#[derive(Debug)]
+pub struct UnsubscribeCommandPara {
+    pub kind: Kind,
+    pub channels: Vec<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl UnsubscribeCommandPara {
+    pub fn new(kind: Kind, channels: Vec<String>, para: Parameter) -> Self {
+        Self {
+            kind,
+            channels,
+            para,
+        }
+    }
+}
+
+impl Processor for UnsubscribeCommandPara {
+    fn process(&self, _data: &Data) -> Result<Resp, anyhow::Error> {
+        // 订阅状态属于连接，由连接自己处理
+        Ok(Resp::SimpleErrors(SimpleErrors::new(
+            "ERR UNSUBSCRIBE must be handled by the connection".to_owned(),
+        )))
+    }
+}
@@ -0,0 +1,12 @@
+pub mod psync;
+pub mod replconf;
+pub mod replicaof;
+pub mod role;
+
+#[derive(Debug)]
+pub enum ReplicationCommand {
+    ReplicaOf(replicaof::ReplicaOfCommandPara),
+    ReplConf(replconf::ReplConfCommandPara),
+    PSync(psync::PSyncCommandPara),
+    Role(role::RoleCommandPara),
+}
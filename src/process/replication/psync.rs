@@ -0,0 +1,31 @@
+use crate::{process::Parameter, Data, Processor, Resp, SimpleErrors};
+
+/// PSYNC <replid> <offset>
+///
+/// 由连接处理循环接管整个连接（见 `replication::master::serve_replica`），
+/// 不会走普通的命令处理流程。
+#[derive(Debug)]
+pub struct PSyncCommandPara {
+    pub replid: String,
+    pub offset: i64,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl PSyncCommandPara {
+    pub fn new(replid: String, offset: i64, para: Parameter) -> Self {
+        Self {
+            replid,
+            offset,
+            para,
+        }
+    }
+}
+
+impl Processor for PSyncCommandPara {
+    fn process(&self, _data: &Data) -> Result<Resp, anyhow::Error> {
+        Ok(Resp::SimpleErrors(SimpleErrors::new(
+            "ERR PSYNC must be handled by the connection".to_owned(),
+        )))
+    }
+}
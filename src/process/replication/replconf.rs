@@ -0,0 +1,32 @@
+use tracing::info;
+
+use crate::{process::Parameter, Data, Processor, Resp, SimpleStringsData};
+
+/// REPLCONF <option> <value> ...，复制握手阶段副本用来告知自身信息
+#[derive(Debug)]
+pub struct ReplConfCommandPara {
+    pub options: Vec<(String, String)>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl ReplConfCommandPara {
+    pub fn new(options: Vec<(String, String)>, para: Parameter) -> Self {
+        Self { options, para }
+    }
+
+    /// 副本真正监听的端口，INFO replication 中展示的是这个端口
+    pub fn listening_port(&self) -> Option<u16> {
+        self.options
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case("listening-port"))
+            .and_then(|(_, v)| v.parse().ok())
+    }
+}
+
+impl Processor for ReplConfCommandPara {
+    fn process(&self, _data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("ReplConfCommandPara process start: {:?}", &self);
+        Ok(Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned())))
+    }
+}
@@ -0,0 +1,51 @@
+use tracing::info;
+
+use crate::replication::MasterAddr;
+use crate::{process::Parameter, Data, Processor, Resp, SimpleErrors, SimpleStringsData};
+
+#[derive(Debug)]
+pub struct ReplicaOfCommandPara {
+    pub host: String,
+    pub port: String,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl ReplicaOfCommandPara {
+    pub fn new(host: String, port: String, para: Parameter) -> Self {
+        Self { host, port, para }
+    }
+}
+
+impl Processor for ReplicaOfCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("ReplicaOfCommandPara process start: {:?}", &self);
+
+        // REPLICAOF NO ONE：停止复制，提升为主节点
+        if self.host.eq_ignore_ascii_case("no") && self.port.eq_ignore_ascii_case("one") {
+            data.replication.replicaof_no_one();
+            return Ok(Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned())));
+        }
+
+        let port = match self.port.parse::<u16>() {
+            Ok(port) if port > 0 => port,
+            _ => {
+                return Ok(Resp::SimpleErrors(SimpleErrors::new(
+                    "ERR Invalid master port".to_owned(),
+                )))
+            }
+        };
+
+        let addr = MasterAddr {
+            host: self.host.clone(),
+            port,
+        };
+        if data.replication.replicaof(addr) {
+            Ok(Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned())))
+        } else {
+            Ok(Resp::SimpleStrings(SimpleStringsData::new(
+                "OK Already connected to specified master".to_owned(),
+            )))
+        }
+    }
+}
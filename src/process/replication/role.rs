@@ -0,0 +1,23 @@
+use tracing::info;
+
+use crate::{process::Parameter, Data, Processor, Resp};
+
+#[derive(Debug)]
+pub struct RoleCommandPara {
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl RoleCommandPara {
+    pub fn new(para: Parameter) -> Self {
+        Self { para }
+    }
+}
+
+impl Processor for RoleCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        let role = data.replication.role();
+        info!("👥 ROLE -> {:?}", role);
+        Ok(role)
+    }
+}
@@ -0,0 +1,385 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::Rng;
+use tracing::info;
+
+use crate::acl::{self, LogEntry};
+use crate::{
+    client, process::Parameter, Arrays, BulkStrings, Data, Integers, Nulls, Processor, Resp,
+    SimpleErrors, SimpleStringsData,
+};
+
+// ACL LOG 默认返回的条数
+const DEFAULT_LOG_COUNT: usize = 10;
+// ACL GENPASS 默认的位数
+const DEFAULT_GENPASS_BITS: usize = 256;
+
+const HELP: &[&str] = &[
+    "ACL <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+    "CAT [<category>]",
+    "    List all commands that belong to <category>, or all command categories",
+    "    when no category is specified.",
+    "DELUSER <username> [<username> ...]",
+    "    Delete a list of users.",
+    "DRYRUN <username> <command> [<arg> ...]",
+    "    Returns whether the user can execute the given command without executing the command.",
+    "GETUSER <username>",
+    "    Get the user's details.",
+    "GENPASS [<bits>]",
+    "    Generate a secure 256-bit user password. The optional `bits` argument can",
+    "    be used to specify a different size.",
+    "LIST",
+    "    Show users details in config file format.",
+    "LOAD",
+    "    Reload users from the ACL file.",
+    "LOG [<count> | RESET]",
+    "    Show the ACL log entries.",
+    "SAVE",
+    "    Save the current config to the ACL file.",
+    "SETUSER <username> <attribute> [<attribute> ...]",
+    "    Create or modify a user with the specified attributes.",
+    "USERS",
+    "    List all the registered usernames.",
+    "WHOAMI",
+    "    Return the current connection username.",
+    "HELP",
+    "    Print this help.",
+];
+
+#[derive(Debug)]
+pub struct AclCommandPara {
+    pub subcommand: String,
+    pub args: Vec<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl AclCommandPara {
+    pub fn new(subcommand: String, args: Vec<String>, para: Parameter) -> Self {
+        Self {
+            subcommand,
+            args,
+            para,
+        }
+    }
+
+    // ACL SETUSER username [rule ...]
+    fn set_user(&self, data: &Data) -> Resp {
+        let Some((name, rules)) = self.args.split_first() else {
+            return error("ERR wrong number of arguments for 'acl|setuser' command");
+        };
+        let rules: Vec<&str> = rules.iter().map(String::as_str).collect();
+        match data.acl.set_user(name, &rules) {
+            Ok(()) => ok(),
+            Err(e) => error(&e),
+        }
+    }
+
+    // ACL GETUSER username
+    fn get_user(&self, data: &Data) -> Resp {
+        let [name] = self.args.as_slice() else {
+            return error("ERR wrong number of arguments for 'acl|getuser' command");
+        };
+        let Some(user) = data.acl.user(name) else {
+            return Resp::Nulls(Nulls::new());
+        };
+        Resp::Arrays(Arrays::new(vec![
+            bulk("flags"),
+            Resp::Arrays(Arrays::new(user.flags().into_iter().map(bulk).collect())),
+            bulk("passwords"),
+            Resp::Arrays(Arrays::new(
+                user.passwords().iter().map(|hash| bulk(hash)).collect(),
+            )),
+            bulk("commands"),
+            bulk(&user.describe_commands()),
+            bulk("keys"),
+            bulk(&user.describe_keys()),
+            bulk("channels"),
+            bulk(&user.describe_channels()),
+            bulk("selectors"),
+            Resp::Arrays(Arrays::new(Vec::new())),
+        ]))
+    }
+
+    // ACL DELUSER username [username ...]，同时断开以这些用户认证的连接
+    fn delete_users(&self, data: &Data) -> Resp {
+        if self.args.is_empty() {
+            return error("ERR wrong number of arguments for 'acl|deluser' command");
+        }
+        match data.acl.delete_users(&self.args) {
+            Ok(deleted) => {
+                kill_orphaned_clients(data);
+                integer(deleted as i64)
+            }
+            Err(e) => error(&e),
+        }
+    }
+
+    // ACL CAT [category]
+    fn cat(&self) -> Resp {
+        match self.args.as_slice() {
+            [] => Resp::Arrays(Arrays::new(
+                acl::CATEGORIES.iter().map(|(name, _)| bulk(name)).collect(),
+            )),
+            [category] => match acl::category(category) {
+                Some(bit) => Resp::Arrays(Arrays::new(
+                    acl::category_commands(bit)
+                        .iter()
+                        .map(|name| bulk(name))
+                        .collect(),
+                )),
+                None => error(&format!("ERR Unknown category '{category}'")),
+            },
+            _ => error("ERR wrong number of arguments for 'acl|cat' command"),
+        }
+    }
+
+    // ACL DRYRUN username command [arg ...]
+    fn dry_run(&self, data: &Data) -> Resp {
+        let [name, command, ..] = self.args.as_slice() else {
+            return error("ERR wrong number of arguments for 'acl|dryrun' command");
+        };
+        let Some(user) = data.acl.user(name) else {
+            return error(&format!("ERR User '{name}' not found"));
+        };
+        if crate::process::command_table::lookup(command).is_none() {
+            return error(&format!("ERR Command '{command}' not found"));
+        }
+        let argv: Vec<&str> = self.args[1..].iter().map(String::as_str).collect();
+        match user.check(&argv) {
+            Ok(()) => ok(),
+            Err(denial) => bulk(&denial.describe(name)),
+        }
+    }
+
+    // ACL LOG [count | RESET]
+    fn log(&self, data: &Data) -> Resp {
+        let count = match self.args.as_slice() {
+            [] => DEFAULT_LOG_COUNT,
+            [arg] if arg.eq_ignore_ascii_case("reset") => {
+                data.acl.reset_log();
+                return ok();
+            }
+            [count] => match count.parse::<usize>() {
+                Ok(count) => count,
+                Err(_) => return error("ERR value is out of range, must be positive"),
+            },
+            _ => return error("ERR wrong number of arguments for 'acl|log' command"),
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        Resp::Arrays(Arrays::new(
+            data.acl
+                .log_entries(count)
+                .iter()
+                .map(|entry| log_entry(entry, now))
+                .collect(),
+        ))
+    }
+
+    // ACL GENPASS [bits]
+    fn genpass(&self) -> Resp {
+        let bits = match self.args.as_slice() {
+            [] => DEFAULT_GENPASS_BITS,
+            [bits] => match bits.parse::<usize>() {
+                Ok(bits) if (1..=4096).contains(&bits) => bits,
+                _ => return error("ERR ACL GENPASS argument must be the number of bits for the output password, a positive number up to 4096"),
+            },
+            _ => return error("ERR wrong number of arguments for 'acl|genpass' command"),
+        };
+        let mut rng = rand::thread_rng();
+        let password: String = (0..bits.div_ceil(4))
+            .map(|_| char::from_digit(rng.gen_range(0..16), 16).unwrap_or('0'))
+            .collect();
+        bulk(&password)
+    }
+}
+
+impl Processor for AclCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("AclCommandPara process start: {:?}", &self.subcommand);
+        let subcommand = self.subcommand.to_lowercase();
+        let reply = match subcommand.as_str() {
+            "setuser" => self.set_user(data),
+            "getuser" => self.get_user(data),
+            "deluser" => self.delete_users(data),
+            "cat" => self.cat(),
+            "dryrun" => self.dry_run(data),
+            "log" => self.log(data),
+            "genpass" => self.genpass(),
+            "list" if self.args.is_empty() => Resp::Arrays(Arrays::new(
+                data.acl.list().iter().map(|line| bulk(line)).collect(),
+            )),
+            "users" if self.args.is_empty() => Resp::Arrays(Arrays::new(
+                data.acl.usernames().iter().map(|name| bulk(name)).collect(),
+            )),
+            "whoami" if self.args.is_empty() => bulk(
+                &client::current()
+                    .map(|client| client.user())
+                    .unwrap_or_else(|| acl::DEFAULT_USER.to_owned()),
+            ),
+            "save" if self.args.is_empty() => match data.acl.save() {
+                Ok(()) => ok(),
+                Err(e) => error(&e),
+            },
+            "load" if self.args.is_empty() => match data.acl.load() {
+                Ok(()) => {
+                    kill_orphaned_clients(data);
+                    ok()
+                }
+                Err(e) => error(&e),
+            },
+            "help" if self.args.is_empty() => {
+                Resp::Arrays(Arrays::new(HELP.iter().map(|line| bulk(line)).collect()))
+            }
+            "list" | "users" | "whoami" | "save" | "load" | "help" => error(&format!(
+                "ERR wrong number of arguments for 'acl|{subcommand}' command"
+            )),
+            _ => error(&format!(
+                "ERR unknown subcommand '{}'. Try ACL HELP.",
+                self.subcommand
+            )),
+        };
+        Ok(reply)
+    }
+}
+
+// 断开认证用户已经不存在的连接
+fn kill_orphaned_clients(data: &Data) {
+    for client in data.clients.list() {
+        if client.is_authenticated() && !data.acl.exists(&client.user()) {
+            info!(
+                "🔪 Killing client {} of removed user {}",
+                client.addr,
+                client.user()
+            );
+            client.kill();
+        }
+    }
+}
+
+fn log_entry(entry: &LogEntry, now: u64) -> Resp {
+    let age = now.saturating_sub(entry.created) as f64 / 1000.0;
+    Resp::Arrays(Arrays::new(vec![
+        bulk("count"),
+        integer(entry.count as i64),
+        bulk("reason"),
+        bulk(entry.reason.as_str()),
+        bulk("context"),
+        bulk(&entry.context),
+        bulk("object"),
+        bulk(&entry.object),
+        bulk("username"),
+        bulk(&entry.username),
+        bulk("age-seconds"),
+        bulk(&format!("{age:.3}")),
+        bulk("client-info"),
+        bulk(&entry.client_info),
+        bulk("entry-id"),
+        integer(entry.id as i64),
+        bulk("timestamp-created"),
+        integer(entry.created as i64),
+        bulk("timestamp-last-updated"),
+        integer(entry.updated as i64),
+    ]))
+}
+
+fn ok() -> Resp {
+    Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned()))
+}
+
+fn bulk(val: &str) -> Resp {
+    Resp::BulkStrings(BulkStrings::new(val.to_owned()))
+}
+
+fn integer(val: i64) -> Resp {
+    Resp::Integers(Integers::new(val))
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acl(data: &Data, args: &[&str]) -> Resp {
+        AclCommandPara::new(
+            args[0].to_owned(),
+            args[1..].iter().map(|arg| arg.to_string()).collect(),
+            Parameter::new(),
+        )
+        .process(data)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_acl_setuser_getuser_deluser() {
+        let data = Data::new();
+        assert_eq!(
+            acl(&data, &["SETUSER", "alice", "on", "nopass", "~k*", "+get"]),
+            ok()
+        );
+        let Resp::Arrays(user) = acl(&data, &["GETUSER", "alice"]) else {
+            panic!("expected an array");
+        };
+        assert_eq!(
+            user.val[1],
+            Resp::Arrays(Arrays::new(vec![bulk("on"), bulk("nopass")]))
+        );
+        assert_eq!(user.val[5], bulk("-@all +get"));
+        assert_eq!(user.val[7], bulk("~k*"));
+        assert_eq!(
+            acl(&data, &["USERS"]),
+            Resp::Arrays(Arrays::new(vec![bulk("alice"), bulk("default")]))
+        );
+        assert_eq!(acl(&data, &["GETUSER", "bob"]), Resp::Nulls(Nulls::new()));
+        assert!(matches!(
+            acl(&data, &["DELUSER", "default"]),
+            Resp::SimpleErrors(_)
+        ));
+        assert_eq!(acl(&data, &["DELUSER", "alice", "bob"]), integer(1));
+    }
+
+    #[test]
+    fn test_acl_dryrun_and_cat() {
+        let data = Data::new();
+        acl(&data, &["SETUSER", "alice", "on", "~k*", "+@string"]);
+        assert_eq!(acl(&data, &["DRYRUN", "alice", "get", "k1"]), ok());
+        assert_eq!(
+            acl(&data, &["DRYRUN", "alice", "get", "other"]),
+            bulk("User alice has no permissions to access the 'other' key")
+        );
+        assert_eq!(
+            acl(&data, &["DRYRUN", "alice", "hget", "k1", "f"]),
+            bulk("User alice has no permissions to run the 'hget' command")
+        );
+        assert!(matches!(
+            acl(&data, &["DRYRUN", "bob", "get", "k"]),
+            Resp::SimpleErrors(_)
+        ));
+        let Resp::Arrays(commands) = acl(&data, &["CAT", "string"]) else {
+            panic!("expected an array");
+        };
+        assert!(commands.val.contains(&bulk("incr")));
+        assert!(matches!(
+            acl(&data, &["CAT", "nosuch"]),
+            Resp::SimpleErrors(_)
+        ));
+    }
+
+    #[test]
+    fn test_acl_genpass() {
+        let Resp::BulkStrings(password) = acl(&Data::new(), &["GENPASS"]) else {
+            panic!("expected a bulk string");
+        };
+        assert_eq!(password.val.len(), 64);
+        let Resp::BulkStrings(password) = acl(&Data::new(), &["GENPASS", "5"]) else {
+            panic!("expected a bulk string");
+        };
+        assert_eq!(password.val.len(), 2);
+    }
+}
@@ -0,0 +1,294 @@
+use tracing::info;
+
+use crate::config::{self, Update};
+use crate::memory;
+use crate::{
+    glob, process::Parameter, Arrays, BulkStrings, Data, Processor, Resp, SimpleErrors,
+    SimpleStringsData,
+};
+
+#[derive(Debug)]
+pub struct ConfigCommandPara {
+    pub subcommand: String,
+    pub args: Vec<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl ConfigCommandPara {
+    pub fn new(subcommand: String, args: Vec<String>, para: Parameter) -> Self {
+        Self {
+            subcommand,
+            args,
+            para,
+        }
+    }
+
+    // CONFIG GET parameter [parameter ...]，参数名支持 glob 模式
+    fn get(&self, data: &Data) -> Resp {
+        let mut reply = Vec::new();
+        for param in config::PARAMS {
+            let matched = self
+                .args
+                .iter()
+                .any(|pattern| glob::string_match(pattern.as_bytes(), param.name.as_bytes(), true));
+            if matched {
+                reply.push(bulk(param.name));
+                reply.push(bulk(&config::get(data, param.name)));
+            }
+        }
+        Resp::Arrays(Arrays::new(reply))
+    }
+
+    // CONFIG SET parameter value [parameter value ...]，任一参数非法时都不生效
+    fn set(&self, data: &Data) -> Resp {
+        if self.args.is_empty() || !self.args.len().is_multiple_of(2) {
+            return error("ERR wrong number of arguments for 'config|set' command");
+        }
+        let mut updates = Vec::new();
+        let mut names = Vec::new();
+        for pair in self.args.chunks(2) {
+            let name = pair[0].to_lowercase();
+            let value = &pair[1];
+            let Some(param) = config::param(&name) else {
+                return error(&format!(
+                    "ERR Unknown option or number of arguments for CONFIG SET - '{name}'"
+                ));
+            };
+            if param.is_immutable() {
+                return error(&format!(
+                    "ERR CONFIG SET failed (possibly related to argument '{name}') - can't set immutable config"
+                ));
+            }
+            if names.contains(&param.name) {
+                return error(&format!(
+                    "ERR CONFIG SET failed (possibly related to argument '{name}') - duplicate parameter"
+                ));
+            }
+            names.push(param.name);
+            match Update::parse(param, std::slice::from_ref(value)) {
+                Some(update) => updates.push(update),
+                None => {
+                    return error(&format!(
+                        "ERR Invalid argument '{value}' for CONFIG SET '{name}'"
+                    ))
+                }
+            }
+        }
+        for update in updates {
+            update.apply(data);
+        }
+        // 调小 maxmemory 后立即淘汰
+        memory::perform_evictions(data);
+        ok()
+    }
+}
+
+impl Processor for ConfigCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("ConfigCommandPara process start: {:?}", &self);
+        let subcommand = self.subcommand.to_lowercase();
+        let reply = match subcommand.as_str() {
+            "get" if !self.args.is_empty() => self.get(data),
+            "set" => self.set(data),
+            "rewrite" if self.args.is_empty() => match config::rewrite(data) {
+                Ok(()) => {
+                    info!("📝 Config file rewritten");
+                    ok()
+                }
+                Err(e) => error(&e),
+            },
+            "resetstat" if self.args.is_empty() => {
+                data.stats.reset();
+                data.limits.reset_stats();
+                data.memory.reset_stats(data.used_memory());
+                ok()
+            }
+            "resetstat" => error("ERR wrong number of arguments for 'config|resetstat' command"),
+            "rewrite" => error("ERR wrong number of arguments for 'config|rewrite' command"),
+            "get" => error("ERR wrong number of arguments for 'config|get' command"),
+            _ => error(&format!(
+                "ERR unknown subcommand '{}'. Try CONFIG HELP.",
+                self.subcommand
+            )),
+        };
+        Ok(reply)
+    }
+}
+
+fn ok() -> Resp {
+    Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned()))
+}
+
+fn bulk(val: &str) -> Resp {
+    Resp::BulkStrings(BulkStrings::new(val.to_owned()))
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(data: &Data, args: &[&str]) -> Resp {
+        ConfigCommandPara::new(
+            args[0].to_owned(),
+            args[1..].iter().map(|arg| arg.to_string()).collect(),
+            Parameter::new(),
+        )
+        .process(data)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_config_notify_keyspace_events() {
+        let data = Data::new();
+        assert!(matches!(
+            config(&data, &["SET", "notify-keyspace-events", "KEA"]),
+            Resp::SimpleStrings(_)
+        ));
+        assert_eq!(
+            config(&data, &["GET", "notify-*"]),
+            Resp::Arrays(Arrays::new(vec![
+                bulk("notify-keyspace-events"),
+                bulk("AKE")
+            ]))
+        );
+        assert!(matches!(
+            config(&data, &["SET", "notify-keyspace-events", "KQ"]),
+            Resp::SimpleErrors(_)
+        ));
+        assert!(matches!(
+            config(&data, &["SET", "no-such-option", "1"]),
+            Resp::SimpleErrors(_)
+        ));
+        assert_eq!(
+            config(&data, &["GET", "no-such-option"]),
+            Resp::Arrays(Arrays::new(vec![]))
+        );
+    }
+
+    #[test]
+    fn test_config_maxmemory() {
+        let data = Data::new();
+        assert!(matches!(
+            config(
+                &data,
+                &[
+                    "SET",
+                    "maxmemory",
+                    "10mb",
+                    "maxmemory-policy",
+                    "allkeys-lru"
+                ]
+            ),
+            Resp::SimpleStrings(_)
+        ));
+        assert_eq!(data.memory.maxmemory(), 10 * 1024 * 1024);
+        assert_eq!(
+            config(&data, &["GET", "maxmemory-p*"]),
+            Resp::Arrays(Arrays::new(vec![
+                bulk("maxmemory-policy"),
+                bulk("allkeys-lru")
+            ]))
+        );
+        // 任一参数非法时都不生效
+        assert!(matches!(
+            config(
+                &data,
+                &["SET", "maxmemory", "1gb", "maxmemory-policy", "lru"]
+            ),
+            Resp::SimpleErrors(_)
+        ));
+        assert_eq!(data.memory.maxmemory(), 10 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_config_requirepass() {
+        let data = Data::new();
+        assert!(matches!(
+            config(&data, &["SET", "requirepass", "secret"]),
+            Resp::SimpleStrings(_)
+        ));
+        assert!(data.acl.requires_auth());
+        assert!(data.acl.authenticate("default", "secret"));
+        assert_eq!(
+            config(&data, &["GET", "requirepass"]),
+            Resp::Arrays(Arrays::new(vec![bulk("requirepass"), bulk("secret")]))
+        );
+        assert!(matches!(
+            config(&data, &["SET", "aclfile", "/tmp/users.acl"]),
+            Resp::SimpleErrors(_)
+        ));
+        config(&data, &["SET", "requirepass", ""]);
+        assert!(!data.acl.requires_auth());
+    }
+
+    #[test]
+    fn test_config_set_validation() {
+        let data = Data::new();
+        assert!(matches!(
+            config(&data, &["SET", "port", "7000"]),
+            Resp::SimpleErrors(_)
+        ));
+        assert!(matches!(
+            config(&data, &["SET", "maxmemory", "1mb", "MAXMEMORY", "2mb"]),
+            Resp::SimpleErrors(_)
+        ));
+        assert_eq!(data.memory.maxmemory(), 0);
+        assert!(matches!(
+            config(
+                &data,
+                &["SET", "client-output-buffer-limit", "pubsub 1mb 512kb"]
+            ),
+            Resp::SimpleErrors(_)
+        ));
+        assert_eq!(
+            config(
+                &data,
+                &["SET", "client-output-buffer-limit", "pubsub 1mb 512kb 10"]
+            ),
+            ok()
+        );
+        assert_eq!(
+            data.limits.output_config(),
+            "normal 0 0 0 slave 268435456 67108864 60 pubsub 1048576 524288 10"
+        );
+        assert!(matches!(
+            config(&data, &["SET", "client-query-buffer-limit", "1kb"]),
+            Resp::SimpleErrors(_)
+        ));
+        assert_eq!(
+            config(&data, &["GET", "port", "databases"]),
+            Resp::Arrays(Arrays::new(vec![
+                bulk("port"),
+                bulk("6379"),
+                bulk("databases"),
+                bulk("16")
+            ]))
+        );
+        assert!(matches!(
+            config(&data, &["REWRITE"]),
+            Resp::SimpleErrors(e) if e.error_msg.contains("without a config file")
+        ));
+    }
+
+    #[test]
+    fn test_config_resetstat() {
+        let data = Data::new();
+        data.stats.record_error("ERR boom");
+        data.stats.record_expired();
+        assert!(matches!(
+            config(&data, &["RESETSTAT"]),
+            Resp::SimpleStrings(_)
+        ));
+        assert_eq!(data.stats.total_error_replies(), 0);
+        assert_eq!(data.stats.expired_keys(), 0);
+        assert!(matches!(
+            config(&data, &["RESETSTAT", "x"]),
+            Resp::SimpleErrors(_)
+        ));
+    }
+}
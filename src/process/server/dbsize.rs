@@ -0,0 +1,23 @@
+use tracing::info;
+
+use crate::{process::Parameter, Data, Integers, Processor, Resp};
+
+#[derive(Debug)]
+pub struct DbSizeCommandPara {
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl DbSizeCommandPara {
+    pub fn new(para: Parameter) -> Self {
+        Self { para }
+    }
+}
+
+impl Processor for DbSizeCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        let size = data.db().len();
+        info!("📏 DBSIZE -> {}", size);
+        Ok(Resp::Integers(Integers::new(size as i64)))
+    }
+}
@@ -0,0 +1,45 @@
+use tracing::info;
+
+use crate::{process::Parameter, Data, Processor, Resp, SimpleErrors, SimpleStringsData};
+
+#[derive(Debug)]
+pub struct FlushDbCommandPara {
+    // FLUSHALL 清空所有库，FLUSHDB 只清空当前库
+    pub all: bool,
+    // ASYNC 或 SYNC，未指定时同步清空
+    pub mode: Option<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl FlushDbCommandPara {
+    pub fn new(all: bool, mode: Option<String>, para: Parameter) -> Self {
+        Self { all, mode, para }
+    }
+}
+
+impl Processor for FlushDbCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("FlushDbCommandPara process start: {:?}", &self);
+        let lazy = match self.mode.as_deref().map(str::to_lowercase).as_deref() {
+            None | Some("sync") => false,
+            Some("async") => true,
+            Some(_) => {
+                return Ok(Resp::SimpleErrors(SimpleErrors::new(
+                    "ERR syntax error".to_owned(),
+                )))
+            }
+        };
+        if self.all {
+            data.flush(lazy);
+        } else {
+            data.flush_db(lazy);
+        }
+        info!(
+            "🧹 {} done (lazy: {})",
+            if self.all { "FLUSHALL" } else { "FLUSHDB" },
+            lazy
+        );
+        Ok(Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned())))
+    }
+}
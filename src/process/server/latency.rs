@@ -0,0 +1,393 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tracing::info;
+
+use crate::latency::{
+    EventHistory, EVENT_COMMAND, EVENT_EVICTION_CYCLE, EVENT_EXPIRE_CYCLE, EVENT_FAST_COMMAND,
+    EVENT_FORK,
+};
+use crate::stats::bucket_upper_bound;
+use crate::{
+    process::Parameter, Arrays, BulkStrings, Data, Integers, Processor, Resp, SimpleErrors,
+};
+
+// LATENCY GRAPH 的行数和最多显示的样本数
+const GRAPH_ROWS: usize = 4;
+const GRAPH_COLUMNS: usize = 80;
+
+const HELP: &[&str] = &[
+    "LATENCY <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+    "DOCTOR",
+    "    Return a human readable latency analysis report.",
+    "GRAPH <event>",
+    "    Return an ASCII latency graph for the <event> class.",
+    "HISTORY <event>",
+    "    Return time-latency samples for the <event> class.",
+    "LATEST",
+    "    Return the latest latency samples for all events.",
+    "RESET [<event> ...]",
+    "    Reset latency data of one or more <event> classes.",
+    "    (default: reset all data for all event classes)",
+    "HISTOGRAM [COMMAND ...]",
+    "    Return a cumulative distribution of latencies in the format of a histogram for the specified command names.",
+    "    If no commands are specified then all histograms are replied.",
+    "HELP",
+    "    Print this help.",
+];
+
+#[derive(Debug)]
+pub struct LatencyCommandPara {
+    pub subcommand: String,
+    pub args: Vec<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl LatencyCommandPara {
+    pub fn new(subcommand: String, args: Vec<String>, para: Parameter) -> Self {
+        Self {
+            subcommand,
+            args,
+            para,
+        }
+    }
+
+    // LATENCY LATEST：每个事件最新样本的时间、耗时和历史最大耗时
+    fn latest(&self, data: &Data) -> Resp {
+        let events = data
+            .latency
+            .events()
+            .into_iter()
+            .filter_map(|(event, history)| {
+                let latest = history.latest()?;
+                Some(Resp::Arrays(Arrays::new(vec![
+                    bulk(event),
+                    integer(latest.time),
+                    integer(latest.latency),
+                    integer(history.max),
+                ])))
+            })
+            .collect();
+        Resp::Arrays(Arrays::new(events))
+    }
+
+    // LATENCY HISTORY event
+    fn history(&self, data: &Data, event: &str) -> Resp {
+        let samples = data
+            .latency
+            .history(event)
+            .map(|history| history.samples)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|sample| {
+                Resp::Arrays(Arrays::new(vec![
+                    integer(sample.time),
+                    integer(sample.latency),
+                ]))
+            })
+            .collect();
+        Resp::Arrays(Arrays::new(samples))
+    }
+
+    // LATENCY HISTOGRAM [command ...]：每个命令的调用次数和按 2 的幂分桶的累计分布
+    fn histogram(&self, data: &Data) -> Resp {
+        let mut reply = Vec::new();
+        for (name, stats) in data.stats.commands() {
+            let requested =
+                self.args.is_empty() || self.args.iter().any(|arg| arg.eq_ignore_ascii_case(name));
+            if !requested || stats.calls == 0 {
+                continue;
+            }
+            let mut buckets = Vec::new();
+            let mut cumulative = 0;
+            for (bucket, count) in stats.histogram.iter().enumerate() {
+                if *count == 0 {
+                    continue;
+                }
+                cumulative += count;
+                buckets.push(integer(bucket_upper_bound(bucket)));
+                buckets.push(integer(cumulative));
+            }
+            reply.push(bulk(name));
+            reply.push(Resp::Arrays(Arrays::new(vec![
+                bulk("calls"),
+                integer(stats.calls),
+                bulk("histogram_usec"),
+                Resp::Arrays(Arrays::new(buckets)),
+            ])));
+        }
+        Resp::Arrays(Arrays::new(reply))
+    }
+}
+
+impl Processor for LatencyCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("LatencyCommandPara process start: {:?}", &self);
+        let subcommand = self.subcommand.to_lowercase();
+        let reply = match (subcommand.as_str(), self.args.as_slice()) {
+            ("latest", []) => self.latest(data),
+            ("history", [event]) => self.history(data, event),
+            ("reset", events) => integer(data.latency.reset(events) as u64),
+            ("doctor", []) => Resp::BulkStrings(BulkStrings::new(doctor(data))),
+            ("graph", [event]) => match data.latency.history(event) {
+                Some(history) if !history.samples.is_empty() => {
+                    Resp::BulkStrings(BulkStrings::new(graph(event, &history)))
+                }
+                _ => error(&format!("ERR No samples available for event '{event}'")),
+            },
+            ("histogram", _) => self.histogram(data),
+            ("help", []) => Resp::Arrays(Arrays::new(HELP.iter().map(|line| bulk(line)).collect())),
+            ("latest" | "history" | "doctor" | "graph" | "help", _) => error(&format!(
+                "ERR wrong number of arguments for 'latency|{subcommand}' command"
+            )),
+            _ => error(&format!(
+                "ERR unknown subcommand '{}'. Try LATENCY HELP.",
+                self.subcommand
+            )),
+        };
+        Ok(reply)
+    }
+}
+
+// LATENCY DOCTOR：分析每个事件的样本并给出建议
+fn doctor(data: &Data) -> String {
+    let events = data.latency.events();
+    if events.is_empty() {
+        if data.latency.threshold() == 0 {
+            return "I'm sorry, Dave, I can't do that. Latency monitoring is disabled in this \
+                Redis instance. You may use \"CONFIG SET latency-monitor-threshold \
+                <milliseconds>.\" in order to enable it.\n"
+                .to_owned();
+        }
+        return "Dave, no latency spike was observed during the lifetime of this Redis instance, \
+            not in the slightest bit. I honestly think you ought to sleep tonight.\n"
+            .to_owned();
+    }
+
+    let mut report = String::from(
+        "Dave, I have observed latency spikes in this Redis instance. \
+        You don't mind talking about it, do you Dave?\n\n",
+    );
+    let mut advices = Vec::new();
+    for (index, (event, history)) in events.iter().enumerate() {
+        let samples: Vec<u64> = history.samples.iter().map(|s| s.latency).collect();
+        let count = samples.len() as u64;
+        let avg = samples.iter().sum::<u64>() / count.max(1);
+        let mad = samples.iter().map(|s| s.abs_diff(avg)).sum::<u64>() / count.max(1);
+        let period = match (history.samples.front(), history.samples.back()) {
+            (Some(first), Some(last)) if count > 1 => {
+                (last.time - first.time) as f64 / (count - 1) as f64
+            }
+            _ => 0.0,
+        };
+        report.push_str(&format!(
+            "{}. {event}: {count} latency spikes (average {avg}ms, mean deviation {mad}ms, \
+            period {period:.2} sec). Worst all time event {}ms.\n",
+            index + 1,
+            history.max
+        ));
+        let advice = match *event {
+            EVENT_COMMAND | EVENT_FAST_COMMAND => {
+                "Check your Slow Log to understand what are the commands you are running \
+                which are too slow to execute. Please check SLOWLOG GET for more information."
+            }
+            EVENT_EXPIRE_CYCLE | EVENT_EVICTION_CYCLE => {
+                "Deleting, expiring or evicting (because of maxmemory policy) large objects \
+                is a blocking operation. If you have very large objects that are often \
+                deleted, expired, or evicted, try to fragment those objects into multiple \
+                smaller objects."
+            }
+            EVENT_FORK => {
+                "Full resynchronizations serialize the whole dataset before replying to the \
+                replica. Reduce the dataset size or avoid frequent full resynchronizations, \
+                for example by increasing the replication backlog size."
+            }
+            _ => continue,
+        };
+        if !advices.contains(&advice) {
+            advices.push(advice);
+        }
+    }
+    report.push_str("\nI have a few advices for you:\n\n");
+    for advice in advices {
+        report.push_str(&format!("- {advice}\n"));
+    }
+    report
+}
+
+// LATENCY GRAPH：按最高和最低耗时缩放的 ASCII 柱状图，下方竖排显示样本距今的时间
+fn graph(event: &str, history: &EventHistory) -> String {
+    let skip = history.samples.len().saturating_sub(GRAPH_COLUMNS);
+    let samples: Vec<_> = history.samples.iter().skip(skip).collect();
+    let high = samples.iter().map(|s| s.latency).max().unwrap_or(0);
+    let low = samples.iter().map(|s| s.latency).min().unwrap_or(0);
+    let mut graph = format!(
+        "{event} - high {high} ms, low {low} ms (all time high {} ms)\n{}\n",
+        history.max,
+        "-".repeat(GRAPH_COLUMNS)
+    );
+
+    // 每一列的高度，以半行为单位，最低的样本也至少有半行
+    let heights: Vec<usize> = samples
+        .iter()
+        .map(|s| {
+            let range = (high - low).max(1) as f64;
+            let scaled = (s.latency - low) as f64 / range * (GRAPH_ROWS * 2 - 1) as f64;
+            scaled.round() as usize + 1
+        })
+        .collect();
+    for row in (0..GRAPH_ROWS).rev() {
+        let line: String = heights
+            .iter()
+            .map(|height| match height.cmp(&(row * 2 + 2)) {
+                std::cmp::Ordering::Greater => '|',
+                std::cmp::Ordering::Equal => '#',
+                std::cmp::Ordering::Less if *height == row * 2 + 1 => '_',
+                std::cmp::Ordering::Less => ' ',
+            })
+            .collect();
+        graph.push_str(line.trim_end());
+        graph.push('\n');
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let labels: Vec<Vec<char>> = samples
+        .iter()
+        .map(|s| age_label(now.saturating_sub(s.time)).chars().collect())
+        .collect();
+    let label_rows = labels.iter().map(Vec::len).max().unwrap_or(0);
+    graph.push('\n');
+    for row in 0..label_rows {
+        let line: String = labels
+            .iter()
+            .map(|label| label.get(row).copied().unwrap_or(' '))
+            .collect();
+        graph.push_str(line.trim_end());
+        graph.push('\n');
+    }
+    graph
+}
+
+// 样本距今的时间，例如 15s、3m、2h、1d
+fn age_label(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+fn bulk(val: &str) -> Resp {
+    Resp::BulkStrings(BulkStrings::new(val.to_owned()))
+}
+
+fn integer(val: u64) -> Resp {
+    Resp::Integers(Integers::new(val as i64))
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn latency(data: &Data, args: &[&str]) -> Resp {
+        LatencyCommandPara::new(
+            args[0].to_owned(),
+            args[1..].iter().map(|arg| arg.to_string()).collect(),
+            Parameter::new(),
+        )
+        .process(data)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_latency_events() {
+        let data = Data::new();
+        let Resp::BulkStrings(report) = latency(&data, &["DOCTOR"]) else {
+            panic!("expected bulk string");
+        };
+        assert!(report.val.contains("Latency monitoring is disabled"));
+
+        data.latency.set_threshold(1);
+        data.latency
+            .record(EVENT_EXPIRE_CYCLE, Duration::from_millis(25));
+        let Resp::Arrays(latest) = latency(&data, &["LATEST"]) else {
+            panic!("expected array");
+        };
+        let Resp::Arrays(entry) = &latest.val[0] else {
+            panic!("expected array");
+        };
+        assert_eq!(entry.val[0], bulk("expire-cycle"));
+        assert_eq!(entry.val[2], integer(25));
+        assert_eq!(entry.val[3], integer(25));
+
+        let Resp::Arrays(history) = latency(&data, &["HISTORY", "expire-cycle"]) else {
+            panic!("expected array");
+        };
+        assert_eq!(history.val.len(), 1);
+
+        let Resp::BulkStrings(graph) = latency(&data, &["GRAPH", "expire-cycle"]) else {
+            panic!("expected bulk string");
+        };
+        assert!(graph
+            .val
+            .starts_with("expire-cycle - high 25 ms, low 25 ms (all time high 25 ms)\n"));
+        assert!(matches!(
+            latency(&data, &["GRAPH", "command"]),
+            Resp::SimpleErrors(_)
+        ));
+
+        let Resp::BulkStrings(report) = latency(&data, &["DOCTOR"]) else {
+            panic!("expected bulk string");
+        };
+        assert!(report.val.contains("1. expire-cycle: 1 latency spikes"));
+
+        assert_eq!(latency(&data, &["RESET"]), integer(1));
+        assert_eq!(
+            latency(&data, &["LATEST"]),
+            Resp::Arrays(Arrays::new(vec![]))
+        );
+    }
+
+    #[test]
+    fn test_latency_histogram() {
+        let data = Data::new();
+        let ok = Resp::Nulls(crate::Nulls::new());
+        data.stats.record_call("get", Duration::from_micros(1), &ok);
+        data.stats.record_call("get", Duration::from_micros(3), &ok);
+        data.stats.record_call("get", Duration::from_micros(4), &ok);
+        data.stats
+            .record_call("set", Duration::from_micros(10), &ok);
+
+        assert_eq!(
+            latency(&data, &["HISTOGRAM", "GET", "nosuch"]),
+            Resp::Arrays(Arrays::new(vec![
+                bulk("get"),
+                Resp::Arrays(Arrays::new(vec![
+                    bulk("calls"),
+                    integer(3),
+                    bulk("histogram_usec"),
+                    Resp::Arrays(Arrays::new(vec![
+                        integer(1),
+                        integer(1),
+                        integer(4),
+                        integer(3)
+                    ])),
+                ])),
+            ]))
+        );
+        let Resp::Arrays(all) = latency(&data, &["HISTOGRAM"]) else {
+            panic!("expected array");
+        };
+        assert_eq!(all.val.len(), 4);
+    }
+}
@@ -0,0 +1,247 @@
+use tracing::info;
+
+use crate::memory::{bytes_to_human, MemoryReport, DEFAULT_SAMPLES};
+use crate::{
+    process::Parameter, Arrays, BulkStrings, Data, Integers, Nulls, Processor, Resp, SimpleErrors,
+};
+
+// 内存占用低于这个值时 MEMORY DOCTOR 不做诊断
+const DOCTOR_MIN_MEMORY: usize = 5 * 1024 * 1024;
+
+const HELP: &[&str] = &[
+    "MEMORY <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+    "DOCTOR",
+    "    Return memory problems reports.",
+    "STATS",
+    "    Return information about the memory usage of the server.",
+    "USAGE <key> [SAMPLES <count>]",
+    "    Return memory in bytes used by <key> and its value. Nested values are",
+    "    sampled up to <count> times (default: 5, 0 means sample all).",
+    "HELP",
+    "    Print this help.",
+];
+
+#[derive(Debug)]
+pub struct MemoryCommandPara {
+    pub subcommand: String,
+    pub args: Vec<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl MemoryCommandPara {
+    pub fn new(subcommand: String, args: Vec<String>, para: Parameter) -> Self {
+        Self {
+            subcommand,
+            args,
+            para,
+        }
+    }
+
+    // MEMORY USAGE key [SAMPLES count]
+    fn usage(&self, data: &Data) -> Resp {
+        let Some(key) = self.args.first() else {
+            return error("ERR wrong number of arguments for 'memory|usage' command");
+        };
+        let samples = match &self.args[1..] {
+            [] => DEFAULT_SAMPLES,
+            [option, count] if option.eq_ignore_ascii_case("samples") => {
+                match count.parse::<usize>() {
+                    Ok(count) => count,
+                    Err(_) => return error("ERR value is not an integer or out of range"),
+                }
+            }
+            _ => return error("ERR syntax error"),
+        };
+        if data.expire_if_needed(key) {
+            return Resp::Nulls(Nulls::new());
+        }
+        match data.db().memory_usage(key, samples) {
+            Some(bytes) => Resp::Integers(Integers::new(bytes as i64)),
+            None => Resp::Nulls(Nulls::new()),
+        }
+    }
+
+    // MEMORY STATS：名称和值交替排列的数组
+    fn stats(&self, data: &Data) -> Resp {
+        let report = MemoryReport::new(data);
+        let mut reply = vec![
+            bulk("peak.allocated"),
+            integer(report.peak),
+            bulk("total.allocated"),
+            integer(report.used),
+            bulk("replication.backlog"),
+            integer(report.backlog),
+        ];
+        for (index, main, expires) in &report.dbs {
+            reply.push(bulk(&format!("db.{index}")));
+            reply.push(Resp::Arrays(Arrays::new(vec![
+                bulk("overhead.hashtable.main"),
+                integer(*main),
+                bulk("overhead.hashtable.expires"),
+                integer(*expires),
+            ])));
+        }
+        let bytes_per_key = report.used.checked_div(report.keys).unwrap_or(0);
+        reply.extend([
+            bulk("overhead.total"),
+            integer(report.overhead),
+            bulk("keys.count"),
+            integer(report.keys),
+            bulk("keys.bytes-per-key"),
+            integer(bytes_per_key),
+            bulk("dataset.bytes"),
+            integer(report.dataset),
+            bulk("dataset.percentage"),
+            bulk(&format!("{:.2}", report.dataset_percentage())),
+            bulk("peak.percentage"),
+            bulk(&format!("{:.2}", report.peak_percentage())),
+        ]);
+        Resp::Arrays(Arrays::new(reply))
+    }
+
+    // MEMORY DOCTOR：根据内存明细给出建议
+    fn doctor(&self, data: &Data) -> Resp {
+        let report = MemoryReport::new(data);
+        let text = if report.used < DOCTOR_MIN_MEMORY {
+            "Hi Sam, this instance is empty or is using very little memory, \
+            my issues detector can't be used in these conditions. \
+            Please, leave for your mission on Earth and fill it with some data. \
+            The new Sam and I will be back to our programming as soon as I finished rebooting."
+                .to_owned()
+        } else {
+            let mut issues = Vec::new();
+            if report.peak > report.used / 2 * 3 {
+                issues.push(format!(
+                    " * Peak memory: In the past this instance used more than 150% the memory \
+                    that is currently using ({} peak vs {} now). The allocator is normally not \
+                    able to release memory after a peak, so you can expect to see a big \
+                    fragmentation ratio.",
+                    bytes_to_human(report.peak as u64),
+                    bytes_to_human(report.used as u64)
+                ));
+            }
+            if report.overhead > report.dataset {
+                issues.push(format!(
+                    " * High overhead: Keys and other server structures use more memory ({}) \
+                    than the values themselves ({}). Consider using fewer, larger keys such as \
+                    hashes instead of many small strings.",
+                    bytes_to_human(report.overhead as u64),
+                    bytes_to_human(report.dataset as u64)
+                ));
+            }
+            if issues.is_empty() {
+                "Hi Sam, I can't find any memory issue in your instance. \
+                I can only account for what occurs on this base."
+                    .to_owned()
+            } else {
+                format!(
+                    "Sam, I detected a few issues in this Redis instance memory implants:\n\n{}\n\n\
+                    I'm here to keep you safe, Sam. I want to help you.\n",
+                    issues.join("\n\n")
+                )
+            }
+        };
+        Resp::BulkStrings(BulkStrings::new(text))
+    }
+}
+
+impl Processor for MemoryCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("MemoryCommandPara process start: {:?}", &self);
+        let reply = match self.subcommand.to_lowercase().as_str() {
+            "usage" => self.usage(data),
+            "stats" => self.stats(data),
+            "doctor" => self.doctor(data),
+            "help" => Resp::Arrays(Arrays::new(HELP.iter().map(|line| bulk(line)).collect())),
+            _ => error(&format!(
+                "ERR unknown subcommand '{}'. Try MEMORY HELP.",
+                self.subcommand
+            )),
+        };
+        Ok(reply)
+    }
+}
+
+fn bulk(val: &str) -> Resp {
+    Resp::BulkStrings(BulkStrings::new(val.to_owned()))
+}
+
+fn integer(val: usize) -> Resp {
+    Resp::Integers(Integers::new(val as i64))
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+
+    fn memory(data: &Data, args: &[&str]) -> Resp {
+        MemoryCommandPara::new(
+            args[0].to_owned(),
+            args[1..].iter().map(|arg| arg.to_string()).collect(),
+            Parameter::new(),
+        )
+        .process(data)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_memory_usage() {
+        let data = Data::new();
+        let list: VecDeque<String> = (0..100).map(|i| format!("item:{i:03}")).collect();
+        data.db().list_data.insert("list".to_owned(), list);
+
+        let Resp::Integers(sampled) = memory(&data, &["USAGE", "list"]) else {
+            panic!("expected integer");
+        };
+        let Resp::Integers(full) = memory(&data, &["usage", "list", "SAMPLES", "0"]) else {
+            panic!("expected integer");
+        };
+        // 元素大小相同，抽样和完整统计的结果一致
+        assert_eq!(sampled.val, full.val);
+        assert!(full.val > 100 * 8);
+
+        assert_eq!(
+            memory(&data, &["USAGE", "missing"]),
+            Resp::Nulls(Nulls::new())
+        );
+        assert!(matches!(
+            memory(&data, &["USAGE", "list", "SAMPLES", "x"]),
+            Resp::SimpleErrors(_)
+        ));
+        assert!(matches!(
+            memory(&data, &["USAGE", "list", "COUNT", "1"]),
+            Resp::SimpleErrors(_)
+        ));
+    }
+
+    #[test]
+    fn test_memory_stats_and_doctor() {
+        let data = Data::new();
+        data.db()
+            .list_data
+            .insert("list".to_owned(), VecDeque::new());
+        let Resp::Arrays(stats) = memory(&data, &["STATS"]) else {
+            panic!("expected array");
+        };
+        let keys = stats
+            .val
+            .iter()
+            .position(|item| *item == bulk("keys.count"))
+            .unwrap();
+        assert_eq!(stats.val[keys + 1], integer(1));
+        assert!(stats.val.contains(&bulk("db.0")));
+
+        let Resp::BulkStrings(doctor) = memory(&data, &["DOCTOR"]) else {
+            panic!("expected bulk string");
+        };
+        assert!(doctor.val.contains("very little memory"));
+        assert!(matches!(memory(&data, &["NOPE"]), Resp::SimpleErrors(_)));
+    }
+}
@@ -0,0 +1,22 @@
+pub mod acl;
+pub mod config;
+pub mod dbsize;
+pub mod flushdb;
+pub mod latency;
+pub mod memory;
+pub mod shutdown;
+pub mod slowlog;
+pub mod swapdb;
+
+#[derive(Debug)]
+pub enum ServerCommand {
+    Config(config::ConfigCommandPara),
+    DbSize(dbsize::DbSizeCommandPara),
+    FlushDb(flushdb::FlushDbCommandPara),
+    SwapDb(swapdb::SwapDbCommandPara),
+    Memory(memory::MemoryCommandPara),
+    SlowLog(slowlog::SlowLogCommandPara),
+    Latency(latency::LatencyCommandPara),
+    Acl(acl::AclCommandPara),
+    Shutdown(shutdown::ShutdownCommandPara),
+}
@@ -0,0 +1,101 @@
+use tracing::info;
+
+use crate::shutdown::ShutdownOptions;
+use crate::{process::Parameter, Data, Processor, Resp, SimpleErrors, SimpleStringsData};
+
+#[derive(Debug)]
+pub struct ShutdownCommandPara {
+    pub args: Vec<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl ShutdownCommandPara {
+    pub fn new(args: Vec<String>, para: Parameter) -> Self {
+        Self { args, para }
+    }
+
+    /// SHUTDOWN [NOSAVE|SAVE] [NOW] [FORCE] [ABORT]，返回选项和是否为 ABORT
+    pub fn options(&self) -> Result<(ShutdownOptions, bool), Resp> {
+        let mut options = ShutdownOptions::default();
+        let mut abort = false;
+        for arg in &self.args {
+            match arg.to_lowercase().as_str() {
+                "save" if options.save.is_none() => options.save = Some(true),
+                "nosave" if options.save.is_none() => options.save = Some(false),
+                "now" => options.now = true,
+                "force" => options.force = true,
+                "abort" => abort = true,
+                _ => return Err(error("ERR syntax error")),
+            }
+        }
+        // ABORT 不能和其他选项一起使用
+        if abort && self.args.len() > 1 {
+            return Err(error("ERR syntax error"));
+        }
+        Ok((options, abort))
+    }
+
+    /// 发起关闭的 SHUTDOWN，连接等待关闭完成，不立即回复
+    pub fn is_request(&self) -> bool {
+        self.options().is_ok_and(|(_, abort)| !abort)
+    }
+}
+
+impl Processor for ShutdownCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        let (options, abort) = match self.options() {
+            Ok(options) => options,
+            Err(e) => return Ok(e),
+        };
+        if abort {
+            if !data.shutdown.abort() {
+                return Ok(error("ERR No shutdown in progress."));
+            }
+            info!("🛑 SHUTDOWN ABORT");
+            return Ok(Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned())));
+        }
+        // 关闭成功时连接被断开，不会收到这个回复
+        info!("🛑 SHUTDOWN requested: {:?}", options);
+        data.shutdown.request(options);
+        Ok(Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned())))
+    }
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shutdown(args: &[&str]) -> ShutdownCommandPara {
+        ShutdownCommandPara::new(
+            args.iter().map(|arg| arg.to_string()).collect(),
+            Parameter::new(),
+        )
+    }
+
+    #[test]
+    fn test_shutdown_options() {
+        let (options, abort) = shutdown(&["NOSAVE", "now"]).options().unwrap();
+        assert_eq!(options.save, Some(false));
+        assert!(options.now && !options.force && !abort);
+        assert!(shutdown(&["SAVE", "NOSAVE"]).options().is_err());
+        assert!(shutdown(&["ABORT", "NOW"]).options().is_err());
+        assert!(!shutdown(&["ABORT"]).is_request());
+
+        let data = Data::new();
+        assert!(matches!(
+            shutdown(&["ABORT"]).process(&data).unwrap(),
+            Resp::SimpleErrors(_)
+        ));
+        shutdown(&[]).process(&data).unwrap();
+        assert!(data.shutdown.is_requested());
+        assert!(matches!(
+            shutdown(&["ABORT"]).process(&data).unwrap(),
+            Resp::SimpleStrings(_)
+        ));
+    }
+}
@@ -0,0 +1,173 @@
+use tracing::info;
+
+use crate::{
+    process::Parameter, Arrays, BulkStrings, Data, Integers, Processor, Resp, SimpleErrors,
+    SimpleStringsData,
+};
+
+// SLOWLOG GET 默认返回的条数
+const DEFAULT_GET_COUNT: usize = 10;
+
+const HELP: &[&str] = &[
+    "SLOWLOG <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+    "GET [<count>]",
+    "    Return top <count> entries from the slowlog (default: 10, -1 mean all).",
+    "    Entries are made of:",
+    "    id, timestamp, time in microseconds, arguments array, client IP and port,",
+    "    client name",
+    "LEN",
+    "    Return the length of the slowlog.",
+    "RESET",
+    "    Reset the slowlog.",
+    "HELP",
+    "    Print this help.",
+];
+
+#[derive(Debug)]
+pub struct SlowLogCommandPara {
+    pub subcommand: String,
+    pub args: Vec<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl SlowLogCommandPara {
+    pub fn new(subcommand: String, args: Vec<String>, para: Parameter) -> Self {
+        Self {
+            subcommand,
+            args,
+            para,
+        }
+    }
+
+    // SLOWLOG GET [count]，count 为负数时返回全部
+    fn get(&self, data: &Data) -> Resp {
+        let count = match self.args.as_slice() {
+            [] => DEFAULT_GET_COUNT,
+            [count] => match count.parse::<i64>() {
+                Ok(count) if count < -1 => {
+                    return error("ERR count should be greater than or equal to -1")
+                }
+                Ok(-1) => usize::MAX,
+                Ok(count) => count as usize,
+                Err(_) => return error("ERR value is not an integer or out of range"),
+            },
+            _ => return error("ERR wrong number of arguments for 'slowlog|get' command"),
+        };
+        let entries = data
+            .slowlog
+            .get(count)
+            .into_iter()
+            .map(|entry| {
+                Resp::Arrays(Arrays::new(vec![
+                    integer(entry.id as i64),
+                    integer(entry.timestamp as i64),
+                    integer(entry.duration as i64),
+                    Resp::Arrays(Arrays::new(
+                        entry.argv.iter().map(|arg| bulk(arg)).collect(),
+                    )),
+                    bulk(&entry.client_addr),
+                    bulk(&entry.client_name),
+                ]))
+            })
+            .collect();
+        Resp::Arrays(Arrays::new(entries))
+    }
+}
+
+impl Processor for SlowLogCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("SlowLogCommandPara process start: {:?}", &self);
+        let subcommand = self.subcommand.to_lowercase();
+        let reply = match subcommand.as_str() {
+            "get" => self.get(data),
+            "len" if self.args.is_empty() => integer(data.slowlog.len() as i64),
+            "reset" if self.args.is_empty() => {
+                data.slowlog.reset();
+                Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned()))
+            }
+            "help" if self.args.is_empty() => {
+                Resp::Arrays(Arrays::new(HELP.iter().map(|line| bulk(line)).collect()))
+            }
+            "len" | "reset" | "help" => error(&format!(
+                "ERR wrong number of arguments for 'slowlog|{subcommand}' command"
+            )),
+            _ => error(&format!(
+                "ERR unknown subcommand '{}'. Try SLOWLOG HELP.",
+                self.subcommand
+            )),
+        };
+        Ok(reply)
+    }
+}
+
+fn bulk(val: &str) -> Resp {
+    Resp::BulkStrings(BulkStrings::new(val.to_owned()))
+}
+
+fn integer(val: i64) -> Resp {
+    Resp::Integers(Integers::new(val))
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn slowlog(data: &Data, args: &[&str]) -> Resp {
+        SlowLogCommandPara::new(
+            args[0].to_owned(),
+            args[1..].iter().map(|arg| arg.to_string()).collect(),
+            Parameter::new(),
+        )
+        .process(data)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_slowlog_get_len_reset() {
+        let data = Data::new();
+        data.slowlog.set_log_slower_than(0);
+        for i in 0..3 {
+            data.slowlog.record(
+                1_700_000_000,
+                Duration::from_micros(i),
+                vec!["GET".to_owned(), format!("key:{i}")],
+            );
+        }
+        assert_eq!(slowlog(&data, &["LEN"]), integer(3));
+
+        let Resp::Arrays(entries) = slowlog(&data, &["get", "2"]) else {
+            panic!("expected array");
+        };
+        assert_eq!(entries.val.len(), 2);
+        assert_eq!(
+            entries.val[0],
+            Resp::Arrays(Arrays::new(vec![
+                integer(2),
+                integer(1_700_000_000),
+                integer(2),
+                Resp::Arrays(Arrays::new(vec![bulk("GET"), bulk("key:2")])),
+                bulk(""),
+                bulk(""),
+            ]))
+        );
+        let Resp::Arrays(all) = slowlog(&data, &["GET", "-1"]) else {
+            panic!("expected array");
+        };
+        assert_eq!(all.val.len(), 3);
+        assert!(matches!(
+            slowlog(&data, &["GET", "-2"]),
+            Resp::SimpleErrors(_)
+        ));
+
+        slowlog(&data, &["RESET"]);
+        assert_eq!(slowlog(&data, &["LEN"]), integer(0));
+        assert!(matches!(slowlog(&data, &["NOPE"]), Resp::SimpleErrors(_)));
+    }
+}
@@ -0,0 +1,73 @@
+use tracing::info;
+
+use crate::{process::Parameter, Data, Processor, Resp, SimpleErrors, SimpleStringsData};
+
+#[derive(Debug)]
+pub struct SwapDbCommandPara {
+    pub first: String,
+    pub second: String,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl SwapDbCommandPara {
+    pub fn new(first: String, second: String, para: Parameter) -> Self {
+        Self {
+            first,
+            second,
+            para,
+        }
+    }
+}
+
+impl Processor for SwapDbCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("SwapDbCommandPara process start: {:?}", &self);
+        if data.cluster.is_enabled() {
+            return Ok(error("ERR SWAPDB is not allowed in cluster mode"));
+        }
+        let Ok(first) = self.first.parse::<i64>() else {
+            return Ok(error("ERR invalid first DB index"));
+        };
+        let Ok(second) = self.second.parse::<i64>() else {
+            return Ok(error("ERR invalid second DB index"));
+        };
+        let databases = data.databases() as i64;
+        if !(0..databases).contains(&first) || !(0..databases).contains(&second) {
+            return Ok(error("ERR DB index is out of range"));
+        }
+        data.swap_db(first as usize, second as usize);
+        info!("🔀 SWAPDB {} {}", first, second);
+        Ok(Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned())))
+    }
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Integers;
+
+    #[test]
+    fn test_swapdb() {
+        let data = Data::new();
+        data.with_db(1, || {
+            data.db()
+                .string_data
+                .insert("k".to_owned(), Resp::Integers(Integers::new(1)));
+        });
+        let swap = |first: &str, second: &str| {
+            SwapDbCommandPara::new(first.to_owned(), second.to_owned(), Parameter::new())
+                .process(&data)
+                .unwrap()
+        };
+        assert!(matches!(swap("0", "1"), Resp::SimpleStrings(_)));
+        assert!(data.contains_key("k"));
+        assert!(data.db_at(1).is_empty());
+        assert!(matches!(swap("0", "16"), Resp::SimpleErrors(_)));
+        assert!(matches!(swap("a", "1"), Resp::SimpleErrors(_)));
+    }
+}
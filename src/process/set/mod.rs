@@ -1,6 +1,3 @@
-use crate::process::Parameter;
-use crate::{Data, Processor, Resp};
-
 pub mod sadd;
 pub mod scard;
 pub mod sdiff;
@@ -11,6 +8,7 @@ pub mod smove;
 pub mod spop;
 pub mod srandmember;
 pub mod srem;
+pub mod sscan;
 pub mod sunion;
 
 #[derive(Debug)]
@@ -25,5 +23,6 @@ pub enum SetCommand {
     SPop(spop::SPopCommandPara),
     SRandMember(srandmember::SRandMemberCommandPara),
     SRem(srem::SRemCommandPara),
+    SScan(sscan::SScanCommandPara),
     SUnion(sunion::SUnionCommandPara),
 }
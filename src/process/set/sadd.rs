@@ -1,35 +1,23 @@
-use crate::process::Parameter;
-use crate::{Data, Processor, Resp};
-use std::collections::HashSet;
+use crate::{notify, Data, Processor, Resp};
 
 #[derive(Debug)]
 pub struct SAddCommandPara {
     key: String,
     members: Vec<String>,
-    parameter: Parameter,
 }
 
 impl SAddCommandPara {
-    pub fn new(key: String, members: Vec<String>, parameter: Parameter) -> Self {
-        Self {
-            key,
-            members,
-            parameter,
-        }
+    pub fn new(key: String, members: Vec<String>) -> Self {
+        Self { key, members }
     }
 }
 
 impl Processor for SAddCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         // 检查键是否过期
-        if data.is_expired(&self.key) {
-            data.remove_key(&self.key);
-        }
+        data.expire_if_needed(&self.key);
 
-        let mut set = data
-            .set_data
-            .entry(self.key.clone())
-            .or_insert_with(HashSet::new);
+        let mut set = data.db().set_data.entry(self.key.clone()).or_default();
         let mut added_count = 0;
 
         for member in &self.members {
@@ -37,7 +25,11 @@ impl Processor for SAddCommandPara {
                 added_count += 1;
             }
         }
+        drop(set);
 
+        if added_count > 0 {
+            data.notify_keyspace_event(notify::NOTIFY_SET, "sadd", &self.key);
+        }
         Ok(Resp::Integers(crate::resp::Integers::new(added_count)))
     }
 }
@@ -1,16 +1,13 @@
-use crate::process::Parameter;
 use crate::{Data, Processor, Resp};
-use std::collections::HashSet;
 
 #[derive(Debug)]
 pub struct SDiffCommandPara {
     keys: Vec<String>,
-    parameter: Parameter,
 }
 
 impl SDiffCommandPara {
-    pub fn new(keys: Vec<String>, parameter: Parameter) -> Self {
-        Self { keys, parameter }
+    pub fn new(keys: Vec<String>) -> Self {
+        Self { keys }
     }
 }
 
@@ -23,12 +20,12 @@ impl Processor for SDiffCommandPara {
         let first_key = &self.keys[0];
 
         // 检查第一个键是否过期
-        if data.is_expired(first_key) {
-            data.remove_key(first_key);
+        if data.expire_if_needed(first_key) {
             return Ok(Resp::Arrays(crate::resp::Arrays::new(vec![])));
         }
 
         let mut result = data
+            .db()
             .set_data
             .get(first_key)
             .map(|set| set.clone())
@@ -37,12 +34,11 @@ impl Processor for SDiffCommandPara {
         // 从第二个键开始，从结果中移除这些集合的成员
         for key in &self.keys[1..] {
             // 检查键是否过期
-            if data.is_expired(key) {
-                data.remove_key(key);
+            if data.expire_if_needed(key) {
                 continue;
             }
 
-            if let Some(set) = data.set_data.get(key) {
+            if let Some(set) = data.db().set_data.get(key) {
                 for member in set.iter() {
                     result.remove(member);
                 }
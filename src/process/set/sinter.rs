@@ -1,16 +1,14 @@
-use crate::process::Parameter;
 use crate::{Data, Processor, Resp};
 use std::collections::HashSet;
 
 #[derive(Debug)]
 pub struct SInterCommandPara {
     keys: Vec<String>,
-    parameter: Parameter,
 }
 
 impl SInterCommandPara {
-    pub fn new(keys: Vec<String>, parameter: Parameter) -> Self {
-        Self { keys, parameter }
+    pub fn new(keys: Vec<String>) -> Self {
+        Self { keys }
     }
 }
 
@@ -24,12 +22,11 @@ impl Processor for SInterCommandPara {
 
         for key in &self.keys {
             // 检查键是否过期
-            if data.is_expired(key) {
-                data.remove_key(key);
+            if data.expire_if_needed(key) {
                 return Ok(Resp::Arrays(crate::resp::Arrays::new(vec![])));
             }
 
-            if let Some(set) = data.set_data.get(key) {
+            if let Some(set) = data.db().set_data.get(key) {
                 match result {
                     None => {
                         result = Some(set.clone());
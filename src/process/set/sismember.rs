@@ -1,32 +1,26 @@
-use crate::process::Parameter;
 use crate::{Data, Processor, Resp};
 
 #[derive(Debug)]
 pub struct SIsMemberCommandPara {
     key: String,
     member: String,
-    parameter: Parameter,
 }
 
 impl SIsMemberCommandPara {
-    pub fn new(key: String, member: String, parameter: Parameter) -> Self {
-        Self {
-            key,
-            member,
-            parameter,
-        }
+    pub fn new(key: String, member: String) -> Self {
+        Self { key, member }
     }
 }
 
 impl Processor for SIsMemberCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         // 检查键是否过期
-        if data.is_expired(&self.key) {
-            data.remove_key(&self.key);
+        if data.expire_if_needed(&self.key) {
             return Ok(Resp::Integers(crate::resp::Integers::new(0)));
         }
 
         let is_member = data
+            .db()
             .set_data
             .get(&self.key)
             .map(|set| set.contains(&self.member))
@@ -1,27 +1,25 @@
-use crate::process::Parameter;
 use crate::{Data, Processor, Resp};
 
 #[derive(Debug)]
 pub struct SMembersCommandPara {
     key: String,
-    parameter: Parameter,
 }
 
 impl SMembersCommandPara {
-    pub fn new(key: String, parameter: Parameter) -> Self {
-        Self { key, parameter }
+    pub fn new(key: String) -> Self {
+        Self { key }
     }
 }
 
 impl Processor for SMembersCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         // 检查键是否过期
-        if data.is_expired(&self.key) {
-            data.remove_key(&self.key);
+        if data.expire_if_needed(&self.key) {
             return Ok(Resp::Arrays(crate::resp::Arrays::new(vec![])));
         }
 
         let members = data
+            .db()
             .set_data
             .get(&self.key)
             .map(|set| {
@@ -29,7 +27,7 @@ impl Processor for SMembersCommandPara {
                     .map(|member| Resp::BulkStrings(crate::resp::BulkStrings::new(member.clone())))
                     .collect()
             })
-            .unwrap_or_else(Vec::new);
+            .unwrap_or_default();
 
         Ok(Resp::Arrays(crate::resp::Arrays::new(members)))
     }
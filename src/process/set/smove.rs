@@ -1,22 +1,18 @@
-use crate::process::Parameter;
-use crate::{Data, Processor, Resp};
-use std::collections::HashSet;
+use crate::{notify, Data, Processor, Resp};
 
 #[derive(Debug)]
 pub struct SMoveCommandPara {
     source: String,
     destination: String,
     member: String,
-    parameter: Parameter,
 }
 
 impl SMoveCommandPara {
-    pub fn new(source: String, destination: String, member: String, parameter: Parameter) -> Self {
+    pub fn new(source: String, destination: String, member: String) -> Self {
         Self {
             source,
             destination,
             member,
-            parameter,
         }
     }
 }
@@ -24,30 +20,31 @@ impl SMoveCommandPara {
 impl Processor for SMoveCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         // 检查源键是否过期
-        if data.is_expired(&self.source) {
-            data.remove_key(&self.source);
+        if data.expire_if_needed(&self.source) {
             return Ok(Resp::Integers(crate::resp::Integers::new(0)));
         }
 
         // 检查目标键是否过期
-        if data.is_expired(&self.destination) {
-            data.remove_key(&self.destination);
-        }
+        data.expire_if_needed(&self.destination);
 
-        let moved = if let Some(mut source_set) = data.set_data.get_mut(&self.source) {
+        let moved = if let Some(mut source_set) = data.db().set_data.get_mut(&self.source) {
             if source_set.remove(&self.member) {
                 // 如果源集合为空，删除键
-                if source_set.is_empty() {
-                    drop(source_set); // 释放可变引用
-                    data.set_data.remove(&self.source);
+                let is_empty = source_set.is_empty();
+                drop(source_set); // 释放可变引用
+                data.notify_keyspace_event(notify::NOTIFY_SET, "srem", &self.source);
+                if is_empty {
+                    data.db().set_data.remove(&self.source);
+                    data.notify_keyspace_event(notify::NOTIFY_GENERIC, "del", &self.source);
                 }
 
                 // 添加到目标集合
-                let mut dest_set = data
+                data.db()
                     .set_data
                     .entry(self.destination.clone())
-                    .or_insert_with(HashSet::new);
-                dest_set.insert(self.member.clone());
+                    .or_default()
+                    .insert(self.member.clone());
+                data.notify_keyspace_event(notify::NOTIFY_SET, "sadd", &self.destination);
                 true
             } else {
                 false
@@ -1,35 +1,27 @@
-use crate::process::Parameter;
-use crate::{Data, Processor, Resp};
-use std::collections::HashSet;
+use crate::{notify, Data, Processor, Resp};
 
 #[derive(Debug)]
 pub struct SPopCommandPara {
     key: String,
     count: Option<i64>,
-    parameter: Parameter,
 }
 
 impl SPopCommandPara {
-    pub fn new(key: String, count: Option<i64>, parameter: Parameter) -> Self {
-        Self {
-            key,
-            count,
-            parameter,
-        }
+    pub fn new(key: String, count: Option<i64>) -> Self {
+        Self { key, count }
     }
 }
 
 impl Processor for SPopCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         // 检查键是否过期
-        if data.is_expired(&self.key) {
-            data.remove_key(&self.key);
+        if data.expire_if_needed(&self.key) {
             return Ok(Resp::Nulls(crate::resp::Nulls::new()));
         }
 
         let count = self.count.unwrap_or(1);
 
-        if let Some(mut set) = data.set_data.get_mut(&self.key) {
+        if let Some(mut set) = data.db().set_data.get_mut(&self.key) {
             let mut popped = Vec::new();
             let members: Vec<String> = set.iter().cloned().collect();
 
@@ -45,10 +37,16 @@ impl Processor for SPopCommandPara {
                 }
             }
 
+            let is_empty = set.is_empty();
+            drop(set); // 释放可变引用
+
+            if !popped.is_empty() {
+                data.notify_keyspace_event(notify::NOTIFY_SET, "spop", &self.key);
+            }
             // 如果集合为空，删除键
-            if set.is_empty() {
-                drop(set); // 释放可变引用
-                data.set_data.remove(&self.key);
+            if is_empty {
+                data.db().set_data.remove(&self.key);
+                data.notify_keyspace_event(notify::NOTIFY_GENERIC, "del", &self.key);
             }
 
             if self.count.is_some() {
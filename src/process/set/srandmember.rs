@@ -1,32 +1,25 @@
-use crate::process::Parameter;
 use crate::{Data, Processor, Resp};
 
 #[derive(Debug)]
 pub struct SRandMemberCommandPara {
     key: String,
     count: Option<i64>,
-    parameter: Parameter,
 }
 
 impl SRandMemberCommandPara {
-    pub fn new(key: String, count: Option<i64>, parameter: Parameter) -> Self {
-        Self {
-            key,
-            count,
-            parameter,
-        }
+    pub fn new(key: String, count: Option<i64>) -> Self {
+        Self { key, count }
     }
 }
 
 impl Processor for SRandMemberCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         // 检查键是否过期
-        if data.is_expired(&self.key) {
-            data.remove_key(&self.key);
+        if data.expire_if_needed(&self.key) {
             return Ok(Resp::Nulls(crate::resp::Nulls::new()));
         }
 
-        if let Some(set) = data.set_data.get(&self.key) {
+        if let Some(set) = data.db().set_data.get(&self.key) {
             let members: Vec<String> = set.iter().cloned().collect();
 
             if members.is_empty() {
@@ -34,7 +27,7 @@ impl Processor for SRandMemberCommandPara {
             }
 
             if let Some(count) = self.count {
-                let selected_count = std::cmp::min(count.abs() as usize, members.len());
+                let selected_count = std::cmp::min(count.unsigned_abs() as usize, members.len());
                 let selected: Vec<Resp> = members
                     .iter()
                     .take(selected_count)
@@ -0,0 +1,28 @@
+use crate::{process::string::scan::ElementScan, Data, Processor, Resp};
+
+#[derive(Debug)]
+pub struct SScanCommandPara {
+    key: String,
+    scan: ElementScan,
+}
+
+impl SScanCommandPara {
+    pub fn new(key: String, scan: ElementScan) -> Self {
+        Self { key, scan }
+    }
+}
+
+impl Processor for SScanCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        // 检查键是否过期
+        if data.expire_if_needed(&self.key) {
+            return Ok(ElementScan::empty());
+        }
+        match data.db().set_data.get(&self.key) {
+            Some(set) => Ok(self
+                .scan
+                .reply(set.iter().map(|member| (member.as_str(), ())), |_| None)),
+            None => Ok(ElementScan::empty()),
+        }
+    }
+}
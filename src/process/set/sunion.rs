@@ -1,16 +1,14 @@
-use crate::process::Parameter;
 use crate::{Data, Processor, Resp};
 use std::collections::HashSet;
 
 #[derive(Debug)]
 pub struct SUnionCommandPara {
     keys: Vec<String>,
-    parameter: Parameter,
 }
 
 impl SUnionCommandPara {
-    pub fn new(keys: Vec<String>, parameter: Parameter) -> Self {
-        Self { keys, parameter }
+    pub fn new(keys: Vec<String>) -> Self {
+        Self { keys }
     }
 }
 
@@ -20,12 +18,11 @@ impl Processor for SUnionCommandPara {
 
         for key in &self.keys {
             // 检查键是否过期
-            if data.is_expired(key) {
-                data.remove_key(key);
+            if data.expire_if_needed(key) {
                 continue;
             }
 
-            if let Some(set) = data.set_data.get(key) {
+            if let Some(set) = data.db().set_data.get(key) {
                 for member in set.iter() {
                     result.insert(member.clone());
                 }
@@ -1,35 +1,27 @@
-use crate::process::Parameter;
-use crate::{Data, Processor, Resp};
-use std::collections::BTreeMap;
+use crate::{notify, Data, Processor, Resp};
 
 #[derive(Debug)]
 pub struct ZAddCommandPara {
     key: String,
     score_members: Vec<(f64, String)>,
-    parameter: Parameter,
 }
 
 impl ZAddCommandPara {
-    pub fn new(key: String, score_members: Vec<(f64, String)>, parameter: Parameter) -> Self {
-        Self {
-            key,
-            score_members,
-            parameter,
-        }
+    pub fn new(key: String, score_members: Vec<(f64, String)>) -> Self {
+        Self { key, score_members }
     }
 }
 
 impl Processor for ZAddCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         // 检查键是否过期
-        if data.is_expired(&self.key) {
-            data.remove_key(&self.key);
-        }
+        data.expire_if_needed(&self.key);
 
         let mut sorted_set = data
+            .db()
             .sorted_set_data
             .entry(self.key.clone())
-            .or_insert_with(BTreeMap::new);
+            .or_default();
         let mut added_count = 0;
 
         for (score, member) in &self.score_members {
@@ -38,7 +30,9 @@ impl Processor for ZAddCommandPara {
             }
             sorted_set.insert(member.clone(), *score);
         }
+        drop(sorted_set);
 
+        data.notify_keyspace_event(notify::NOTIFY_ZSET, "zadd", &self.key);
         Ok(Resp::Integers(crate::resp::Integers::new(added_count)))
     }
 }
@@ -1,27 +1,25 @@
-use crate::process::Parameter;
 use crate::{Data, Processor, Resp};
 
 #[derive(Debug)]
 pub struct ZCardCommandPara {
     key: String,
-    parameter: Parameter,
 }
 
 impl ZCardCommandPara {
-    pub fn new(key: String, parameter: Parameter) -> Self {
-        Self { key, parameter }
+    pub fn new(key: String) -> Self {
+        Self { key }
     }
 }
 
 impl Processor for ZCardCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         // 检查键是否过期
-        if data.is_expired(&self.key) {
-            data.remove_key(&self.key);
+        if data.expire_if_needed(&self.key) {
             return Ok(Resp::Integers(crate::resp::Integers::new(0)));
         }
 
         let count = data
+            .db()
             .sorted_set_data
             .get(&self.key)
             .map(|sorted_set| sorted_set.len() as i64)
@@ -0,0 +1,31 @@
+use crate::{process::string::scan::ElementScan, Data, Processor, Resp};
+
+#[derive(Debug)]
+pub struct ZScanCommandPara {
+    key: String,
+    scan: ElementScan,
+}
+
+impl ZScanCommandPara {
+    pub fn new(key: String, scan: ElementScan) -> Self {
+        Self { key, scan }
+    }
+}
+
+impl Processor for ZScanCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        // 检查键是否过期
+        if data.expire_if_needed(&self.key) {
+            return Ok(ElementScan::empty());
+        }
+        match data.db().sorted_set_data.get(&self.key) {
+            Some(sorted_set) => Ok(self.scan.reply(
+                sorted_set
+                    .iter()
+                    .map(|(member, score)| (member.as_str(), *score)),
+                |score: f64| Some(score.to_string()),
+            )),
+            None => Ok(ElementScan::empty()),
+        }
+    }
+}
@@ -1,32 +1,26 @@
-use crate::process::Parameter;
 use crate::{Data, Processor, Resp};
 
 #[derive(Debug)]
 pub struct ZScoreCommandPara {
     key: String,
     member: String,
-    parameter: Parameter,
 }
 
 impl ZScoreCommandPara {
-    pub fn new(key: String, member: String, parameter: Parameter) -> Self {
-        Self {
-            key,
-            member,
-            parameter,
-        }
+    pub fn new(key: String, member: String) -> Self {
+        Self { key, member }
     }
 }
 
 impl Processor for ZScoreCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         // 检查键是否过期
-        if data.is_expired(&self.key) {
-            data.remove_key(&self.key);
+        if data.expire_if_needed(&self.key) {
             return Ok(Resp::Nulls(crate::resp::Nulls::new()));
         }
 
         let score = data
+            .db()
             .sorted_set_data
             .get(&self.key)
             .and_then(|sorted_set| sorted_set.get(&self.member).copied());
@@ -0,0 +1,145 @@
+use tracing::info;
+
+use crate::{notify, process::Parameter, Data, Integers, Processor, Resp, SimpleErrors};
+
+#[derive(Debug)]
+pub struct CopyCommandPara {
+    pub source: String,
+    pub destination: String,
+    // DB 选项指定的目标库，未指定时为当前库
+    pub db: Option<String>,
+    pub replace: bool,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl CopyCommandPara {
+    pub fn new(
+        source: String,
+        destination: String,
+        db: Option<String>,
+        replace: bool,
+        para: Parameter,
+    ) -> Self {
+        Self {
+            source,
+            destination,
+            db,
+            replace,
+            para,
+        }
+    }
+}
+
+impl Processor for CopyCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("CopyCommandPara process start: {:?}", &self);
+        let source_db = crate::db::selected_db();
+        let target = match self.db.as_deref().map(str::parse::<i64>) {
+            None => source_db,
+            Some(Ok(target)) if target >= 0 && (target as usize) < data.databases() => {
+                target as usize
+            }
+            Some(Ok(_)) => return Ok(error("ERR DB index is out of range")),
+            Some(Err(_)) => return Ok(error("ERR value is not an integer or out of range")),
+        };
+        if target != source_db && data.cluster.is_enabled() {
+            return Ok(error(
+                "ERR Copying to another database is not allowed in cluster mode",
+            ));
+        }
+        if target == source_db && self.source == self.destination {
+            return Ok(error("ERR source and destination objects are the same"));
+        }
+
+        data.expire_if_needed(&self.source);
+        let Some(value) = data.db().get_value(&self.source) else {
+            return Ok(Resp::Integers(Integers::new(0)));
+        };
+        let expiry = data.db().expiry_data.get(&self.source).map(|v| *v);
+
+        let copied = data.with_db(target, || {
+            data.expire_if_needed(&self.destination);
+            if data.contains_key(&self.destination) {
+                if !self.replace {
+                    return false;
+                }
+                data.remove_key(&self.destination);
+            }
+            data.db().put_value(self.destination.clone(), value);
+            if let Some(expiry) = expiry {
+                data.set_expiry(&self.destination, expiry);
+            }
+            data.notify_keyspace_event(notify::NOTIFY_GENERIC, "copy_to", &self.destination);
+            true
+        });
+        if copied && target != source_db {
+            data.signal_modified_key(target, &self.destination);
+        }
+        info!(
+            "📋 COPY '{}' -> db{} '{}': {}",
+            self.source, target, self.destination, copied
+        );
+        Ok(Resp::Integers(Integers::new(copied as i64)))
+    }
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn copy(data: &Data, args: (&str, &str, Option<&str>, bool)) -> Resp {
+        let (source, destination, db, replace) = args;
+        CopyCommandPara::new(
+            source.to_owned(),
+            destination.to_owned(),
+            db.map(str::to_owned),
+            replace,
+            Parameter::new(),
+        )
+        .process(data)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_copy() {
+        let data = Data::new();
+        data.db()
+            .list_data
+            .insert("l".to_owned(), ["a".to_owned()].into());
+        data.db()
+            .list_data
+            .insert("other".to_owned(), ["b".to_owned()].into());
+
+        assert_eq!(
+            copy(&data, ("l", "c", None, false)),
+            Resp::Integers(Integers::new(1))
+        );
+        assert_eq!(
+            copy(&data, ("l", "other", None, false)),
+            Resp::Integers(Integers::new(0))
+        );
+        assert_eq!(
+            copy(&data, ("l", "other", None, true)),
+            Resp::Integers(Integers::new(1))
+        );
+        assert_eq!(data.db().list_data.get("other").unwrap()[0], "a");
+        assert_eq!(
+            copy(&data, ("l", "l", Some("3"), false)),
+            Resp::Integers(Integers::new(1))
+        );
+        data.with_db(3, || assert!(data.contains_key("l")));
+        assert!(matches!(
+            copy(&data, ("l", "l", None, false)),
+            Resp::SimpleErrors(_)
+        ));
+        assert_eq!(
+            copy(&data, ("missing", "x", None, false)),
+            Resp::Integers(Integers::new(0))
+        );
+    }
+}
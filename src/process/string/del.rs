@@ -1,6 +1,6 @@
 use tracing::info;
 
-use crate::{process::Parameter, Data, Integers, Processor, Resp};
+use crate::{notify, process::Parameter, Data, Integers, Processor, Resp};
 
 #[derive(Debug)]
 pub struct DelCommandPara {
@@ -21,16 +21,17 @@ impl Processor for DelCommandPara {
         let mut deleted_count = 0i64;
 
         for key in &self.keys {
-            let removed = data.string_data.remove(key).is_some()
-                || data.hash_data.remove(key).is_some()
-                || data.list_data.remove(key).is_some()
-                || data.set_data.remove(key).is_some()
-                || data.sorted_set_data.remove(key).is_some();
+            let removed = data.db().string_data.remove(key).is_some()
+                || data.db().hash_data.remove(key).is_some()
+                || data.db().list_data.remove(key).is_some()
+                || data.db().set_data.remove(key).is_some()
+                || data.db().sorted_set_data.remove(key).is_some();
 
             if removed {
                 deleted_count += 1;
                 // 同时移除过期时间
-                data.expiry_data.remove(key);
+                data.db().expiry_data.remove(key);
+                data.notify_keyspace_event(notify::NOTIFY_GENERIC, "del", key);
             }
         }
 
@@ -0,0 +1,27 @@
+use tracing::info;
+
+use crate::{process::Parameter, snapshot, BulkStrings, Data, Nulls, Processor, Resp};
+
+#[derive(Debug)]
+pub struct DumpCommandPara {
+    pub key: String,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl DumpCommandPara {
+    pub fn new(key: String, para: Parameter) -> Self {
+        Self { key, para }
+    }
+}
+
+impl Processor for DumpCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("DumpCommandPara process start: {:?}", &self);
+        data.expire_if_needed(&self.key);
+        match snapshot::dump_key(data, &self.key) {
+            Some(payload) => Ok(Resp::BulkStrings(BulkStrings::new(payload))),
+            None => Ok(Resp::Nulls(Nulls::new())),
+        }
+    }
+}
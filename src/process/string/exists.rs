@@ -21,11 +21,11 @@ impl Processor for ExistsCommandPara {
         let mut exist_count = 0i64;
 
         for key in &self.keys {
-            if data.string_data.contains_key(key)
-                || data.hash_data.contains_key(key)
-                || data.list_data.contains_key(key)
-                || data.set_data.contains_key(key)
-                || data.sorted_set_data.contains_key(key)
+            if data.db().string_data.contains_key(key)
+                || data.db().hash_data.contains_key(key)
+                || data.db().list_data.contains_key(key)
+                || data.db().set_data.contains_key(key)
+                || data.db().sorted_set_data.contains_key(key)
             {
                 exist_count += 1;
             }
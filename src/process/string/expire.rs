@@ -1,6 +1,6 @@
 use tracing::info;
 
-use crate::{process::Parameter, Data, Integers, Processor, Resp};
+use crate::{notify, process::Parameter, Data, Integers, Processor, Resp};
 
 #[derive(Debug)]
 pub struct ExpireCommandPara {
@@ -21,11 +21,11 @@ impl Processor for ExpireCommandPara {
         info!("ExpireCommandPara process start: {:?}", &self);
 
         // 检查键是否存在
-        let key_exists = data.string_data.contains_key(&self.key)
-            || data.hash_data.contains_key(&self.key)
-            || data.list_data.contains_key(&self.key)
-            || data.set_data.contains_key(&self.key)
-            || data.sorted_set_data.contains_key(&self.key);
+        let key_exists = data.db().string_data.contains_key(&self.key)
+            || data.db().hash_data.contains_key(&self.key)
+            || data.db().list_data.contains_key(&self.key)
+            || data.db().set_data.contains_key(&self.key)
+            || data.db().sorted_set_data.contains_key(&self.key);
 
         if key_exists {
             // 计算过期时间戳（毫秒）
@@ -33,6 +33,7 @@ impl Processor for ExpireCommandPara {
             let expiry_millis = now + (self.seconds * 1000);
 
             data.set_expiry(&self.key, expiry_millis);
+            data.notify_keyspace_event(notify::NOTIFY_GENERIC, "expire", &self.key);
             info!(
                 "⏰ EXPIRE '{}' set to expire in {} seconds",
                 self.key, self.seconds
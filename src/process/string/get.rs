@@ -40,14 +40,13 @@ impl Processor for GetCommandPara {
                 }
 
                 // 检查键是否过期
-                if data.is_expired(k) {
+                if data.expire_if_needed(k) {
                     info!("⏰ GET '{}' -> expired, removing", k);
-                    data.remove_key(k);
                     return Ok(Resp::Nulls(Nulls::new()));
                 }
 
                 // 正常的GET操作
-                match data.string_data.get(k) {
+                match data.db().string_data.get(k) {
                     Some(value) => {
                         info!("✅ GET '{}' -> found: {:?}", k, value);
                         Ok(value.clone())
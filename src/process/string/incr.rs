@@ -1,6 +1,8 @@
 use tracing::info;
 
-use crate::{process::Parameter, Data, Integers, Processor, Resp, SimpleErrors, SimpleStringsData};
+use crate::{
+    notify, process::Parameter, Data, Integers, Processor, Resp, SimpleErrors, SimpleStringsData,
+};
 
 #[derive(Debug)]
 pub struct IncrCommandPara {
@@ -26,8 +28,9 @@ impl Processor for IncrCommandPara {
 
         let increment = self.increment.unwrap_or(1);
 
-        match data.string_data.get(&self.key) {
-            Some(entry) => {
+        // 持有 get_mut 的引用原地修改，不能再对同一个 map 调用 insert，否则会死锁
+        let reply = match data.db().string_data.get_mut(&self.key) {
+            Some(mut entry) => {
                 let current_value = match entry.value() {
                     Resp::SimpleStrings(s) => &s.val,
                     Resp::BulkStrings(s) => &s.val,
@@ -41,10 +44,8 @@ impl Processor for IncrCommandPara {
                 match current_value.parse::<i64>() {
                     Ok(num) => {
                         let new_value = num + increment;
-                        data.string_data.insert(
-                            self.key.clone(),
-                            Resp::SimpleStrings(SimpleStringsData::new(new_value.to_string())),
-                        );
+                        *entry.value_mut() =
+                            Resp::SimpleStrings(SimpleStringsData::new(new_value.to_string()));
                         Ok(Resp::Integers(Integers::new(new_value)))
                     }
                     Err(_) => Ok(Resp::SimpleErrors(SimpleErrors::new(
@@ -54,12 +55,16 @@ impl Processor for IncrCommandPara {
             }
             None => {
                 // 键不存在，初始化为0再增加
-                data.string_data.insert(
+                data.db().string_data.insert(
                     self.key.clone(),
                     Resp::SimpleStrings(SimpleStringsData::new(increment.to_string())),
                 );
                 Ok(Resp::Integers(Integers::new(increment)))
             }
+        };
+        if matches!(reply, Ok(Resp::Integers(_))) {
+            data.notify_keyspace_event(notify::NOTIFY_STRING, "incrby", &self.key);
         }
+        reply
     }
 }
@@ -3,76 +3,256 @@ use tracing::info;
 
 use crate::{process::Parameter, BulkStrings, Data, Processor, Resp};
 
+// 不带参数或 INFO default 时输出的部分
+const DEFAULT_SECTIONS: &[&str] = &[
+    "server",
+    "clients",
+    "memory",
+    "persistence",
+    "stats",
+    "replication",
+    "cpu",
+    "errorstats",
+    "cluster",
+    "keyspace",
+];
+
+// INFO all / everything 额外输出的部分
+const EXTRA_SECTIONS: &[&str] = &["commandstats", "latencystats"];
+
 #[derive(Debug)]
 pub struct InfoCommandPara {
-    pub section: Option<String>,
+    pub sections: Vec<String>,
     #[allow(dead_code)]
     para: Parameter,
 }
 
 impl InfoCommandPara {
-    pub fn new(section: Option<String>, para: Parameter) -> Self {
-        Self { section, para }
+    pub fn new(sections: Vec<String>, para: Parameter) -> Self {
+        Self { sections, para }
+    }
+
+    // 按 Redis 的顺序展开要输出的部分，all/everything/default 是部分的集合
+    fn selected_sections(&self) -> Vec<&'static str> {
+        let requested: Vec<String> = self.sections.iter().map(|s| s.to_lowercase()).collect();
+        if requested.is_empty() {
+            return DEFAULT_SECTIONS.to_vec();
+        }
+        let has = |name: &str| requested.iter().any(|s| s == name);
+        let all = has("all") || has("everything");
+        let default = all || has("default");
+        DEFAULT_SECTIONS
+            .iter()
+            .map(|name| (name, default))
+            .chain(EXTRA_SECTIONS.iter().map(|name| (name, all)))
+            .filter(|(name, included)| *included || has(name))
+            .map(|(name, _)| *name)
+            .collect()
     }
 }
 
 impl Processor for InfoCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
-        let uptime = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        // 统计数据
-        let string_keys = data.string_data.len();
-        let hash_keys = data.hash_data.len();
-        let list_keys = data.list_data.len();
-        let set_keys = data.set_data.len();
-        let sorted_set_keys = data.sorted_set_data.len();
-        let total_keys = string_keys + hash_keys + list_keys + set_keys + sorted_set_keys;
-
-        let info_text = match self.section.as_deref() {
-            Some("server") | None => {
-                format!(
-                    "# Server\r\n\
-                    redis_version:7.0.0-simple\r\n\
-                    redis_mode:standalone\r\n\
-                    arch_bits:64\r\n\
-                    uptime_in_seconds:{uptime}\r\n\
-                    \r\n\
-                    # Keyspace\r\n\
-                    db0:keys={total_keys},expires=0,avg_ttl=0\r\n\
-                    \r\n\
-                    # Stats\r\n\
-                    total_connections_received:1\r\n\
-                    total_commands_processed:1\r\n\
-                    \r\n\
-                    # Memory\r\n\
-                    used_memory:1024\r\n\
-                    used_memory_human:1.00K\r\n"
-                )
-            }
-            Some("keyspace") => {
-                format!(
-                    "# Keyspace\r\n\
-                    db0:keys={total_keys},expires=0,avg_ttl=0\r\n"
-                )
-            }
-            Some("stats") => "# Stats\r\n\
-                    total_connections_received:1\r\n\
-                    total_commands_processed:1\r\n"
-                .to_string(),
-            Some(section) => {
-                format!("# {section}\r\n")
-            }
+        let info_text = self
+            .selected_sections()
+            .into_iter()
+            .map(|section| section_info(data, section))
+            .collect::<Vec<_>>()
+            .join("\r\n");
+
+        info!("ℹ️ INFO {:?} -> {} bytes", self.sections, info_text.len());
+
+        Ok(Resp::BulkStrings(BulkStrings::new(info_text)))
+    }
+}
+
+fn section_info(data: &Data, section: &str) -> String {
+    match section {
+        "server" => server_info(data),
+        "clients" => format!(
+            "# Clients\r\n\
+            connected_clients:{}\r\n\
+            maxclients:{}\r\n\
+            blocked_clients:0\r\n\
+            tracking_clients:{}\r\n",
+            data.stats.connected_clients(),
+            data.limits.maxclients(),
+            data.clients
+                .list()
+                .iter()
+                .filter(|client| client.tracking().is_some())
+                .count()
+        ),
+        "memory" => format!("# Memory\r\n{}", crate::memory::info(data)),
+        "persistence" => format!(
+            "# Persistence\r\n\
+            loading:0\r\n\
+            rdb_changes_since_last_save:{}\r\n\
+            rdb_bgsave_in_progress:0\r\n\
+            rdb_last_save_time:{}\r\n\
+            aof_enabled:0\r\n",
+            data.stats.dirty(),
+            data.stats.start_time()
+        ),
+        "stats" => data.stats.info(data),
+        "replication" => data.replication.info(),
+        "cpu" => cpu_info(),
+        "commandstats" => data.stats.commandstats_info(),
+        "errorstats" => data.stats.errorstats_info(),
+        "latencystats" => data.stats.latencystats_info(),
+        "cluster" => format!(
+            "# Cluster\r\ncluster_enabled:{}\r\n",
+            data.cluster.is_enabled() as u8
+        ),
+        "keyspace" => keyspace_info(data),
+        _ => String::new(),
+    }
+}
+
+fn server_info(data: &Data) -> String {
+    let uptime = data.stats.uptime().as_secs();
+    let server_time_usec = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros();
+    let executable = std::env::current_exe()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default();
+    format!(
+        "# Server\r\n\
+        redis_version:7.0.0-simple\r\n\
+        redis_mode:{}\r\n\
+        os:{} {}\r\n\
+        arch_bits:{}\r\n\
+        process_id:{}\r\n\
+        run_id:{}\r\n\
+        tcp_port:{}\r\n\
+        server_time_usec:{server_time_usec}\r\n\
+        uptime_in_seconds:{uptime}\r\n\
+        uptime_in_days:{}\r\n\
+        executable:{executable}\r\n",
+        if data.cluster.is_enabled() {
+            "cluster"
+        } else {
+            "standalone"
+        },
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        usize::BITS,
+        std::process::id(),
+        data.stats.run_id(),
+        data.replication.listening_port(),
+        uptime / 86400,
+    )
+}
+
+// 进程消耗的 CPU 时间（秒）
+fn cpu_info() -> String {
+    let (sys, user) = cpu_usage();
+    format!(
+        "# CPU\r\n\
+        used_cpu_sys:{sys:.6}\r\n\
+        used_cpu_user:{user:.6}\r\n"
+    )
+}
+
+// 从 /proc/self/stat 读取进程的内核态和用户态 CPU 时间，单位是 USER_HZ（Linux 上固定为 100）
+#[cfg(target_os = "linux")]
+fn cpu_usage() -> (f64, f64) {
+    const USER_HZ: f64 = 100.0;
+    let Ok(stat) = std::fs::read_to_string("/proc/self/stat") else {
+        return (0.0, 0.0);
+    };
+    // 进程名可能包含空格，从最后一个 ')' 之后开始按空格切分，utime、stime 是第 14、15 个字段
+    let fields: Vec<&str> = stat
+        .rsplit_once(')')
+        .map(|(_, rest)| rest.split_whitespace().collect())
+        .unwrap_or_default();
+    let ticks = |index: usize| {
+        fields
+            .get(index)
+            .and_then(|field| field.parse::<f64>().ok())
+            .unwrap_or(0.0)
+    };
+    (ticks(12) / USER_HZ, ticks(11) / USER_HZ)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cpu_usage() -> (f64, f64) {
+    (0.0, 0.0)
+}
+
+// 每个非空的库一行：键数量、设置了过期时间的键数量和平均剩余过期时间（毫秒）
+fn keyspace_info(data: &Data) -> String {
+    let now = data.current_timestamp_millis();
+    let mut info = String::from("# Keyspace\r\n");
+    for index in 0..data.databases() {
+        let db = data.db_at(index);
+        let keys = db.len();
+        if keys == 0 {
+            continue;
+        }
+        let ttls: Vec<u64> = db
+            .expiry_data
+            .iter()
+            .map(|entry| entry.value().saturating_sub(now))
+            .collect();
+        let avg_ttl = if ttls.is_empty() {
+            0
+        } else {
+            ttls.iter().sum::<u64>() / ttls.len() as u64
         };
+        info.push_str(&format!(
+            "db{index}:keys={keys},expires={},avg_ttl={avg_ttl}\r\n",
+            ttls.len()
+        ));
+    }
+    info
+}
 
-        info!(
-            "ℹ️ INFO {} -> {} bytes",
-            self.section.as_deref().unwrap_or("all"),
-            info_text.len()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(data: &Data, sections: &[&str]) -> String {
+        let sections = sections.iter().map(|s| s.to_string()).collect();
+        match InfoCommandPara::new(sections, Parameter::new())
+            .process(data)
+            .unwrap()
+        {
+            Resp::BulkStrings(text) => text.val,
+            other => panic!("unexpected reply {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_info_sections() {
+        let data = Data::new();
+        data.db().string_data.insert(
+            "k".to_owned(),
+            Resp::BulkStrings(BulkStrings::new("v".to_owned())),
         );
+        data.set_expiry("k", data.current_timestamp_millis() + 60_000);
 
-        Ok(Resp::BulkStrings(BulkStrings::new(info_text)))
+        let default = info(&data, &[]);
+        for header in [
+            "# Server",
+            "# Clients",
+            "# Persistence",
+            "# CPU",
+            "# Keyspace",
+        ] {
+            assert!(default.contains(header), "missing {header}");
+        }
+        assert!(!default.contains("# Commandstats"));
+        assert!(default.contains("db0:keys=1,expires=1,"));
+
+        let all = info(&data, &["ALL"]);
+        assert!(all.contains("# Commandstats") && all.contains("# Latencystats"));
+
+        let only = info(&data, &["stats", "commandstats"]);
+        assert!(only.starts_with("# Stats\r\n"));
+        assert!(only.contains("# Commandstats") && !only.contains("# Server"));
+        assert_eq!(info(&data, &["nosuch"]), "");
     }
 }
@@ -1,6 +1,6 @@
 use tracing::info;
 
-use crate::{process::Parameter, Arrays, BulkStrings, Data, Processor, Resp};
+use crate::{glob::glob_match, process::Parameter, Arrays, BulkStrings, Data, Processor, Resp};
 
 #[derive(Debug)]
 pub struct KeysCommandPara {
@@ -20,34 +20,30 @@ impl Processor for KeysCommandPara {
         let mut keys = Vec::new();
 
         // 收集所有类型的键
-        for key in data.string_data.iter() {
+        for key in data.db().string_data.iter() {
             keys.push(key.key().clone());
         }
 
-        for key in data.hash_data.iter() {
+        for key in data.db().hash_data.iter() {
             keys.push(key.key().clone());
         }
 
-        for key in data.list_data.iter() {
+        for key in data.db().list_data.iter() {
             keys.push(key.key().clone());
         }
 
-        for key in data.set_data.iter() {
+        for key in data.db().set_data.iter() {
             keys.push(key.key().clone());
         }
 
-        for key in data.sorted_set_data.iter() {
+        for key in data.db().sorted_set_data.iter() {
             keys.push(key.key().clone());
         }
 
-        // 简单的模式匹配（只支持 * 通配符）
-        let filtered_keys: Vec<String> = if self.pattern == "*" {
-            keys
-        } else {
-            keys.into_iter()
-                .filter(|key| key.contains(&self.pattern.replace("*", "")))
-                .collect()
-        };
+        let filtered_keys: Vec<String> = keys
+            .into_iter()
+            .filter(|key| self.pattern == "*" || glob_match(&self.pattern, key))
+            .collect();
 
         info!(
             "🔑 KEYS '{}' -> {} keys found",
@@ -1,18 +1,29 @@
 use crate::process::string::{get::GetCommandPara, set::SetCommandPara};
 
+pub mod copy;
 pub mod decr;
 pub mod del;
+pub mod dump;
 pub mod exists;
 pub mod expire;
 pub mod get;
 pub mod incr;
 pub mod info;
 pub mod keys;
+pub mod move_cmd;
+pub mod object;
 pub mod persist;
+pub mod pexpireat;
+pub mod randomkey;
+pub mod rename;
+pub mod restore;
 pub mod scan;
 pub mod set;
+pub mod strlen;
+pub mod touch;
 pub mod ttl;
 pub mod type_cmd;
+pub mod unlink;
 
 #[derive(Debug)]
 pub enum StringCommand {
@@ -29,4 +40,15 @@ pub enum StringCommand {
     Expire(expire::ExpireCommandPara),
     Ttl(ttl::TtlCommandPara),
     Persist(persist::PersistCommandPara),
+    PExpireAt(pexpireat::PExpireAtCommandPara),
+    Dump(dump::DumpCommandPara),
+    Restore(restore::RestoreCommandPara),
+    Move(move_cmd::MoveCommandPara),
+    Rename(rename::RenameCommandPara),
+    Copy(copy::CopyCommandPara),
+    RandomKey(randomkey::RandomKeyCommandPara),
+    Touch(touch::TouchCommandPara),
+    Unlink(unlink::UnlinkCommandPara),
+    Object(object::ObjectCommandPara),
+    StrLen(strlen::StrLenCommandPara),
 }
@@ -0,0 +1,90 @@
+use tracing::info;
+
+use crate::{notify, process::Parameter, Data, Integers, Processor, Resp, SimpleErrors};
+
+#[derive(Debug)]
+pub struct MoveCommandPara {
+    pub key: String,
+    pub db: String,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl MoveCommandPara {
+    pub fn new(key: String, db: String, para: Parameter) -> Self {
+        Self { key, db, para }
+    }
+}
+
+impl Processor for MoveCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("MoveCommandPara process start: {:?}", &self);
+        if data.cluster.is_enabled() {
+            return Ok(error("ERR MOVE is not allowed in cluster mode"));
+        }
+        let target = match self.db.parse::<i64>() {
+            Ok(target) if target >= 0 && (target as usize) < data.databases() => target as usize,
+            Ok(_) => return Ok(error("ERR DB index is out of range")),
+            Err(_) => return Ok(error("ERR value is not an integer or out of range")),
+        };
+        let source = crate::db::selected_db();
+        if source == target {
+            return Ok(error("ERR source and destination objects are the same"));
+        }
+
+        data.expire_if_needed(&self.key);
+        // 目标库中已经存在同名的键时不移动
+        let exists_in_target = data.with_db(target, || {
+            data.expire_if_needed(&self.key);
+            data.contains_key(&self.key)
+        });
+        if exists_in_target || !data.db().move_key(&self.key, data.db_at(target), &self.key) {
+            info!("🚚 MOVE '{}' -> db{} skipped", self.key, target);
+            return Ok(Resp::Integers(Integers::new(0)));
+        }
+
+        data.notify_keyspace_event(notify::NOTIFY_GENERIC, "move_from", &self.key);
+        data.with_db(target, || {
+            data.notify_keyspace_event(notify::NOTIFY_GENERIC, "move_to", &self.key);
+        });
+        data.signal_modified_key(target, &self.key);
+        info!("🚚 MOVE '{}' db{} -> db{}", self.key, source, target);
+        Ok(Resp::Integers(Integers::new(1)))
+    }
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleStringsData;
+
+    #[test]
+    fn test_move() {
+        let data = Data::new();
+        data.db().string_data.insert(
+            "k".to_owned(),
+            Resp::SimpleStrings(SimpleStringsData::new("v".to_owned())),
+        );
+        data.set_expiry("k", data.current_timestamp_millis() + 10_000);
+        let move_to = |db: &str| {
+            MoveCommandPara::new("k".to_owned(), db.to_owned(), Parameter::new())
+                .process(&data)
+                .unwrap()
+        };
+
+        assert_eq!(move_to("1"), Resp::Integers(Integers::new(1)));
+        assert!(!data.contains_key("k"));
+        data.with_db(1, || {
+            assert!(data.contains_key("k"));
+            assert!(data.get_ttl_millis("k").is_some());
+        });
+        // 源库中已经没有这个键
+        assert_eq!(move_to("1"), Resp::Integers(Integers::new(0)));
+        assert!(matches!(move_to("0"), Resp::SimpleErrors(_)));
+        assert!(matches!(move_to("16"), Resp::SimpleErrors(_)));
+    }
+}
@@ -0,0 +1,228 @@
+use tracing::info;
+
+use crate::db::Value;
+use crate::{
+    process::Parameter, Arrays, BulkStrings, Data, Integers, Nulls, Processor, Resp, SimpleErrors,
+    SimpleStringsData,
+};
+
+// 与 Redis 默认配置一致的紧凑编码阈值
+const EMBSTR_SIZE_LIMIT: usize = 44;
+const LISTPACK_MAX_ENTRIES: usize = 128;
+const LISTPACK_MAX_VALUE: usize = 64;
+const INTSET_MAX_ENTRIES: usize = 512;
+
+const HELP: &[&str] = &[
+    "OBJECT <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+    "ENCODING <key>",
+    "    Return the kind of internal representation used in order to store the value",
+    "    associated with a <key>.",
+    "FREQ <key>",
+    "    Return the access frequency index of the <key>. The returned integer is",
+    "    proportional to the logarithm of the recent access frequency of the key.",
+    "IDLETIME <key>",
+    "    Return the idle time of the <key>, that is the approximated number of",
+    "    seconds elapsed since the last access to the key.",
+    "REFCOUNT <key>",
+    "    Return the number of references of the value associated with the specified",
+    "    <key>.",
+    "HELP",
+    "    Print this help.",
+];
+
+#[derive(Debug)]
+pub struct ObjectCommandPara {
+    pub subcommand: String,
+    pub args: Vec<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl ObjectCommandPara {
+    pub fn new(subcommand: String, args: Vec<String>, para: Parameter) -> Self {
+        Self {
+            subcommand,
+            args,
+            para,
+        }
+    }
+}
+
+impl Processor for ObjectCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("ObjectCommandPara process start: {:?}", &self);
+        let subcommand = self.subcommand.to_lowercase();
+        let key = match (subcommand.as_str(), self.args.as_slice()) {
+            ("help", []) => {
+                return Ok(Resp::Arrays(Arrays::new(
+                    HELP.iter()
+                        .map(|line| Resp::SimpleStrings(SimpleStringsData::new(line.to_string())))
+                        .collect(),
+                )))
+            }
+            ("encoding" | "refcount" | "idletime" | "freq", [key]) => key,
+            _ => {
+                return Ok(Resp::SimpleErrors(SimpleErrors::new(format!(
+                "ERR unknown subcommand or wrong number of arguments for '{}'. Try OBJECT HELP.",
+                self.subcommand
+            ))))
+            }
+        };
+
+        data.expire_if_needed(key);
+        let Some(value) = data.db().get_value(key) else {
+            return Ok(Resp::Nulls(Nulls::new()));
+        };
+        // 和 Redis 一样，键存在时才检查淘汰策略
+        if subcommand == "freq" && !data.memory.policy().is_lfu() {
+            return Ok(Resp::SimpleErrors(SimpleErrors::new(
+                "ERR An LFU maxmemory policy is not selected, access frequency not tracked. \
+                 Please note that when switching between policies at runtime LRU and LFU data \
+                 will take some time to adjust."
+                    .to_owned(),
+            )));
+        }
+        let now = data.current_timestamp_millis();
+        let access = data.db().access(key, now);
+        let reply = match subcommand.as_str() {
+            "encoding" => Resp::BulkStrings(BulkStrings::new(encoding(&value).to_owned())),
+            "refcount" => Resp::Integers(Integers::new(1)),
+            "idletime" => Resp::Integers(Integers::new(
+                (now.saturating_sub(access.last_access) / 1000) as i64,
+            )),
+            _ => Resp::Integers(Integers::new(access.frequency(now) as i64)),
+        };
+        Ok(reply)
+    }
+}
+
+// 按 Redis 的规则推断值会使用的内部编码
+fn encoding(value: &Value) -> &'static str {
+    let small = |len: usize, mut items: Box<dyn Iterator<Item = &String> + '_>| {
+        len <= LISTPACK_MAX_ENTRIES && items.all(|item| item.len() <= LISTPACK_MAX_VALUE)
+    };
+    match value {
+        Value::String(resp) => {
+            let s = match resp {
+                Resp::SimpleStrings(s) => s.val.as_str(),
+                Resp::BulkStrings(s) => s.val.as_str(),
+                _ => return "int",
+            };
+            if s.len() <= 20 && s.parse::<i64>().is_ok() {
+                "int"
+            } else if s.len() <= EMBSTR_SIZE_LIMIT {
+                "embstr"
+            } else {
+                "raw"
+            }
+        }
+        Value::Hash(hash) => {
+            if small(hash.len(), Box::new(hash.iter().flat_map(|(k, v)| [k, v]))) {
+                "listpack"
+            } else {
+                "hashtable"
+            }
+        }
+        Value::List(list) => {
+            if small(list.len(), Box::new(list.iter())) {
+                "listpack"
+            } else {
+                "quicklist"
+            }
+        }
+        Value::Set(set) => {
+            if set.len() <= INTSET_MAX_ENTRIES && set.iter().all(|m| m.parse::<i64>().is_ok()) {
+                "intset"
+            } else if small(set.len(), Box::new(set.iter())) {
+                "listpack"
+            } else {
+                "hashtable"
+            }
+        }
+        Value::ZSet(zset) => {
+            if small(zset.len(), Box::new(zset.keys())) {
+                "listpack"
+            } else {
+                "skiplist"
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(data: &Data, args: &[&str]) -> Resp {
+        ObjectCommandPara::new(
+            args[0].to_owned(),
+            args[1..].iter().map(|arg| arg.to_string()).collect(),
+            Parameter::new(),
+        )
+        .process(data)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_object_encoding() {
+        let data = Data::new();
+        let set = |key: &str, value: &str| {
+            data.db().string_data.insert(
+                key.to_owned(),
+                Resp::SimpleStrings(SimpleStringsData::new(value.to_owned())),
+            );
+        };
+        set("int", "12345");
+        set("short", "hello");
+        set("long", &"x".repeat(100));
+        data.db()
+            .set_data
+            .insert("ints".to_owned(), ["1".to_owned(), "2".to_owned()].into());
+
+        let encoding = |key: &str| object(&data, &["ENCODING", key]);
+        assert_eq!(
+            encoding("int"),
+            Resp::BulkStrings(BulkStrings::new("int".into()))
+        );
+        assert_eq!(
+            encoding("short"),
+            Resp::BulkStrings(BulkStrings::new("embstr".into()))
+        );
+        assert_eq!(
+            encoding("long"),
+            Resp::BulkStrings(BulkStrings::new("raw".into()))
+        );
+        assert_eq!(
+            encoding("ints"),
+            Resp::BulkStrings(BulkStrings::new("intset".into()))
+        );
+        assert_eq!(encoding("missing"), Resp::Nulls(Nulls::new()));
+    }
+
+    #[test]
+    fn test_object_idletime_and_freq() {
+        let data = Data::new();
+        data.db()
+            .string_data
+            .insert("k".to_owned(), Resp::Integers(Integers::new(1)));
+        let now = data.current_timestamp_millis();
+        data.db().record_access("k", now);
+        assert_eq!(
+            object(&data, &["IDLETIME", "k"]),
+            Resp::Integers(Integers::new(0))
+        );
+        // 不是 LFU 策略时不返回访问频率
+        assert!(matches!(
+            object(&data, &["FREQ", "k"]),
+            Resp::SimpleErrors(e) if e.error_msg.starts_with("ERR An LFU maxmemory policy is not selected")
+        ));
+        data.memory
+            .set_policy(crate::memory::EvictionPolicy::AllKeysLfu);
+        assert!(matches!(object(&data, &["FREQ", "k"]), Resp::Integers(i) if i.val >= 5));
+        assert!(matches!(object(&data, &["HELP"]), Resp::Arrays(_)));
+        assert!(matches!(
+            object(&data, &["NOPE", "k"]),
+            Resp::SimpleErrors(_)
+        ));
+    }
+}
@@ -1,6 +1,6 @@
 use tracing::info;
 
-use crate::{process::Parameter, Data, Integers, Processor, Resp};
+use crate::{notify, process::Parameter, Data, Integers, Processor, Resp};
 
 #[derive(Debug)]
 pub struct PersistCommandPara {
@@ -20,15 +20,16 @@ impl Processor for PersistCommandPara {
         info!("PersistCommandPara process start: {:?}", &self);
 
         // 检查键是否存在
-        let key_exists = data.string_data.contains_key(&self.key)
-            || data.hash_data.contains_key(&self.key)
-            || data.list_data.contains_key(&self.key)
-            || data.set_data.contains_key(&self.key)
-            || data.sorted_set_data.contains_key(&self.key);
+        let key_exists = data.db().string_data.contains_key(&self.key)
+            || data.db().hash_data.contains_key(&self.key)
+            || data.db().list_data.contains_key(&self.key)
+            || data.db().set_data.contains_key(&self.key)
+            || data.db().sorted_set_data.contains_key(&self.key);
 
         if key_exists {
             let removed = data.remove_expiry(&self.key);
             if removed {
+                data.notify_keyspace_event(notify::NOTIFY_GENERIC, "persist", &self.key);
                 info!("⏰ PERSIST '{}' expiry removed", self.key);
                 Ok(Resp::Integers(Integers::new(1))) // 成功移除过期时间
             } else {
@@ -0,0 +1,48 @@
+use tracing::info;
+
+use crate::{notify, process::Parameter, Data, Integers, Processor, Resp};
+
+#[derive(Debug)]
+pub struct PExpireAtCommandPara {
+    pub key: String,
+    // 过期时间戳（毫秒）
+    pub timestamp_millis: u64,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl PExpireAtCommandPara {
+    pub fn new(key: String, timestamp_millis: u64, para: Parameter) -> Self {
+        Self {
+            key,
+            timestamp_millis,
+            para,
+        }
+    }
+}
+
+impl Processor for PExpireAtCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("PExpireAtCommandPara process start: {:?}", &self);
+
+        // 检查键是否存在
+        let key_exists = data.db().string_data.contains_key(&self.key)
+            || data.db().hash_data.contains_key(&self.key)
+            || data.db().list_data.contains_key(&self.key)
+            || data.db().set_data.contains_key(&self.key)
+            || data.db().sorted_set_data.contains_key(&self.key);
+
+        if key_exists {
+            data.set_expiry(&self.key, self.timestamp_millis);
+            data.notify_keyspace_event(notify::NOTIFY_GENERIC, "expire", &self.key);
+            info!(
+                "⏰ PEXPIREAT '{}' set to expire at {}",
+                self.key, self.timestamp_millis
+            );
+            Ok(Resp::Integers(Integers::new(1)))
+        } else {
+            info!("⏰ PEXPIREAT '{}' key not found", self.key);
+            Ok(Resp::Integers(Integers::new(0)))
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use tracing::info;
+
+use crate::{process::Parameter, BulkStrings, Data, Nulls, Processor, Resp};
+
+// 随机到过期键时重试的次数上限
+const MAX_TRIES: usize = 100;
+
+#[derive(Debug)]
+pub struct RandomKeyCommandPara {
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl RandomKeyCommandPara {
+    pub fn new(para: Parameter) -> Self {
+        Self { para }
+    }
+}
+
+impl Processor for RandomKeyCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        for _ in 0..MAX_TRIES {
+            let Some(key) = data.db().random_key() else {
+                break;
+            };
+            // 随机到已过期的键时删除后重新随机
+            if !data.expire_if_needed(&key) {
+                info!("🎲 RANDOMKEY -> '{}'", key);
+                return Ok(Resp::BulkStrings(BulkStrings::new(key)));
+            }
+        }
+        Ok(Resp::Nulls(Nulls::new()))
+    }
+}
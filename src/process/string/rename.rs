@@ -0,0 +1,121 @@
+use tracing::info;
+
+use crate::{
+    notify, process::Parameter, Data, Integers, Processor, Resp, SimpleErrors, SimpleStringsData,
+};
+
+#[derive(Debug)]
+pub struct RenameCommandPara {
+    pub key: String,
+    pub new_key: String,
+    // RENAMENX：新键已存在时不改名
+    pub nx: bool,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl RenameCommandPara {
+    pub fn new(key: String, new_key: String, nx: bool, para: Parameter) -> Self {
+        Self {
+            key,
+            new_key,
+            nx,
+            para,
+        }
+    }
+
+    fn reply(&self, renamed: bool) -> Resp {
+        if self.nx {
+            Resp::Integers(Integers::new(renamed as i64))
+        } else {
+            Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned()))
+        }
+    }
+}
+
+impl Processor for RenameCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("RenameCommandPara process start: {:?}", &self);
+        data.expire_if_needed(&self.key);
+        if !data.contains_key(&self.key) {
+            return Ok(Resp::SimpleErrors(SimpleErrors::new(
+                "ERR no such key".to_owned(),
+            )));
+        }
+        if self.key == self.new_key {
+            return Ok(self.reply(false));
+        }
+        data.expire_if_needed(&self.new_key);
+        if data.contains_key(&self.new_key) {
+            if self.nx {
+                return Ok(self.reply(false));
+            }
+            // 旧值可能很大，放到后台释放
+            if let Some(old) = data.db().take_value(&self.new_key) {
+                old.free();
+            }
+            data.remove_key(&self.new_key);
+        }
+
+        // 过期时间和访问信息跟着键一起改名
+        data.db().move_key(&self.key, data.db(), &self.new_key);
+        data.notify_keyspace_event(notify::NOTIFY_GENERIC, "rename_from", &self.key);
+        data.notify_keyspace_event(notify::NOTIFY_GENERIC, "rename_to", &self.new_key);
+        info!("✏️ RENAME '{}' -> '{}'", self.key, self.new_key);
+        Ok(self.reply(true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(data: &Data, key: &str, value: &str) {
+        data.db().string_data.insert(
+            key.to_owned(),
+            Resp::SimpleStrings(SimpleStringsData::new(value.to_owned())),
+        );
+    }
+
+    fn rename(data: &Data, key: &str, new_key: &str, nx: bool) -> Resp {
+        RenameCommandPara::new(key.to_owned(), new_key.to_owned(), nx, Parameter::new())
+            .process(data)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rename_keeps_ttl() {
+        let data = Data::new();
+        set(&data, "a", "1");
+        let expire_at = data.current_timestamp_millis() + 10_000;
+        data.set_expiry("a", expire_at);
+        set(&data, "b", "2");
+
+        assert!(matches!(
+            rename(&data, "a", "b", false),
+            Resp::SimpleStrings(_)
+        ));
+        assert!(!data.contains_key("a"));
+        assert_eq!(data.db().expiry_data.get("b").map(|v| *v), Some(expire_at));
+        assert!(matches!(
+            rename(&data, "a", "c", false),
+            Resp::SimpleErrors(_)
+        ));
+    }
+
+    #[test]
+    fn test_renamenx() {
+        let data = Data::new();
+        set(&data, "a", "1");
+        set(&data, "b", "2");
+        assert_eq!(
+            rename(&data, "a", "b", true),
+            Resp::Integers(Integers::new(0))
+        );
+        assert_eq!(
+            rename(&data, "a", "c", true),
+            Resp::Integers(Integers::new(1))
+        );
+        assert!(data.contains_key("c"));
+    }
+}
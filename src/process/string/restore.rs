@@ -0,0 +1,65 @@
+use tracing::info;
+
+use crate::{
+    notify, process::Parameter, snapshot, Data, Processor, Resp, SimpleErrors, SimpleStringsData,
+};
+
+#[derive(Debug)]
+pub struct RestoreCommandPara {
+    pub key: String,
+    // 过期时间（毫秒），0 表示不过期
+    pub ttl_millis: u64,
+    pub payload: String,
+    pub replace: bool,
+    // ttl 是否为绝对时间戳
+    pub absttl: bool,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl RestoreCommandPara {
+    pub fn new(
+        key: String,
+        ttl_millis: u64,
+        payload: String,
+        replace: bool,
+        absttl: bool,
+        para: Parameter,
+    ) -> Self {
+        Self {
+            key,
+            ttl_millis,
+            payload,
+            replace,
+            absttl,
+            para,
+        }
+    }
+}
+
+impl Processor for RestoreCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("RestoreCommandPara process start: {:?}", &self);
+        data.expire_if_needed(&self.key);
+        if !self.replace && data.contains_key(&self.key) {
+            return Ok(Resp::SimpleErrors(SimpleErrors::new(
+                "BUSYKEY Target key name already exists.".to_owned(),
+            )));
+        }
+
+        if let Err(e) = snapshot::restore_key(data, &self.key, &self.payload) {
+            return Ok(Resp::SimpleErrors(SimpleErrors::new(format!("ERR {e}"))));
+        }
+        if self.ttl_millis > 0 {
+            let expire_at = if self.absttl {
+                self.ttl_millis
+            } else {
+                data.current_timestamp_millis() + self.ttl_millis
+            };
+            data.set_expiry(&self.key, expire_at);
+        }
+        data.notify_keyspace_event(notify::NOTIFY_GENERIC, "restore", &self.key);
+        info!("📦 RESTORE '{}' done", self.key);
+        Ok(Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned())))
+    }
+}
@@ -1,20 +1,31 @@
-use crate::{process::Parameter, Data, Processor, Resp};
+use crate::{
+    glob::glob_match, keymap::scan_elements, process::Parameter, Arrays, BulkStrings, Data,
+    Processor, Resp, SimpleErrors,
+};
 
 #[derive(Debug)]
 pub struct ScanCommandPara {
     cursor: u64,
     pattern: Option<String>,
     count: Option<u64>,
+    key_type: Option<String>,
     #[allow(dead_code)]
     para: Parameter,
 }
 
 impl ScanCommandPara {
-    pub fn new(cursor: u64, pattern: Option<String>, count: Option<u64>, para: Parameter) -> Self {
+    pub fn new(
+        cursor: u64,
+        pattern: Option<String>,
+        count: Option<u64>,
+        key_type: Option<String>,
+        para: Parameter,
+    ) -> Self {
         Self {
             cursor,
             pattern,
             count,
+            key_type,
             para,
         }
     }
@@ -22,91 +33,249 @@ impl ScanCommandPara {
 
 impl Processor for ScanCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
-        // 获取所有键（从所有数据类型中）
-        let mut all_keys: Vec<String> = Vec::new();
-
-        // 添加字符串键
-        all_keys.extend(data.string_data.iter().map(|entry| entry.key().clone()));
-
-        // 添加哈希键
-        all_keys.extend(data.hash_data.iter().map(|entry| entry.key().clone()));
-
-        // 添加列表键
-        all_keys.extend(data.list_data.iter().map(|entry| entry.key().clone()));
-
-        // 添加集合键
-        all_keys.extend(data.set_data.iter().map(|entry| entry.key().clone()));
-
-        // 添加有序集合键
-        all_keys.extend(data.sorted_set_data.iter().map(|entry| entry.key().clone()));
-
-        // 应用模式匹配（如果指定了MATCH参数）
-        let matched_keys: Vec<String> = if let Some(pattern) = &self.pattern {
-            if pattern == "*" {
-                // 匹配所有键
-                all_keys
-            } else {
-                // 简单的模式匹配实现（支持*通配符）
-                all_keys
-                    .into_iter()
-                    .filter(|key| simple_pattern_match(key, pattern))
-                    .collect()
-            }
-        } else {
-            all_keys
+        let key_type = match self.key_type.as_deref().map(str::to_lowercase) {
+            Some(key_type) => match key_type.as_str() {
+                "string" | "hash" | "list" | "set" | "zset" => Some(key_type),
+                _ => {
+                    return Ok(Resp::SimpleErrors(SimpleErrors::new(format!(
+                        "ERR unknown type name '{key_type}'"
+                    ))))
+                }
+            },
+            None => None,
         };
+        if self.count == Some(0) {
+            return Ok(Resp::SimpleErrors(SimpleErrors::new(
+                "ERR syntax error".to_owned(),
+            )));
+        }
 
-        // 分页处理
+        // 游标是键名的哈希值，每次只取出 COUNT 个左右的键，
+        // 过期和不匹配的键在取出之后再过滤，与 Redis 一致
         let count = self.count.unwrap_or(10) as usize;
-        let start_index = self.cursor as usize;
-        let end_index = std::cmp::min(start_index + count, matched_keys.len());
+        let (next_cursor, keys) = data.db().scan(self.cursor, count, key_type.as_deref());
+        let keys: Vec<Resp> = keys
+            .into_iter()
+            .filter(|key| !data.expire_if_needed(key))
+            .filter(|key| {
+                self.pattern
+                    .as_deref()
+                    .is_none_or(|pattern| pattern == "*" || glob_match(pattern, key))
+            })
+            .map(|key| Resp::BulkStrings(BulkStrings::new(key)))
+            .collect();
 
-        let page_keys = if start_index < matched_keys.len() {
-            matched_keys[start_index..end_index].to_vec()
-        } else {
-            Vec::new()
-        };
+        Ok(Resp::Arrays(Arrays::new(vec![
+            Resp::BulkStrings(BulkStrings::new(next_cursor.to_string())),
+            Resp::Arrays(Arrays::new(keys)),
+        ])))
+    }
+}
 
-        // 计算下一个游标
-        let next_cursor = if end_index >= matched_keys.len() {
-            0 // 表示扫描完成
-        } else {
-            end_index as u64
-        };
+/// SSCAN/HSCAN/ZSCAN 共用的游标、MATCH 和 COUNT，游标规则和 SCAN 一致
+#[derive(Debug)]
+pub struct ElementScan {
+    cursor: u64,
+    pattern: Option<String>,
+    count: Option<u64>,
+}
 
-        // 构建响应数组
-        let mut key_responses = Vec::new();
-        for key in page_keys {
-            key_responses.push(Resp::BulkStrings(crate::BulkStrings { val: key }));
+impl ElementScan {
+    pub fn new(cursor: u64, pattern: Option<String>, count: Option<u64>) -> Self {
+        Self {
+            cursor,
+            pattern,
+            count,
         }
+    }
+
+    /// 键不存在时和空集合一样，直接结束遍历
+    pub fn empty() -> Resp {
+        Resp::Arrays(Arrays::new(vec![
+            Resp::BulkStrings(BulkStrings::new("0".to_owned())),
+            Resp::Arrays(Arrays::new(Vec::new())),
+        ]))
+    }
 
-        let response = Resp::Arrays(crate::Arrays {
-            val: vec![
-                // 下一个游标
-                Resp::BulkStrings(crate::BulkStrings {
-                    val: next_cursor.to_string(),
-                }),
-                // 键列表数组
-                Resp::Arrays(crate::Arrays { val: key_responses }),
-            ],
-        });
-
-        Ok(response)
+    /// 遍历 (元素名, 值) 并生成回复，`value` 返回回复中跟在元素名后面的值（字段值、分数），
+    /// MATCH 只匹配元素名
+    pub fn reply<'a, T>(
+        &self,
+        items: impl Iterator<Item = (&'a str, T)>,
+        value: impl Fn(T) -> Option<String>,
+    ) -> Resp {
+        if self.count == Some(0) {
+            return Resp::SimpleErrors(SimpleErrors::new("ERR syntax error".to_owned()));
+        }
+        let count = self.count.unwrap_or(10) as usize;
+        let (next_cursor, items) = scan_elements(items, self.cursor, count);
+        let mut reply = Vec::new();
+        for (name, item) in items {
+            let matched = self
+                .pattern
+                .as_deref()
+                .is_none_or(|pattern| pattern == "*" || glob_match(pattern, name));
+            if !matched {
+                continue;
+            }
+            reply.push(Resp::BulkStrings(BulkStrings::new(name.to_owned())));
+            if let Some(value) = value(item) {
+                reply.push(Resp::BulkStrings(BulkStrings::new(value)));
+            }
+        }
+        Resp::Arrays(Arrays::new(vec![
+            Resp::BulkStrings(BulkStrings::new(next_cursor.to_string())),
+            Resp::Arrays(Arrays::new(reply)),
+        ]))
     }
 }
 
-// 简单的模式匹配函数，支持*通配符
-fn simple_pattern_match(text: &str, pattern: &str) -> bool {
-    if pattern == "*" {
-        return true;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleStringsData;
+
+    fn scan(data: &Data, cursor: u64, count: u64, key_type: Option<&str>) -> (u64, Vec<String>) {
+        let cmd = ScanCommandPara::new(
+            cursor,
+            None,
+            Some(count),
+            key_type.map(str::to_owned),
+            Parameter::new(),
+        );
+        let Resp::Arrays(reply) = cmd.process(data).unwrap() else {
+            panic!("unexpected reply");
+        };
+        let (Resp::BulkStrings(next), Resp::Arrays(keys)) = (&reply.val[0], &reply.val[1]) else {
+            panic!("unexpected reply");
+        };
+        let keys = keys
+            .val
+            .iter()
+            .map(|key| match key {
+                Resp::BulkStrings(key) => key.val.clone(),
+                _ => panic!("unexpected key"),
+            })
+            .collect();
+        (next.val.parse().unwrap(), keys)
+    }
+
+    fn set(data: &Data, key: &str) {
+        data.db().string_data.insert(
+            key.to_owned(),
+            Resp::SimpleStrings(SimpleStringsData::new("v".to_owned())),
+        );
+    }
+
+    #[test]
+    fn test_scan_survives_concurrent_writes() {
+        let data = Data::new();
+        for i in 0..100 {
+            set(&data, &format!("key:{i}"));
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        let mut cursor = 0;
+        let mut round = 0;
+        loop {
+            let (next, keys) = scan(&data, cursor, 7, None);
+            assert!(keys.len() <= 7);
+            seen.extend(keys);
+            // 遍历过程中不断插入和删除其它键
+            set(&data, &format!("new:{round}"));
+            data.remove_key(&format!("key:{}", 50 + round % 50));
+            round += 1;
+            cursor = next;
+            if cursor == 0 {
+                break;
+            }
+        }
+        for i in 0..50 {
+            assert!(seen.contains(&format!("key:{i}")));
+        }
     }
 
-    // 简单实现：将*替换为正则表达式的.*
-    let regex_pattern = pattern.replace("*", ".*");
-    let regex = match regex::Regex::new(&format!("^{regex_pattern}$")) {
-        Ok(re) => re,
-        Err(_) => return false,
-    };
+    #[test]
+    fn test_scan_type_filter() {
+        let data = Data::new();
+        set(&data, "s");
+        data.db()
+            .list_data
+            .insert("l".to_owned(), ["a".to_owned()].into());
+        assert_eq!(scan(&data, 0, 10, Some("list")), (0, vec!["l".to_owned()]));
+        assert_eq!(scan(&data, 0, 10, None).1.len(), 2);
+        assert_eq!(scan(&data, 0, 10, Some("hash")), (0, vec![]));
+    }
+
+    // 执行一条命令，返回 (下一个游标, 回复中的元素)
+    fn element_scan(data: &Data, args: &[&str]) -> (u64, Vec<String>) {
+        let frame = Resp::Arrays(Arrays::new(
+            args.iter()
+                .map(|arg| Resp::BulkStrings(BulkStrings::new(arg.to_string())))
+                .collect(),
+        ));
+        let command = crate::process::CommandGroup::try_from(frame).unwrap();
+        let Resp::Arrays(reply) = command.process(data).unwrap() else {
+            panic!("unexpected reply");
+        };
+        let (Resp::BulkStrings(next), Resp::Arrays(items)) = (&reply.val[0], &reply.val[1]) else {
+            panic!("unexpected reply");
+        };
+        let items = items
+            .val
+            .iter()
+            .map(|item| match item {
+                Resp::BulkStrings(item) => item.val.clone(),
+                _ => panic!("unexpected item"),
+            })
+            .collect();
+        (next.val.parse().unwrap(), items)
+    }
+
+    #[test]
+    fn test_element_scan() {
+        let data = Data::new();
+        let hash = (0..100)
+            .map(|i| (format!("field:{i}"), i.to_string()))
+            .collect();
+        data.db().hash_data.insert("h".to_owned(), hash);
 
-    regex.is_match(text)
+        // 按游标分批遍历，每个字段和它的值只返回一次
+        let mut seen = std::collections::HashMap::new();
+        let mut cursor = 0;
+        loop {
+            let (next, items) =
+                element_scan(&data, &["HSCAN", "h", &cursor.to_string(), "COUNT", "7"]);
+            assert!(items.len() <= 14);
+            for pair in items.chunks(2) {
+                assert!(seen.insert(pair[0].clone(), pair[1].clone()).is_none());
+            }
+            cursor = next;
+            if cursor == 0 {
+                break;
+            }
+        }
+        assert_eq!(seen.len(), 100);
+        assert_eq!(seen["field:42"], "42");
+
+        data.db().set_data.insert(
+            "s".to_owned(),
+            ["apple", "banana", "avocado"].map(str::to_owned).into(),
+        );
+        let (next, mut members) = element_scan(&data, &["SSCAN", "s", "0", "MATCH", "a*"]);
+        members.sort();
+        assert_eq!(
+            (next, members),
+            (0, vec!["apple".to_owned(), "avocado".to_owned()])
+        );
+
+        data.db()
+            .sorted_set_data
+            .insert("z".to_owned(), [("m".to_owned(), 1.5)].into());
+        assert_eq!(
+            element_scan(&data, &["ZSCAN", "z", "0"]),
+            (0, vec!["m".to_owned(), "1.5".to_owned()])
+        );
+        assert_eq!(element_scan(&data, &["ZSCAN", "missing", "0"]), (0, vec![]));
+    }
 }
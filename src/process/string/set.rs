@@ -1,7 +1,7 @@
 use anyhow::Ok;
 use tracing::info;
 
-use crate::{process::Parameter, Data, Processor, Resp, SimpleStringsData};
+use crate::{notify, process::Parameter, Data, Processor, Resp, SimpleStringsData};
 
 #[derive(Debug)]
 #[allow(unused)]
@@ -23,20 +23,11 @@ impl Processor for SetCommandPara {
     fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
         match (&self.key, &self.value) {
             (Some(k), Some(v)) => {
-                // 处理特殊的管理命令
-                match k.as_str() {
-                    "__client__" | "__select__" | "__unsupported__" => {
-                        info!("🔧 Management command -> OK");
-                        return Ok(Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned())));
-                    }
-                    _ => {}
-                }
-
-                // 正常的SET操作
-                data.string_data.insert(
+                data.db().string_data.insert(
                     k.clone(),
                     Resp::SimpleStrings(SimpleStringsData::new(v.clone())),
                 );
+                data.notify_keyspace_event(notify::NOTIFY_STRING, "set", k);
                 info!("✅ SET '{}' = '{}' -> OK", k, v);
                 Ok(Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned())))
             }
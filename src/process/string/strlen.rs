@@ -0,0 +1,31 @@
+use tracing::info;
+
+use crate::{process::Parameter, Data, Integers, Processor, Resp};
+
+#[derive(Debug)]
+pub struct StrLenCommandPara {
+    pub key: String,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl StrLenCommandPara {
+    pub fn new(key: String, para: Parameter) -> Self {
+        Self { key, para }
+    }
+}
+
+impl Processor for StrLenCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("StrLenCommandPara process start: {:?}", &self);
+        data.expire_if_needed(&self.key);
+
+        let len = match data.db().string_data.get(&self.key).as_deref() {
+            Some(Resp::SimpleStrings(s)) => s.val.len(),
+            Some(Resp::BulkStrings(s)) => s.val.len(),
+            Some(Resp::Integers(i)) => i.val.to_string().len(),
+            _ => 0,
+        };
+        Ok(Resp::Integers(Integers::new(len as i64)))
+    }
+}
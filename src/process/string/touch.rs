@@ -0,0 +1,29 @@
+use tracing::info;
+
+use crate::{process::Parameter, Data, Integers, Processor, Resp};
+
+#[derive(Debug)]
+pub struct TouchCommandPara {
+    pub keys: Vec<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl TouchCommandPara {
+    pub fn new(keys: Vec<String>, para: Parameter) -> Self {
+        Self { keys, para }
+    }
+}
+
+impl Processor for TouchCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        // 访问时间由命令执行框架统一更新，这里只统计存在的键
+        let touched = self
+            .keys
+            .iter()
+            .filter(|key| !data.expire_if_needed(key) && data.contains_key(key))
+            .count();
+        info!("👆 TOUCH {:?} -> {}", self.keys, touched);
+        Ok(Resp::Integers(Integers::new(touched as i64)))
+    }
+}
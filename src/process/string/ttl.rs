@@ -20,11 +20,11 @@ impl Processor for TtlCommandPara {
         info!("TtlCommandPara process start: {:?}", &self);
 
         // 检查键是否存在
-        let key_exists = data.string_data.contains_key(&self.key)
-            || data.hash_data.contains_key(&self.key)
-            || data.list_data.contains_key(&self.key)
-            || data.set_data.contains_key(&self.key)
-            || data.sorted_set_data.contains_key(&self.key);
+        let key_exists = data.db().string_data.contains_key(&self.key)
+            || data.db().hash_data.contains_key(&self.key)
+            || data.db().list_data.contains_key(&self.key)
+            || data.db().set_data.contains_key(&self.key)
+            || data.db().sorted_set_data.contains_key(&self.key);
 
         if !key_exists {
             info!("⏰ TTL '{}' key not found", self.key);
@@ -35,7 +35,7 @@ impl Processor for TtlCommandPara {
             Some(ttl_millis) => {
                 if ttl_millis < 0 {
                     // 键已过期，清理它
-                    data.remove_key(&self.key);
+                    data.expire_if_needed(&self.key);
                     info!("⏰ TTL '{}' key expired and removed", self.key);
                     Ok(Resp::Integers(Integers::new(-2))) // 键不存在
                 } else {
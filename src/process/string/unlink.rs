@@ -0,0 +1,36 @@
+use tracing::info;
+
+use crate::{notify, process::Parameter, Data, Integers, Processor, Resp};
+
+#[derive(Debug)]
+pub struct UnlinkCommandPara {
+    pub keys: Vec<String>,
+    #[allow(dead_code)]
+    para: Parameter,
+}
+
+impl UnlinkCommandPara {
+    pub fn new(keys: Vec<String>, para: Parameter) -> Self {
+        Self { keys, para }
+    }
+}
+
+impl Processor for UnlinkCommandPara {
+    fn process(&self, data: &Data) -> Result<Resp, anyhow::Error> {
+        info!("UnlinkCommandPara process start: {:?}", &self);
+        let mut unlinked = 0i64;
+        for key in &self.keys {
+            if data.expire_if_needed(key) {
+                continue;
+            }
+            // 先把值从库中摘下，大的值在后台线程释放
+            if let Some(value) = data.db().take_value(key) {
+                data.remove_key(key);
+                value.free();
+                unlinked += 1;
+                data.notify_keyspace_event(notify::NOTIFY_GENERIC, "del", key);
+            }
+        }
+        Ok(Resp::Integers(Integers::new(unlinked)))
+    }
+}
@@ -0,0 +1,316 @@
+//! 发布/订阅
+//!
+//! `PubSub` 是所有连接共享的消息代理，记录每个频道、模式和分片频道的订阅者。
+//! 每个订阅连接持有一个 `Subscriber`，消息通过 channel 异步推送给连接，
+//! 由连接按 RESP2（数组）或 RESP3（Push）格式写出。
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use tokio::sync::mpsc;
+
+use crate::glob::glob_match;
+use crate::limits::Outbox;
+use crate::{Arrays, BulkStrings, Data, Integers, Nulls, Resp};
+
+/// 推送给订阅连接的消息，元素按 `["message", channel, payload]` 排列
+pub type Message = Vec<Resp>;
+
+type Subscribers = HashMap<u64, Outbox<Message>>;
+
+#[derive(Debug, Default)]
+pub struct PubSub {
+    channels: DashMap<String, Subscribers>,
+    patterns: DashMap<String, Subscribers>,
+    shard_channels: DashMap<String, Subscribers>,
+}
+
+// 订阅的种类，决定回复中使用的消息名
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Channel,
+    Pattern,
+    Shard,
+}
+
+impl Kind {
+    fn subscribe_name(self) -> &'static str {
+        match self {
+            Kind::Channel => "subscribe",
+            Kind::Pattern => "psubscribe",
+            Kind::Shard => "ssubscribe",
+        }
+    }
+
+    fn unsubscribe_name(self) -> &'static str {
+        match self {
+            Kind::Channel => "unsubscribe",
+            Kind::Pattern => "punsubscribe",
+            Kind::Shard => "sunsubscribe",
+        }
+    }
+}
+
+impl PubSub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn registry(&self, kind: Kind) -> &DashMap<String, Subscribers> {
+        match kind {
+            Kind::Channel => &self.channels,
+            Kind::Pattern => &self.patterns,
+            Kind::Shard => &self.shard_channels,
+        }
+    }
+
+    /// PUBLISH：返回收到消息的订阅者数量
+    pub fn publish(&self, channel: &str, payload: &str) -> usize {
+        let mut receivers = 0;
+        if let Some(subscribers) = self.channels.get(channel) {
+            let message = vec![bulk("message"), bulk(channel), bulk(payload)];
+            receivers += send_all(&subscribers, &message);
+        }
+        for entry in self.patterns.iter() {
+            if glob_match(entry.key(), channel) {
+                let message = vec![
+                    bulk("pmessage"),
+                    bulk(entry.key()),
+                    bulk(channel),
+                    bulk(payload),
+                ];
+                receivers += send_all(entry.value(), &message);
+            }
+        }
+        receivers
+    }
+
+    /// SPUBLISH：只投递给分片频道的订阅者
+    pub fn spublish(&self, channel: &str, payload: &str) -> usize {
+        match self.shard_channels.get(channel) {
+            Some(subscribers) => {
+                let message = vec![bulk("smessage"), bulk(channel), bulk(payload)];
+                send_all(&subscribers, &message)
+            }
+            None => 0,
+        }
+    }
+
+    /// PUBSUB CHANNELS / SHARDCHANNELS
+    pub fn active_channels(&self, pattern: Option<&str>, shard: bool) -> Vec<String> {
+        let registry = if shard {
+            &self.shard_channels
+        } else {
+            &self.channels
+        };
+        let mut channels: Vec<String> = registry
+            .iter()
+            .filter(|entry| pattern.is_none_or(|pattern| glob_match(pattern, entry.key())))
+            .map(|entry| entry.key().clone())
+            .collect();
+        channels.sort();
+        channels
+    }
+
+    /// PUBSUB NUMSUB / SHARDNUMSUB
+    pub fn num_subscribers(&self, channel: &str, shard: bool) -> usize {
+        let registry = if shard {
+            &self.shard_channels
+        } else {
+            &self.channels
+        };
+        registry.get(channel).map(|subs| subs.len()).unwrap_or(0)
+    }
+
+    /// PUBSUB NUMPAT：被订阅的模式数量
+    pub fn num_patterns(&self) -> usize {
+        self.patterns.len()
+    }
+
+    fn add(&self, kind: Kind, name: &str, id: u64, tx: &Outbox<Message>) {
+        self.registry(kind)
+            .entry(name.to_owned())
+            .or_default()
+            .insert(id, tx.clone());
+    }
+
+    fn remove(&self, kind: Kind, name: &str, id: u64) {
+        self.registry(kind).remove_if_mut(name, |_, subscribers| {
+            subscribers.remove(&id);
+            subscribers.is_empty()
+        });
+    }
+}
+
+/// 连接上的订阅状态，连接断开时自动退订
+#[derive(Debug)]
+pub struct Subscriber {
+    data: Arc<Data>,
+    id: u64,
+    tx: Outbox<Message>,
+    channels: BTreeSet<String>,
+    patterns: BTreeSet<String>,
+    shard_channels: BTreeSet<String>,
+}
+
+impl Subscriber {
+    /// `id` 为连接 id，返回订阅状态和接收消息的一端
+    pub fn new(data: Arc<Data>, id: u64) -> (Self, mpsc::UnboundedReceiver<Message>) {
+        let (tx, rx) = Outbox::channel(data.clients.get(id), &data.limits);
+        let subscriber = Self {
+            data,
+            id,
+            tx,
+            channels: BTreeSet::new(),
+            patterns: BTreeSet::new(),
+            shard_channels: BTreeSet::new(),
+        };
+        (subscriber, rx)
+    }
+
+    fn set(&mut self, kind: Kind) -> &mut BTreeSet<String> {
+        match kind {
+            Kind::Channel => &mut self.channels,
+            Kind::Pattern => &mut self.patterns,
+            Kind::Shard => &mut self.shard_channels,
+        }
+    }
+
+    // 回复中的订阅数：分片频道单独计数
+    fn count(&self, kind: Kind) -> i64 {
+        match kind {
+            Kind::Shard => self.shard_channels.len() as i64,
+            _ => (self.channels.len() + self.patterns.len()) as i64,
+        }
+    }
+
+    /// 是否处于订阅状态（RESP2 下只允许订阅相关命令）
+    pub fn is_subscribed(&self) -> bool {
+        !self.channels.is_empty() || !self.patterns.is_empty() || !self.shard_channels.is_empty()
+    }
+
+    /// 推送消息给这个连接的一端，失效消息等也通过它发送
+    pub fn sender(&self) -> Outbox<Message> {
+        self.tx.clone()
+    }
+
+    /// 订阅的频道、模式和分片频道数
+    pub fn counts(&self) -> (usize, usize, usize) {
+        (
+            self.channels.len(),
+            self.patterns.len(),
+            self.shard_channels.len(),
+        )
+    }
+
+    /// (P|S)SUBSCRIBE：每个频道回复一条确认消息
+    pub fn subscribe(&mut self, kind: Kind, names: &[String]) -> Vec<Message> {
+        names
+            .iter()
+            .map(|name| {
+                if self.set(kind).insert(name.clone()) {
+                    self.data.pubsub.add(kind, name, self.id, &self.tx);
+                }
+                vec![
+                    bulk(kind.subscribe_name()),
+                    bulk(name),
+                    Resp::Integers(Integers::new(self.count(kind))),
+                ]
+            })
+            .collect()
+    }
+
+    /// (P|S)UNSUBSCRIBE：不带参数时退订全部
+    pub fn unsubscribe(&mut self, kind: Kind, names: &[String]) -> Vec<Message> {
+        let names: Vec<String> = if names.is_empty() {
+            self.set(kind).iter().cloned().collect()
+        } else {
+            names.to_vec()
+        };
+        if names.is_empty() {
+            return vec![vec![
+                bulk(kind.unsubscribe_name()),
+                Resp::Nulls(Nulls::new()),
+                Resp::Integers(Integers::new(self.count(kind))),
+            ]];
+        }
+        names
+            .iter()
+            .map(|name| {
+                if self.set(kind).remove(name) {
+                    self.data.pubsub.remove(kind, name, self.id);
+                }
+                vec![
+                    bulk(kind.unsubscribe_name()),
+                    bulk(name),
+                    Resp::Integers(Integers::new(self.count(kind))),
+                ]
+            })
+            .collect()
+    }
+
+    fn unsubscribe_all(&mut self) {
+        for kind in [Kind::Channel, Kind::Pattern, Kind::Shard] {
+            let names = std::mem::take(self.set(kind));
+            for name in names {
+                self.data.pubsub.remove(kind, &name, self.id);
+            }
+        }
+    }
+}
+
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        self.unsubscribe_all();
+    }
+}
+
+fn send_all(subscribers: &Subscribers, message: &Message) -> usize {
+    subscribers
+        .values()
+        .filter(|tx| tx.send(message.clone()))
+        .count()
+}
+
+/// 按协议版本把消息包装成数组（RESP2）或 Push（RESP3）
+pub fn into_frame(message: Message, protocol: u8) -> Resp {
+    if protocol >= 3 {
+        Resp::Pushes(crate::Pushes::new(message))
+    } else {
+        Resp::Arrays(Arrays::new(message))
+    }
+}
+
+fn bulk(val: &str) -> Resp {
+    Resp::BulkStrings(BulkStrings::new(val.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_and_subscribe() {
+        let data = Arc::new(Data::new());
+        let (mut sub, mut rx) = Subscriber::new(data.clone(), 1);
+        let replies = sub.subscribe(Kind::Channel, &["news".to_owned()]);
+        assert_eq!(replies[0][2], Resp::Integers(Integers::new(1)));
+        sub.subscribe(Kind::Pattern, &["n*".to_owned()]);
+        assert!(sub.is_subscribed());
+
+        assert_eq!(data.pubsub.publish("news", "hi"), 2);
+        assert_eq!(rx.try_recv().unwrap()[0], bulk("message"));
+        assert_eq!(rx.try_recv().unwrap()[0], bulk("pmessage"));
+        assert_eq!(data.pubsub.spublish("news", "hi"), 0);
+        assert_eq!(data.pubsub.num_subscribers("news", false), 1);
+        assert_eq!(data.pubsub.num_patterns(), 1);
+
+        let replies = sub.unsubscribe(Kind::Channel, &[]);
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0][2], Resp::Integers(Integers::new(1)));
+        drop(sub);
+        assert_eq!(data.pubsub.num_patterns(), 0);
+        assert!(data.pubsub.active_channels(None, false).is_empty());
+    }
+}
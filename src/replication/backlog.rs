@@ -0,0 +1,83 @@
+use std::collections::VecDeque;
+
+/// 复制积压缓冲区（固定大小的环形缓冲）
+///
+/// 保存最近写入复制流的字节，副本短暂断线后可以从这里补发缺失的部分，
+/// 避免重新做一次全量同步。偏移量和 Redis 一致：从 1 开始计数。
+#[derive(Debug)]
+pub struct Backlog {
+    buf: VecDeque<u8>,
+    size: usize,
+    // buf[0] 对应的复制偏移量
+    first_byte_offset: u64,
+}
+
+impl Backlog {
+    /// 创建积压缓冲区，`first_byte_offset` 为下一个写入字节的偏移量
+    pub fn new(size: usize, first_byte_offset: u64) -> Self {
+        Self {
+            buf: VecDeque::with_capacity(size.min(64 * 1024)),
+            size,
+            first_byte_offset,
+        }
+    }
+
+    pub fn append(&mut self, bytes: &[u8]) {
+        self.buf.extend(bytes);
+        if self.buf.len() > self.size {
+            let overflow = self.buf.len() - self.size;
+            self.buf.drain(..overflow);
+            self.first_byte_offset += overflow as u64;
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn first_byte_offset(&self) -> u64 {
+        self.first_byte_offset
+    }
+
+    pub fn histlen(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// 取出从 `offset` 开始的全部数据，偏移量不在缓冲区范围内时返回 None
+    pub fn range_from(&self, offset: u64) -> Option<Vec<u8>> {
+        let end = self.first_byte_offset + self.buf.len() as u64;
+        if offset < self.first_byte_offset || offset > end {
+            return None;
+        }
+        let start = (offset - self.first_byte_offset) as usize;
+        Some(self.buf.range(start..).copied().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backlog_range_from() {
+        let mut backlog = Backlog::new(16, 1);
+        backlog.append(b"hello");
+        backlog.append(b"world");
+        assert_eq!(backlog.range_from(1), Some(b"helloworld".to_vec()));
+        assert_eq!(backlog.range_from(6), Some(b"world".to_vec()));
+        // 副本已经拿到全部数据
+        assert_eq!(backlog.range_from(11), Some(Vec::new()));
+        assert_eq!(backlog.range_from(12), None);
+        assert_eq!(backlog.range_from(0), None);
+    }
+
+    #[test]
+    fn test_backlog_overflow() {
+        let mut backlog = Backlog::new(8, 1);
+        backlog.append(b"0123456789");
+        assert_eq!(backlog.histlen(), 8);
+        assert_eq!(backlog.first_byte_offset(), 3);
+        assert_eq!(backlog.range_from(2), None);
+        assert_eq!(backlog.range_from(3), Some(b"23456789".to_vec()));
+    }
+}
@@ -0,0 +1,98 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use futures::StreamExt;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio_util::codec::{Framed, FramedRead};
+use tracing::{debug, info, warn};
+
+use crate::client::Client;
+use crate::limits::Outbox;
+use crate::network::RespFrameCodec;
+use crate::process::replication::psync::PSyncCommandPara;
+use crate::{Data, Resp};
+
+/// 把一个执行了 PSYNC 的客户端连接转为复制连接
+///
+/// 先发送 +FULLRESYNC 快照或 +CONTINUE 积压数据，之后持续推送复制流，
+/// 同时读取副本定时发来的 REPLCONF ACK。
+pub async fn serve_replica<T>(
+    framed: Framed<T, RespFrameCodec>,
+    data: Arc<Data>,
+    client: &Arc<Client>,
+    psync: &PSyncCommandPara,
+    listening_port: Option<u16>,
+    addr: SocketAddr,
+) -> Result<()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let ip = addr.ip().to_string();
+    let port = listening_port.unwrap_or(addr.port());
+    let (tx, mut rx) = Outbox::channel(Some(client.clone()), &data.limits);
+    let (id, reply) = data.replication.attach_replica(
+        &data,
+        &psync.replid,
+        psync.offset,
+        ip.clone(),
+        port,
+        tx,
+    )?;
+
+    let parts = framed.into_parts();
+    let (reader, mut writer) = tokio::io::split(parts.io);
+    let mut reader = FramedRead::new(reader, parts.codec);
+    reader.read_buffer_mut().extend_from_slice(&parts.read_buf);
+
+    let res = async {
+        writer.write_all(&reply).await?;
+        loop {
+            tokio::select! {
+                bytes = rx.recv() => match bytes {
+                    Some(bytes) => {
+                        writer.write_all(&bytes).await?;
+                        client.release_output(bytes.len());
+                    }
+                    // 主节点主动断开了这个副本（例如自己变成了别人的副本）
+                    None => return Ok(()),
+                },
+                frame = reader.next() => match frame {
+                    Some(Ok(frame)) => {
+                        if let Some(offset) = parse_ack(&frame) {
+                            debug!("Replica {}:{} acked offset {}", ip, port, offset);
+                            data.replication.record_ack(id, offset);
+                        }
+                    }
+                    Some(Err(e)) => return Err(e),
+                    None => return Ok(()),
+                },
+                // CLIENT KILL TYPE replica 或者 SHUTDOWN
+                _ = client.killed() => return Ok(()),
+            }
+        }
+    }
+    .await;
+
+    data.replication.detach_replica(id);
+    match &res {
+        Ok(_) => info!("👋 Replica {}:{} disconnected", ip, port),
+        Err(e) => warn!("❌ Replica {}:{} connection lost: {}", ip, port, e),
+    }
+    res
+}
+
+// REPLCONF ACK <offset>
+fn parse_ack(frame: &Resp) -> Option<u64> {
+    let Resp::Arrays(arr) = frame else {
+        return None;
+    };
+    match arr.val.as_slice() {
+        [Resp::BulkStrings(cmd), Resp::BulkStrings(sub), Resp::BulkStrings(offset)]
+            if cmd.val.eq_ignore_ascii_case("replconf") && sub.val.eq_ignore_ascii_case("ack") =>
+        {
+            offset.val.parse().ok()
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,932 @@
+//! 主从复制
+//!
+//! - 主节点：把写命令追加到复制流（积压缓冲区 + 所有在线副本）
+//! - 副本：通过 REPLICAOF 连接主节点，完成 PING/REPLCONF/PSYNC 握手后持续接收复制流
+
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use bytes::Bytes;
+use rand::Rng;
+use tokio::sync::watch;
+use tokio_rustls::rustls::ClientConfig;
+use tokio_rustls::TlsConnector;
+use tracing::{debug, info};
+
+use crate::client::Client;
+use crate::limits::Outbox;
+use crate::process::command_table;
+use crate::process::connection::ConnectionCommand;
+use crate::process::CommandGroup;
+use crate::transaction::Transaction;
+use crate::{Arrays, BulkStrings, Data, Integers, Resp, RespEncoder};
+
+use self::backlog::Backlog;
+
+pub mod backlog;
+pub mod master;
+pub mod replica;
+
+// 默认积压缓冲区大小 1MB，与 Redis 的 repl-backlog-size 默认值一致
+pub const DEFAULT_BACKLOG_SIZE: usize = 1024 * 1024;
+// 主节点向副本发送 PING 的间隔
+pub const PING_REPLICA_PERIOD: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MasterAddr {
+    pub host: String,
+    pub port: u16,
+}
+
+/// 副本到主节点的连接状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkState {
+    Connect,
+    Connecting,
+    Sync,
+    Connected,
+}
+
+impl LinkState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkState::Connect => "connect",
+            LinkState::Connecting => "connecting",
+            LinkState::Sync => "sync",
+            LinkState::Connected => "connected",
+        }
+    }
+}
+
+/// 主节点上一个在线副本
+#[derive(Debug)]
+struct ReplicaHandle {
+    id: u64,
+    ip: String,
+    port: u16,
+    tx: Outbox<Bytes>,
+    ack_offset: u64,
+    last_ack: Instant,
+}
+
+#[derive(Debug)]
+struct ReplicationState {
+    replid: String,
+    // 上一任主节点的 replid，用于故障切换后的部分重同步
+    replid2: String,
+    second_replid_offset: i64,
+    master_repl_offset: u64,
+    backlog: Option<Backlog>,
+    replicas: Vec<ReplicaHandle>,
+    next_replica_id: u64,
+    link_state: LinkState,
+    last_master_io: Option<Instant>,
+    // 复制流中最近一次 SELECT 的库，None 表示下一条写命令前需要先发送 SELECT
+    stream_db: Option<usize>,
+    // 副本：主节点复制流当前选择的库
+    master_db: usize,
+    // EXEC 执行期间为 Some，值表示是否已经向复制流写入了 MULTI
+    exec: Option<bool>,
+}
+
+#[derive(Debug)]
+pub struct Replication {
+    state: Mutex<ReplicationState>,
+    master: watch::Sender<Option<MasterAddr>>,
+    listening_port: AtomicU16,
+    // tls-replication 时副本连接主节点使用的 TLS 配置
+    tls: Mutex<Option<Arc<ClientConfig>>>,
+    read_only: AtomicBool,
+    backlog_size: usize,
+    // 主节点删除的过期键 (库, 键)，等待以 DEL 写入复制流
+    expired: Mutex<Vec<(usize, String)>>,
+}
+
+impl Replication {
+    pub fn new() -> Self {
+        let (master, _) = watch::channel(None);
+        Self {
+            state: Mutex::new(ReplicationState {
+                replid: random_replid(),
+                replid2: "0".repeat(40),
+                second_replid_offset: -1,
+                master_repl_offset: 0,
+                backlog: None,
+                replicas: Vec::new(),
+                next_replica_id: 0,
+                link_state: LinkState::Connect,
+                last_master_io: None,
+                stream_db: None,
+                master_db: 0,
+                exec: None,
+            }),
+            master,
+            listening_port: AtomicU16::new(6379),
+            tls: Mutex::new(None),
+            read_only: AtomicBool::new(true),
+            backlog_size: DEFAULT_BACKLOG_SIZE,
+            expired: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ReplicationState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set_listening_port(&self, port: u16) {
+        self.listening_port.store(port, Ordering::Relaxed);
+    }
+
+    pub fn listening_port(&self) -> u16 {
+        self.listening_port.load(Ordering::Relaxed)
+    }
+
+    /// 开启 tls-replication：之后连接主节点时使用 TLS
+    pub fn set_tls(&self, config: Arc<ClientConfig>) {
+        *self.tls.lock().unwrap_or_else(|e| e.into_inner()) = Some(config);
+    }
+
+    pub fn tls_connector(&self) -> Option<TlsConnector> {
+        let config = self.tls.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        config.map(TlsConnector::from)
+    }
+
+    pub fn set_read_only(&self, read_only: bool) {
+        self.read_only.store(read_only, Ordering::Relaxed);
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Relaxed)
+    }
+
+    pub fn master_addr(&self) -> Option<MasterAddr> {
+        self.master.borrow().clone()
+    }
+
+    pub fn is_replica(&self) -> bool {
+        self.master.borrow().is_some()
+    }
+
+    /// 复制积压缓冲区中已经使用的字节数
+    pub fn backlog_memory(&self) -> usize {
+        self.lock().backlog.as_ref().map_or(0, Backlog::histlen)
+    }
+
+    /// 只读副本拒绝普通客户端的写命令
+    pub fn is_read_only_replica(&self) -> bool {
+        self.is_replica() && self.read_only.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn subscribe_master(&self) -> watch::Receiver<Option<MasterAddr>> {
+        self.master.subscribe()
+    }
+
+    /// REPLICAOF host port，已经是该主节点的副本时返回 false
+    pub fn replicaof(&self, addr: MasterAddr) -> bool {
+        if self.master.borrow().as_ref() == Some(&addr) {
+            return false;
+        }
+        let mut state = self.lock();
+        // 更换主节点后数据集会被全量替换，下挂的副本需要重新同步
+        state.replicas.clear();
+        state.link_state = LinkState::Connect;
+        state.last_master_io = None;
+        info!("🔗 Replica of {}:{}", addr.host, addr.port);
+        self.master.send_replace(Some(addr));
+        true
+    }
+
+    /// REPLICAOF NO ONE，提升为主节点
+    pub fn replicaof_no_one(&self) {
+        if self.master.send_replace(None).is_none() {
+            return;
+        }
+        let mut state = self.lock();
+        // 保留旧的 replid，让其他副本可以对新主节点做部分重同步
+        state.replid2 = std::mem::replace(&mut state.replid, random_replid());
+        state.second_replid_offset = state.master_repl_offset as i64 + 1;
+        state.link_state = LinkState::Connect;
+        state.stream_db = None;
+        info!("👑 Promoted to master, new replid {}", state.replid);
+    }
+
+    /// 执行写命令，成功后把命令追加到复制流
+    ///
+    /// 写命令在复制锁内执行，保证复制流中的顺序与命令真正生效的顺序一致。
+    pub fn propagate<F>(&self, data: &Data, argv: &Resp, exec: F) -> Result<Resp>
+    where
+        F: FnOnce() -> Result<Resp>,
+    {
+        let mut state = self.lock();
+        let reply = exec()?;
+        // 副本原样转发主节点的复制流，不追加自己执行的命令
+        if self.is_replica() {
+            return Ok(reply);
+        }
+        // 命令执行过程中过期的键先于命令本身删除
+        self.feed_expired(&mut state)?;
+        if !matches!(reply, Resp::SimpleErrors(_)) {
+            if let Some(frame) = rewrite_for_replication(data, argv) {
+                self.feed_in_db(&mut state, crate::db::selected_db(), &frame)?;
+            }
+        }
+        Ok(reply)
+    }
+
+    /// 主节点删除了当前库中过期的键，记录下来以 DEL 发送给副本
+    ///
+    /// 过期可能发生在写命令执行过程中，此时已经持有复制锁，
+    /// 所以先放进队列，由 `propagate` 或 `propagate_expired` 写入复制流。
+    /// 副本自己不产生 DEL，等待主节点的同步。
+    pub fn expired(&self, key: &str) {
+        if self.is_replica() {
+            return;
+        }
+        self.expired
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((crate::db::selected_db(), key.to_owned()));
+    }
+
+    /// 把队列中过期键的 DEL 写入复制流
+    pub fn propagate_expired(&self) -> Result<()> {
+        if self
+            .expired
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty()
+        {
+            return Ok(());
+        }
+        let mut state = self.lock();
+        self.feed_expired(&mut state)
+    }
+
+    fn feed_expired(&self, state: &mut ReplicationState) -> Result<()> {
+        let expired = std::mem::take(&mut *self.expired.lock().unwrap_or_else(|e| e.into_inner()));
+        for (db, key) in expired {
+            self.feed_in_db(state, db, &command(&["DEL", &key]))?;
+        }
+        Ok(())
+    }
+
+    // 命令执行的库和复制流中的不一致时，先让副本切换到同一个库
+    fn feed_in_db(&self, state: &mut ReplicationState, db: usize, frame: &Resp) -> Result<()> {
+        if state.stream_db != Some(db) {
+            self.feed(state, &command(&["SELECT", &db.to_string()]))?;
+            state.stream_db = Some(db);
+        }
+        // 事务中的第一条写命令之前写入 MULTI
+        if state.exec == Some(false) {
+            self.feed(state, &command(&["MULTI"]))?;
+            state.exec = Some(true);
+        }
+        self.feed(state, frame)
+    }
+
+    /// EXEC 开始执行排队的命令：和 Redis 一样，其中的写命令在复制流中用 MULTI/EXEC 包起来，
+    /// 副本也作为一个整体执行，不会只执行一半
+    pub fn begin_exec(&self) {
+        self.lock().exec = Some(false);
+    }
+
+    /// EXEC 执行结束，写入过 MULTI 时写入 EXEC；没有写命令的事务不产生复制流
+    pub fn end_exec(&self) -> Result<()> {
+        let mut state = self.lock();
+        if state.exec.take() == Some(true) {
+            self.feed(&mut state, &command(&["EXEC"]))?;
+        }
+        Ok(())
+    }
+
+    /// 主节点定时向副本发送 PING，副本据此判断连接是否存活
+    pub fn ping_replicas(&self) -> Result<()> {
+        if self.is_replica() {
+            return Ok(());
+        }
+        let mut state = self.lock();
+        if state.replicas.is_empty() {
+            return Ok(());
+        }
+        self.feed(&mut state, &command(&["PING"]))
+    }
+
+    fn feed(&self, state: &mut ReplicationState, frame: &Resp) -> Result<()> {
+        if state.backlog.is_none() && state.replicas.is_empty() {
+            return Ok(());
+        }
+        let bytes = Bytes::from(frame.clone().encode()?);
+        state.master_repl_offset += bytes.len() as u64;
+        if let Some(backlog) = state.backlog.as_mut() {
+            backlog.append(&bytes);
+        }
+        // 发送失败说明副本连接已经断开或者超过了输出缓冲区限制
+        state
+            .replicas
+            .retain(|replica| replica.tx.send(bytes.clone()));
+        Ok(())
+    }
+
+    /// 处理副本的 PSYNC 请求，返回副本 id 和需要先发送给副本的数据
+    ///
+    /// 能从积压缓冲区补发时回复 +CONTINUE，否则回复 +FULLRESYNC 并附带快照。
+    pub(crate) fn attach_replica(
+        &self,
+        data: &Data,
+        replid: &str,
+        offset: i64,
+        ip: String,
+        port: u16,
+        tx: Outbox<Bytes>,
+    ) -> Result<(u64, Vec<u8>)> {
+        let mut state = self.lock();
+        let id_matches = replid == state.replid
+            || (replid == state.replid2 && offset <= state.second_replid_offset);
+        let partial = match (&state.backlog, id_matches && offset > 0) {
+            (Some(backlog), true) => backlog.range_from(offset as u64),
+            _ => None,
+        };
+
+        let mut reply = Vec::new();
+        match partial {
+            Some(missing) => {
+                info!(
+                    "🔁 Partial resync for replica {}:{} from offset {}",
+                    ip, port, offset
+                );
+                reply.extend(format!("+CONTINUE {}\r\n", state.replid).into_bytes());
+                reply.extend(missing);
+            }
+            None => {
+                info!(
+                    "📦 Full resync for replica {}:{} at offset {}",
+                    ip, port, state.master_repl_offset
+                );
+                if state.backlog.is_none() {
+                    let first_byte_offset = state.master_repl_offset + 1;
+                    state.backlog = Some(Backlog::new(self.backlog_size, first_byte_offset));
+                }
+                reply.extend(
+                    format!(
+                        "+FULLRESYNC {} {}\r\n",
+                        state.replid, state.master_repl_offset
+                    )
+                    .into_bytes(),
+                );
+                let payload = data
+                    .latency
+                    .monitor(crate::latency::EVENT_FORK, || crate::snapshot::encode(data))?;
+                let payload = String::from_utf8(payload)?;
+                // 快照中可能切换过库，之后的写命令需要重新 SELECT
+                state.stream_db = None;
+                reply.extend(Resp::BulkStrings(BulkStrings::new(payload)).encode()?);
+            }
+        }
+
+        let id = state.next_replica_id;
+        state.next_replica_id += 1;
+        let ack_offset = state.master_repl_offset;
+        state.replicas.push(ReplicaHandle {
+            id,
+            ip,
+            port,
+            tx,
+            ack_offset,
+            last_ack: Instant::now(),
+        });
+        Ok((id, reply))
+    }
+
+    pub(crate) fn detach_replica(&self, id: u64) {
+        self.lock().replicas.retain(|replica| replica.id != id);
+    }
+
+    /// 确认的偏移量还没有追上主节点的副本数，SHUTDOWN 时等待它们
+    pub fn lagging_replicas(&self) -> usize {
+        let state = self.lock();
+        state
+            .replicas
+            .iter()
+            .filter(|replica| replica.ack_offset < state.master_repl_offset)
+            .count()
+    }
+
+    pub(crate) fn record_ack(&self, id: u64, offset: u64) {
+        let mut state = self.lock();
+        if let Some(replica) = state.replicas.iter_mut().find(|replica| replica.id == id) {
+            replica.ack_offset = offset;
+            replica.last_ack = Instant::now();
+        }
+    }
+
+    /// 副本发起 PSYNC 时使用的 replid 和偏移量
+    pub(crate) fn psync_request(&self) -> (String, i64) {
+        let state = self.lock();
+        (state.replid.clone(), state.master_repl_offset as i64 + 1)
+    }
+
+    pub(crate) fn set_link_state(&self, link_state: LinkState) {
+        let mut state = self.lock();
+        state.link_state = link_state;
+        if link_state == LinkState::Connected {
+            state.last_master_io = Some(Instant::now());
+        }
+    }
+
+    /// 全量同步：清空数据集并加载主节点发来的快照
+    pub(crate) fn full_sync(
+        &self,
+        data: &Data,
+        replid: String,
+        offset: u64,
+        payload: &[u8],
+    ) -> Result<()> {
+        let mut state = self.lock();
+        data.flush(false);
+        let loaded = crate::snapshot::load(data, payload)?;
+        info!(
+            "📥 Loaded {} commands from master snapshot, replid {} offset {}",
+            loaded, replid, offset
+        );
+        state.replid = replid;
+        state.replid2 = "0".repeat(40);
+        state.second_replid_offset = -1;
+        state.master_repl_offset = offset;
+        state.backlog = Some(Backlog::new(self.backlog_size, offset + 1));
+        state.master_db = 0;
+        state.stream_db = None;
+        // 数据集已被替换，下挂的副本需要重新全量同步
+        state.replicas.clear();
+        Ok(())
+    }
+
+    /// 部分重同步：主节点可能已经更换了 replid
+    pub(crate) fn continue_sync(&self, replid: Option<String>) {
+        let mut state = self.lock();
+        if let Some(replid) = replid {
+            if replid != state.replid {
+                state.replid2 = std::mem::replace(&mut state.replid, replid);
+                state.second_replid_offset = state.master_repl_offset as i64 + 1;
+            }
+        }
+        if state.backlog.is_none() {
+            let first_byte_offset = state.master_repl_offset + 1;
+            state.backlog = Some(Backlog::new(self.backlog_size, first_byte_offset));
+        }
+    }
+
+    /// 副本执行主节点发来的命令，并原样转发给下挂的副本
+    ///
+    /// 命令以主节点客户端的身份经过 `transaction::call` 执行，和普通客户端的写命令一样
+    /// 更新统计、让 WATCH 失效、发送客户端缓存的失效消息，但不检查 ACL 和只读副本。
+    /// MULTI 和 EXEC 之间的命令先排队，收到 EXEC 后作为一个事务执行。
+    pub(crate) fn apply_from_master(
+        &self,
+        data: &Data,
+        link: &mut MasterLink,
+        frame: Resp,
+    ) -> Result<()> {
+        let name = command_table::command_name(&frame)
+            .unwrap_or_default()
+            .to_lowercase();
+        let mut state = self.lock();
+        state.last_master_io = Some(Instant::now());
+        let bytes = Bytes::from(frame.clone().encode()?);
+        state.master_repl_offset += bytes.len() as u64;
+        if let Some(backlog) = state.backlog.as_mut() {
+            backlog.append(&bytes);
+        }
+        state
+            .replicas
+            .retain(|replica| replica.tx.send(bytes.clone()));
+        let mut db = state.master_db;
+        // 执行命令时才对复制状态加锁，这里先释放，避免和 EXEC 死锁
+        drop(state);
+
+        debug!("Applying replicated command: {:?}", frame);
+        let client = link.client.clone();
+        match name.as_str() {
+            "multi" => {
+                link.transaction.multi();
+            }
+            "exec" => {
+                let reply = crate::client::with_client(&client, || link.transaction.exec(&mut db));
+                if let Resp::SimpleErrors(e) = reply {
+                    return Err(anyhow::anyhow!("EXEC from master failed: {}", e.error_msg));
+                }
+                // 事务中可能 SELECT 过
+                self.lock().master_db = db;
+            }
+            _ if link.transaction.in_multi() => {
+                link.transaction.queue(frame);
+            }
+            _ => {
+                let command = CommandGroup::try_from(frame.clone())?;
+                if let CommandGroup::Connection(ConnectionCommand::Select(select)) = &command {
+                    self.lock().master_db = select
+                        .validate(data)
+                        .map_err(|e| anyhow::anyhow!("invalid SELECT from master: {e:?}"))?;
+                    return Ok(());
+                }
+                // 和客户端命令一样持有事务读锁
+                let _guard = data.transactions.shared();
+                crate::client::with_client(&client, || {
+                    data.with_db(db, || crate::transaction::call(data, frame, &command))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn repl_offset(&self) -> u64 {
+        self.lock().master_repl_offset
+    }
+
+    /// ROLE 命令的返回值
+    pub fn role(&self) -> Resp {
+        let state = self.lock();
+        match self.master_addr() {
+            Some(addr) => Resp::Arrays(Arrays::new(vec![
+                bulk("slave"),
+                bulk(&addr.host),
+                Resp::Integers(Integers::new(addr.port as i64)),
+                bulk(state.link_state.as_str()),
+                Resp::Integers(Integers::new(state.master_repl_offset as i64)),
+            ])),
+            None => Resp::Arrays(Arrays::new(vec![
+                bulk("master"),
+                Resp::Integers(Integers::new(state.master_repl_offset as i64)),
+                Resp::Arrays(Arrays::new(
+                    state
+                        .replicas
+                        .iter()
+                        .map(|replica| {
+                            Resp::Arrays(Arrays::new(vec![
+                                bulk(&replica.ip),
+                                bulk(&replica.port.to_string()),
+                                bulk(&replica.ack_offset.to_string()),
+                            ]))
+                        })
+                        .collect(),
+                )),
+            ])),
+        }
+    }
+
+    /// INFO replication 的内容
+    pub fn info(&self) -> String {
+        let state = self.lock();
+        let mut info = String::from("# Replication\r\n");
+        match self.master_addr() {
+            Some(addr) => {
+                let link_up = state.link_state == LinkState::Connected;
+                let last_io = state
+                    .last_master_io
+                    .map(|at| at.elapsed().as_secs() as i64)
+                    .unwrap_or(-1);
+                info.push_str("role:slave\r\n");
+                info.push_str(&format!("master_host:{}\r\n", addr.host));
+                info.push_str(&format!("master_port:{}\r\n", addr.port));
+                info.push_str(&format!(
+                    "master_link_status:{}\r\n",
+                    if link_up { "up" } else { "down" }
+                ));
+                info.push_str(&format!("master_last_io_seconds_ago:{last_io}\r\n"));
+                info.push_str(&format!(
+                    "master_sync_in_progress:{}\r\n",
+                    (state.link_state == LinkState::Sync) as u8
+                ));
+                info.push_str(&format!(
+                    "slave_read_repl_offset:{}\r\n",
+                    state.master_repl_offset
+                ));
+                info.push_str(&format!(
+                    "slave_repl_offset:{}\r\n",
+                    state.master_repl_offset
+                ));
+                info.push_str("slave_priority:100\r\n");
+                info.push_str(&format!(
+                    "slave_read_only:{}\r\n",
+                    self.read_only.load(Ordering::Relaxed) as u8
+                ));
+                info.push_str("replica_announced:1\r\n");
+            }
+            None => info.push_str("role:master\r\n"),
+        }
+        info.push_str(&format!("connected_slaves:{}\r\n", state.replicas.len()));
+        for (i, replica) in state.replicas.iter().enumerate() {
+            info.push_str(&format!(
+                "slave{}:ip={},port={},state=online,offset={},lag={}\r\n",
+                i,
+                replica.ip,
+                replica.port,
+                replica.ack_offset,
+                replica.last_ack.elapsed().as_secs()
+            ));
+        }
+        info.push_str(&format!("master_replid:{}\r\n", state.replid));
+        info.push_str(&format!("master_replid2:{}\r\n", state.replid2));
+        info.push_str(&format!(
+            "master_repl_offset:{}\r\n",
+            state.master_repl_offset
+        ));
+        info.push_str(&format!(
+            "second_repl_offset:{}\r\n",
+            state.second_replid_offset
+        ));
+        match &state.backlog {
+            Some(backlog) => {
+                info.push_str("repl_backlog_active:1\r\n");
+                info.push_str(&format!("repl_backlog_size:{}\r\n", backlog.size()));
+                info.push_str(&format!(
+                    "repl_backlog_first_byte_offset:{}\r\n",
+                    backlog.first_byte_offset()
+                ));
+                info.push_str(&format!("repl_backlog_histlen:{}\r\n", backlog.histlen()));
+            }
+            None => {
+                info.push_str("repl_backlog_active:0\r\n");
+                info.push_str(&format!("repl_backlog_size:{}\r\n", self.backlog_size));
+                info.push_str("repl_backlog_first_byte_offset:0\r\n");
+                info.push_str("repl_backlog_histlen:0\r\n");
+            }
+        }
+        info
+    }
+}
+
+/// 副本到主节点的连接：复制流中的命令以主节点客户端的身份执行
+#[derive(Debug)]
+pub(crate) struct MasterLink {
+    client: Arc<Client>,
+    // 复制流中 MULTI 之后排队的命令
+    transaction: Transaction,
+}
+
+impl MasterLink {
+    pub(crate) fn new(data: Arc<Data>, client: Arc<Client>) -> Self {
+        client.set_master();
+        Self {
+            client,
+            transaction: Transaction::new(data),
+        }
+    }
+
+    /// CLIENT KILL TYPE master 时就绪
+    pub(crate) async fn killed(&self) {
+        self.client.killed().await
+    }
+}
+
+impl Default for Replication {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 启动复制相关的后台任务：副本连接主节点、主节点定时 PING 副本
+pub fn start(data: Arc<Data>) {
+    tokio::spawn(replica::run(data.clone()));
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PING_REPLICA_PERIOD);
+        loop {
+            interval.tick().await;
+            if let Err(e) = data.replication.ping_replicas() {
+                debug!("Failed to ping replicas: {}", e);
+            }
+        }
+    });
+}
+
+/// 把命令改写为可以在副本上确定性重放的形式
+///
+/// EXPIRE 使用相对时间，改写为 PEXPIREAT 绝对时间戳；键不存在时不需要传播。
+fn rewrite_for_replication<'a>(data: &Data, argv: &'a Resp) -> Option<Cow<'a, Resp>> {
+    let name = command_table::command_name(argv)?;
+    if !name.eq_ignore_ascii_case("expire") {
+        return Some(Cow::Borrowed(argv));
+    }
+    let key = match argv {
+        Resp::Arrays(arr) => match arr.val.get(1) {
+            Some(Resp::BulkStrings(key)) => key.val.as_str(),
+            _ => return Some(Cow::Borrowed(argv)),
+        },
+        _ => return Some(Cow::Borrowed(argv)),
+    };
+    let expire_at = data.db().expiry_data.get(key).map(|at| *at)?;
+    Some(Cow::Owned(command(&[
+        "PEXPIREAT",
+        key,
+        &expire_at.to_string(),
+    ])))
+}
+
+pub(crate) fn command(args: &[&str]) -> Resp {
+    Resp::Arrays(Arrays::new(args.iter().map(|arg| bulk(arg)).collect()))
+}
+
+fn bulk(val: &str) -> Resp {
+    Resp::BulkStrings(BulkStrings::new(val.to_owned()))
+}
+
+pub(crate) fn random_replid() -> String {
+    let mut rng = rand::thread_rng();
+    (0..40)
+        .map(|_| char::from_digit(rng.gen_range(0..16), 16).unwrap_or('0'))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespDecoder;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_expire_is_rewritten_to_pexpireat() {
+        let data = Data::new();
+        data.set_expiry("session", 1_700_000_000_000);
+        let rewrite =
+            |args: &[&str]| rewrite_for_replication(&data, &command(args)).map(Cow::into_owned);
+        assert_eq!(
+            rewrite(&["EXPIRE", "session", "10"]),
+            Some(command(&["PEXPIREAT", "session", "1700000000000"]))
+        );
+        assert_eq!(rewrite(&["EXPIRE", "missing", "10"]), None);
+        assert_eq!(
+            rewrite(&["SET", "k", "v"]),
+            Some(command(&["SET", "k", "v"]))
+        );
+    }
+
+    #[test]
+    fn test_partial_resync_from_backlog() {
+        let data = Data::new();
+        let (tx, _rx) = Outbox::channel(None, &data.limits);
+        let (_, reply) = data
+            .replication
+            .attach_replica(&data, "?", -1, "127.0.0.1".to_owned(), 6380, tx)
+            .unwrap();
+        assert!(reply.starts_with(b"+FULLRESYNC "));
+
+        data.replication
+            .propagate(&data, &command(&["SET", "k", "v"]), || {
+                Ok(Resp::Integers(Integers::new(1)))
+            })
+            .unwrap();
+        let (replid, _) = data.replication.psync_request();
+
+        let (tx, _rx) = Outbox::channel(None, &data.limits);
+        let (_, reply) = data
+            .replication
+            .attach_replica(&data, &replid, 1, "127.0.0.1".to_owned(), 6381, tx)
+            .unwrap();
+        let mut expected = format!("+CONTINUE {replid}\r\n").into_bytes();
+        // 复制流中的第一条写命令之前先 SELECT 命令所在的库
+        expected.extend(command(&["SELECT", "0"]).encode().unwrap());
+        expected.extend(command(&["SET", "k", "v"]).encode().unwrap());
+        assert_eq!(reply, expected);
+    }
+
+    #[test]
+    fn test_expired_keys_are_deleted_on_replica() {
+        let master = Data::new();
+        let replica = Arc::new(Data::new());
+        let (tx, mut rx) = Outbox::channel(None, &master.limits);
+        master
+            .replication
+            .attach_replica(&master, "?", -1, "127.0.0.1".to_owned(), 6380, tx)
+            .unwrap();
+
+        let now = master.current_timestamp_millis();
+        for data in [&master, &*replica] {
+            for key in ["active", "lazy"] {
+                data.db().string_data.insert(key.to_owned(), bulk("v"));
+            }
+            data.set_expiry("active", now - 1);
+            data.set_expiry("lazy", now + 100_000);
+        }
+
+        // 主动过期周期删除的键
+        master.cleanup_expired();
+        // 写命令执行过程中惰性删除的键先于命令传播
+        master.set_expiry("lazy", now - 1);
+        master
+            .replication
+            .propagate(&master, &command(&["SET", "k", "v"]), || {
+                master.expire_if_needed("lazy");
+                Ok(Resp::Integers(Integers::new(1)))
+            })
+            .unwrap();
+
+        let stream = drain(&mut rx);
+        assert_eq!(
+            stream,
+            frames(&[
+                &["SELECT", "0"],
+                &["DEL", "active"],
+                &["DEL", "lazy"],
+                &["SET", "k", "v"],
+            ])
+        );
+
+        apply_stream(&replica, &mut master_link(&replica), stream);
+        assert!(!replica.contains_key("active"));
+        assert!(!replica.contains_key("lazy"));
+        assert!(replica.contains_key("k"));
+    }
+
+    #[test]
+    fn test_replicated_writes_go_through_call() {
+        let replica = Arc::new(Data::new());
+        let mut tx = Transaction::new(replica.clone());
+        tx.watch(1, &["k".to_owned()]);
+
+        let stream = frames(&[&["SELECT", "1"], &["SET", "k", "v"]]);
+        apply_stream(&replica, &mut master_link(&replica), stream);
+        assert!(replica.with_db(1, || replica.contains_key("k")));
+        assert_eq!(replica.stats.dirty(), 1);
+        // 主节点的写入和普通写命令一样让 WATCH 失效
+        tx.multi();
+        assert_eq!(tx.exec(&mut 1), Resp::Nulls(crate::Nulls::new()));
+    }
+
+    #[test]
+    fn test_exec_is_replicated_as_a_transaction() {
+        let master = Arc::new(Data::new());
+        let (tx, mut rx) = Outbox::channel(None, &master.limits);
+        master
+            .replication
+            .attach_replica(&master, "?", -1, "127.0.0.1".to_owned(), 6380, tx)
+            .unwrap();
+        let mut transaction = Transaction::new(master.clone());
+        let mut run = |commands: &[&[&str]]| {
+            transaction.multi();
+            for args in commands {
+                transaction.queue(command(args));
+            }
+            transaction.exec(&mut 0)
+        };
+        // 只有读命令的事务不产生复制流
+        run(&[&["GET", "a"]]);
+        run(&[&["SET", "a", "1"], &["GET", "a"], &["SET", "b", "2"]]);
+        let mut stream = drain(&mut rx);
+        assert_eq!(
+            stream,
+            frames(&[
+                &["SELECT", "0"],
+                &["MULTI"],
+                &["SET", "a", "1"],
+                &["SET", "b", "2"],
+                &["EXEC"],
+            ])
+        );
+
+        // 副本上的 ACL 不限制主节点的复制流
+        let replica = Arc::new(Data::new());
+        replica.acl.set_user("default", &["-@all"]).unwrap();
+        let mut link = master_link(&replica);
+        let exec = stream.split_off(stream.len() - frames(&[&["EXEC"]]).len());
+        apply_stream(&replica, &mut link, stream);
+        // 没有收到 EXEC 之前事务中的命令都不执行
+        assert!(!replica.contains_key("a"));
+        apply_stream(&replica, &mut link, exec);
+        assert!(replica.contains_key("a"));
+        assert!(replica.contains_key("b"));
+    }
+
+    fn frames(commands: &[&[&str]]) -> BytesMut {
+        let mut stream = BytesMut::new();
+        for args in commands {
+            stream.extend(command(args).encode().unwrap());
+        }
+        stream
+    }
+
+    fn drain(rx: &mut tokio::sync::mpsc::UnboundedReceiver<Bytes>) -> BytesMut {
+        let mut stream = BytesMut::new();
+        while let Ok(bytes) = rx.try_recv() {
+            stream.extend_from_slice(&bytes);
+        }
+        stream
+    }
+
+    fn master_link(replica: &Arc<Data>) -> MasterLink {
+        let client = Arc::new(Client::new(1, "127.0.0.1:6379".to_owned(), String::new()));
+        MasterLink::new(replica.clone(), client)
+    }
+
+    // 以主节点客户端的身份在副本上执行复制流
+    fn apply_stream(replica: &Arc<Data>, link: &mut MasterLink, mut stream: BytesMut) {
+        while !stream.is_empty() {
+            let frame = Resp::decode(&mut stream).unwrap();
+            replica
+                .replication
+                .apply_from_master(replica, link, frame)
+                .unwrap();
+        }
+    }
+}
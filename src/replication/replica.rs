@@ -0,0 +1,226 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::{SinkExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_util::codec::Framed;
+use tracing::{info, warn};
+
+use crate::network::RespFrameCodec;
+use crate::{Data, Resp};
+
+use super::{command, LinkState, MasterAddr, MasterLink};
+
+// 连接主节点失败后的重试间隔
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+// 副本向主节点汇报复制偏移量的间隔
+const ACK_PERIOD: Duration = Duration::from_secs(1);
+
+/// 副本后台任务：跟随 REPLICAOF 的设置连接主节点，断线后自动重连
+pub async fn run(data: Arc<Data>) {
+    let mut master_rx = data.replication.subscribe_master();
+    loop {
+        let target = master_rx.borrow_and_update().clone();
+        match target {
+            Some(addr) => {
+                tokio::select! {
+                    _ = sync_forever(&data, &addr) => {}
+                    changed = master_rx.changed() => {
+                        if changed.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            None => {
+                if master_rx.changed().await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+async fn sync_forever(data: &Arc<Data>, addr: &MasterAddr) {
+    loop {
+        if let Err(e) = sync_with_master(data, addr).await {
+            warn!(
+                "❌ Replication link to {}:{} failed: {}",
+                addr.host, addr.port, e
+            );
+        }
+        data.replication.set_link_state(LinkState::Connect);
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn sync_with_master(data: &Arc<Data>, addr: &MasterAddr) -> Result<()> {
+    data.replication.set_link_state(LinkState::Connecting);
+    let socket = TcpStream::connect((addr.host.as_str(), addr.port)).await?;
+    info!("🔗 Connected to master {}:{}", addr.host, addr.port);
+    // 主节点的连接也出现在 CLIENT LIST 中，带有 M 标志
+    let registered = data.clients.register(
+        socket.peer_addr()?.to_string(),
+        socket.local_addr()?.to_string(),
+    );
+    let mut link = MasterLink::new(data.clone(), registered.client().clone());
+    match data.replication.tls_connector() {
+        Some(connector) => {
+            let name = ServerName::try_from(addr.host.clone())?;
+            let stream = connector.connect(name, socket).await?;
+            info!(
+                "🔒 TLS handshake with master {}:{} done",
+                addr.host, addr.port
+            );
+            sync_over(data, &mut link, Framed::new(stream, RespFrameCodec::new())).await
+        }
+        None => sync_over(data, &mut link, Framed::new(socket, RespFrameCodec::new())).await,
+    }
+}
+
+// 在建立好的连接上完成握手，然后持续接收主节点的复制流
+async fn sync_over<T>(
+    data: &Arc<Data>,
+    link: &mut MasterLink,
+    mut framed: Framed<T, RespFrameCodec>,
+) -> Result<()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    // 握手：PING -> REPLCONF listening-port -> REPLCONF capa -> PSYNC
+    request(&mut framed, &["PING"]).await?;
+    let port = data.replication.listening_port().to_string();
+    request(&mut framed, &["REPLCONF", "listening-port", &port]).await?;
+    request(&mut framed, &["REPLCONF", "capa", "psync2"]).await?;
+
+    data.replication.set_link_state(LinkState::Sync);
+    let (replid, offset) = data.replication.psync_request();
+    let reply = request(&mut framed, &["PSYNC", &replid, &offset.to_string()]).await?;
+    match PSyncReply::parse(&reply)? {
+        PSyncReply::FullResync { replid, offset } => {
+            let payload = match framed.next().await {
+                Some(Ok(Resp::BulkStrings(payload))) => payload.val,
+                Some(Ok(other)) => {
+                    return Err(anyhow::anyhow!("unexpected snapshot frame: {:?}", other))
+                }
+                Some(Err(e)) => return Err(e),
+                None => return Err(anyhow::anyhow!("master closed during full resync")),
+            };
+            data.replication
+                .full_sync(data, replid, offset, payload.as_bytes())?;
+        }
+        PSyncReply::Continue { replid } => {
+            info!("🔁 Partial resync accepted by master");
+            data.replication.continue_sync(replid);
+        }
+    }
+    data.replication.set_link_state(LinkState::Connected);
+
+    let mut ack_interval = tokio::time::interval(ACK_PERIOD);
+    loop {
+        tokio::select! {
+            frame = framed.next() => match frame {
+                Some(Ok(frame)) => {
+                    if is_getack(&frame) {
+                        send_ack(&mut framed, data).await?;
+                    }
+                    data.replication.apply_from_master(data, link, frame)?;
+                }
+                Some(Err(e)) => return Err(e),
+                None => return Err(anyhow::anyhow!("master closed the connection")),
+            },
+            _ = ack_interval.tick() => send_ack(&mut framed, data).await?,
+            // CLIENT KILL TYPE master：断开后重新连接
+            _ = link.killed() => return Err(anyhow::anyhow!("master connection killed")),
+        }
+    }
+}
+
+async fn request<T>(framed: &mut Framed<T, RespFrameCodec>, args: &[&str]) -> Result<Resp>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    framed.send(command(args)).await?;
+    match framed.next().await {
+        Some(Ok(Resp::SimpleErrors(e))) => Err(anyhow::anyhow!(
+            "master replied {} to {}",
+            e.error_msg,
+            args.join(" ")
+        )),
+        Some(Ok(reply)) => Ok(reply),
+        Some(Err(e)) => Err(e),
+        None => Err(anyhow::anyhow!("master closed the connection")),
+    }
+}
+
+async fn send_ack<T>(framed: &mut Framed<T, RespFrameCodec>, data: &Data) -> Result<()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let offset = data.replication.repl_offset().to_string();
+    framed.send(command(&["REPLCONF", "ACK", &offset])).await
+}
+
+// REPLCONF GETACK *
+fn is_getack(frame: &Resp) -> bool {
+    let Resp::Arrays(arr) = frame else {
+        return false;
+    };
+    matches!(
+        arr.val.as_slice(),
+        [Resp::BulkStrings(cmd), Resp::BulkStrings(sub), ..]
+            if cmd.val.eq_ignore_ascii_case("replconf") && sub.val.eq_ignore_ascii_case("getack")
+    )
+}
+
+#[derive(Debug, PartialEq)]
+enum PSyncReply {
+    FullResync { replid: String, offset: u64 },
+    Continue { replid: Option<String> },
+}
+
+impl PSyncReply {
+    fn parse(reply: &Resp) -> Result<Self> {
+        let Resp::SimpleStrings(line) = reply else {
+            return Err(anyhow::anyhow!("unexpected PSYNC reply: {:?}", reply));
+        };
+        let mut parts = line.val.split_whitespace();
+        match parts.next() {
+            Some("FULLRESYNC") => {
+                let replid = parts.next().unwrap_or_default().to_owned();
+                let offset = parts.next().unwrap_or_default().parse()?;
+                Ok(PSyncReply::FullResync { replid, offset })
+            }
+            Some("CONTINUE") => Ok(PSyncReply::Continue {
+                replid: parts.next().map(|id| id.to_owned()),
+            }),
+            _ => Err(anyhow::anyhow!("unexpected PSYNC reply: {}", line.val)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleStringsData;
+
+    #[test]
+    fn test_parse_psync_reply() {
+        let reply = Resp::SimpleStrings(SimpleStringsData::new("FULLRESYNC abc 42".to_owned()));
+        assert_eq!(
+            PSyncReply::parse(&reply).unwrap(),
+            PSyncReply::FullResync {
+                replid: "abc".to_owned(),
+                offset: 42
+            }
+        );
+        let reply = Resp::SimpleStrings(SimpleStringsData::new("CONTINUE".to_owned()));
+        assert_eq!(
+            PSyncReply::parse(&reply).unwrap(),
+            PSyncReply::Continue { replid: None }
+        );
+    }
+}
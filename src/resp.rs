@@ -12,6 +12,8 @@ pub const NULLS_BYTE: u8 = b'_';
 pub const BOOLEANS_BYTE: u8 = b'#';
 pub const DOUBLES_BYTE: u8 = b',';
 pub const BIG_NUMBERS_BYTE: u8 = b'(';
+pub const MAPS_BYTE: u8 = b'%';
+pub const PUSHES_BYTE: u8 = b'>';
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Resp {
@@ -24,6 +26,8 @@ pub enum Resp {
     Booleans(Booleans),
     Doubles(Doubles),
     BigNumbers(BigNumbers),
+    Maps(Maps),
+    Pushes(Pushes),
 }
 
 // 手动实现RespEncoder for Resp
@@ -39,6 +43,8 @@ impl crate::RespEncoder for Resp {
             Resp::Booleans(data) => data.encode(),
             Resp::Doubles(data) => data.encode(),
             Resp::BigNumbers(data) => data.encode(),
+            Resp::Maps(data) => data.encode(),
+            Resp::Pushes(data) => data.encode(),
         }
     }
 }
@@ -61,6 +67,9 @@ pub enum RespError {
     ParseFloatError(#[from] std::num::ParseFloatError),
     #[error("Parse frame error: {0}")]
     InternalError(String),
+    // 违反协议限制，连接回复 -ERR Protocol error 之后关闭
+    #[error("Protocol error: {0}")]
+    Protocol(String),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -167,3 +176,26 @@ impl BigNumbers {
         Self { val }
     }
 }
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Maps {
+    pub val: Vec<(Resp, Resp)>,
+}
+
+impl Maps {
+    pub fn new(val: Vec<(Resp, Resp)>) -> Self {
+        Self { val }
+    }
+}
+
+// RESP3 服务端主动推送的数据，例如 pub/sub 消息
+#[derive(Debug, PartialEq, Clone)]
+pub struct Pushes {
+    pub val: Vec<Resp>,
+}
+
+impl Pushes {
+    pub fn new(val: Vec<Resp>) -> Self {
+        Self { val }
+    }
+}
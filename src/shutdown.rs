@@ -0,0 +1,371 @@
+//! 优雅关闭
+//!
+//! SHUTDOWN 命令和 SIGTERM/SIGINT 都通过 `Shutdown::request` 发起关闭，`main` 在 `wait` 返回后
+//! 停止接受新连接并调用 `close`：
+//!
+//! 1. 除非指定了 NOW，先暂停写命令，等待副本追上复制偏移量，最多等待 shutdown-timeout 秒，
+//!    期间可以被 SHUTDOWN ABORT 取消
+//! 2. SAVE 时暂停写命令并把快照保存到 `dir/dbfilename`，保存失败时除非指定了 FORCE，
+//!    和 Redis 一样取消关闭，SHUTDOWN 返回错误
+//! 3. 断开所有客户端，正在执行的命令先完成并写回回复
+//! 4. 删除 pidfile 和 unix socket，FORCE 时保存失败的关闭以非零状态退出
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+use crate::client::PauseMode;
+use crate::Data;
+
+// 默认等待副本追上复制偏移量的秒数，和 Redis 的 shutdown-timeout 一致
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+// 断开客户端时最多等待正在执行的命令完成的时间
+const CLOSE_CLIENTS_TIMEOUT: Duration = Duration::from_secs(5);
+// 保存快照时暂停写命令的时间，保存成功之后马上断开客户端，不会等到暂停结束
+const SAVE_PAUSE: Duration = Duration::from_secs(60);
+
+/// SHUTDOWN 的选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ShutdownOptions {
+    // SAVE 为 Some(true)，NOSAVE 为 Some(false)
+    pub save: Option<bool>,
+    // 不等待副本
+    pub now: bool,
+    // 保存失败时仍然退出
+    pub force: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Running,
+    Requested(ShutdownOptions),
+}
+
+#[derive(Debug)]
+pub struct Shutdown {
+    phase: watch::Sender<Phase>,
+    timeout_secs: AtomicU64,
+    // FORCE 时保存失败，仍然关闭但以非零状态退出
+    save_failed: AtomicBool,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self {
+            phase: watch::Sender::new(Phase::Running),
+            timeout_secs: AtomicU64::new(DEFAULT_TIMEOUT_SECS),
+            save_failed: AtomicBool::new(false),
+        }
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 发起关闭，已经在关闭中时使用新的选项（例如 SHUTDOWN NOW 不再等待副本）
+    pub fn request(&self, options: ShutdownOptions) {
+        self.phase.send_replace(Phase::Requested(options));
+    }
+
+    /// SHUTDOWN ABORT：取消还在等待副本的关闭
+    pub fn abort(&self) -> bool {
+        self.phase.send_if_modified(|phase| {
+            let requested = matches!(phase, Phase::Requested(_));
+            *phase = Phase::Running;
+            requested
+        })
+    }
+
+    pub fn is_requested(&self) -> bool {
+        matches!(*self.phase.borrow(), Phase::Requested(_))
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Phase> {
+        self.phase.subscribe()
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.load(Ordering::Relaxed))
+    }
+
+    pub fn set_timeout(&self, secs: u64) {
+        self.timeout_secs.store(secs, Ordering::Relaxed);
+    }
+}
+
+/// 监听 SIGTERM/SIGINT，第一次收到时发起关闭，关闭中再次收到时不再等待副本
+pub fn listen_signals(data: Arc<Data>) -> Result<()> {
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    tokio::spawn(async move {
+        loop {
+            let name = tokio::select! {
+                _ = terminate.recv() => "SIGTERM",
+                _ = interrupt.recv() => "SIGINT",
+            };
+            if data.shutdown.is_requested() {
+                warn!(
+                    "⚠️ Received {}, exiting now without waiting for replicas",
+                    name
+                );
+                data.shutdown.request(ShutdownOptions {
+                    now: true,
+                    ..Default::default()
+                });
+            } else {
+                info!("🛑 Received {}, scheduling shutdown...", name);
+                data.shutdown.request(ShutdownOptions::default());
+            }
+        }
+    });
+    Ok(())
+}
+
+/// 等待关闭请求，除非 NOW，等待副本追上之后才返回；SAVE 时保存完快照才返回。
+/// 被 SHUTDOWN ABORT 取消或者保存失败时继续等待
+pub async fn wait(data: &Arc<Data>) -> ShutdownOptions {
+    let mut phase = data.shutdown.subscribe();
+    loop {
+        let options = match *phase.borrow_and_update() {
+            Phase::Requested(options) => Some(options),
+            Phase::Running => None,
+        };
+        if let Some(options) = options {
+            let now = match options.now {
+                true => Some(true),
+                false => wait_for_replicas(data, &mut phase).await,
+            };
+            match now {
+                Some(now) if save(data, options).await => {
+                    return ShutdownOptions { now, ..options };
+                }
+                Some(_) => {
+                    data.shutdown.abort();
+                    data.clients.unpause();
+                }
+                None => info!("🛑 Shutdown aborted, resuming normal operation"),
+            }
+        }
+        if phase.changed().await.is_err() {
+            return ShutdownOptions::default();
+        }
+    }
+}
+
+// SAVE 时暂停写命令并保存快照，返回 false 表示保存失败、需要取消关闭。
+// 编码和写文件都是阻塞操作，放到 spawn_blocking 中执行，不占用异步线程
+async fn save(data: &Arc<Data>, options: ShutdownOptions) -> bool {
+    if options.save != Some(true) {
+        return true;
+    }
+    data.clients.pause(SAVE_PAUSE, PauseMode::Write);
+    let server = data.config.server();
+    let path = server.dir.join(&server.dbfilename);
+    info!(
+        "💾 Saving the final snapshot before exiting to {}",
+        path.display()
+    );
+    let saved = {
+        let data = data.clone();
+        tokio::task::spawn_blocking(move || crate::snapshot::save(&data, &path)).await
+    };
+    match saved.map_err(anyhow::Error::from).and_then(|saved| saved) {
+        Ok(()) => {
+            info!("💾 DB saved on disk");
+            true
+        }
+        Err(e) if options.force => {
+            error!(
+                "❌ Error trying to save the DB, exiting anyway (FORCE): {}",
+                e
+            );
+            data.shutdown.save_failed.store(true, Ordering::Relaxed);
+            true
+        }
+        Err(e) => {
+            error!("❌ Error trying to save the DB, can't exit: {}", e);
+            false
+        }
+    }
+}
+
+// 暂停写命令，等待副本确认的偏移量追上主节点，返回 None 表示被取消
+async fn wait_for_replicas(data: &Data, phase: &mut watch::Receiver<Phase>) -> Option<bool> {
+    if data.replication.lagging_replicas() == 0 {
+        return Some(false);
+    }
+    let timeout = data.shutdown.timeout();
+    let deadline = Instant::now() + timeout;
+    info!(
+        "⏳ Waiting for {} lagging replicas before shutting down",
+        data.replication.lagging_replicas()
+    );
+    data.clients.pause(timeout, PauseMode::Write);
+    loop {
+        if data.replication.lagging_replicas() == 0 {
+            return Some(false);
+        }
+        if Instant::now() >= deadline {
+            warn!("⚠️ Lagging replicas did not catch up before shutdown-timeout");
+            return Some(false);
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_millis(100)) => {}
+            changed = phase.changed() => {
+                match *phase.borrow_and_update() {
+                    _ if changed.is_err() => return Some(false),
+                    Phase::Running => {
+                        data.clients.unpause();
+                        return None;
+                    }
+                    Phase::Requested(options) if options.now => return Some(true),
+                    Phase::Requested(_) => {}
+                }
+            }
+        }
+    }
+}
+
+/// 关闭服务：断开客户端、删除 pidfile 和 unix socket，FORCE 时保存失败返回错误
+pub async fn close(data: &Data) -> Result<()> {
+    info!("🛑 User requested shutdown...");
+    for client in data.clients.list() {
+        client.kill();
+    }
+    let deadline = Instant::now() + CLOSE_CLIENTS_TIMEOUT;
+    while !data.clients.is_empty() && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    if !data.clients.is_empty() {
+        warn!(
+            "⚠️ {} clients did not close before shutdown",
+            data.clients.len()
+        );
+    }
+    let server = data.config.server();
+    for path in [server.pidfile, server.unixsocket].into_iter().flatten() {
+        match std::fs::remove_file(&path) {
+            Ok(()) => info!("🧹 Removed {}", path.display()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("⚠️ Failed to remove {}: {}", path.display(), e),
+        }
+    }
+    if data.shutdown.save_failed.load(Ordering::Relaxed) {
+        return Err(anyhow::anyhow!("the final snapshot could not be saved"));
+    }
+    info!("👋 Simple Redis is now ready to exit, bye bye...");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_request_and_abort() {
+        let data = Arc::new(Data::new());
+        assert!(!data.shutdown.abort());
+        data.shutdown.request(ShutdownOptions {
+            save: Some(false),
+            ..Default::default()
+        });
+        assert!(data.shutdown.is_requested());
+        // 没有副本时不需要等待
+        let options = wait(&data).await;
+        assert_eq!(options.save, Some(false));
+        assert!(data.shutdown.abort());
+        assert!(!data.shutdown.is_requested());
+    }
+
+    #[tokio::test]
+    async fn test_close_kills_clients() {
+        let data = Data::new();
+        let registered = data
+            .clients
+            .register("127.0.0.1:5000".to_owned(), "127.0.0.1:6379".to_owned());
+        let client = registered.client().clone();
+        tokio::spawn(async move {
+            client.killed().await;
+            drop(registered);
+        });
+        close(&data).await.unwrap();
+        assert!(data.clients.is_empty());
+    }
+
+    fn with_dir(dir: &std::path::Path) -> Arc<Data> {
+        let data = Arc::new(Data::new());
+        data.config.set_server(crate::config::ServerConfig {
+            dir: dir.to_path_buf(),
+            ..Default::default()
+        });
+        data
+    }
+
+    #[tokio::test]
+    async fn test_save_before_exit() {
+        let dir = std::env::temp_dir().join(format!("simple-redis-save-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let data = with_dir(&dir);
+        data.db().string_data.insert(
+            "k".to_owned(),
+            crate::Resp::SimpleStrings(crate::SimpleStringsData::new("v".to_owned())),
+        );
+        data.shutdown.request(ShutdownOptions {
+            save: Some(true),
+            ..Default::default()
+        });
+        wait(&data).await;
+        close(&data).await.unwrap();
+
+        // 重启之后从同一个文件读回数据
+        let restored = Data::new();
+        let path = dir.join("dump.rdb");
+        assert_eq!(
+            crate::snapshot::load_file(&restored, &path).unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            restored
+                .db()
+                .string_data
+                .get("k")
+                .map(|v| v.value().clone()),
+            Some(crate::Resp::SimpleStrings(crate::SimpleStringsData::new(
+                "v".to_owned()
+            )))
+        );
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(crate::snapshot::load_file(&restored, &path).unwrap(), None);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_failed_save() {
+        let data = with_dir(std::path::Path::new("/nonexistent/simple-redis"));
+        let save = ShutdownOptions {
+            save: Some(true),
+            ..Default::default()
+        };
+        // 保存失败时取消关闭
+        data.shutdown.request(save);
+        let waited = tokio::time::timeout(Duration::from_millis(100), wait(&data)).await;
+        assert!(waited.is_err());
+        assert!(!data.shutdown.is_requested());
+
+        // FORCE 时仍然关闭，但 close 返回错误
+        data.shutdown.request(ShutdownOptions {
+            force: true,
+            ..save
+        });
+        wait(&data).await;
+        assert!(close(&data).await.is_err());
+    }
+}
@@ -0,0 +1,215 @@
+//! 慢查询日志
+//!
+//! 每条经过 `transaction::call` 的命令都会计时，耗时超过 `slowlog-log-slower-than`
+//! 微秒的命令记录到一个长度不超过 `slowlog-max-len` 的环形缓冲区中，最新的在前。
+//! 和 Redis 一样只保存截断后的参数，避免大的命令占用太多内存。
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicI64, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::Resp;
+
+pub const DEFAULT_LOG_SLOWER_THAN: i64 = 10_000;
+pub const DEFAULT_MAX_LEN: usize = 128;
+// 每条记录最多保存的参数个数和每个参数最多保存的字节数
+const MAX_ARGC: usize = 32;
+const MAX_ARG_LEN: usize = 128;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlowLogEntry {
+    pub id: u64,
+    // Unix 时间（秒）
+    pub timestamp: u64,
+    // 耗时（微秒）
+    pub duration: u64,
+    pub argv: Vec<String>,
+    pub client_addr: String,
+    pub client_name: String,
+}
+
+#[derive(Debug)]
+pub struct SlowLog {
+    // 负数表示关闭，0 表示记录所有命令
+    log_slower_than: AtomicI64,
+    max_len: AtomicUsize,
+    next_id: AtomicU64,
+    entries: Mutex<VecDeque<SlowLogEntry>>,
+}
+
+impl Default for SlowLog {
+    fn default() -> Self {
+        Self {
+            log_slower_than: AtomicI64::new(DEFAULT_LOG_SLOWER_THAN),
+            max_len: AtomicUsize::new(DEFAULT_MAX_LEN),
+            next_id: AtomicU64::new(0),
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl SlowLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn log_slower_than(&self) -> i64 {
+        self.log_slower_than.load(Ordering::Relaxed)
+    }
+
+    pub fn set_log_slower_than(&self, micros: i64) {
+        self.log_slower_than.store(micros, Ordering::Relaxed);
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.max_len.load(Ordering::Relaxed)
+    }
+
+    pub fn set_max_len(&self, max_len: usize) {
+        self.max_len.store(max_len, Ordering::Relaxed);
+        self.lock().truncate(max_len);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<SlowLogEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 耗时是否达到了记录的阈值
+    pub fn is_slow(&self, duration: Duration) -> bool {
+        let threshold = self.log_slower_than();
+        threshold >= 0 && duration.as_micros() as u64 >= threshold as u64
+    }
+
+    /// 命令耗时超过阈值时记录，`argv` 应该已经用 `truncate_argv` 截断
+    pub fn record(&self, timestamp: u64, duration: Duration, argv: Vec<String>) {
+        if !self.is_slow(duration) {
+            return;
+        }
+        let duration = duration.as_micros() as u64;
+        let (client_addr, client_name) = crate::client::current_addr_and_name();
+        let entry = SlowLogEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            timestamp,
+            duration,
+            argv,
+            client_addr,
+            client_name,
+        };
+        let max_len = self.max_len();
+        let mut entries = self.lock();
+        entries.push_front(entry);
+        entries.truncate(max_len);
+    }
+
+    /// 最新的 `count` 条记录
+    pub fn get(&self, count: usize) -> Vec<SlowLogEntry> {
+        self.lock().iter().take(count).cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub fn reset(&self) {
+        self.lock().clear();
+    }
+}
+
+/// 取出命令参数，隐去其中的密码，最多保留 32 个参数，每个参数最多 128 字节
+pub fn truncate_argv(frame: &Resp) -> Vec<String> {
+    let Resp::Arrays(arr) = frame else {
+        return Vec::new();
+    };
+    let args: Vec<&str> = arr
+        .val
+        .iter()
+        .map(|arg| match arg {
+            Resp::BulkStrings(arg) => arg.val.as_str(),
+            Resp::SimpleStrings(arg) => arg.val.as_str(),
+            _ => "",
+        })
+        .collect();
+    let args = crate::monitor::redact(&args);
+    let argc = args.len();
+    let keep = if argc > MAX_ARGC { MAX_ARGC - 1 } else { argc };
+    let mut argv: Vec<String> = args[..keep]
+        .iter()
+        .map(|&arg| {
+            if arg.len() <= MAX_ARG_LEN {
+                return arg.to_owned();
+            }
+            let mut end = MAX_ARG_LEN;
+            while !arg.is_char_boundary(end) {
+                end -= 1;
+            }
+            format!("{}... ({} more bytes)", &arg[..end], arg.len() - end)
+        })
+        .collect();
+    if argc > keep {
+        argv.push(format!("... ({} more arguments)", argc - keep));
+    }
+    argv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::client::{with_client, Client};
+    use crate::{Arrays, BulkStrings};
+
+    fn frame(args: &[String]) -> Resp {
+        Resp::Arrays(Arrays::new(
+            args.iter()
+                .map(|arg| Resp::BulkStrings(BulkStrings::new(arg.clone())))
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn test_truncate_argv() {
+        let mut args = vec!["SET".to_owned(), "k".to_owned(), "v".repeat(200)];
+        let argv = truncate_argv(&frame(&args));
+        assert_eq!(argv[2], format!("{}... (72 more bytes)", "v".repeat(128)));
+
+        args.extend((0..40).map(|i| i.to_string()));
+        let argv = truncate_argv(&frame(&args));
+        assert_eq!(argv.len(), MAX_ARGC);
+        assert_eq!(argv[MAX_ARGC - 1], "... (12 more arguments)");
+    }
+
+    #[test]
+    fn test_record_threshold_and_max_len() {
+        let log = SlowLog::new();
+        log.set_log_slower_than(100);
+        log.record(1, Duration::from_micros(99), vec!["GET".to_owned()]);
+        assert!(log.is_empty());
+
+        log.set_max_len(2);
+        let client = Arc::new(Client::new(1, "127.0.0.1:5000".to_owned(), String::new()));
+        client.set_name("worker");
+        with_client(&client, || {
+            for i in 0..3 {
+                log.record(i, Duration::from_micros(100), vec![format!("cmd{i}")]);
+            }
+        });
+        let entries = log.get(10);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, 2);
+        assert_eq!(entries[0].argv, vec!["cmd2".to_owned()]);
+        assert_eq!(entries[0].client_addr, "127.0.0.1:5000");
+        assert_eq!(entries[0].client_name, "worker");
+
+        log.set_log_slower_than(-1);
+        log.record(4, Duration::from_secs(1), Vec::new());
+        assert_eq!(log.len(), 2);
+        log.reset();
+        assert!(log.is_empty());
+    }
+}
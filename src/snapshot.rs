@@ -0,0 +1,353 @@
+//! 数据集快照
+//!
+//! 快照是一组 RESP 编码的命令（SET/HSET/RPUSH/SADD/ZADD/PEXPIREAT），
+//! 每个非空的库以一条 SELECT 开头。全量同步时由主节点发送给副本，
+//! 副本按顺序执行即可重建数据集。
+//!
+//! SHUTDOWN SAVE 把同样的快照写入 `dir/dbfilename`，启动时在监听端口之前从这里加载。
+//!
+//! 单个键的序列化（DUMP/RESTORE/MIGRATE）使用 `[类型, 值...]` 形式的 RESP 数组，
+//! 不包含键名，可以恢复到任意键上。
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::Path;
+
+use anyhow::Result;
+use bytes::BytesMut;
+
+use crate::process::connection::ConnectionCommand;
+use crate::process::CommandGroup;
+use crate::{Arrays, BulkStrings, Data, Processor, Resp, RespDecoder, RespEncoder, RespError};
+
+// dbfilename 的默认值，和 Redis 一致
+pub const DEFAULT_DBFILENAME: &str = "dump.rdb";
+
+/// 把所有库导出为命令列表，已过期的键会被跳过
+pub fn dump(data: &Data) -> Vec<Resp> {
+    let mut commands = Vec::new();
+    for index in 0..data.databases() {
+        if data.db_at(index).is_empty() {
+            continue;
+        }
+        commands.push(command(vec!["SELECT".to_owned(), index.to_string()]));
+        data.with_db(index, || dump_db(data, &mut commands));
+    }
+    commands
+}
+
+// 导出当前选择的库
+fn dump_db(data: &Data, commands: &mut Vec<Resp>) {
+    for entry in data.db().string_data.iter() {
+        if data.is_expired(entry.key()) {
+            continue;
+        }
+        let value = match entry.value() {
+            Resp::SimpleStrings(s) => s.val.clone(),
+            Resp::BulkStrings(s) => s.val.clone(),
+            Resp::Integers(i) => i.val.to_string(),
+            _ => continue,
+        };
+        commands.push(command(vec!["SET".to_owned(), entry.key().clone(), value]));
+    }
+
+    for entry in data.db().hash_data.iter() {
+        if data.is_expired(entry.key()) || entry.value().is_empty() {
+            continue;
+        }
+        let mut args = vec!["HSET".to_owned(), entry.key().clone()];
+        for (field, value) in entry.value() {
+            args.push(field.clone());
+            args.push(value.clone());
+        }
+        commands.push(command(args));
+    }
+
+    for entry in data.db().list_data.iter() {
+        if data.is_expired(entry.key()) || entry.value().is_empty() {
+            continue;
+        }
+        let mut args = vec!["RPUSH".to_owned(), entry.key().clone()];
+        args.extend(entry.value().iter().cloned());
+        commands.push(command(args));
+    }
+
+    for entry in data.db().set_data.iter() {
+        if data.is_expired(entry.key()) || entry.value().is_empty() {
+            continue;
+        }
+        let mut args = vec!["SADD".to_owned(), entry.key().clone()];
+        args.extend(entry.value().iter().cloned());
+        commands.push(command(args));
+    }
+
+    for entry in data.db().sorted_set_data.iter() {
+        if data.is_expired(entry.key()) || entry.value().is_empty() {
+            continue;
+        }
+        let mut args = vec!["ZADD".to_owned(), entry.key().clone()];
+        for (member, score) in entry.value() {
+            args.push(score.to_string());
+            args.push(member.clone());
+        }
+        commands.push(command(args));
+    }
+
+    let now = data.current_timestamp_millis();
+    for entry in data.db().expiry_data.iter() {
+        if *entry.value() > now {
+            commands.push(command(vec![
+                "PEXPIREAT".to_owned(),
+                entry.key().clone(),
+                entry.value().to_string(),
+            ]));
+        }
+    }
+}
+
+/// 导出快照并编码为字节
+pub fn encode(data: &Data) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    for command in dump(data) {
+        buf.extend(command.encode()?);
+    }
+    Ok(buf)
+}
+
+/// 保存快照到 `path`：先写入同一目录下的临时文件再重命名，失败时不会留下不完整的文件
+pub fn save(data: &Data, path: &Path) -> Result<()> {
+    let payload = encode(data)?;
+    let tmp = path.with_file_name(format!("temp-{}.rdb", std::process::id()));
+    let written = std::fs::write(&tmp, payload).and_then(|_| std::fs::rename(&tmp, path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    Ok(written?)
+}
+
+/// 在数据集上依次执行快照中的命令，返回执行的命令数（不含 SELECT）
+pub fn load(data: &Data, payload: &[u8]) -> Result<usize> {
+    let mut buf = BytesMut::from(payload);
+    let mut count = 0;
+    let mut db = 0;
+    while !buf.is_empty() {
+        let frame = match Resp::decode(&mut buf) {
+            Ok(frame) => frame,
+            Err(RespError::NotComplete) => {
+                return Err(anyhow::anyhow!("truncated snapshot payload"));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        match CommandGroup::try_from(frame)? {
+            CommandGroup::Connection(ConnectionCommand::Select(select)) => {
+                db = select
+                    .validate(data)
+                    .map_err(|e| anyhow::anyhow!("invalid SELECT in snapshot: {e:?}"))?;
+            }
+            command => {
+                data.with_db(db, || command.process(data))?;
+                count += 1;
+            }
+        }
+    }
+    Ok(count)
+}
+
+/// 启动时从 `path` 加载快照，文件不存在时返回 None
+pub fn load_file(data: &Data, path: &Path) -> Result<Option<usize>> {
+    match std::fs::read(path) {
+        Ok(payload) => load(data, &payload).map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 序列化单个键的值，键不存在时返回 None
+pub fn dump_key(data: &Data, key: &str) -> Option<String> {
+    let mut args = Vec::new();
+    if let Some(value) = data.db().string_data.get(key) {
+        let value = match value.value() {
+            Resp::SimpleStrings(s) => s.val.clone(),
+            Resp::BulkStrings(s) => s.val.clone(),
+            Resp::Integers(i) => i.val.to_string(),
+            _ => return None,
+        };
+        args.push("string".to_owned());
+        args.push(value);
+    } else if let Some(hash) = data.db().hash_data.get(key) {
+        args.push("hash".to_owned());
+        for (field, value) in hash.value() {
+            args.push(field.clone());
+            args.push(value.clone());
+        }
+    } else if let Some(list) = data.db().list_data.get(key) {
+        args.push("list".to_owned());
+        args.extend(list.value().iter().cloned());
+    } else if let Some(set) = data.db().set_data.get(key) {
+        args.push("set".to_owned());
+        args.extend(set.value().iter().cloned());
+    } else if let Some(zset) = data.db().sorted_set_data.get(key) {
+        args.push("zset".to_owned());
+        for (member, score) in zset.value() {
+            args.push(member.clone());
+            args.push(score.to_string());
+        }
+    } else {
+        return None;
+    }
+    let payload = command(args).encode().ok()?;
+    String::from_utf8(payload).ok()
+}
+
+/// 把 `dump_key` 的结果恢复到指定键上，键已存在时会被覆盖
+pub fn restore_key(data: &Data, key: &str, payload: &str) -> Result<()> {
+    let mut buf = BytesMut::from(payload.as_bytes());
+    let Ok(Resp::Arrays(arr)) = Resp::decode(&mut buf) else {
+        return Err(anyhow::anyhow!(
+            "DUMP payload version or checksum are wrong"
+        ));
+    };
+    let mut values = Vec::with_capacity(arr.val.len());
+    for item in &arr.val {
+        match item {
+            Resp::BulkStrings(item) => values.push(item.val.clone()),
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "DUMP payload version or checksum are wrong"
+                ))
+            }
+        }
+    }
+    let Some((kind, values)) = values.split_first() else {
+        return Err(anyhow::anyhow!(
+            "DUMP payload version or checksum are wrong"
+        ));
+    };
+
+    data.remove_key(key);
+    match kind.as_str() {
+        "string" => {
+            let value = values.first().cloned().unwrap_or_default();
+            data.db().string_data.insert(
+                key.to_owned(),
+                Resp::SimpleStrings(crate::SimpleStringsData::new(value)),
+            );
+        }
+        "hash" => {
+            let hash: HashMap<String, String> = values
+                .chunks(2)
+                .map(|pair| (pair[0].clone(), pair.get(1).cloned().unwrap_or_default()))
+                .collect();
+            data.db().hash_data.insert(key.to_owned(), hash);
+        }
+        "list" => {
+            let list: VecDeque<String> = values.iter().cloned().collect();
+            data.db().list_data.insert(key.to_owned(), list);
+        }
+        "set" => {
+            let set: HashSet<String> = values.iter().cloned().collect();
+            data.db().set_data.insert(key.to_owned(), set);
+        }
+        "zset" => {
+            let mut zset = BTreeMap::new();
+            for pair in values.chunks(2) {
+                let score = pair.get(1).and_then(|s| s.parse::<f64>().ok());
+                zset.insert(pair[0].clone(), score.unwrap_or_default());
+            }
+            data.db().sorted_set_data.insert(key.to_owned(), zset);
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "DUMP payload version or checksum are wrong"
+            ))
+        }
+    }
+    Ok(())
+}
+
+fn command(args: Vec<String>) -> Resp {
+    Resp::Arrays(Arrays::new(
+        args.into_iter()
+            .map(|arg| Resp::BulkStrings(BulkStrings::new(arg)))
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleStringsData;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let data = Data::new();
+        data.db().string_data.insert(
+            "name".to_owned(),
+            Resp::SimpleStrings(SimpleStringsData::new("simple redis".to_owned())),
+        );
+        data.db().hash_data.insert(
+            "user:1".to_owned(),
+            HashMap::from([("age".to_owned(), "18".to_owned())]),
+        );
+        data.db().list_data.insert(
+            "queue".to_owned(),
+            VecDeque::from(["a".to_owned(), "b".to_owned()]),
+        );
+        data.db()
+            .set_data
+            .insert("tags".to_owned(), HashSet::from(["rust".to_owned()]));
+        data.db().sorted_set_data.insert(
+            "rank".to_owned(),
+            BTreeMap::from([("alice".to_owned(), 1.5)]),
+        );
+        let expire_at = data.current_timestamp_millis() + 60_000;
+        data.set_expiry("name", expire_at);
+
+        let payload = encode(&data).unwrap();
+        let replica = Data::new();
+        assert_eq!(load(&replica, &payload).unwrap(), 6);
+
+        assert_eq!(
+            replica.db().string_data.get("name").map(|v| v.clone()),
+            data.db().string_data.get("name").map(|v| v.clone())
+        );
+        assert_eq!(
+            replica.db().hash_data.get("user:1").map(|v| v.clone()),
+            data.db().hash_data.get("user:1").map(|v| v.clone())
+        );
+        assert_eq!(
+            replica.db().list_data.get("queue").map(|v| v.clone()),
+            data.db().list_data.get("queue").map(|v| v.clone())
+        );
+        assert!(replica.db().set_data.get("tags").unwrap().contains("rust"));
+        assert_eq!(
+            replica
+                .db()
+                .sorted_set_data
+                .get("rank")
+                .unwrap()
+                .get("alice"),
+            Some(&1.5)
+        );
+        assert_eq!(
+            replica.db().expiry_data.get("name").map(|v| *v),
+            Some(expire_at)
+        );
+    }
+
+    #[test]
+    fn test_dump_and_restore_key() {
+        let data = Data::new();
+        data.db().list_data.insert(
+            "queue".to_owned(),
+            VecDeque::from(["a".to_owned(), "b".to_owned()]),
+        );
+        let payload = dump_key(&data, "queue").unwrap();
+        assert!(dump_key(&data, "missing").is_none());
+
+        restore_key(&data, "queue:copy", &payload).unwrap();
+        assert_eq!(
+            data.db().list_data.get("queue:copy").map(|v| v.clone()),
+            Some(VecDeque::from(["a".to_owned(), "b".to_owned()]))
+        );
+    }
+}
@@ -0,0 +1,482 @@
+//! 服务器运行统计：连接、命令调用次数和耗时、错误回复、键空间命中等
+//!
+//! 连接循环和命令分发（`transaction::call`）在执行过程中更新这里的计数器，
+//! INFO 的 stats、commandstats、errorstats、latencystats 等部分从这里读取，
+//! CONFIG RESETSTAT 清零。
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use dashmap::DashMap;
+
+use crate::acl::Reason;
+use crate::{Data, Resp};
+
+// 耗时分布的桶数，第 i 个桶统计 (2^(i-1), 2^i] 微秒的调用
+pub const HISTOGRAM_BUCKETS: usize = 64;
+// errorstats 最多记录的错误类型数，与 Redis 一致，超过后只计入总数
+const MAX_ERROR_TYPES: usize = 128;
+// instantaneous_ops_per_sec 的采样周期和样本数
+const OPS_SAMPLE_PERIOD: Duration = Duration::from_millis(100);
+const OPS_SAMPLES: usize = 16;
+// latencystats 输出的百分位
+const LATENCY_PERCENTILES: [f64; 3] = [50.0, 99.0, 99.9];
+
+/// 单个命令的调用统计
+#[derive(Debug, Clone)]
+pub struct CommandStats {
+    pub calls: u64,
+    pub usec: u64,
+    // 执行前被拒绝（参数错误、OOM、READONLY、重定向等）
+    pub rejected_calls: u64,
+    // 执行后返回了错误
+    pub failed_calls: u64,
+    pub histogram: [u64; HISTOGRAM_BUCKETS],
+}
+
+impl Default for CommandStats {
+    fn default() -> Self {
+        Self {
+            calls: 0,
+            usec: 0,
+            rejected_calls: 0,
+            failed_calls: 0,
+            histogram: [0; HISTOGRAM_BUCKETS],
+        }
+    }
+}
+
+impl CommandStats {
+    /// 耗时的百分位（微秒），取所在桶的上界
+    pub fn percentile(&self, percentile: f64) -> f64 {
+        let total: u64 = self.histogram.iter().sum();
+        if total == 0 {
+            return 0.0;
+        }
+        let target = ((total as f64) * percentile / 100.0).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.histogram.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                return bucket_upper_bound(bucket) as f64;
+            }
+        }
+        bucket_upper_bound(HISTOGRAM_BUCKETS - 1) as f64
+    }
+}
+
+/// 耗时所在的桶
+pub fn histogram_bucket(usec: u64) -> usize {
+    if usec <= 1 {
+        0
+    } else {
+        (u64::BITS - (usec - 1).leading_zeros()) as usize
+    }
+}
+
+/// 桶的上界（微秒）
+pub fn bucket_upper_bound(bucket: usize) -> u64 {
+    1u64.checked_shl(bucket as u32).unwrap_or(u64::MAX)
+}
+
+// 最近一段时间的每秒命令数
+#[derive(Debug)]
+struct OpsSampler {
+    last_at: Instant,
+    last_commands: u64,
+    samples: [u64; OPS_SAMPLES],
+    index: usize,
+}
+
+#[derive(Debug)]
+pub struct Stats {
+    started_at: Instant,
+    // 启动时的 Unix 时间（秒）
+    start_time: u64,
+    // 每次启动随机生成，用于区分不同的运行实例
+    run_id: String,
+    connected_clients: AtomicU64,
+    total_connections_received: AtomicU64,
+    total_commands_processed: AtomicU64,
+    total_error_replies: AtomicU64,
+    expired_keys: AtomicU64,
+    keyspace_hits: AtomicU64,
+    keyspace_misses: AtomicU64,
+    // 上次保存以来执行的写命令数
+    dirty: AtomicU64,
+    commands: DashMap<&'static str, CommandStats>,
+    // 错误前缀（ERR、WRONGTYPE 等）-> 次数
+    errors: DashMap<String, u64>,
+    ops: Mutex<OpsSampler>,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            start_time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            run_id: crate::replication::random_replid(),
+            connected_clients: AtomicU64::new(0),
+            total_connections_received: AtomicU64::new(0),
+            total_commands_processed: AtomicU64::new(0),
+            total_error_replies: AtomicU64::new(0),
+            expired_keys: AtomicU64::new(0),
+            keyspace_hits: AtomicU64::new(0),
+            keyspace_misses: AtomicU64::new(0),
+            dirty: AtomicU64::new(0),
+            commands: DashMap::new(),
+            errors: DashMap::new(),
+            ops: Mutex::new(OpsSampler {
+                last_at: Instant::now(),
+                last_commands: 0,
+                samples: [0; OPS_SAMPLES],
+                index: 0,
+            }),
+        }
+    }
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    pub fn start_time(&self) -> u64 {
+        self.start_time
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// 新连接建立，返回的 guard 在连接断开时减少在线连接数
+    pub fn client_connected(data: &Arc<Data>) -> ConnectedClient {
+        data.stats
+            .total_connections_received
+            .fetch_add(1, Ordering::Relaxed);
+        data.stats.connected_clients.fetch_add(1, Ordering::Relaxed);
+        ConnectedClient(data.clone())
+    }
+
+    pub fn connected_clients(&self) -> u64 {
+        self.connected_clients.load(Ordering::Relaxed)
+    }
+
+    pub fn total_connections_received(&self) -> u64 {
+        self.total_connections_received.load(Ordering::Relaxed)
+    }
+
+    pub fn total_commands_processed(&self) -> u64 {
+        self.total_commands_processed.load(Ordering::Relaxed)
+    }
+
+    pub fn total_error_replies(&self) -> u64 {
+        self.total_error_replies.load(Ordering::Relaxed)
+    }
+
+    pub fn expired_keys(&self) -> u64 {
+        self.expired_keys.load(Ordering::Relaxed)
+    }
+
+    pub fn keyspace_hits(&self) -> u64 {
+        self.keyspace_hits.load(Ordering::Relaxed)
+    }
+
+    pub fn keyspace_misses(&self) -> u64 {
+        self.keyspace_misses.load(Ordering::Relaxed)
+    }
+
+    pub fn dirty(&self) -> u64 {
+        self.dirty.load(Ordering::Relaxed)
+    }
+
+    pub fn record_expired(&self) {
+        self.expired_keys.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_keyspace_lookup(&self, hit: bool) {
+        let counter = if hit {
+            &self.keyspace_hits
+        } else {
+            &self.keyspace_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_write(&self) {
+        self.dirty.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 命令执行完成，返回错误时计为失败
+    pub fn record_call(&self, name: &'static str, duration: Duration, reply: &Resp) {
+        self.total_commands_processed
+            .fetch_add(1, Ordering::Relaxed);
+        let usec = duration.as_micros() as u64;
+        let mut stats = self.commands.entry(name).or_default();
+        stats.calls += 1;
+        stats.usec += usec;
+        stats.histogram[histogram_bucket(usec)] += 1;
+        if let Resp::SimpleErrors(e) = reply {
+            stats.failed_calls += 1;
+            drop(stats);
+            self.record_error(&e.error_msg);
+        }
+    }
+
+    /// 命令在执行前被拒绝，`name` 为 None 表示未知命令
+    pub fn record_rejected(&self, name: Option<&'static str>, error_msg: &str) {
+        if let Some(name) = name {
+            self.commands.entry(name).or_default().rejected_calls += 1;
+        }
+        self.record_error(error_msg);
+    }
+
+    /// 向客户端回复了一个错误，按错误前缀分类
+    pub fn record_error(&self, error_msg: &str) {
+        self.total_error_replies.fetch_add(1, Ordering::Relaxed);
+        let prefix = error_msg.split(' ').next().unwrap_or_default();
+        if let Some(mut count) = self.errors.get_mut(prefix) {
+            *count += 1;
+        } else if self.errors.len() < MAX_ERROR_TYPES {
+            *self.errors.entry(prefix.to_owned()).or_default() += 1;
+        }
+    }
+
+    /// 命令的调用统计
+    pub fn command(&self, name: &str) -> Option<CommandStats> {
+        self.commands.get(name).map(|stats| stats.clone())
+    }
+
+    /// 所有被调用过的命令，按命令名排序
+    pub fn commands(&self) -> Vec<(&'static str, CommandStats)> {
+        let mut commands: Vec<_> = self
+            .commands
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect();
+        commands.sort_by_key(|(name, _)| *name);
+        commands
+    }
+
+    /// 采样一次命令数，由后台任务周期性调用
+    pub fn sample_ops(&self) {
+        let mut ops = self.ops.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let elapsed = now.duration_since(ops.last_at).as_millis().max(1) as u64;
+        let commands = self.total_commands_processed();
+        let index = ops.index;
+        ops.samples[index] = commands.saturating_sub(ops.last_commands) * 1000 / elapsed;
+        ops.index = (index + 1) % OPS_SAMPLES;
+        ops.last_at = now;
+        ops.last_commands = commands;
+    }
+
+    /// 最近几次采样的平均每秒命令数
+    pub fn instantaneous_ops_per_sec(&self) -> u64 {
+        let ops = self.ops.lock().unwrap_or_else(|e| e.into_inner());
+        ops.samples.iter().sum::<u64>() / OPS_SAMPLES as u64
+    }
+
+    /// CONFIG RESETSTAT：清零累计的统计，在线连接数等状态不变
+    pub fn reset(&self) {
+        for counter in [
+            &self.total_connections_received,
+            &self.total_commands_processed,
+            &self.total_error_replies,
+            &self.expired_keys,
+            &self.keyspace_hits,
+            &self.keyspace_misses,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        self.commands.clear();
+        self.errors.clear();
+        let mut ops = self.ops.lock().unwrap_or_else(|e| e.into_inner());
+        ops.samples = [0; OPS_SAMPLES];
+        ops.last_commands = 0;
+    }
+
+    /// INFO stats
+    pub fn info(&self, data: &Data) -> String {
+        format!(
+            "# Stats\r\n\
+            total_connections_received:{}\r\n\
+            total_commands_processed:{}\r\n\
+            instantaneous_ops_per_sec:{}\r\n\
+            rejected_connections:{}\r\n\
+            expired_keys:{}\r\n\
+            evicted_keys:{}\r\n\
+            keyspace_hits:{}\r\n\
+            keyspace_misses:{}\r\n\
+            pubsub_channels:{}\r\n\
+            pubsub_patterns:{}\r\n\
+            tracking_total_keys:{}\r\n\
+            tracking_total_items:{}\r\n\
+            tracking_total_prefixes:{}\r\n\
+            total_error_replies:{}\r\n\
+            client_query_buffer_limit_disconnections:{}\r\n\
+            client_output_buffer_limit_disconnections:{}\r\n\
+            acl_access_denied_auth:{}\r\n\
+            acl_access_denied_cmd:{}\r\n\
+            acl_access_denied_key:{}\r\n\
+            acl_access_denied_channel:{}\r\n",
+            self.total_connections_received(),
+            self.total_commands_processed(),
+            self.instantaneous_ops_per_sec(),
+            data.limits.rejected_connections(),
+            self.expired_keys(),
+            data.memory.evicted_keys(),
+            self.keyspace_hits(),
+            self.keyspace_misses(),
+            data.pubsub.active_channels(None, false).len(),
+            data.pubsub.num_patterns(),
+            data.tracking.total_keys(),
+            data.tracking.total_items(),
+            data.tracking.total_prefixes(),
+            self.total_error_replies(),
+            data.limits.query_buffer_disconnections(),
+            data.limits.output_buffer_disconnections(),
+            data.acl.denied(Reason::Auth),
+            data.acl.denied(Reason::Command),
+            data.acl.denied(Reason::Key),
+            data.acl.denied(Reason::Channel),
+        )
+    }
+
+    /// INFO commandstats
+    pub fn commandstats_info(&self) -> String {
+        let mut info = String::from("# Commandstats\r\n");
+        for (name, stats) in self.commands() {
+            let usec_per_call = if stats.calls == 0 {
+                0.0
+            } else {
+                stats.usec as f64 / stats.calls as f64
+            };
+            info.push_str(&format!(
+                "cmdstat_{name}:calls={},usec={},usec_per_call={usec_per_call:.2},rejected_calls={},failed_calls={}\r\n",
+                stats.calls, stats.usec, stats.rejected_calls, stats.failed_calls
+            ));
+        }
+        info
+    }
+
+    /// INFO errorstats
+    pub fn errorstats_info(&self) -> String {
+        let mut errors: Vec<(String, u64)> = self
+            .errors
+            .iter()
+            .map(|entry| (entry.key().clone(), *entry.value()))
+            .collect();
+        errors.sort();
+        let mut info = String::from("# Errorstats\r\n");
+        for (prefix, count) in errors {
+            info.push_str(&format!("errorstat_{prefix}:count={count}\r\n"));
+        }
+        info
+    }
+
+    /// INFO latencystats：每个命令耗时的 p50、p99、p99.9（微秒）
+    pub fn latencystats_info(&self) -> String {
+        let mut info = String::from("# Latencystats\r\n");
+        for (name, stats) in self.commands() {
+            if stats.calls == 0 {
+                continue;
+            }
+            let percentiles: Vec<String> = LATENCY_PERCENTILES
+                .iter()
+                .map(|p| format!("p{p}={:.3}", stats.percentile(*p)))
+                .collect();
+            info.push_str(&format!(
+                "latency_percentiles_usec_{name}:{}\r\n",
+                percentiles.join(",")
+            ));
+        }
+        info
+    }
+}
+
+/// 在线的客户端连接，drop 时减少在线连接数
+#[derive(Debug)]
+pub struct ConnectedClient(Arc<Data>);
+
+impl Drop for ConnectedClient {
+    fn drop(&mut self) {
+        self.0
+            .stats
+            .connected_clients
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// 定期采样每秒命令数
+pub fn start_sampler(data: Arc<Data>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(OPS_SAMPLE_PERIOD);
+        loop {
+            interval.tick().await;
+            data.stats.sample_ops();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleErrors;
+
+    #[test]
+    fn test_histogram_bucket() {
+        assert_eq!(histogram_bucket(0), 0);
+        assert_eq!(histogram_bucket(1), 0);
+        assert_eq!(histogram_bucket(2), 1);
+        assert_eq!(histogram_bucket(3), 2);
+        assert_eq!(histogram_bucket(4), 2);
+        assert_eq!(histogram_bucket(5), 3);
+        assert_eq!(bucket_upper_bound(3), 8);
+    }
+
+    #[test]
+    fn test_record_and_reset() {
+        let stats = Stats::new();
+        let ok = Resp::Nulls(crate::Nulls::new());
+        let err = Resp::SimpleErrors(SimpleErrors::new("WRONGTYPE Operation".to_owned()));
+        for _ in 0..99 {
+            stats.record_call("get", Duration::from_micros(3), &ok);
+        }
+        stats.record_call("get", Duration::from_micros(1000), &err);
+        stats.record_rejected(Some("set"), "OOM command not allowed");
+        stats.record_rejected(None, "ERR unknown command 'nosuch'");
+
+        let get = stats.command("get").unwrap();
+        assert_eq!(get.calls, 100);
+        assert_eq!(get.failed_calls, 1);
+        assert_eq!(get.percentile(50.0), 4.0);
+        assert_eq!(get.percentile(99.9), 1024.0);
+        assert_eq!(stats.command("set").unwrap().rejected_calls, 1);
+        assert_eq!(stats.total_commands_processed(), 100);
+        assert_eq!(stats.total_error_replies(), 3);
+
+        let errorstats = stats.errorstats_info();
+        assert!(errorstats.contains("errorstat_WRONGTYPE:count=1\r\n"));
+        assert!(errorstats.contains("errorstat_OOM:count=1\r\n"));
+        assert!(stats.commandstats_info().contains(
+            "cmdstat_get:calls=100,usec=1297,usec_per_call=12.97,rejected_calls=0,failed_calls=1"
+        ));
+        assert!(stats
+            .latencystats_info()
+            .contains("latency_percentiles_usec_get:p50=4.000,p99=4.000,p99.9=1024.000"));
+
+        stats.reset();
+        assert_eq!(stats.total_commands_processed(), 0);
+        assert!(stats.commands().is_empty());
+        assert_eq!(stats.errorstats_info(), "# Errorstats\r\n");
+    }
+}
@@ -0,0 +1,269 @@
+//! TLS 监听和复制连接
+//!
+//! 证书和私钥从本地的 PEM 文件读取（tls-cert-file / tls-key-file），tls-ca-cert-file 用来
+//! 校验客户端证书（`tls-auth-clients yes|optional`）和副本连接主节点时主节点的证书。
+//! 加密套件使用 rustls 的 ring 实现，握手之后的 `TlsStream` 和普通的 `TcpStream`
+//! 一样交给 `connection::serve`。
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use tokio_rustls::rustls::crypto::{ring, CryptoProvider};
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore, ServerConfig};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+/// tls-auth-clients：是否要求客户端提供证书
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AuthClients {
+    No,
+    // 和 Redis 一样默认要求客户端证书
+    #[default]
+    Yes,
+    // 提供了证书时校验，不提供也允许连接
+    Optional,
+}
+
+impl AuthClients {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "no" => Some(Self::No),
+            "yes" => Some(Self::Yes),
+            "optional" => Some(Self::Optional),
+            _ => None,
+        }
+    }
+}
+
+/// TLS 相关的配置
+#[derive(Debug, Clone, Default)]
+pub struct TlsConfig {
+    pub cert_file: Option<PathBuf>,
+    pub key_file: Option<PathBuf>,
+    pub ca_cert_file: Option<PathBuf>,
+    pub auth_clients: AuthClients,
+    // 副本连接主节点时使用 TLS
+    pub replication: bool,
+}
+
+impl TlsConfig {
+    /// tls-port 上使用的 acceptor
+    pub fn acceptor(&self) -> Result<TlsAcceptor> {
+        let (certs, key) = self.cert_and_key()?;
+        let builder = ServerConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()?;
+        let builder = match self.auth_clients {
+            AuthClients::No => builder.with_no_client_auth(),
+            auth_clients => {
+                let roots = self
+                    .roots()
+                    .context("tls-auth-clients requires tls-ca-cert-file")?;
+                let verifier = WebPkiClientVerifier::builder_with_provider(roots, provider());
+                let verifier = if auth_clients == AuthClients::Optional {
+                    verifier.allow_unauthenticated().build()?
+                } else {
+                    verifier.build()?
+                };
+                builder.with_client_cert_verifier(verifier)
+            }
+        };
+        let config = builder.with_single_cert(certs, key)?;
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+
+    /// 副本连接主节点时使用的 connector
+    pub fn connector(&self) -> Result<TlsConnector> {
+        Ok(TlsConnector::from(self.client_config()?))
+    }
+
+    /// 用 CA 校验主节点，配置了证书时同时出示自己的证书
+    pub fn client_config(&self) -> Result<Arc<ClientConfig>> {
+        let roots = self
+            .roots()
+            .context("tls-replication requires tls-ca-cert-file")?;
+        let builder = ClientConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(roots);
+        let config = match (&self.cert_file, &self.key_file) {
+            (Some(_), Some(_)) => {
+                let (certs, key) = self.cert_and_key()?;
+                builder.with_client_auth_cert(certs, key)?
+            }
+            _ => builder.with_no_client_auth(),
+        };
+        Ok(Arc::new(config))
+    }
+
+    fn cert_and_key(&self) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)> {
+        let cert_file = self
+            .cert_file
+            .as_ref()
+            .ok_or_else(|| anyhow!("TLS requires tls-cert-file"))?;
+        let key_file = self
+            .key_file
+            .as_ref()
+            .ok_or_else(|| anyhow!("TLS requires tls-key-file"))?;
+        let certs = load_certs(cert_file)?;
+        let key = rustls_pemfile::private_key(&mut reader(key_file)?)?
+            .ok_or_else(|| anyhow!("no private key found in {}", key_file.display()))?;
+        Ok((certs, key))
+    }
+
+    fn roots(&self) -> Result<Arc<RootCertStore>> {
+        let ca_cert_file = self
+            .ca_cert_file
+            .as_ref()
+            .ok_or_else(|| anyhow!("tls-ca-cert-file is not configured"))?;
+        let mut roots = RootCertStore::empty();
+        for cert in load_certs(ca_cert_file)? {
+            roots.add(cert)?;
+        }
+        Ok(Arc::new(roots))
+    }
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+fn reader(path: &Path) -> Result<BufReader<File>> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    Ok(BufReader::new(file))
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    let certs = rustls_pemfile::certs(&mut reader(path)?).collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        return Err(anyhow!("no certificate found in {}", path.display()));
+    }
+    Ok(certs)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::SinkExt;
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+    use tokio_rustls::rustls::pki_types::ServerName;
+    use tokio_stream::StreamExt;
+    use tokio_util::codec::Framed;
+
+    use super::*;
+    use crate::network::RespFrameCodec;
+    use crate::{Arrays, BulkStrings, Data, Resp};
+
+    static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "simple-redis-tls-{}-{}",
+            std::process::id(),
+            NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // 在 `dir` 中生成 CA 和由它签发的证书，服务端和客户端共用
+    fn generate_certs(dir: &Path) -> TlsConfig {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+        let key = KeyPair::generate().unwrap();
+        let cert = CertificateParams::new(vec!["localhost".to_owned(), "127.0.0.1".to_owned()])
+            .unwrap()
+            .signed_by(&key, &ca, &ca_key)
+            .unwrap();
+        std::fs::write(dir.join("ca.crt"), ca.pem()).unwrap();
+        std::fs::write(dir.join("redis.crt"), cert.pem()).unwrap();
+        std::fs::write(dir.join("redis.key"), key.serialize_pem()).unwrap();
+        TlsConfig {
+            cert_file: Some(dir.join("redis.crt")),
+            key_file: Some(dir.join("redis.key")),
+            ca_cert_file: Some(dir.join("ca.crt")),
+            auth_clients: AuthClients::Yes,
+            replication: true,
+        }
+    }
+
+    fn ping() -> Resp {
+        Resp::Arrays(Arrays::new(vec![Resp::BulkStrings(BulkStrings::new(
+            "PING".to_owned(),
+        ))]))
+    }
+
+    #[tokio::test]
+    async fn test_serve_over_mutual_tls() {
+        let dir = temp_dir();
+        let config = generate_certs(&dir);
+        let acceptor = config.acceptor().unwrap();
+        let connector = config.connector().unwrap();
+        let data = Arc::new(Data::new());
+        let (client, server) = tokio::io::duplex(16 * 1024);
+
+        let registered = data
+            .clients
+            .register("127.0.0.1:5000".to_owned(), "127.0.0.1:6380".to_owned());
+        let server_data = data.clone();
+        tokio::spawn(async move {
+            let stream = acceptor.accept(server).await.unwrap();
+            crate::connection::serve(stream, registered, server_data).await
+        });
+        let name = ServerName::try_from("localhost").unwrap();
+        let stream = connector.connect(name, client).await.unwrap();
+        let mut framed = Framed::new(stream, RespFrameCodec::new());
+        framed.send(ping()).await.unwrap();
+        let reply = framed.next().await.unwrap().unwrap();
+        assert!(matches!(reply, Resp::SimpleStrings(pong) if pong.val == "PONG"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_client_certificate_required() {
+        let dir = temp_dir();
+        let mut config = generate_certs(&dir);
+        let (client, server) = tokio::io::duplex(16 * 1024);
+        let acceptor = config.acceptor().unwrap();
+        let server = tokio::spawn(async move { acceptor.accept(server).await.is_ok() });
+        // 不出示证书的客户端
+        config.cert_file = None;
+        let connector = config.connector().unwrap();
+        let name = ServerName::try_from("127.0.0.1").unwrap();
+        let _ = connector.connect(name, client).await;
+        assert!(!server.await.unwrap());
+
+        // optional 时不出示证书也可以连接
+        let mut config = generate_certs(&dir);
+        config.auth_clients = AuthClients::Optional;
+        let acceptor = config.acceptor().unwrap();
+        config.cert_file = None;
+        let connector = config.connector().unwrap();
+        let (client, server) = tokio::io::duplex(16 * 1024);
+        let server = tokio::spawn(async move { acceptor.accept(server).await.is_ok() });
+        let name = ServerName::try_from("127.0.0.1").unwrap();
+        // 保持客户端连接，直到服务端写完握手后的 session ticket
+        let stream = connector.connect(name, client).await;
+        assert!(stream.is_ok());
+        assert!(server.await.unwrap());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_missing_files() {
+        let config = TlsConfig::default();
+        assert!(config.acceptor().is_err());
+        let config = TlsConfig {
+            cert_file: Some(PathBuf::from("/nonexistent/redis.crt")),
+            key_file: Some(PathBuf::from("/nonexistent/redis.key")),
+            auth_clients: AuthClients::No,
+            ..Default::default()
+        };
+        assert!(config.acceptor().is_err());
+    }
+}
@@ -0,0 +1,392 @@
+//! 客户端缓存（CLIENT TRACKING）
+//!
+//! 和 Redis 一样支持两种模式：
+//! - 默认模式：记住每个客户端读过的键，键被修改时给读过它的客户端发送一次失效消息，
+//!   之后除非再次读取，不会再收到这个键的消息。OPTIN/OPTOUT 配合 CLIENT CACHING
+//!   控制哪些读命令需要记住。
+//! - BCAST 模式：客户端订阅若干前缀，任何匹配前缀的键被修改都会收到失效消息。
+//!   修改的键先攒起来，由后台任务批量发送，一次修改多个键时只发一条消息。
+//!
+//! 失效消息在 RESP3 下以 `invalidate` Push 发给客户端自己；使用 REDIRECT 时发给
+//! 另一个连接，RESP2 的连接需要订阅 `__redis__:invalidate` 频道才能收到。
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use dashmap::DashMap;
+use tokio::sync::Notify;
+
+use crate::client::{self, Client, Clients};
+use crate::{Arrays, BulkStrings, Data, Integers, Nulls, Resp};
+
+pub const INVALIDATE_CHANNEL: &str = "__redis__:invalidate";
+
+/// CLIENT TRACKING ON 的选项
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackingOptions {
+    // 接收失效消息的连接，None 表示发给自己
+    pub redirect: Option<u64>,
+    pub bcast: bool,
+    pub prefixes: Vec<String>,
+    pub optin: bool,
+    pub optout: bool,
+    // 不接收自己修改的键的失效消息
+    pub noloop: bool,
+}
+
+// BCAST 模式订阅的一个前缀
+#[derive(Debug, Default)]
+struct Prefix {
+    clients: HashSet<u64>,
+    // 等待广播的键 -> 修改它的客户端，多个客户端修改过时为 None
+    keys: HashMap<String, Option<u64>>,
+}
+
+#[derive(Debug, Default)]
+pub struct Tracking {
+    // 默认模式：键 -> 读过这个键的客户端
+    table: DashMap<String, HashSet<u64>>,
+    prefixes: Mutex<BTreeMap<String, Prefix>>,
+    // 订阅的前缀数，没有 BCAST 客户端时不需要加锁
+    prefix_count: AtomicUsize,
+    pending: Notify,
+}
+
+impl Tracking {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, Prefix>> {
+        self.prefixes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 默认模式下记住的键数
+    pub fn total_keys(&self) -> usize {
+        self.table.len()
+    }
+
+    /// 默认模式下记住的（键, 客户端）数
+    pub fn total_items(&self) -> usize {
+        self.table.iter().map(|entry| entry.value().len()).sum()
+    }
+
+    /// BCAST 模式订阅的前缀数
+    pub fn total_prefixes(&self) -> usize {
+        self.prefix_count.load(Ordering::Relaxed)
+    }
+
+    /// 开启 tracking，BCAST 模式下已经订阅的前缀会保留
+    pub fn enable(&self, client: &Client, mut options: TrackingOptions) {
+        if options.bcast {
+            if options.prefixes.is_empty() && client.tracking().is_none() {
+                // 不带 PREFIX 时订阅所有键
+                options.prefixes.push(String::new());
+            }
+            let mut prefixes = self.lock();
+            for prefix in &options.prefixes {
+                prefixes
+                    .entry(prefix.clone())
+                    .or_default()
+                    .clients
+                    .insert(client.id);
+            }
+            self.prefix_count.store(prefixes.len(), Ordering::Relaxed);
+            if let Some(current) = client.tracking() {
+                let mut merged = current.prefixes;
+                merged.extend(options.prefixes);
+                options.prefixes = merged;
+            }
+        }
+        client.set_tracking(Some(options));
+    }
+
+    pub fn disable(&self, client: &Client) {
+        let Some(options) = client.tracking() else {
+            return;
+        };
+        if options.bcast {
+            let mut prefixes = self.lock();
+            for prefix in &options.prefixes {
+                if let Some(state) = prefixes.get_mut(prefix) {
+                    state.clients.remove(&client.id);
+                    if state.clients.is_empty() {
+                        prefixes.remove(prefix);
+                    }
+                }
+            }
+            self.prefix_count.store(prefixes.len(), Ordering::Relaxed);
+        }
+        client.set_tracking(None);
+    }
+
+    /// 读命令执行后记住客户端读过的键
+    pub fn remember_keys(&self, client: &Client, keys: &[String]) {
+        if !client.tracks_reads() {
+            return;
+        }
+        for key in keys {
+            self.table.entry(key.clone()).or_default().insert(client.id);
+        }
+    }
+
+    /// 键被修改：默认模式立即通知读过它的客户端，BCAST 模式等待后台任务批量发送
+    pub fn invalidate_key(&self, clients: &Clients, key: &str) {
+        let current = client::current().map(|client| client.id);
+        if let Some((_, ids)) = self.table.remove(key) {
+            for id in ids {
+                let Some(client) = clients.get(id) else {
+                    continue;
+                };
+                let Some(options) = client.tracking() else {
+                    continue;
+                };
+                if options.bcast || (options.noloop && current == Some(id)) {
+                    continue;
+                }
+                send(clients, &client, &options, Some(vec![key.to_owned()]));
+            }
+        }
+        if self.prefix_count.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let mut prefixes = self.lock();
+        let mut matched = false;
+        for (prefix, state) in prefixes.iter_mut() {
+            if !key.starts_with(prefix.as_str()) {
+                continue;
+            }
+            matched = true;
+            state
+                .keys
+                .entry(key.to_owned())
+                .and_modify(|modifier| {
+                    if *modifier != current {
+                        *modifier = None;
+                    }
+                })
+                .or_insert(current);
+        }
+        if matched {
+            self.pending.notify_one();
+        }
+    }
+
+    /// FLUSHDB/FLUSHALL/SWAPDB：通知所有开启 tracking 的客户端清空整个缓存
+    pub fn invalidate_all(&self, clients: &Clients) {
+        self.table.clear();
+        for state in self.lock().values_mut() {
+            state.keys.clear();
+        }
+        for client in clients.list() {
+            if let Some(options) = client.tracking() {
+                send(clients, &client, &options, None);
+            }
+        }
+    }
+
+    /// 把 BCAST 模式下攒起来的键发给订阅了对应前缀的客户端，每个前缀一条消息
+    pub fn flush_pending(&self, clients: &Clients) {
+        let mut prefixes = self.lock();
+        prefixes.retain(|_, state| {
+            let keys = std::mem::take(&mut state.keys);
+            state.clients.retain(|id| {
+                let Some(client) = clients.get(*id) else {
+                    // 连接已经断开
+                    return false;
+                };
+                let Some(options) = client.tracking().filter(|options| options.bcast) else {
+                    return false;
+                };
+                let keys: Vec<String> = keys
+                    .iter()
+                    .filter(|(_, modifier)| !(options.noloop && **modifier == Some(*id)))
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                if !keys.is_empty() {
+                    send(clients, &client, &options, Some(keys));
+                }
+                true
+            });
+            !state.clients.is_empty()
+        });
+        self.prefix_count.store(prefixes.len(), Ordering::Relaxed);
+    }
+}
+
+// 发送失效消息，`keys` 为 None 时表示清空所有键
+fn send(
+    clients: &Clients,
+    client: &Arc<Client>,
+    options: &TrackingOptions,
+    keys: Option<Vec<String>>,
+) {
+    let target = match options.redirect {
+        Some(id) => match clients.get(id) {
+            Some(target) => target,
+            None => {
+                // 重定向的连接已经断开，RESP3 客户端会收到通知
+                if client.protocol() >= 3 {
+                    client.push(vec![bulk("tracking-redir-broken"), integer(id as i64)]);
+                }
+                return;
+            }
+        },
+        None => client.clone(),
+    };
+    let payload = match keys {
+        Some(keys) => Resp::Arrays(Arrays::new(keys.iter().map(|key| bulk(key)).collect())),
+        None => Resp::Nulls(Nulls::new()),
+    };
+    if target.protocol() >= 3 {
+        target.push(vec![bulk("invalidate"), payload]);
+    } else if options.redirect.is_some() && target.is_pubsub() {
+        // RESP2 只能通过订阅了失效频道的连接接收
+        target.push(vec![bulk("message"), bulk(INVALIDATE_CHANNEL), payload]);
+    }
+}
+
+/// 后台批量发送 BCAST 模式的失效消息
+pub fn start(data: Arc<Data>) {
+    tokio::spawn(async move {
+        loop {
+            data.tracking.pending.notified().await;
+            data.tracking.flush_pending(&data.clients);
+        }
+    });
+}
+
+fn bulk(val: &str) -> Resp {
+    Resp::BulkStrings(BulkStrings::new(val.to_owned()))
+}
+
+fn integer(val: i64) -> Resp {
+    Resp::Integers(Integers::new(val))
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::client::Snapshot;
+    use crate::limits::Outbox;
+    use crate::pubsub::Message;
+
+    fn register(
+        data: &Data,
+        resp: u8,
+    ) -> (crate::client::Registered, mpsc::UnboundedReceiver<Message>) {
+        let registered = data
+            .clients
+            .register("127.0.0.1:5000".to_owned(), "127.0.0.1:6379".to_owned());
+        let (tx, rx) = Outbox::channel(None, &data.limits);
+        registered.client().set_push(tx);
+        registered.client().update(Snapshot {
+            resp,
+            ..Default::default()
+        });
+        (registered, rx)
+    }
+
+    fn keys(message: Message) -> Vec<Resp> {
+        match message.last() {
+            Some(Resp::Arrays(keys)) => keys.val.clone(),
+            other => panic!("unexpected payload {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_default_mode_and_noloop() {
+        let data = Data::new();
+        let (reader, mut rx) = register(&data, 3);
+        data.tracking
+            .enable(reader.client(), TrackingOptions::default());
+        data.tracking
+            .remember_keys(reader.client(), &["a".to_owned(), "b".to_owned()]);
+        assert_eq!(data.tracking.total_keys(), 2);
+
+        data.tracking.invalidate_key(&data.clients, "a");
+        let message = rx.try_recv().unwrap();
+        assert_eq!(message[0], bulk("invalidate"));
+        assert_eq!(keys(message), vec![bulk("a")]);
+        // 只通知一次
+        data.tracking.invalidate_key(&data.clients, "a");
+        assert!(rx.try_recv().is_err());
+
+        // NOLOOP：自己修改的键不通知
+        data.tracking.enable(
+            reader.client(),
+            TrackingOptions {
+                noloop: true,
+                ..Default::default()
+            },
+        );
+        client::with_client(reader.client(), || {
+            data.tracking.invalidate_key(&data.clients, "b");
+        });
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_optin_and_redirect() {
+        let data = Data::new();
+        let (reader, mut rx) = register(&data, 2);
+        let (listener, mut listener_rx) = register(&data, 2);
+        listener.client().update(Snapshot {
+            channels: 1,
+            ..Default::default()
+        });
+        data.tracking.enable(
+            reader.client(),
+            TrackingOptions {
+                redirect: Some(listener.client().id),
+                optin: true,
+                ..Default::default()
+            },
+        );
+        data.tracking
+            .remember_keys(reader.client(), &["a".to_owned()]);
+        assert_eq!(data.tracking.total_keys(), 0);
+        reader.client().set_caching(true);
+        data.tracking
+            .remember_keys(reader.client(), &["a".to_owned()]);
+
+        data.tracking.invalidate_key(&data.clients, "a");
+        assert!(rx.try_recv().is_err());
+        let message = listener_rx.try_recv().unwrap();
+        assert_eq!(message[1], bulk(INVALIDATE_CHANNEL));
+        assert_eq!(keys(message), vec![bulk("a")]);
+
+        data.tracking.invalidate_all(&data.clients);
+        let message = listener_rx.try_recv().unwrap();
+        assert_eq!(message[2], Resp::Nulls(Nulls::new()));
+    }
+
+    #[test]
+    fn test_bcast() {
+        let data = Data::new();
+        let (client, mut rx) = register(&data, 3);
+        data.tracking.enable(
+            client.client(),
+            TrackingOptions {
+                bcast: true,
+                prefixes: vec!["user:".to_owned()],
+                ..Default::default()
+            },
+        );
+        assert_eq!(data.tracking.total_prefixes(), 1);
+        data.tracking.invalidate_key(&data.clients, "user:1");
+        data.tracking.invalidate_key(&data.clients, "user:1");
+        data.tracking.invalidate_key(&data.clients, "order:1");
+        assert!(rx.try_recv().is_err());
+
+        data.tracking.flush_pending(&data.clients);
+        assert_eq!(keys(rx.try_recv().unwrap()), vec![bulk("user:1")]);
+        assert!(rx.try_recv().is_err());
+
+        data.tracking.disable(client.client());
+        assert_eq!(data.tracking.total_prefixes(), 0);
+    }
+}
@@ -0,0 +1,475 @@
+//! 事务：MULTI/EXEC/DISCARD/WATCH
+//!
+//! 所有命令执行时都持有全局读锁，EXEC 持有写锁，保证事务中的命令
+//! 不会和其他客户端的命令交错执行。
+//!
+//! WATCH 采用乐观锁：每个被 WATCH 的键（按库区分）有一个版本号，写命令修改键时
+//! 把版本号加一，EXEC 时只要有一个键的版本号变了就放弃执行。
+
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Instant;
+
+use anyhow::Result;
+use dashmap::DashMap;
+use tracing::warn;
+
+use crate::process::connection::ConnectionCommand;
+use crate::process::{command_table, CommandGroup};
+use crate::{Arrays, Data, Nulls, Processor, Resp, SimpleErrors, SimpleStringsData};
+
+#[derive(Debug, Default)]
+struct WatchedKey {
+    version: u64,
+    // WATCH 这个键的连接数，为 0 时移除
+    watchers: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Transactions {
+    lock: RwLock<()>,
+    // (库编号, 键) -> 版本号
+    watched: DashMap<(usize, String), WatchedKey>,
+}
+
+impl Transactions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 普通命令执行期间持有
+    pub fn shared(&self) -> RwLockReadGuard<'_, ()> {
+        self.lock.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// EXEC 执行期间持有
+    pub fn exclusive(&self) -> RwLockWriteGuard<'_, ()> {
+        self.lock.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn watch(&self, db: usize, key: &str) -> u64 {
+        let mut entry = self.watched.entry((db, key.to_owned())).or_default();
+        entry.watchers += 1;
+        entry.version
+    }
+
+    fn unwatch(&self, db: usize, key: &str) {
+        self.watched
+            .remove_if_mut(&(db, key.to_owned()), |_, entry| {
+                entry.watchers -= 1;
+                entry.watchers == 0
+            });
+    }
+
+    fn version(&self, db: usize, key: &str) -> u64 {
+        self.watched
+            .get(&(db, key.to_owned()))
+            .map(|entry| entry.version)
+            .unwrap_or(0)
+    }
+
+    /// 键被修改，让 WATCH 了它的事务失效
+    pub fn touch(&self, db: usize, key: &str) {
+        if let Some(mut entry) = self.watched.get_mut(&(db, key.to_owned())) {
+            entry.version += 1;
+        }
+    }
+
+    /// 库被清空或交换，WATCH 了这个库中键的事务都失效
+    pub fn touch_db(&self, db: usize) {
+        self.watched
+            .iter_mut()
+            .filter(|entry| entry.key().0 == db)
+            .for_each(|mut entry| entry.version += 1);
+    }
+
+    /// 数据集被清空，所有 WATCH 都失效
+    pub fn touch_all(&self) {
+        self.watched
+            .iter_mut()
+            .for_each(|mut entry| entry.version += 1);
+    }
+}
+
+/// 执行一条命令：写命令会被传播给副本，并让 WATCH 了相关键的事务失效，
+/// 命令涉及的键会记录一次访问
+///
+/// 调用方需要持有 `Transactions` 的锁，并通过 `Data::with_db` 选好库。
+pub fn call(data: &Data, frame: Resp, command: &CommandGroup) -> Result<Resp> {
+    let Some(spec) = command_table::command_name(&frame).and_then(command_table::lookup) else {
+        let reply = command.process(data)?;
+        if let Resp::SimpleErrors(e) = &reply {
+            data.stats.record_error(&e.error_msg);
+        }
+        return Ok(reply);
+    };
+    let keys: Vec<String> = command_table::command_keys(&frame)
+        .into_iter()
+        .map(str::to_owned)
+        .collect();
+    // 超过 maxmemory 时先淘汰键，仍然超过时拒绝可能增加内存的命令
+    if !crate::memory::perform_evictions(data) && spec.is_denyoom() {
+        let msg = "OOM command not allowed when used memory > 'maxmemory'.";
+        data.stats.record_rejected(Some(spec.name), msg);
+        return Ok(Resp::SimpleErrors(SimpleErrors::new(msg.to_owned())));
+    }
+    let start = Instant::now();
+    let reply = if spec.is_write() {
+        data.replication
+            .propagate(data, &frame, || command.process(data))?
+    } else {
+        let reply = command.process(data)?;
+        // 读命令访问到的过期键
+        data.replication.propagate_expired()?;
+        reply
+    };
+    let duration = start.elapsed();
+    data.stats.record_call(spec.name, duration, &reply);
+    let event = if spec.is_fast() {
+        crate::latency::EVENT_FAST_COMMAND
+    } else {
+        crate::latency::EVENT_COMMAND
+    };
+    data.latency.record(event, duration);
+    // 只有慢的命令才复制参数，不给每条命令增加一次分配
+    if data.slowlog.is_slow(duration) {
+        let timestamp = data
+            .current_timestamp_millis()
+            .saturating_sub(duration.as_millis() as u64)
+            / 1000;
+        data.slowlog
+            .record(timestamp, duration, crate::slowlog::truncate_argv(&frame));
+    }
+    if matches!(reply, Resp::SimpleErrors(_)) {
+        return Ok(reply);
+    }
+    let db = crate::db::selected_db();
+    if !spec.is_write() && spec.touches_keys() {
+        // 读命令执行后键仍然存在即为命中
+        keys.iter()
+            .for_each(|key| data.stats.record_keyspace_lookup(data.contains_key(key)));
+        if let Some(client) = crate::client::current() {
+            data.tracking.remember_keys(&client, &keys);
+        }
+    }
+    if spec.is_write() {
+        data.stats.record_write();
+        keys.iter()
+            .for_each(|key| data.signal_modified_key(db, key));
+        keys.iter().for_each(|key| data.db().refresh_memory(key));
+        data.memory.update_peak(data.used_memory());
+    }
+    if spec.touches_keys() {
+        let now = data.current_timestamp_millis();
+        keys.iter()
+            .for_each(|key| data.db().record_access(key, now));
+    }
+    Ok(reply)
+}
+
+/// 连接上的事务状态，连接断开时自动取消 WATCH
+#[derive(Debug)]
+pub struct Transaction {
+    data: Arc<Data>,
+    // (库编号, 键, WATCH 时的版本号)
+    watched: Vec<(usize, String, u64)>,
+    // MULTI 之后排队的命令，None 表示不在事务中；
+    // 连接级别的命令（只有 UNWATCH 会入队）不解析成 CommandGroup
+    queued: Option<Vec<(Resp, Option<CommandGroup>)>>,
+    // 排队时出现过错误，EXEC 时直接放弃
+    aborted: bool,
+}
+
+impl Transaction {
+    pub fn new(data: Arc<Data>) -> Self {
+        Self {
+            data,
+            watched: Vec::new(),
+            queued: None,
+            aborted: false,
+        }
+    }
+
+    pub fn in_multi(&self) -> bool {
+        self.queued.is_some()
+    }
+
+    /// 排队的命令数，不在事务中时为 None
+    pub fn queued_len(&self) -> Option<usize> {
+        self.queued.as_ref().map(Vec::len)
+    }
+
+    /// 排队的命令中是否有写命令
+    pub fn queued_writes(&self) -> bool {
+        self.queued.as_ref().is_some_and(|queued| {
+            queued
+                .iter()
+                .any(|(frame, _)| command_table::is_write_command(frame))
+        })
+    }
+
+    pub fn watched_len(&self) -> usize {
+        self.watched.len()
+    }
+
+    pub fn multi(&mut self) -> Resp {
+        if self.in_multi() {
+            return error("ERR MULTI calls can not be nested");
+        }
+        self.queued = Some(Vec::new());
+        self.aborted = false;
+        ok()
+    }
+
+    /// 命令入队，返回 QUEUED 或错误
+    pub fn queue(&mut self, frame: Resp) -> Resp {
+        let spec = match command_table::check_arity(&frame) {
+            Ok(spec) => spec,
+            Err(e) => {
+                self.aborted = true;
+                return error(&e);
+            }
+        };
+        let command = if spec.is_connection_level() {
+            None
+        } else {
+            match CommandGroup::try_from(frame.clone()) {
+                Ok(command) => Some(command),
+                Err(e) => {
+                    self.aborted = true;
+                    return error(&format!("ERR {e}"));
+                }
+            }
+        };
+        if let Some(queued) = self.queued.as_mut() {
+            queued.push((frame, command));
+        }
+        Resp::SimpleStrings(SimpleStringsData::new("QUEUED".to_owned()))
+    }
+
+    /// 排队阶段的错误（例如 READONLY、MOVED）也会让事务失败
+    pub fn flag_error(&mut self) {
+        if self.in_multi() {
+            self.aborted = true;
+        }
+    }
+
+    /// 执行排队的命令，`db` 为连接当前选择的库，事务中的 SELECT 会修改它
+    pub fn exec(&mut self, db: &mut usize) -> Resp {
+        let Some(queued) = self.queued.take() else {
+            return error("ERR EXEC without MULTI");
+        };
+        let aborted = std::mem::take(&mut self.aborted);
+        let data = self.data.clone();
+        let _guard = data.transactions.exclusive();
+        let dirty = self
+            .watched
+            .iter()
+            .any(|(index, key, version)| data.transactions.version(*index, key) != *version);
+        self.clear_watched();
+        if aborted {
+            return error("EXECABORT Transaction discarded because of previous errors.");
+        }
+        if dirty {
+            return Resp::Nulls(Nulls::new());
+        }
+
+        data.replication.begin_exec();
+        let replies = queued
+            .into_iter()
+            .map(|(frame, command)| {
+                // 排队之后权限可能被修改，执行前再检查一次
+                if let Err(e) = data.acl.check_current(&frame, "multi") {
+                    return error(&e);
+                }
+                // 事务中的命令在执行时才推送给 MONITOR
+                data.monitors.feed(*db, &frame);
+                match &command {
+                    // EXEC 已经取消了所有 WATCH，事务中的 UNWATCH 直接返回 OK
+                    None => ok(),
+                    Some(CommandGroup::Connection(ConnectionCommand::Select(select))) => {
+                        match select.validate(&data) {
+                            Ok(index) => {
+                                *db = index;
+                                ok()
+                            }
+                            Err(e) => e,
+                        }
+                    }
+                    Some(command) => data
+                        .with_db(*db, || call(&data, frame, command))
+                        .unwrap_or_else(|e| error(&format!("ERR {e}"))),
+                }
+            })
+            .collect();
+        if let Err(e) = data.replication.end_exec() {
+            warn!("⚠️ Failed to propagate EXEC: {}", e);
+        }
+        Resp::Arrays(Arrays::new(replies))
+    }
+
+    pub fn discard(&mut self) -> Resp {
+        if self.queued.take().is_none() {
+            return error("ERR DISCARD without MULTI");
+        }
+        self.aborted = false;
+        self.clear_watched();
+        ok()
+    }
+
+    pub fn watch(&mut self, db: usize, keys: &[String]) -> Resp {
+        if self.in_multi() {
+            return error("ERR WATCH inside MULTI is not allowed");
+        }
+        for key in keys {
+            if self
+                .watched
+                .iter()
+                .any(|(index, watched, _)| *index == db && watched == key)
+            {
+                continue;
+            }
+            let version = self.data.transactions.watch(db, key);
+            self.watched.push((db, key.clone(), version));
+        }
+        ok()
+    }
+
+    pub fn unwatch(&mut self) -> Resp {
+        self.clear_watched();
+        ok()
+    }
+
+    fn clear_watched(&mut self) {
+        for (db, key, _) in self.watched.drain(..) {
+            self.data.transactions.unwatch(db, &key);
+        }
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        self.clear_watched();
+    }
+}
+
+fn ok() -> Resp {
+    Resp::SimpleStrings(SimpleStringsData::new("OK".to_owned()))
+}
+
+fn error(msg: &str) -> Resp {
+    Resp::SimpleErrors(SimpleErrors::new(msg.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkStrings, Integers};
+
+    fn frame(args: &[&str]) -> Resp {
+        Resp::Arrays(Arrays::new(
+            args.iter()
+                .map(|arg| Resp::BulkStrings(BulkStrings::new(arg.to_string())))
+                .collect(),
+        ))
+    }
+
+    fn run(data: &Data, args: &[&str]) -> Resp {
+        let frame = frame(args);
+        let command = CommandGroup::try_from(frame.clone()).unwrap();
+        let _guard = data.transactions.shared();
+        call(data, frame, &command).unwrap()
+    }
+
+    #[test]
+    fn test_multi_exec() {
+        let data = Arc::new(Data::new());
+        let mut tx = Transaction::new(data.clone());
+        assert_eq!(tx.multi(), ok());
+        assert!(
+            matches!(tx.queue(frame(&["SET", "k", "1"])), Resp::SimpleStrings(s) if s.val == "QUEUED")
+        );
+        tx.queue(frame(&["INCR", "k"]));
+        let Resp::Arrays(replies) = tx.exec(&mut 0) else {
+            panic!("EXEC should return an array");
+        };
+        assert_eq!(replies.val.len(), 2);
+        assert_eq!(replies.val[1], Resp::Integers(Integers::new(2)));
+        assert_eq!(tx.exec(&mut 0), error("ERR EXEC without MULTI"));
+    }
+
+    #[test]
+    fn test_syntax_error_aborts() {
+        let data = Arc::new(Data::new());
+        let mut tx = Transaction::new(data.clone());
+        tx.multi();
+        tx.queue(frame(&["SET", "k", "1"]));
+        assert!(matches!(tx.queue(frame(&["GET"])), Resp::SimpleErrors(_)));
+        assert_eq!(
+            tx.exec(&mut 0),
+            error("EXECABORT Transaction discarded because of previous errors.")
+        );
+        assert!(!data.contains_key("k"));
+    }
+
+    #[test]
+    fn test_watch() {
+        let data = Arc::new(Data::new());
+        let mut tx = Transaction::new(data.clone());
+        tx.watch(0, &["k".to_owned()]);
+        // 其他客户端修改了键
+        run(&data, &["SET", "k", "other"]);
+        tx.multi();
+        tx.queue(frame(&["SET", "k", "mine"]));
+        assert_eq!(tx.exec(&mut 0), Resp::Nulls(Nulls::new()));
+        assert!(data.transactions.watched.is_empty());
+
+        // 没有修改时正常执行
+        tx.watch(0, &["k".to_owned()]);
+        run(&data, &["GET", "k"]);
+        tx.multi();
+        tx.queue(frame(&["SET", "k", "mine"]));
+        assert!(matches!(tx.exec(&mut 0), Resp::Arrays(_)));
+
+        tx.watch(0, &["k".to_owned()]);
+        drop(tx);
+        assert!(data.transactions.watched.is_empty());
+    }
+
+    #[test]
+    fn test_slowlog_redacts_secrets() {
+        let data = Data::new();
+        data.slowlog.set_log_slower_than(0);
+        let client = Arc::new(crate::client::Client::new(
+            1,
+            "127.0.0.1:5000".to_owned(),
+            String::new(),
+        ));
+        crate::client::with_client(&client, || {
+            run(
+                &data,
+                &["ACL", "SETUSER", "carol", "on", ">s3cret", "+@all"],
+            );
+            run(&data, &["AUTH", "carol", "s3cret"]);
+            run(&data, &["CONFIG", "SET", "requirepass", "s3cret"]);
+        });
+        let entries = format!("{:?}", run(&data, &["SLOWLOG", "GET", "-1"]));
+        assert!(entries.contains("SETUSER") && entries.contains("AUTH"));
+        assert!(!entries.contains("s3cret"), "{entries}");
+    }
+
+    #[test]
+    fn test_emptied_keys_release_memory() {
+        let data = Arc::new(Data::new());
+        for _ in 0..10 {
+            run(&data, &["RPUSH", "list", "a", "b", "c"]);
+            run(&data, &["LPOP", "list"]);
+            run(&data, &["RPOP", "list"]);
+            run(&data, &["LREM", "list", "0", "b"]);
+            run(&data, &["HSET", "hash", "f", "v"]);
+            run(&data, &["HDEL", "hash", "f"]);
+        }
+        assert!(!data.contains_key("list"));
+        assert!(!data.contains_key("hash"));
+        assert_eq!(data.used_memory(), 0);
+    }
+}